@@ -1,8 +1,22 @@
-/// Manages the state and lifecycle of the agentic loop for Qwen models
+use serde::Serialize;
+
+/// Snapshot of the agentic loop for `get_agentic_status`
+#[derive(Debug, Clone, Serialize)]
+pub struct AgenticStatus {
+    pub active: bool,
+    pub current_turn: usize,
+    pub max_turns: usize,
+    /// Why the loop last stopped (max turns, loop detected, user stop, ...)
+    pub last_stop_reason: Option<String>,
+}
+
+/// Manages the state and lifecycle of the agentic loop (tool-result round trips)
 pub struct AgenticLoop {
     active: bool,
     turns: usize,
     max_turns: usize,
+    max_turns_reached: bool,
+    last_stop_reason: Option<String>,
 }
 
 impl AgenticLoop {
@@ -11,17 +25,29 @@ impl AgenticLoop {
             active: false,
             turns: 0,
             max_turns,
+            max_turns_reached: false,
+            last_stop_reason: None,
         }
     }
 
+    /// Applies a new turn limit, e.g. from project settings
+    pub fn set_max_turns(&mut self, max_turns: usize) {
+        self.max_turns = max_turns;
+    }
+
+    pub fn max_turns(&self) -> usize {
+        self.max_turns
+    }
+
     pub fn is_active(&self) -> bool {
         self.active
     }
 
     pub fn start(&mut self) {
-        eprintln!("[AGENTIC LOOP] Starting for Qwen model");
+        eprintln!("[AGENTIC LOOP] Starting");
         self.active = true;
         self.turns = 0;
+        self.max_turns_reached = false;
     }
 
     pub fn stop(&mut self, reason: &str) {
@@ -31,14 +57,59 @@ impl AgenticLoop {
         );
         self.active = false;
         self.turns = 0;
+        self.max_turns_reached = false;
+        self.last_stop_reason = Some(reason.to_string());
+    }
+
+    /// Whether the loop last stopped because it hit `max_turns`
+    pub fn reached_max_turns(&self) -> bool {
+        self.max_turns_reached
+    }
+
+    pub fn status(&self) -> AgenticStatus {
+        AgenticStatus {
+            active: self.active,
+            current_turn: self.turns,
+            max_turns: self.max_turns,
+            last_stop_reason: self.last_stop_reason.clone(),
+        }
     }
 
     pub fn increment_turn(&mut self) {
         self.turns += 1;
-        eprintln!("[AGENTIC LOOP] Turn {} for Qwen model", self.turns);
+        eprintln!("[AGENTIC LOOP] Turn {}", self.turns);
 
         if self.turns > self.max_turns {
             self.stop(&format!("reached max turns ({})", self.max_turns));
+            self.max_turns_reached = true;
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_status_reports_stop_reason() {
+        let mut agentic_loop = AgenticLoop::new(2);
+        agentic_loop.start();
+        agentic_loop.increment_turn();
+        let status = agentic_loop.status();
+        assert!(status.active);
+        assert!(!agentic_loop.reached_max_turns());
+        assert_eq!(status.current_turn, 1);
+        assert_eq!(status.last_stop_reason, None);
+
+        agentic_loop.increment_turn();
+        agentic_loop.increment_turn();
+        let status = agentic_loop.status();
+        assert!(!status.active);
+        assert_eq!(status.max_turns, 2);
+        assert_eq!(
+            status.last_stop_reason.as_deref(),
+            Some("reached max turns (2)")
+        );
+        assert!(agentic_loop.reached_max_turns());
+    }
+}
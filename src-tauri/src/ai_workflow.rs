@@ -7,7 +7,7 @@ use serde_json::Value;
 use std::collections::HashMap;
 use std::fs;
 use std::path::Path;
-use std::process::Command;
+use std::time::Duration;
 
 // use eframe::egui; // Removed for Tauri migration
 
@@ -16,13 +16,15 @@ use crate::tool_execution::ToolExecutionContext;
 use crate::tools;
 use tauri::Emitter;
 
-pub use tool_defs::get_tool_definitions;
+pub use tool_defs::{get_enabled_tool_definitions, get_tool_definitions};
 
 #[derive(Clone)]
 pub struct PendingCommand {
     pub call: ToolCall,
     pub command: String,
     pub cwd: Option<String>,
+    /// When the command was sent to the terminal, for the tool log
+    pub started_at: Option<std::time::SystemTime>,
 }
 
 fn normalize_json_string(input: &str) -> String {
@@ -59,6 +61,10 @@ pub enum ChangeType {
     DeleteFile {
         old_content: Option<String>,
     },
+    /// AST-aware edit targeting a named symbol (semantic_edit tool)
+    Semantic {
+        patch: crate::semantic_patch::SemanticPatch,
+    },
 }
 
 #[derive(Clone, serde::Serialize)]
@@ -77,6 +83,27 @@ pub struct PendingConfirm {
     pub description: String,
 }
 
+/// Why a batch ends the agentic loop; its results still go back to the model
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StopReason {
+    /// The model repeated a call or stopped making progress
+    LoopDetected,
+    /// The message used up `max_tool_calls_per_message`
+    ToolCallLimit,
+    /// zcoderd sent a server-side tool here to run
+    MisroutedTool,
+}
+
+impl StopReason {
+    pub fn describe(self) -> &'static str {
+        match self {
+            StopReason::LoopDetected => "loop detected",
+            StopReason::ToolCallLimit => "tool call limit reached",
+            StopReason::MisroutedTool => "server-side tool misrouted",
+        }
+    }
+}
+
 #[derive(Default, Clone)]
 pub struct PendingToolBatch {
     pub calls: Vec<ToolCall>,
@@ -84,7 +111,13 @@ pub struct PendingToolBatch {
     pub commands: Vec<PendingCommand>,
     pub changes: Vec<PendingChange>,
     pub confirms: Vec<PendingConfirm>,
-    pub loop_detected: bool,
+    /// Set when the agentic loop should stop after this batch
+    pub stop_reason: Option<StopReason>,
+    /// History group for every snapshot taken while applying this batch, so the
+    /// whole AI edit can be undone atomically via `undo_batch`.
+    pub batch_id: String,
+    /// Conversation the calls came from, for tools run after approval
+    pub conversation_id: Option<String>,
 }
 
 #[derive(Default)]
@@ -94,6 +127,8 @@ pub struct AiWorkflow {
     recent_file_tool_cache: Vec<((String, String), tools::ToolResult)>,
     last_assistant_content_fingerprint: Option<String>,
     stagnant_tool_turns: usize,
+    /// Tool calls made since the user's last message, for `max_tool_calls_per_message`
+    tool_calls_this_message: usize,
 }
 
 impl AiWorkflow {
@@ -104,9 +139,15 @@ impl AiWorkflow {
             recent_file_tool_cache: Vec::new(),
             last_assistant_content_fingerprint: None,
             stagnant_tool_turns: 0,
+            tool_calls_this_message: 0,
         }
     }
 
+    /// Reset the per-message tool call budget when the user sends a message
+    pub fn start_message(&mut self) {
+        self.tool_calls_this_message = 0;
+    }
+
     pub fn has_pending_commands(&self) -> bool {
         self.pending
             .as_ref()
@@ -197,6 +238,7 @@ impl AiWorkflow {
                                                 .to_string(),
                                         ),
                                         skipped: false,
+                                        error_kind: None,
                                     },
                                 ));
                             }
@@ -207,7 +249,9 @@ impl AiWorkflow {
                                 commands: Vec::new(),
                                 changes: Vec::new(),
                                 confirms: Vec::new(),
-                                loop_detected: true,
+                                stop_reason: Some(StopReason::LoopDetected),
+                                batch_id: uuid::Uuid::new_v4().to_string(),
+                                conversation_id: context.conversation_id.clone(),
                             });
                         }
                     }
@@ -223,7 +267,31 @@ impl AiWorkflow {
         let mut commands: Vec<PendingCommand> = Vec::new();
         let changes: Vec<PendingChange> = Vec::new();
         let mut confirms: Vec<PendingConfirm> = Vec::new();
-        let mut loop_detected = false;
+        let mut stop_reason = None;
+        let batch_id = uuid::Uuid::new_v4().to_string();
+        // File changes applied under `batch_id`, reported so the UI can undo them as one set
+        let mut applied_change_ids: Vec<String> = Vec::new();
+        let mut failed_change_ids: Vec<String> = Vec::new();
+        // Files this batch created, which have no snapshot to restore on rollback
+        let mut created_paths: Vec<std::path::PathBuf> = Vec::new();
+        let settings = crate::project_settings::load_project_settings_or_default(workspace_root);
+        let atomic_changes = settings.atomic_ai_changes;
+        let auto_create_cwd = settings.auto_create_cwd;
+        let max_tool_calls = settings.max_tool_calls_per_message;
+        let mut tool_limit_hit = false;
+        // Commands are refused outright in an untrusted workspace rather than
+        // being offered for approval
+        let workspace_trusted = {
+            use tauri::Manager;
+            let app_state = context
+                .app_handle
+                .as_ref()
+                .and_then(|app| app.try_state::<crate::app_state::AppState>());
+            match app_state {
+                Some(state) => state.workspace.lock().unwrap().is_trusted(workspace_root),
+                None => true,
+            }
+        };
         let mut seen_in_batch: HashMap<(String, String), usize> = HashMap::new();
 
         struct PendingRead<R: tauri::Runtime> {
@@ -233,6 +301,33 @@ impl AiWorkflow {
         let mut pending_read_tasks: Vec<PendingRead<R>> = Vec::new();
 
         for call in &calls {
+            if max_tool_calls > 0 && self.tool_calls_this_message >= max_tool_calls {
+                tool_limit_hit = true;
+                file_results.push((
+                    call.clone(),
+                    tools::ToolResult::err(format!(
+                        "SYSTEM WARNING: TOOL CALL LIMIT REACHED - This message has used its {} tool calls. DO NOT call any more tools. Summarize what you have done and what remains so the user can decide how to continue.",
+                        max_tool_calls
+                    )),
+                ));
+                continue;
+            }
+            self.tool_calls_this_message += 1;
+
+            // Tools disabled for this project never run, even ones handled here
+            // rather than in execute_tool (commands and file edits)
+            if let Err(disabled) = settings.tools.check(&call.function.name) {
+                file_results.push((call.clone(), disabled));
+                continue;
+            }
+            if let Some(refused) = crate::tool_execution::read_only_refusal(
+                context.app_handle.as_ref(),
+                &call.function.name,
+            ) {
+                file_results.push((call.clone(), refused));
+                continue;
+            }
+
             // Normalize arguments for comparison
             let normalized_args = normalize_json_string(&call.function.arguments);
 
@@ -256,12 +351,9 @@ impl AiWorkflow {
                 }
             }
 
-            // Loop Detection Exemption
-            let is_exempt = matches!(
-                call.function.name.as_str(),
-                "get_editor_state" | "get_workspace_structure"
-            );
-            if !is_exempt {
+            // Loop detection keys on tool name + normalized arguments, so the same
+            // tool with different arguments never counts as a repeat
+            if let Some(limit) = settings.loop_detection.repeat_limit(&call.function.name) {
                 let recent_count = self
                     .recent_history
                     .iter()
@@ -270,21 +362,12 @@ impl AiWorkflow {
                 let batch_count = *seen_in_batch.get(&call_sig).unwrap_or(&0);
                 let total_seen = recent_count + batch_count;
 
-                let limit = if matches!(
-                    call.function.name.as_str(),
-                    "read_file" | "read_file_range" | "grep_search"
-                ) {
-                    3
-                } else {
-                    2
-                };
-
                 if total_seen >= limit {
                     eprintln!(
                         "[AI WORKFLOW] Loop detected for tool: {}",
                         call.function.name
                     );
-                    loop_detected = true;
+                    stop_reason = Some(StopReason::LoopDetected);
                     file_results.push((
                         call.clone(),
                         tools::ToolResult {
@@ -292,6 +375,7 @@ impl AiWorkflow {
                             content: String::new(),
                             error: Some("SYSTEM WARNING: LOOP DETECTED - You called this tool with identical arguments before. DO NOT call any more tools. Use the information from your previous tool calls to answer the user's question NOW.".to_string()),
                             skipped: false,
+                            error_kind: None,
                         },
                     ));
                     continue;
@@ -306,6 +390,12 @@ impl AiWorkflow {
 
             // INTERCEPTION LOGIC
             if call.function.name == "run_command" {
+                if !workspace_trusted {
+                    let err =
+                        tools::ToolResult::err(crate::workspace_manager::UNTRUSTED_WORKSPACE_ERROR);
+                    file_results.push((call.clone(), err));
+                    continue;
+                }
                 match parse_run_command_args(&call.function.arguments) {
                     Ok((command, cwd)) => {
                         if let Some(err) = should_block_irrelevant_language_scan(
@@ -316,153 +406,154 @@ impl AiWorkflow {
                             file_results.push((call.clone(), tools::ToolResult::err(err)));
                             continue;
                         }
+                        // Resolve cwd up front: the terminal would otherwise fail
+                        // to `cd` and run the command somewhere else
+                        let cwd = match cwd.as_deref().map(|dir| {
+                            crate::utils::resolve_command_cwd(workspace_root, dir, auto_create_cwd)
+                        }) {
+                            Some(Ok(dir)) => Some(dir.to_string_lossy().to_string()),
+                            Some(Err(e)) => {
+                                file_results.push((call.clone(), tools::ToolResult::err(e)));
+                                continue;
+                            }
+                            None => None,
+                        };
                         commands.push(PendingCommand {
                             call: call.clone(),
                             command,
                             cwd,
+                            started_at: None,
                         })
                     }
                     Err(e) => file_results.push((call.clone(), tools::ToolResult::err(e))),
                 }
+            } else if call.function.name == "regex_replace" {
+                if atomic_changes && !failed_change_ids.is_empty() {
+                    let skipped = tools::ToolResult::err(SKIPPED_AFTER_FAILURE);
+                    file_results.push((call.clone(), skipped));
+                    continue;
+                }
+                match change_parser::parse_regex_replace(&call.function.arguments, workspace_root) {
+                    Ok((change, replaced)) => match apply_change_now(
+                        workspace_root,
+                        &change,
+                        &call.id,
+                        &batch_id,
+                        context,
+                        true,
+                    ) {
+                        Ok(_) => {
+                            applied_change_ids.push(call.id.clone());
+                            file_results.push((
+                                call.clone(),
+                                tools::ToolResult::ok(format!(
+                                    "Replaced {} occurrence{} in {}",
+                                    replaced,
+                                    if replaced == 1 { "" } else { "s" },
+                                    change.path
+                                )),
+                            ));
+                        }
+                        Err(e) => {
+                            failed_change_ids.push(call.id.clone());
+                            file_results.push((
+                                call.clone(),
+                                tools::ToolResult::err(format!("Failed to apply change: {}", e)),
+                            ));
+                        }
+                    },
+                    Err(e) => {
+                        report_if_blocked_write(context.app_handle.as_ref(), call, &e);
+                        failed_change_ids.push(call.id.clone());
+                        file_results.push((call.clone(), tools::ToolResult::err(e)));
+                    }
+                }
+            } else if call.function.name == "replace_in_files"
+                && !change_parser::is_dry_run(&call.function.arguments)
+            {
+                if atomic_changes && !failed_change_ids.is_empty() {
+                    let skipped = tools::ToolResult::err(SKIPPED_AFTER_FAILURE);
+                    file_results.push((call.clone(), skipped));
+                    continue;
+                }
+                let replacements = match change_parser::parse_replace_in_files(
+                    &call.function.arguments,
+                    workspace_root,
+                ) {
+                    Ok(replacements) => replacements,
+                    Err(e) => {
+                        failed_change_ids.push(call.id.clone());
+                        file_results.push((call.clone(), tools::ToolResult::err(e)));
+                        continue;
+                    }
+                };
+                // One reviewable change per file, tracked as `<call id>:<n>`
+                let mut applied = Vec::new();
+                let mut failure = None;
+                for (i, (change, replacement)) in replacements.into_iter().enumerate() {
+                    let change_id = format!("{}:{}", call.id, i);
+                    match apply_change_now(
+                        workspace_root,
+                        &change,
+                        &change_id,
+                        &batch_id,
+                        context,
+                        false,
+                    ) {
+                        Ok(_) => {
+                            applied_change_ids.push(change_id);
+                            applied.push(replacement);
+                        }
+                        Err(e) => {
+                            failed_change_ids.push(change_id);
+                            failure = Some(format!("{}: {}", change.path, e));
+                            break;
+                        }
+                    }
+                }
+                let summary = tools::summarize_replacements(&applied, false);
+                let result = match failure {
+                    None => tools::ToolResult::ok(summary),
+                    Some(e) => tools::ToolResult::err(format!(
+                        "Failed to apply change to {}\n{}",
+                        e, summary
+                    )),
+                };
+                file_results.push((call.clone(), result));
             } else if matches!(
                 call.function.name.as_str(),
-                "edit_file" | "apply_edit" | "apply_patch" | "write_file" | "create_file"
+                "edit_file"
+                    | "edit_lines"
+                    | "apply_edit"
+                    | "apply_patch"
+                    | "write_file"
+                    | "create_file"
+                    | "semantic_edit"
             ) {
+                if atomic_changes && !failed_change_ids.is_empty() {
+                    let skipped = tools::ToolResult::err(SKIPPED_AFTER_FAILURE);
+                    file_results.push((call.clone(), skipped));
+                    continue;
+                }
                 match parse_change_args(
                     &call.function.arguments,
                     workspace_root,
                     &call.function.name,
                 ) {
                     Ok(change) => {
-                        // NEW LOGIC: Apply the change IMMEDIATELY to disk
-                        // This makes it act like an "Undo/Redo" buffer - change is live, can be undone.
-
-                        let full_path = workspace_root.join(&change.path);
-
-                        // Read original content before any changes (for diff generation)
-                        let original_content = fs::read_to_string(&full_path).unwrap_or_default();
-
-                        // History Snapshot - capture the snapshot ID for uncommitted tracking
-                        let mut snapshot_id: Option<String> = None;
-                        if let Some(app) = &context.app_handle {
-                            use tauri::Manager;
-                            let state = app.state::<crate::app_state::AppState>();
-                            if full_path.exists() {
-                                match state
-                                    .history_service
-                                    .create_snapshot(&full_path, Some(call.id.clone()))
-                                {
-                                    Ok(entry) => {
-                                        println!("[HISTORY] Snapshot created for {}", change.path);
-                                        snapshot_id = Some(entry.id.clone());
-                                        let _ = app.emit(
-                                            crate::events::event_names::HISTORY_ENTRY_ADDED,
-                                            crate::events::HistoryEntryAddedPayload { entry },
-                                        );
-                                    }
-                                    Err(e) => {
-                                        eprintln!(
-                                            "[HISTORY] Failed to create snapshot for {}: {}",
-                                            change.path, e
-                                        );
-                                    }
-                                }
-                            }
-                        }
-
-                        let apply_result = (|| -> Result<(), String> {
-                            match &change.change_type {
-                                ChangeType::Patch {
-                                    old_content,
-                                    new_content,
-                                } => {
-                                    let current_content = fs::read_to_string(&full_path)
-                                        .map_err(|e| format!("Failed to read file: {}", e))?;
-                                    let new_file_content = tools::apply_patch_to_string(
-                                        &current_content,
-                                        old_content,
-                                        new_content,
-                                    )?;
-                                    fs::write(&full_path, new_file_content)
-                                        .map_err(|e| format!("Failed to write file: {}", e))?;
-                                    Ok(())
-                                }
-                                ChangeType::MultiPatch { patches } => {
-                                    let mut content = fs::read_to_string(&full_path)
-                                        .map_err(|e| format!("Failed to read file: {}", e))?;
-                                    for patch in patches {
-                                        content = tools::apply_patch_to_string(
-                                            &content,
-                                            &patch.old_text,
-                                            &patch.new_text,
-                                        )?;
-                                    }
-                                    fs::write(&full_path, content)
-                                        .map_err(|e| format!("Failed to write file: {}", e))?;
-                                    Ok(())
-                                }
-                                ChangeType::NewFile { content } => {
-                                    if let Some(parent) = full_path.parent() {
-                                        let _ = fs::create_dir_all(parent);
-                                    }
-                                    fs::write(&full_path, content)
-                                        .map_err(|e| format!("Failed to create file: {}", e))?;
-                                    Ok(())
-                                }
-                                ChangeType::DeleteFile { .. } => {
-                                    // Can't "apply" delete safely in a way that is easily undoable without manual backup?
-                                    // Or we just do it. But logic for undo needs content.
-                                    // For now, let's DELAY delete or apply it?
-                                    // User said "AI-applied changes are immediately written".
-                                    // So we delete it.
-                                    fs::remove_file(&full_path)
-                                        .map_err(|e| format!("Failed to delete file: {}", e))?;
-                                    Ok(())
-                                }
-                            }
-                        })();
-
-                        match apply_result {
-                            Ok(_) => {
-                                println!("[AI WORKFLOW] Auto-applied change to {}", change.path);
-                                if let Some(app) = &context.app_handle {
-                                    use tauri::Manager;
-                                    let state = app.state::<crate::app_state::AppState>();
-
-                                    // Track as uncommitted change if we have a snapshot
-                                    if let Some(snap_id) = &snapshot_id {
-                                        // Read new content for diff
-                                        let new_content = fs::read_to_string(&full_path).unwrap_or_default();
-                                        let diff = diffy::create_patch(&original_content, &new_content).to_string();
-                                        let (added, removed) = crate::uncommitted_changes::count_diff_stats(&diff);
-
-                                        let uncommitted = crate::uncommitted_changes::UncommittedChange {
-                                            id: call.id.clone(),
-                                            file_path: full_path.clone(),
-                                            snapshot_id: snap_id.clone(),
-                                            unified_diff: diff,
-                                            added_lines: added,
-                                            removed_lines: removed,
-                                            timestamp: std::time::SystemTime::now()
-                                                .duration_since(std::time::UNIX_EPOCH)
-                                                .unwrap_or_default()
-                                                .as_millis() as u64,
-                                        };
-                                        state.uncommitted_changes.track(uncommitted);
-                                        println!("[UNCOMMITTED] Tracking change {} for {}", call.id, change.path);
-                                    }
-
-                                    let abs_path_str = full_path.to_string_lossy().to_string();
-                                    let _ = app.emit("refresh-explorer", ());
-                                    // Emit open-file to open the file in editor
-                                    let _ = app.emit("open-file", &abs_path_str);
-                                    let _ = app.emit(
-                                        crate::events::event_names::CHANGE_APPLIED,
-                                        crate::events::ChangeAppliedPayload {
-                                            change_id: call.id.clone(),
-                                            file_path: abs_path_str.clone(),
-                                        },
-                                    );
+                        // Applied immediately: the change is live and can be undone
+                        match apply_change_now(
+                            workspace_root,
+                            &change,
+                            &call.id,
+                            &batch_id,
+                            context,
+                            true,
+                        ) {
+                            Ok(created) => {
+                                applied_change_ids.push(call.id.clone());
+                                if created {
+                                    created_paths.push(workspace_root.join(&change.path));
                                 }
                                 file_results.push((
                                     call.clone(),
@@ -473,7 +564,7 @@ impl AiWorkflow {
                                 ));
                             }
                             Err(e) => {
-                                eprintln!("[AI WORKFLOW] Failed to auto-apply change: {}", e);
+                                failed_change_ids.push(call.id.clone());
                                 file_results.push((
                                     call.clone(),
                                     tools::ToolResult::err(format!(
@@ -487,9 +578,18 @@ impl AiWorkflow {
                         // change.call = call.clone();
                         // changes.push(change);
                     }
-                    Err(e) => file_results.push((call.clone(), tools::ToolResult::err(e))),
+                    Err(e) => {
+                        report_if_blocked_write(context.app_handle.as_ref(), call, &e);
+                        failed_change_ids.push(call.id.clone());
+                        file_results.push((call.clone(), tools::ToolResult::err(e)))
+                    }
                 }
             } else if call.function.name == "delete_file" {
+                if atomic_changes && !failed_change_ids.is_empty() {
+                    let skipped = tools::ToolResult::err(SKIPPED_AFTER_FAILURE);
+                    file_results.push((call.clone(), skipped));
+                    continue;
+                }
                 match parse_change_args(
                     &call.function.arguments,
                     workspace_root,
@@ -506,7 +606,7 @@ impl AiWorkflow {
                             if full_path.exists() {
                                 match state
                                     .history_service
-                                    .create_snapshot(&full_path, Some(call.id.clone()))
+                                    .create_snapshot(&full_path, Some(batch_id.clone()))
                                 {
                                     Ok(entry) => {
                                         println!("[HISTORY] Snapshot created for {}", change.path);
@@ -547,6 +647,7 @@ impl AiWorkflow {
                                         },
                                     );
                                 }
+                                applied_change_ids.push(call.id.clone());
                                 file_results.push((
                                     call.clone(),
                                     tools::ToolResult::ok(format!("File deleted: {}", change.path)),
@@ -555,6 +656,7 @@ impl AiWorkflow {
                             Err(e) => {
                                 let err_msg = e.to_string();
                                 eprintln!("[AI WORKFLOW] Failed to auto-delete file: {}", err_msg);
+                                failed_change_ids.push(call.id.clone());
                                 file_results.push((
                                     call.clone(),
                                     tools::ToolResult::err(format!(
@@ -568,7 +670,11 @@ impl AiWorkflow {
                         // change.call = call.clone();
                         // changes.push(change);
                     }
-                    Err(e) => file_results.push((call.clone(), tools::ToolResult::err(e))),
+                    Err(e) => {
+                        report_if_blocked_write(context.app_handle.as_ref(), call, &e);
+                        failed_change_ids.push(call.id.clone());
+                        file_results.push((call.clone(), tools::ToolResult::err(e)))
+                    }
                 }
             } else if matches!(
                 call.function.name.as_str(),
@@ -666,7 +772,8 @@ impl AiWorkflow {
                     cursor_column: context.cursor_column,
                     selection_start_line: context.selection_start_line,
                     selection_end_line: context.selection_end_line,
-                    app_handle: None, // not needed for read operations
+                    app_handle: context.app_handle.clone(), // for the tool log
+                    conversation_id: context.conversation_id.clone(),
                 };
                 pending_read_tasks.push(PendingRead {
                     call: call.clone(),
@@ -675,6 +782,7 @@ impl AiWorkflow {
             } else {
                 let res = crate::tool_execution::execute_tool_with_context(
                     context,
+                    &call.id,
                     &call.function.name,
                     &call.function.arguments,
                 );
@@ -708,6 +816,7 @@ impl AiWorkflow {
                 handles.push(std::thread::spawn(move || {
                     let res = crate::tool_execution::execute_tool_with_context(
                         &task.context,
+                        &task.call.id,
                         &task.call.function.name,
                         &task.call.function.arguments,
                     );
@@ -751,6 +860,107 @@ impl AiWorkflow {
             }
         }
 
+        // All-or-nothing mode: one failed change reverts everything this batch applied
+        let mut rolled_back = false;
+        if atomic_changes && !failed_change_ids.is_empty() && !applied_change_ids.is_empty() {
+            if let Some(app) = &context.app_handle {
+                use tauri::Manager;
+                let state = app.state::<crate::app_state::AppState>();
+                // Fails only when every applied change created a new file (nothing snapshotted)
+                if let Err(e) = state.history_service.undo_batch(&batch_id) {
+                    eprintln!("[AI WORKFLOW] Rollback of batch {}: {}", batch_id, e);
+                }
+                for path in &created_paths {
+                    if let Err(e) = fs::remove_file(path) {
+                        eprintln!("[AI WORKFLOW] Failed to remove {}: {}", path.display(), e);
+                    }
+                }
+                for id in &applied_change_ids {
+                    state.uncommitted_changes.accept(id);
+                }
+                for (call, result) in file_results.iter_mut() {
+                    if applied_change_ids
+                        .iter()
+                        .any(|id| is_change_of_call(id, &call.id))
+                    {
+                        *result = tools::ToolResult::err(
+                            "Change rolled back: another change in this batch failed",
+                        );
+                    }
+                }
+                let _ = app.emit("refresh-explorer", ());
+                eprintln!(
+                    "[AI WORKFLOW] Rolled back {} change(s) in batch {}",
+                    applied_change_ids.len(),
+                    batch_id
+                );
+                failed_change_ids.append(&mut applied_change_ids);
+                rolled_back = true;
+            }
+        }
+
+        // A misrouted server-side tool fails however often the model retries it,
+        // so the loop stops with its own reason
+        let misrouted = file_results
+            .iter()
+            .find(|(_, res)| res.error_kind == Some(tools::ToolErrorKind::ServerSideToolMisrouted));
+        if let Some((call, _)) = misrouted {
+            stop_reason = Some(StopReason::MisroutedTool);
+            if let Some(app) = &context.app_handle {
+                emit_agent_stopped(
+                    app,
+                    &format!(
+                        "Stopped: the server sent '{}', a tool only the server can run",
+                        call.function.name
+                    ),
+                );
+            }
+        }
+
+        if tool_limit_hit {
+            eprintln!(
+                "[AI WORKFLOW] Tool call limit reached ({} per message)",
+                max_tool_calls
+            );
+            // Stops the agentic loop; the model still gets the refusals and answers
+            stop_reason = Some(StopReason::ToolCallLimit);
+            if let Some(app) = &context.app_handle {
+                emit_agent_stopped(
+                    app,
+                    &format!(
+                        "Stopped after {} tool calls for this message (max_tool_calls_per_message)",
+                        max_tool_calls
+                    ),
+                );
+            }
+        }
+
+        if !applied_change_ids.is_empty() || !failed_change_ids.is_empty() {
+            if let Some(app) = &context.app_handle {
+                let _ = app.emit(
+                    "blade-event",
+                    crate::blade_protocol::BladeEventEnvelope {
+                        id: uuid::Uuid::new_v4(),
+                        timestamp: std::time::SystemTime::now()
+                            .duration_since(std::time::UNIX_EPOCH)
+                            .unwrap_or_default()
+                            .as_millis() as u64,
+                        causality_id: None,
+                        event: crate::blade_protocol::BladeEvent::Workflow(
+                            crate::blade_protocol::WorkflowEvent::BatchCompleted {
+                                batch_id: batch_id.clone(),
+                                succeeded: applied_change_ids.len(),
+                                failed: failed_change_ids.len(),
+                                change_ids: applied_change_ids,
+                                failed_ids: failed_change_ids,
+                                rolled_back,
+                            },
+                        ),
+                    },
+                );
+            }
+        }
+
         if !file_results.is_empty()
             || !commands.is_empty()
             || !changes.is_empty()
@@ -762,7 +972,9 @@ impl AiWorkflow {
                 commands,
                 changes,
                 confirms,
-                loop_detected,
+                stop_reason,
+                batch_id,
+                conversation_id: context.conversation_id.clone(),
             });
         }
         self.pending = Some(PendingToolBatch {
@@ -771,7 +983,9 @@ impl AiWorkflow {
             commands,
             changes,
             confirms,
-            loop_detected,
+            stop_reason,
+            batch_id,
+            conversation_id: context.conversation_id.clone(),
         });
         None
     }
@@ -799,6 +1013,271 @@ impl AiWorkflow {
     }
 }
 
+/// Tell the UI why the agent stopped early
+pub fn emit_agent_stopped<R: tauri::Runtime>(emitter: &impl Emitter<R>, reason: &str) {
+    let _ = emitter.emit(
+        "blade-event",
+        crate::blade_protocol::BladeEventEnvelope {
+            id: uuid::Uuid::new_v4(),
+            timestamp: std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_millis() as u64,
+            causality_id: None,
+            event: crate::blade_protocol::BladeEvent::Workflow(
+                crate::blade_protocol::WorkflowEvent::AgentStopped {
+                    reason: reason.to_string(),
+                },
+            ),
+        },
+    );
+}
+
+/// Change IDs are the tool call ID, or `<call id>:<n>` for calls that edit several files
+fn is_change_of_call(change_id: &str, call_id: &str) -> bool {
+    change_id
+        .strip_prefix(call_id)
+        .is_some_and(|rest| rest.is_empty() || rest.starts_with(':'))
+}
+
+/// Write one file change to disk right away. The file is snapshotted into the batch's
+/// history group and tracked as an uncommitted change under `change_id`, so the user can
+/// review or undo it. Returns whether the file was newly created.
+fn apply_change_now<R: tauri::Runtime>(
+    workspace_root: &Path,
+    change: &PendingChange,
+    change_id: &str,
+    batch_id: &str,
+    context: &ToolExecutionContext<R>,
+    open_in_editor: bool,
+) -> Result<bool, String> {
+    use tauri::Manager;
+    let started = std::time::SystemTime::now();
+    let result = apply_change(
+        workspace_root,
+        change,
+        change_id,
+        batch_id,
+        context,
+        open_in_editor,
+    );
+    let state = context
+        .app_handle
+        .as_ref()
+        .and_then(|app| app.try_state::<crate::app_state::AppState>());
+    if let Some(state) = state {
+        crate::tool_execution::record_tool_call(
+            &state,
+            context.conversation_id.as_deref(),
+            change_id,
+            &change.call.function.name,
+            &change.call.function.arguments,
+            result.is_ok(),
+            started,
+        );
+    }
+    result
+}
+
+fn apply_change<R: tauri::Runtime>(
+    workspace_root: &Path,
+    change: &PendingChange,
+    change_id: &str,
+    batch_id: &str,
+    context: &ToolExecutionContext<R>,
+    open_in_editor: bool,
+) -> Result<bool, String> {
+    let full_path = workspace_root.join(&change.path);
+
+    // Read original content before any changes (for diff generation)
+    let read_text = |path: &Path| {
+        crate::encoding::read_text(path)
+            .map(|(text, _)| text)
+            .unwrap_or_default()
+    };
+    let original_content = read_text(&full_path);
+    let existed_before = full_path.exists();
+
+    // History Snapshot - capture the snapshot ID for uncommitted tracking
+    let mut snapshot_id: Option<String> = None;
+    if let Some(app) = &context.app_handle {
+        use tauri::Manager;
+        let state = app.state::<crate::app_state::AppState>();
+        if full_path.exists() {
+            match state
+                .history_service
+                .create_snapshot(&full_path, Some(batch_id.to_string()))
+            {
+                Ok(entry) => {
+                    println!("[HISTORY] Snapshot created for {}", change.path);
+                    snapshot_id = Some(entry.id.clone());
+                    let _ = app.emit(
+                        crate::events::event_names::HISTORY_ENTRY_ADDED,
+                        crate::events::HistoryEntryAddedPayload { entry },
+                    );
+                }
+                Err(e) => {
+                    eprintln!(
+                        "[HISTORY] Failed to create snapshot for {}: {}",
+                        change.path, e
+                    );
+                }
+            }
+        }
+    }
+
+    let apply_result = (|| -> Result<(), String> {
+        match &change.change_type {
+            ChangeType::Patch {
+                old_content,
+                new_content,
+            } => {
+                let (current_content, encoding) = crate::encoding::read_text(&full_path)
+                    .map_err(|e| format!("Failed to read file: {}", e))?;
+                let new_file_content = tools::apply_patches_preserving_eol(
+                    &current_content,
+                    &[(old_content.as_str(), new_content.as_str())],
+                )?;
+                // Keeps the file's encoding and mode (e.g. executable scripts)
+                let bytes = crate::encoding::encode(&new_file_content, encoding)?;
+                crate::utils::atomic_write(&full_path, bytes)
+                    .map_err(|e| format!("Failed to write file: {}", e))?;
+                Ok(())
+            }
+            ChangeType::MultiPatch { patches } => {
+                let (current_content, encoding) = crate::encoding::read_text(&full_path)
+                    .map_err(|e| format!("Failed to read file: {}", e))?;
+                let hunks: Vec<(&str, &str)> = patches
+                    .iter()
+                    .map(|p| (p.old_text.as_str(), p.new_text.as_str()))
+                    .collect();
+                let content = tools::apply_patches_preserving_eol(&current_content, &hunks)?;
+                let bytes = crate::encoding::encode(&content, encoding)?;
+                crate::utils::atomic_write(&full_path, bytes)
+                    .map_err(|e| format!("Failed to write file: {}", e))?;
+                Ok(())
+            }
+            ChangeType::NewFile { content } => {
+                if let Some(parent) = full_path.parent() {
+                    fs::create_dir_all(parent)
+                        .map_err(|e| format!("Failed to create directory: {}", e))?;
+                }
+                crate::utils::atomic_write(&full_path, content)
+                    .map_err(|e| format!("Failed to create file: {}", e))?;
+                Ok(())
+            }
+            ChangeType::Semantic { patch } => {
+                use tauri::Manager;
+                let app = context
+                    .app_handle
+                    .as_ref()
+                    .ok_or_else(|| "semantic_edit requires the language service".to_string())?;
+                let language_service = app
+                    .state::<crate::app_state::AppState>()
+                    .language_service
+                    .clone();
+                // Re-index so symbol ranges reflect what's on disk right now
+                language_service
+                    .index_file(&patch.file_path)
+                    .map_err(|e| format!("Failed to parse file: {}", e))?;
+                let result = crate::semantic_patch::PatchApplier::new(language_service.clone())
+                    .apply(patch)
+                    .map_err(|e| e.to_string())?;
+                crate::utils::atomic_write(&full_path, result.new_content)
+                    .map_err(|e| format!("Failed to write file: {}", e))?;
+                // Keep symbols current for the next semantic edit of this file
+                if let Err(e) = language_service.index_file(&patch.file_path) {
+                    eprintln!("[AI WORKFLOW] Failed to re-index {}: {}", change.path, e);
+                }
+                Ok(())
+            }
+            ChangeType::DeleteFile { .. } => {
+                // Can't "apply" delete safely in a way that is easily undoable without manual backup?
+                // Or we just do it. But logic for undo needs content.
+                // For now, let's DELAY delete or apply it?
+                // User said "AI-applied changes are immediately written".
+                // So we delete it.
+                fs::remove_file(&full_path).map_err(|e| format!("Failed to delete file: {}", e))?;
+                Ok(())
+            }
+        }
+    })();
+
+    if let Err(e) = apply_result {
+        eprintln!("[AI WORKFLOW] Failed to auto-apply change: {}", e);
+        return Err(e);
+    }
+
+    println!("[AI WORKFLOW] Auto-applied change to {}", change.path);
+    if let Some(app) = &context.app_handle {
+        use tauri::Manager;
+        let state = app.state::<crate::app_state::AppState>();
+
+        // Track as uncommitted change if we have a snapshot
+        if let Some(snap_id) = &snapshot_id {
+            // Read new content for diff
+            let new_content = read_text(&full_path);
+            let diff = diffy::create_patch(&original_content, &new_content).to_string();
+            let (added, removed) = crate::uncommitted_changes::count_diff_stats(&diff);
+
+            let uncommitted = crate::uncommitted_changes::UncommittedChange {
+                id: change_id.to_string(),
+                file_path: full_path.clone(),
+                snapshot_id: snap_id.clone(),
+                unified_diff: diff,
+                added_lines: added,
+                removed_lines: removed,
+                timestamp: std::time::SystemTime::now()
+                    .duration_since(std::time::UNIX_EPOCH)
+                    .unwrap_or_default()
+                    .as_millis() as u64,
+            };
+            state.uncommitted_changes.track(uncommitted);
+            println!(
+                "[UNCOMMITTED] Tracking change {} for {}",
+                change_id, change.path
+            );
+        }
+
+        let abs_path_str = full_path.to_string_lossy().to_string();
+        let _ = app.emit("refresh-explorer", ());
+        if open_in_editor {
+            let _ = app.emit("open-file", &abs_path_str);
+        }
+        let _ = app.emit(
+            crate::events::event_names::CHANGE_APPLIED,
+            crate::events::ChangeAppliedPayload {
+                change_id: change_id.to_string(),
+                file_path: abs_path_str.clone(),
+            },
+        );
+    }
+    Ok(!existed_before)
+}
+
+/// Surface a parse failure to the user when it was the workspace sandbox refusing the path
+fn report_if_blocked_write<R: tauri::Runtime>(
+    app_handle: Option<&tauri::AppHandle<R>>,
+    call: &crate::protocol::ToolCall,
+    error: &str,
+) {
+    if !tools::is_outside_workspace_error(error) {
+        return;
+    }
+    let path = serde_json::from_str::<serde_json::Value>(&call.function.arguments)
+        .ok()
+        .and_then(|v| {
+            ["path", "file_path", "filepath", "filename"]
+                .iter()
+                .find_map(|k| v.get(*k).and_then(|p| p.as_str()).map(|s| s.to_string()))
+        })
+        .unwrap_or_default();
+    tools::report_blocked_write(app_handle, &call.function.name, &path);
+}
+
+const SKIPPED_AFTER_FAILURE: &str =
+    "Change skipped: an earlier change in this batch failed and the batch was rolled back";
+
 fn parse_run_command_args(raw_args: &str) -> Result<(String, Option<String>), String> {
     let v: serde_json::Value =
         serde_json::from_str(raw_args).map_err(|e| format!("invalid tool args json: {e}"))?;
@@ -863,10 +1342,13 @@ fn should_block_irrelevant_language_scan(
     None
 }
 
-pub fn run_command_in_workspace(
+/// Run `command` with the user's shell and wait for it, killing it after the
+/// project's `command_timeout_secs`
+pub async fn run_command_in_workspace(
     workspace_root: &Path,
     command: &str,
     cwd: Option<&str>,
+    shell: &crate::config::ShellSettings,
 ) -> tools::ToolResult {
     let ws = match fs::canonicalize(workspace_root) {
         Ok(p) => p,
@@ -876,6 +1358,7 @@ pub fn run_command_in_workspace(
                 content: String::new(),
                 error: Some(e.to_string()),
                 skipped: false,
+                error_kind: None,
             };
         }
     };
@@ -900,6 +1383,7 @@ pub fn run_command_in_workspace(
                         e
                     )),
                     skipped: false,
+                    error_kind: None,
                 };
             }
         };
@@ -913,6 +1397,7 @@ pub fn run_command_in_workspace(
                     candidate.display()
                 )),
                 skipped: false,
+                error_kind: None,
             };
         }
         candidate
@@ -920,39 +1405,68 @@ pub fn run_command_in_workspace(
         ws.clone()
     };
 
-    let output = Command::new("sh")
-        .arg("-lc")
-        .arg(command)
+    let settings = crate::project_settings::load_project_settings_or_default(&ws);
+    let mut cmd = tokio::process::Command::new(shell.program());
+    cmd.args(shell.command_args(command))
         .current_dir(&dir)
-        .output();
+        .kill_on_drop(true);
+    for name in crate::project_settings::denied_command_env(Some(&ws)) {
+        cmd.env_remove(name);
+    }
+    let output = match settings.command_timeout_secs {
+        0 => cmd.output().await,
+        secs => match tokio::time::timeout(Duration::from_secs(secs), cmd.output()).await {
+            Ok(output) => output,
+            Err(_) => {
+                eprintln!("[COMMAND EXEC] Timed out after {}s: {}", secs, command);
+                let message = crate::commands::tools::command_timeout_message(secs);
+                return tools::ToolResult {
+                    success: false,
+                    content: format!(
+                        "exit_code: Some({})\n{}\n",
+                        crate::commands::tools::COMMAND_TIMEOUT_EXIT_CODE,
+                        message
+                    ),
+                    error: Some(message),
+                    skipped: false,
+                    error_kind: None,
+                };
+            }
+        },
+    };
 
     match output {
         Ok(out) => {
-            let mut s = String::new();
-            s.push_str(&format!("exit_code: {:?}\n", out.status.code()));
+            let mut body = String::new();
             if !out.stdout.is_empty() {
-                s.push_str("stdout:\n");
-                s.push_str(&String::from_utf8_lossy(&out.stdout));
-                if !s.ends_with('\n') {
-                    s.push('\n');
+                body.push_str("stdout:\n");
+                body.push_str(&String::from_utf8_lossy(&out.stdout));
+                if !body.ends_with('\n') {
+                    body.push('\n');
                 }
             }
             if !out.stderr.is_empty() {
-                s.push_str("stderr:\n");
-                s.push_str(&String::from_utf8_lossy(&out.stderr));
-                if !s.ends_with('\n') {
-                    s.push('\n');
+                body.push_str("stderr:\n");
+                body.push_str(&String::from_utf8_lossy(&out.stderr));
+                if !body.ends_with('\n') {
+                    body.push('\n');
                 }
             }
-            if s.len() > 50_000 {
-                s.truncate(50_000);
-                s.push_str("\n...truncated...\n");
+            // Same tail as commands run in the terminal
+            let mut s = format!("exit_code: {:?}\n", out.status.code());
+            s.push_str(&crate::commands::tools::tail_lines(
+                &body,
+                settings.command_output_tail_lines,
+            ));
+            if !s.ends_with('\n') {
+                s.push('\n');
             }
             tools::ToolResult {
                 success: out.status.success(),
                 content: s,
                 error: None,
                 skipped: false,
+                error_kind: None,
             }
         }
         Err(e) => tools::ToolResult {
@@ -960,6 +1474,167 @@ pub fn run_command_in_workspace(
             content: String::new(),
             error: Some(e.to_string()),
             skipped: false,
+            error_kind: None,
         },
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn info_call(id: &str, args: &str) -> ToolCall {
+        ToolCall {
+            id: id.to_string(),
+            typ: "function".to_string(),
+            function: crate::protocol::ToolFunction {
+                name: "get_file_info".to_string(),
+                arguments: args.to_string(),
+            },
+            status: None,
+            result: None,
+        }
+    }
+
+    fn run_batch(workflow: &mut AiWorkflow, root: &Path, calls: Vec<ToolCall>) -> PendingToolBatch {
+        let context = ToolExecutionContext::<tauri::Wry> {
+            workspace_root: Some(root.to_string_lossy().to_string()),
+            active_file: None,
+            active_tab_index: 0,
+            open_files: vec![],
+            cursor_line: None,
+            cursor_column: None,
+            selection_start_line: None,
+            selection_end_line: None,
+            app_handle: None,
+            conversation_id: None,
+        };
+        workflow
+            .handle_tool_calls(root, calls, None, &context)
+            .unwrap()
+    }
+
+    #[test]
+    fn test_same_tool_different_args_is_not_a_loop() {
+        let dir = tempfile::tempdir().unwrap();
+        for name in ["a.txt", "b.txt", "c.txt"] {
+            fs::write(dir.path().join(name), "x").unwrap();
+        }
+        let mut workflow = AiWorkflow::new();
+
+        for round in 0..2 {
+            let calls = ["a.txt", "b.txt", "c.txt"]
+                .iter()
+                .map(|f| {
+                    info_call(
+                        &format!("{}-{}", round, f),
+                        &format!(r#"{{"path": "{}"}}"#, f),
+                    )
+                })
+                .collect();
+            let batch = run_batch(&mut workflow, dir.path(), calls);
+            assert_eq!(batch.stop_reason, None);
+        }
+
+        // Whitespace doesn't make arguments different
+        let batch = run_batch(
+            &mut workflow,
+            dir.path(),
+            vec![info_call("again", r#"{ "path":"a.txt" }"#)],
+        );
+        assert_eq!(batch.stop_reason, Some(StopReason::LoopDetected));
+    }
+
+    #[test]
+    fn test_misrouted_server_tool_stops_the_loop() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut workflow = AiWorkflow::new();
+        let mut call = info_call("todo", r#"{"todos": []}"#);
+        call.function.name = "todo_write".to_string();
+
+        let batch = run_batch(&mut workflow, dir.path(), vec![call]);
+        assert_eq!(batch.stop_reason, Some(StopReason::MisroutedTool));
+        let (_, result) = &batch.file_results[0];
+        assert!(!result.success);
+        assert_eq!(
+            result.error_kind,
+            Some(tools::ToolErrorKind::ServerSideToolMisrouted)
+        );
+    }
+
+    #[test]
+    fn test_loop_detection_settings() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("a.txt"), "x").unwrap();
+        crate::project_settings::save_project_settings(
+            dir.path(),
+            &crate::project_settings::ProjectSettings {
+                loop_detection: crate::project_settings::LoopDetectionSettings {
+                    repeat_threshold: 2,
+                    exempt_tools: vec!["get_file_info".to_string()],
+                },
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        let mut workflow = AiWorkflow::new();
+
+        for i in 0..4 {
+            let call = info_call(&i.to_string(), r#"{"path": "a.txt"}"#);
+            let batch = run_batch(&mut workflow, dir.path(), vec![call]);
+            assert_eq!(batch.stop_reason, None);
+        }
+    }
+
+    #[test]
+    fn test_tool_call_limit_stops_the_loop() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("a.txt"), "x").unwrap();
+        crate::project_settings::save_project_settings(
+            dir.path(),
+            &crate::project_settings::ProjectSettings {
+                max_tool_calls_per_message: 1,
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        let mut workflow = AiWorkflow::new();
+
+        let calls = vec![
+            info_call("first", r#"{"path": "a.txt"}"#),
+            info_call("second", r#"{"path": "b.txt"}"#),
+        ];
+        let batch = run_batch(&mut workflow, dir.path(), calls);
+        assert_eq!(batch.stop_reason, Some(StopReason::ToolCallLimit));
+        let result = |id: &str| {
+            let (_, result) = batch.file_results.iter().find(|(c, _)| c.id == id).unwrap();
+            result.success
+        };
+        assert!(result("first"));
+        assert!(!result("second"));
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_run_command_keeps_output_tail() {
+        let dir = tempfile::tempdir().unwrap();
+        let settings = crate::project_settings::ProjectSettings {
+            command_output_tail_lines: 2,
+            ..Default::default()
+        };
+        crate::project_settings::save_project_settings(dir.path(), &settings).unwrap();
+        let shell = crate::config::ShellSettings {
+            shell_path: Some("/bin/sh".to_string()),
+            login_shell: false,
+            extra_args: Vec::new(),
+        };
+
+        let result =
+            run_command_in_workspace(dir.path(), "printf '1\\n2\\n3\\n'", None, &shell).await;
+        assert!(result.success);
+        assert_eq!(
+            result.content,
+            "exit_code: Some(0)\n[... 2 earlier lines omitted ...]\n2\n3\n"
+        );
+    }
+}
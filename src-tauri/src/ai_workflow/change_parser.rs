@@ -1,4 +1,5 @@
 use serde_json::Value;
+use std::collections::HashMap;
 use std::fs;
 use std::path::Path;
 
@@ -9,58 +10,50 @@ pub fn parse_change_args(
     workspace_root: &Path,
     tool_name: &str,
 ) -> Result<PendingChange, String> {
-    let v: Value =
+    let mut obj: HashMap<String, Value> =
         serde_json::from_str(raw_args).map_err(|e| format!("invalid tool args json: {}", e))?;
+    // edit_file takes the same shapes as apply_edit here: patches, or old/new text
+    // with the old text optional
+    let schema = if tool_name == "edit_file" {
+        "apply_edit"
+    } else {
+        tool_name
+    };
+    crate::tool_execution::validate_tool_args(schema, &mut obj)?;
 
-    let obj = v
-        .as_object()
-        .ok_or_else(|| "invalid args: expected object".to_string())?;
-
-    // Get path
-    let path = obj
-        .get("path")
-        .or_else(|| obj.get("file_path"))
-        .or_else(|| obj.get("filepath"))
-        .or_else(|| obj.get("filename"))
-        .and_then(|v| v.as_str())
+    let str_arg = |key: &str| obj.get(key).and_then(|v| v.as_str());
+    let path = str_arg("path")
         .ok_or_else(|| "missing required arg: path".to_string())?
         .to_string();
 
-    // Validate path is under workspace
-    let ws = fs::canonicalize(workspace_root).map_err(|e| e.to_string())?;
-    let requested = Path::new(&path);
-    let target = if requested.is_absolute() {
-        requested.to_path_buf()
-    } else {
-        ws.join(requested)
-    };
-
-    // For new files, the file itself might not exist, so we can't canonicalize it directly.
-    // Let's try to canonicalize the PARENT directory if possible to ensure it's in workspace
-    if let Some(parent) = target.parent() {
-        if parent.exists() {
-            if let Ok(canon_parent) = fs::canonicalize(parent) {
-                if !canon_parent.starts_with(&ws) {
-                    return Err("path (parent) is outside workspace".to_string());
-                }
-            }
-        }
-    }
+    // Validate path is under workspace (doesn't require the file to exist yet)
+    let target = crate::tools::resolve_path_in_workspace(workspace_root, Path::new(&path))?;
 
     // Determine change type based on tool name and file existence
     let change_type = match tool_name {
         "delete_file" => ChangeType::DeleteFile { old_content: None },
         "write_file" | "create_file" => {
             // Always a new file for these tools
-            let content = obj
-                .get("content")
-                .or_else(|| obj.get("contents"))
-                .or_else(|| obj.get("text"))
-                .and_then(|v| v.as_str())
+            let content = str_arg("content")
                 .ok_or_else(|| "missing required arg: content".to_string())?
                 .to_string();
             ChangeType::NewFile { content }
         }
+        "edit_lines" => {
+            // Synthesized as a whole-file patch so the range is unambiguous
+            // and the diff preview works like any other edit
+            let new_text = str_arg("new_content")
+                .ok_or_else(|| "missing required arg: new_content".to_string())?;
+            let (start_line, end_line) = crate::tools::line_range_args(&obj)?;
+            let old_content =
+                fs::read_to_string(&target).map_err(|e| format!("Failed to read file: {}", e))?;
+            let new_content =
+                crate::tools::replace_line_range(&old_content, start_line, end_line, new_text)?;
+            ChangeType::Patch {
+                old_content,
+                new_content,
+            }
+        }
         "edit_file" | "apply_edit" | "apply_patch" => {
             // Check for new multi-patch format FIRST
             if let Some(patches_value) = obj.get("patches") {
@@ -106,25 +99,15 @@ pub fn parse_change_args(
             }
 
             // Fall back to legacy single-patch format
-            let old_content = obj
-                .get("old_content")
-                .or_else(|| obj.get("old"))
-                .or_else(|| obj.get("from"))
-                .or_else(|| obj.get("old_text"))
-                .and_then(|v| v.as_str())
+            let old_content = str_arg("old_text")
                 .map(|s| s.to_string())
                 .unwrap_or_else(|| {
                     // Try to read existing file content
                     fs::read_to_string(&target).unwrap_or_default()
                 });
 
-            let new_content = obj
-                .get("new_content")
-                .or_else(|| obj.get("new"))
-                .or_else(|| obj.get("to"))
-                .or_else(|| obj.get("new_text"))
-                .and_then(|v| v.as_str())
-                .ok_or_else(|| "missing required arg: new_content (or patches array)".to_string())?
+            let new_content = str_arg("new_text")
+                .ok_or_else(|| "missing required arg: new_text (or patches array)".to_string())?
                 .to_string();
 
             ChangeType::Patch {
@@ -132,6 +115,10 @@ pub fn parse_change_args(
                 new_content,
             }
         }
+        "semantic_edit" => {
+            let patch = crate::tools::build_semantic_patch(&obj, &target.to_string_lossy())?;
+            ChangeType::Semantic { patch }
+        }
         _ => {
             return Err(format!(
                 "unsupported tool for change parsing: {}",
@@ -157,3 +144,99 @@ pub fn parse_change_args(
         error: None,
     })
 }
+
+/// Whether a `replace_in_files` call only asks for a preview
+pub fn is_dry_run(raw_args: &str) -> bool {
+    serde_json::from_str::<Value>(raw_args)
+        .ok()
+        .and_then(|v| v.get("dry_run").and_then(|d| d.as_bool()))
+        .unwrap_or(false)
+}
+
+/// Per-file changes for a `replace_in_files` call. Each file becomes a single whole-file
+/// hunk, so it is snapshotted, applied and reviewed like any other edit.
+pub fn parse_replace_in_files(
+    raw_args: &str,
+    workspace_root: &Path,
+) -> Result<Vec<(PendingChange, crate::tools::FileReplacement)>, String> {
+    let args: std::collections::HashMap<String, Value> =
+        serde_json::from_str(raw_args).map_err(|e| format!("invalid tool args json: {}", e))?;
+    let replacements = crate::tools::plan_replace_in_files(workspace_root, &args)?;
+    Ok(replacements
+        .into_iter()
+        .map(|r| {
+            let change = PendingChange {
+                call: crate::protocol::ToolCall {
+                    id: String::new(), // Will be filled by caller
+                    typ: "function".to_string(),
+                    function: crate::protocol::ToolFunction {
+                        name: String::new(),
+                        arguments: String::new(),
+                    },
+                    status: Some("executing".to_string()),
+                    result: None,
+                },
+                path: r.path.clone(),
+                change_type: ChangeType::MultiPatch {
+                    patches: vec![PatchHunk {
+                        old_text: r.old_content.clone(),
+                        new_text: r.new_content.clone(),
+                        start_line: None,
+                        end_line: None,
+                    }],
+                },
+                applied: false,
+                error: None,
+            };
+            (change, r)
+        })
+        .collect())
+}
+
+/// Full-file patch for a `regex_replace` call, with the number of replacements made
+pub fn parse_regex_replace(
+    raw_args: &str,
+    workspace_root: &Path,
+) -> Result<(PendingChange, usize), String> {
+    let v: Value =
+        serde_json::from_str(raw_args).map_err(|e| format!("invalid tool args json: {}", e))?;
+    let str_arg = |keys: &[&str]| {
+        keys.iter()
+            .find_map(|k| v.get(*k).and_then(|a| a.as_str()))
+            .map(|s| s.to_string())
+    };
+    let path = str_arg(&["path", "file_path", "filepath", "filename"])
+        .ok_or_else(|| "missing required arg: path".to_string())?;
+    let pattern = str_arg(&["pattern", "regex"])
+        .ok_or_else(|| "missing required arg: pattern".to_string())?;
+    let replacement = str_arg(&["replacement", "replace"])
+        .ok_or_else(|| "missing required arg: replacement".to_string())?;
+    let count = v.get("count").and_then(|c| c.as_u64()).map(|c| c as usize);
+
+    let target = crate::tools::resolve_path_in_workspace(workspace_root, Path::new(&path))?;
+    let old_content =
+        fs::read_to_string(&target).map_err(|e| format!("Failed to read file: {}", e))?;
+    let (new_content, replaced) =
+        crate::tools::regex_replace_content(&old_content, &pattern, &replacement, count)?;
+
+    let change = PendingChange {
+        call: crate::protocol::ToolCall {
+            id: String::new(), // Will be filled by caller
+            typ: "function".to_string(),
+            function: crate::protocol::ToolFunction {
+                name: String::new(),
+                arguments: String::new(),
+            },
+            status: Some("executing".to_string()),
+            result: None,
+        },
+        path,
+        change_type: ChangeType::Patch {
+            old_content,
+            new_content,
+        },
+        applied: false,
+        error: None,
+    };
+    Ok((change, replaced))
+}
@@ -1,4 +1,5 @@
 use serde_json::Value;
+use std::path::Path;
 
 /// Tool definitions for zblade's internal tool execution.
 ///
@@ -60,6 +61,72 @@ pub fn get_tool_definitions() -> Vec<Value> {
                 }
             }
         }),
+        serde_json::json!({
+            "type": "function",
+            "name": "edit_lines",
+            "function": {
+                "name": "edit_lines",
+                "description": "Replace a line range (1-indexed, inclusive) of a file with new content",
+                "strict": false,
+                "parameters": {
+                    "type": "object",
+                    "properties": {
+                        "path": { "type": "string", "description": "File path" },
+                        "start_line": { "type": "integer", "description": "First line to replace (1-indexed)" },
+                        "end_line": { "type": "integer", "description": "Last line to replace (inclusive)" },
+                        "new_content": { "type": "string", "description": "Replacement text; empty deletes the lines" }
+                    },
+                    "required": ["path", "start_line", "end_line", "new_content"],
+                    "additionalProperties": false
+                }
+            }
+        }),
+        serde_json::json!({
+            "type": "function",
+            "name": "preview_patch",
+            "function": {
+                "name": "preview_patch",
+                "description": "Dry-run a search/replace edit and return the match strategy and unified diff without writing",
+                "strict": false,
+                "parameters": {
+                    "type": "object",
+                    "properties": {
+                        "path": { "type": "string", "description": "File path" },
+                        "old_text": { "type": "string", "description": "Text to find and replace" },
+                        "new_text": { "type": "string", "description": "Replacement text" }
+                    },
+                    "required": ["path", "old_text", "new_text"],
+                    "additionalProperties": false
+                }
+            }
+        }),
+        serde_json::json!({
+            "type": "function",
+            "name": "semantic_edit",
+            "function": {
+                "name": "semantic_edit",
+                "description": "Edit a symbol by name: replace its body or insert code before/after it",
+                "strict": false,
+                "parameters": {
+                    "type": "object",
+                    "properties": {
+                        "path": { "type": "string", "description": "File path" },
+                        "target": {
+                            "type": "object",
+                            "properties": {
+                                "symbol_kind": { "type": "string", "description": "Symbol kind (function, method, class, struct, ...)" },
+                                "symbol_name": { "type": "string", "description": "Symbol name" }
+                            },
+                            "required": ["symbol_name"]
+                        },
+                        "operation": { "type": "string", "enum": ["replace_body", "insert_before", "insert_after"], "description": "Edit to perform" },
+                        "code": { "type": "string", "description": "New body (including braces) or code to insert" }
+                    },
+                    "required": ["path", "target", "operation", "code"],
+                    "additionalProperties": false
+                }
+            }
+        }),
         serde_json::json!({
             "type": "function",
             "name": "get_workspace_structure",
@@ -96,6 +163,26 @@ pub fn get_tool_definitions() -> Vec<Value> {
                 }
             }
         }),
+        serde_json::json!({
+            "type": "function",
+            "name": "read_files_glob",
+            "function": {
+                "name": "read_files_glob",
+                "description": "Read every file matching a glob pattern in one call",
+                "strict": false,
+                "parameters": {
+                    "type": "object",
+                    "properties": {
+                        "pattern": { "type": "string", "description": "Glob pattern (e.g. src/auth/**/*.rs)" },
+                        "path": { "type": "string", "description": "Directory the pattern is relative to (default workspace root)" },
+                        "max_files": { "type": "integer", "description": "Maximum files to return (default 20)" },
+                        "max_bytes_per_file": { "type": "integer", "description": "Skip files larger than this (default 65536)" }
+                    },
+                    "required": ["pattern"],
+                    "additionalProperties": false
+                }
+            }
+        }),
         serde_json::json!({
             "type": "function",
             "name": "write_file",
@@ -114,6 +201,46 @@ pub fn get_tool_definitions() -> Vec<Value> {
                 }
             }
         }),
+        serde_json::json!({
+            "type": "function",
+            "name": "regex_replace",
+            "function": {
+                "name": "regex_replace",
+                "description": "Regex replace within one file; the replacement may use $1 / ${name} backreferences",
+                "strict": false,
+                "parameters": {
+                    "type": "object",
+                    "properties": {
+                        "path": { "type": "string", "description": "File path" },
+                        "pattern": { "type": "string", "description": "Regex to search for" },
+                        "replacement": { "type": "string", "description": "Replacement text" },
+                        "count": { "type": "integer", "description": "Replace only the first N matches (default all)" }
+                    },
+                    "required": ["path", "pattern", "replacement"],
+                    "additionalProperties": false
+                }
+            }
+        }),
+        serde_json::json!({
+            "type": "function",
+            "name": "replace_in_files",
+            "function": {
+                "name": "replace_in_files",
+                "description": "Regex find-and-replace across workspace files; use dry_run to preview affected files first",
+                "strict": false,
+                "parameters": {
+                    "type": "object",
+                    "properties": {
+                        "pattern": { "type": "string", "description": "Regex to search for" },
+                        "replacement": { "type": "string", "description": "Replacement text ($1 / ${name} insert capture groups)" },
+                        "file_glob": { "type": "string", "description": "Files to search (default **/*)" },
+                        "dry_run": { "type": "boolean", "description": "Only report affected files and match counts (default false)" }
+                    },
+                    "required": ["pattern", "replacement"],
+                    "additionalProperties": false
+                }
+            }
+        }),
         serde_json::json!({
             "type": "function",
             "name": "rg",
@@ -125,13 +252,51 @@ pub fn get_tool_definitions() -> Vec<Value> {
                     "type": "object",
                     "properties": {
                         "pattern": { "type": "string", "description": "Search pattern" },
-                        "path": { "type": "string", "description": "Search path" }
+                        "path": { "type": "string", "description": "Search path" },
+                        "format": { "type": "string", "enum": ["json", "text"], "description": "Result format (defaults to json with line/column ranges)" }
                     },
                     "required": ["pattern", "path"],
                     "additionalProperties": false
                 }
             }
         }),
+        serde_json::json!({
+            "type": "function",
+            "name": "find_references",
+            "function": {
+                "name": "find_references",
+                "description": "Find the definition and all usage sites of a symbol (ignores comments and strings)",
+                "strict": false,
+                "parameters": {
+                    "type": "object",
+                    "properties": {
+                        "name": { "type": "string", "description": "Symbol name" },
+                        "path": { "type": "string", "description": "File containing the definition (optional)" },
+                        "line": { "type": "integer", "description": "Line of the definition, 1-indexed (optional)" }
+                    },
+                    "required": ["name"],
+                    "additionalProperties": false
+                }
+            }
+        }),
+        serde_json::json!({
+            "type": "function",
+            "name": "semantic_search",
+            "function": {
+                "name": "semantic_search",
+                "description": "Find code by meaning using local embeddings (requires Ollama); use when regex search can't express the query",
+                "strict": false,
+                "parameters": {
+                    "type": "object",
+                    "properties": {
+                        "query": { "type": "string", "description": "Natural-language description of the code to find" },
+                        "max_results": { "type": "integer", "description": "Maximum chunks to return (default 10)" }
+                    },
+                    "required": ["query"],
+                    "additionalProperties": false
+                }
+            }
+        }),
         serde_json::json!({
             "type": "function",
             "name": "list_dir",
@@ -149,6 +314,26 @@ pub fn get_tool_definitions() -> Vec<Value> {
                 }
             }
         }),
+        serde_json::json!({
+            "type": "function",
+            "name": "diff_files",
+            "function": {
+                "name": "diff_files",
+                "description": "Unified diff of two workspace files, or of one file against git HEAD",
+                "strict": false,
+                "parameters": {
+                    "type": "object",
+                    "properties": {
+                        "left_path": { "type": "string", "description": "Original file" },
+                        "right_path": { "type": "string", "description": "Changed file" },
+                        "path": { "type": "string", "description": "File to compare (with against)" },
+                        "against": { "type": "string", "enum": ["head"], "description": "Compare path against git HEAD" }
+                    },
+                    "required": [],
+                    "additionalProperties": false
+                }
+            }
+        }),
         serde_json::json!({
             "type": "function",
             "name": "run_command",
@@ -170,3 +355,20 @@ pub fn get_tool_definitions() -> Vec<Value> {
         // Note: todo_write is server-side only (handled by zcoderd)
     ]
 }
+
+/// Tool definitions minus the tools the project at `workspace` has disabled,
+/// so local models aren't offered calls that would only be refused
+pub fn get_enabled_tool_definitions(workspace: Option<&Path>) -> Vec<Value> {
+    let settings = workspace
+        .map(crate::project_settings::load_project_settings_or_default)
+        .unwrap_or_default()
+        .tools;
+    get_tool_definitions()
+        .into_iter()
+        .filter(|def| {
+            def["function"]["name"]
+                .as_str()
+                .is_some_and(|name| settings.is_enabled(name))
+        })
+        .collect()
+}
@@ -11,6 +11,7 @@ use crate::workspace_manager::WorkspaceManager;
 use crate::ws_connection_manager::WsConnectionManager;
 use dotenvy::dotenv;
 use notify::RecommendedWatcher;
+use std::path::Path;
 use std::sync::{Arc, Mutex};
 
 pub struct AppState {
@@ -24,6 +25,7 @@ pub struct AppState {
     pub pending_approval: Mutex<Option<tokio::sync::oneshot::Sender<bool>>>,
     pub pending_batch: Mutex<Option<crate::ai_workflow::PendingToolBatch>>,
     pub selected_model_index: Mutex<usize>,
+    pub selected_model_id: Mutex<Option<String>>, // Registry ID of the selected model, for project state
     pub ephemeral_docs: ephemeral_documents::EphemeralDocumentStore,
     pub active_file: Mutex<Option<String>>,
     pub open_files: Mutex<Vec<String>>,
@@ -37,9 +39,14 @@ pub struct AppState {
         Mutex<std::collections::HashMap<String, std::sync::Arc<std::sync::atomic::AtomicBool>>>,
     >,
     pub idempotency_cache: crate::idempotency::IdempotencyCache, // v1.1: Idempotency support
-    pub warmup_client: warmup::WarmupClient,                     // v2.1: Cache warmup
+    pub warmup_client: Mutex<Arc<warmup::WarmupClient>>,         // v2.1: Cache warmup
+    pub blade_http: reqwest::Client, // Shared HTTP client for Blade history/ZLP requests (pooled)
+    pub blocked_writes: std::sync::atomic::AtomicU64, // Writes refused for targeting paths outside the workspace
+    pub history_page_cache: Mutex<Option<crate::blade_protocol::FullConversation>>, // Conversation being paged in the History panel
     pub user_id: Mutex<Option<String>>, // Authenticated user ID from WebSocket
     pub fs_watcher: Mutex<Option<RecommendedWatcher>>, // Workspace file watcher
+    pub watch_paths: Mutex<Vec<std::path::PathBuf>>, // Extra/scoped watch paths for the current workspace
+    pub project_state_autosave: crate::project_state::Autosave, // Debounced save of editor state
     pub history_service: std::sync::Arc<crate::history::HistoryService>, // File history service
     pub language_service: std::sync::Arc<crate::language_service::LanguageService>, // v1.3: Unified Language Service
     pub language_handler: crate::language_service::LanguageHandler, // v1.3: Language Intent Handler
@@ -50,6 +57,9 @@ pub struct AppState {
     pub active_tab_id: Mutex<Option<String>>, // Headless: active tab ID
     pub ws_connection: Arc<WsConnectionManager>, // Persistent WebSocket connection to zcoderd
     pub pending_error_feedback: Mutex<Option<String>>, // Recovery hint to prepend to next user message
+    pub generation_cancel: Mutex<Arc<std::sync::atomic::AtomicBool>>, // Stop flag for the current send_message poll loop
+    pub tool_log: crate::tool_execution::ToolInvocationLog, // Per-conversation record of executed tool calls
+    pub read_only_mode: std::sync::atomic::AtomicBool, // Plan mode: mutating tools are refused
 }
 
 impl AppState {
@@ -57,31 +67,14 @@ impl AppState {
         // Load environment variables from .env file
         dotenv().ok();
 
-        // Load config from disk
+        // Load config from disk, falling back to environment variables for empty fields
         let config_path = config::default_api_config_path();
-        let mut config = config::load_api_config(&config_path);
+        let mut config = config::resolve_api_config(&config_path);
 
         if let Err(e) = config::ensure_global_prompts_dir() {
             eprintln!("[CONFIG] Failed to ensure global prompts directory: {}", e);
         }
 
-        // Fallback or override logic:
-        // If config.blade_url is empty, use default or check environment variable.
-        if config.blade_url.trim().is_empty() {
-            if let Ok(url) = std::env::var("BLADE_URL") {
-                config.blade_url = url;
-            } else {
-                config.blade_url = "https://coder.zaguanai.com".to_string();
-            }
-        }
-
-        // Fallback for api_key from environment variable
-        if config.api_key.trim().is_empty() {
-            if let Ok(key) = std::env::var("BLADE_API_KEY") {
-                config.api_key = key;
-            }
-        }
-
         // Initialize selected model index from config
         // We can't fetch models synchronously here, so we default to 0
         // The actual index will be corrected when models are fetched or when set_selected_model is called
@@ -109,21 +102,39 @@ impl AppState {
             &storage_path.parent().unwrap(),
         ));
 
+        let tool_log = crate::tool_execution::ToolInvocationLog::new(
+            crate::tool_execution::log::DEFAULT_MAX_ENTRIES,
+            Some(storage_path.join("tool_logs")),
+        );
+
         let mut workspace_manager = WorkspaceManager::new();
         // Override workspace if provided via CLI
         if let Some(path_str) = &initial_path {
             workspace_manager.set_workspace(std::path::PathBuf::from(path_str));
         }
 
+        // Seed the approved command roots from the project's persisted allowlist
+        let approved_command_roots = workspace_manager
+            .workspace
+            .as_deref()
+            .map(crate::project_settings::load_command_allowlist)
+            .unwrap_or_default();
+
+        // Project-scoped Blade URL / API key from .zblade/.env win over the global config
+        if let Some(workspace) = workspace_manager.workspace.as_deref() {
+            config::load_project_api_overrides(workspace).apply(&mut config);
+        }
+
         // Get or create user_id
         let user_id = config::get_or_create_user_id(&config_path);
 
         // Initialize warmup client with config values
-        let warmup_client = warmup::WarmupClient::new(
-            config.blade_url.clone(),
+        let endpoints = blade_endpoints(&config);
+        let warmup_client = Arc::new(warmup::WarmupClient::new(
+            endpoints.http_base.clone(),
             config.api_key.clone(),
             user_id.clone(),
-        );
+        ));
 
         // Initialize Language Service
         let db_path = dirs::data_dir()
@@ -133,7 +144,11 @@ impl AppState {
         if let Some(parent) = db_path.parent() {
             std::fs::create_dir_all(parent).ok();
         }
-        let ls_root = initial_path.as_deref().unwrap_or(".");
+        // Follows the restored workspace too; open_workspace re-points it on switch
+        let ls_root = workspace_manager
+            .workspace
+            .clone()
+            .unwrap_or_else(|| std::path::PathBuf::from("."));
 
         let symbol_store = std::sync::Arc::new(
             crate::symbol_index::store::SymbolStore::new(&db_path)
@@ -141,11 +156,8 @@ impl AppState {
         );
 
         let language_service = std::sync::Arc::new(
-            crate::language_service::LanguageService::new(
-                std::path::PathBuf::from(ls_root),
-                symbol_store,
-            )
-            .expect("Failed to initialize Language Service"),
+            crate::language_service::LanguageService::new(ls_root, symbol_store)
+                .expect("Failed to initialize Language Service"),
         );
 
         // Initialize Language Handler
@@ -159,12 +171,12 @@ impl AppState {
 
         // Initialize WebSocket connection manager before config is moved
         let ws_connection = Arc::new(WsConnectionManager::new(
-            config.blade_url.clone(),
+            endpoints.ws_base.clone(),
             config.api_key.clone(),
         ));
 
         Self {
-            chat_manager: Mutex::new(ChatManager::new(50)),
+            chat_manager: Mutex::new(ChatManager::new(crate::project_settings::DEFAULT_MAX_TURNS)),
             conversation: Mutex::new(ConversationHistory::new()),
             conversation_store: Mutex::new(conversation_store),
             workspace: Mutex::new(workspace_manager),
@@ -173,6 +185,7 @@ impl AppState {
             pending_approval: Mutex::new(None),
             pending_batch: Mutex::new(None),
             selected_model_index: Mutex::new(initial_model_index),
+            selected_model_id: Mutex::new(None),
             ephemeral_docs: ephemeral_documents::EphemeralDocumentStore::new(),
             active_file: Mutex::new(None),
             open_files: Mutex::new(Vec::new()),
@@ -182,11 +195,16 @@ impl AppState {
             selection_start_line: Mutex::new(None),
             selection_end_line: Mutex::new(None),
             // virtual_buffers removed
-            approved_command_roots: Mutex::new(std::collections::HashSet::new()),
+            approved_command_roots: Mutex::new(approved_command_roots),
             executing_commands: std::sync::Arc::new(Mutex::new(std::collections::HashMap::new())),
             idempotency_cache: crate::idempotency::IdempotencyCache::default(), // 24h TTL
-            warmup_client, // v2.1: Cache warmup
+            warmup_client: Mutex::new(warmup_client), // v2.1: Cache warmup
+            blade_http: reqwest::Client::new(),
+            blocked_writes: std::sync::atomic::AtomicU64::new(0),
+            history_page_cache: Mutex::new(None),
             fs_watcher: Mutex::new(None),
+            watch_paths: Mutex::new(Vec::new()),
+            project_state_autosave: crate::project_state::Autosave::new(),
             history_service,
             language_service,
             language_handler,
@@ -197,6 +215,159 @@ impl AppState {
             active_tab_id: Mutex::new(None),
             ws_connection,
             pending_error_feedback: Mutex::new(None),
+            generation_cancel: Mutex::new(Arc::new(std::sync::atomic::AtomicBool::new(false))),
+            tool_log,
+            read_only_mode: std::sync::atomic::AtomicBool::new(false),
+        }
+    }
+
+    /// Reset the approved command roots to the workspace's persisted allowlist.
+    /// Session-scoped approvals are dropped; roots saved via "approve always" survive.
+    pub fn reload_command_allowlist(&self) {
+        let roots = self
+            .workspace
+            .lock()
+            .unwrap()
+            .workspace
+            .as_deref()
+            .map(crate::project_settings::load_command_allowlist)
+            .unwrap_or_default();
+        *self.approved_command_roots.lock().unwrap() = roots;
+    }
+
+    /// Current warmup client; replaced whenever the Blade URL or API key changes
+    pub fn warmup(&self) -> Arc<warmup::WarmupClient> {
+        self.warmup_client.lock().unwrap().clone()
+    }
+
+    /// Blade URL / API key overrides from the open workspace's `.zblade/.env`.
+    /// Untrusted workspaces get none, so merely opening a repository can't redirect
+    /// requests (and the user's conversations) to a server it names.
+    pub fn project_api_overrides(&self) -> config::ProjectApiOverrides {
+        let ws = self.workspace.lock().unwrap();
+        match ws.workspace.as_deref() {
+            Some(workspace) if ws.is_current_trusted() => {
+                config::load_project_api_overrides(workspace)
+            }
+            _ => config::ProjectApiOverrides::default(),
+        }
+    }
+
+    /// Re-resolve the API config for the current workspace
+    /// (trusted project `.zblade/.env` > config file > process env > default),
+    /// rebuilding the warmup client if the Blade endpoint changed.
+    /// Returns whether it did; callers update async resources
+    /// (the persistent WebSocket) themselves.
+    pub fn reload_api_config(&self) -> bool {
+        let mut resolved = config::resolve_api_config(&config::default_api_config_path());
+        self.project_api_overrides().apply(&mut resolved);
+
+        let endpoint_changed = {
+            let mut config = self.config.lock().unwrap();
+            let changed =
+                config.blade_url != resolved.blade_url || config.api_key != resolved.api_key;
+            *config = resolved.clone();
+            changed
+        };
+
+        if endpoint_changed {
+            eprintln!("[CONFIG] Blade endpoint changed to {}", resolved.blade_url);
+            crate::models::registry::clear_cache();
+            let user_id = self.user_id.lock().unwrap().clone().unwrap_or_default();
+            *self.warmup_client.lock().unwrap() = Arc::new(warmup::WarmupClient::new(
+                blade_endpoints(&resolved).http_base,
+                resolved.api_key.clone(),
+                user_id,
+            ));
         }
+
+        endpoint_changed
+    }
+
+    /// Point the persistent WebSocket at the current Blade endpoint
+    pub async fn update_ws_credentials(&self) {
+        let (ws_base, api_key) = {
+            let config = self.config.lock().unwrap();
+            (blade_endpoints(&config).ws_base, config.api_key.clone())
+        };
+        self.ws_connection.update_credentials(ws_base, api_key).await;
+    }
+
+    /// Follow a rename/move in the editor state: open files, the active file and tab paths
+    /// at `old` or below it (directory renames) are rewritten to live under `new`.
+    /// Returns whether anything open was affected, so the UI only hears about those moves.
+    pub fn rename_open_paths(&self, old: &Path, new: &Path) -> bool {
+        let mut renamed = false;
+        for path in self.open_files.lock().unwrap().iter_mut() {
+            if let Some(rebased) = rebase_path(path, old, new) {
+                *path = rebased;
+                renamed = true;
+            }
+        }
+
+        if let Some(active) = self.active_file.lock().unwrap().as_mut() {
+            if let Some(rebased) = rebase_path(active, old, new) {
+                *active = rebased;
+                renamed = true;
+            }
+        }
+
+        for tab in self.tabs.lock().unwrap().iter_mut() {
+            let Some(rebased) = tab.path.as_deref().and_then(|p| rebase_path(p, old, new)) else {
+                continue;
+            };
+            let old_name = tab.path.as_deref().and_then(|p| Path::new(p).file_name());
+            if old_name.is_some_and(|name| name.to_string_lossy() == tab.title) {
+                if let Some(new_name) = Path::new(&rebased).file_name() {
+                    tab.title = new_name.to_string_lossy().into_owned();
+                }
+            }
+            tab.path = Some(rebased);
+            renamed = true;
+        }
+
+        renamed
+    }
+}
+
+/// `path` moved from under `old` to under `new`, or None if it isn't affected
+fn rebase_path(path: &str, old: &Path, new: &Path) -> Option<String> {
+    let rest = Path::new(path).strip_prefix(old).ok()?;
+    let moved = if rest.as_os_str().is_empty() {
+        new.to_path_buf()
+    } else {
+        new.join(rest)
+    };
+    Some(moved.to_string_lossy().into_owned())
+}
+
+/// Endpoints for `cfg`, falling back to the default Blade URL when it's malformed
+fn blade_endpoints(cfg: &ApiConfig) -> config::BladeEndpoints {
+    cfg.normalized().unwrap_or_else(|e| {
+        eprintln!("[CONFIG] {}; using {}", e, config::DEFAULT_BLADE_URL);
+        config::normalize_blade_url(config::DEFAULT_BLADE_URL)
+            .expect("default Blade URL is valid")
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rebase_path_handles_files_and_directories() {
+        let old = Path::new("/ws/src/util");
+        let new = Path::new("/ws/src/helpers");
+
+        assert_eq!(
+            rebase_path("/ws/src/util", old, new).as_deref(),
+            Some("/ws/src/helpers")
+        );
+        assert_eq!(
+            rebase_path("/ws/src/util/mod.rs", old, new).as_deref(),
+            Some("/ws/src/helpers/mod.rs")
+        );
+        assert_eq!(rebase_path("/ws/src/utils.rs", old, new), None);
+        assert_eq!(rebase_path("/ws/src/main.rs", old, new), None);
     }
 }
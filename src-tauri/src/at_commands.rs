@@ -0,0 +1,429 @@
+//! `@command` parsing
+//!
+//! A message starting with `@name <argument>` forces a tool on the server side
+//! (zcoderd runs it directly). Commands live in a `CommandRegistry`, so adding
+//! one is a `register` call rather than another parser branch.
+//!
+//! `@file <path>` is the exception: it is expanded locally, inlining the file
+//! into the message before it is sent.
+
+use serde::Serialize;
+use std::path::Path;
+
+/// Cap on file content inlined by `@file` across one message
+pub const MAX_INLINE_FILE_BYTES: usize = 64 * 1024;
+
+/// One `@command` and the tool it forces
+#[derive(Debug, Clone, Serialize)]
+pub struct AtCommand {
+    /// Name typed after `@`
+    pub name: &'static str,
+    /// Tool the command forces on zcoderd; `None` for commands expanded locally
+    pub tool: Option<&'static str>,
+    /// Placeholder for the argument in usage text, e.g. `query`
+    pub argument: &'static str,
+    pub description: &'static str,
+}
+
+impl AtCommand {
+    pub fn usage(&self) -> String {
+        format!("@{} <{}>", self.name, self.argument)
+    }
+}
+
+/// Why a message that looks like an `@command` won't run as one, for the frontend to show
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct CommandHint {
+    /// The `@word` as typed
+    pub input: String,
+    /// Usage of the command the user most likely meant, e.g. `@research <query>`
+    pub suggestion: String,
+    pub message: String,
+}
+
+/// A message after `@command` parsing
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParsedCommand {
+    pub message: String,
+    /// (tool_name, argument) to force on zcoderd
+    pub forced_tool: Option<(String, String)>,
+    /// Set when the message looks like a mistyped or incomplete command; the
+    /// message is still sent as written
+    pub hint: Option<CommandHint>,
+}
+
+#[derive(Default)]
+pub struct CommandRegistry {
+    commands: Vec<AtCommand>,
+}
+
+lazy_static::lazy_static! {
+    static ref BUILTIN_COMMANDS: CommandRegistry = CommandRegistry::builtin();
+}
+
+impl CommandRegistry {
+    /// The commands zcoderd handles
+    pub fn builtin() -> Self {
+        let mut registry = Self::default();
+        registry.register(AtCommand {
+            name: "research",
+            tool: Some("research"),
+            argument: "query",
+            description: "Research a topic in depth",
+        });
+        registry.register(AtCommand {
+            name: "search",
+            tool: Some("search"),
+            argument: "query",
+            description: "Search the web",
+        });
+        registry.register(AtCommand {
+            name: "web",
+            tool: Some("fetch_url"),
+            argument: "url",
+            description: "Fetch a web page",
+        });
+        registry.register(AtCommand {
+            name: "file",
+            tool: None,
+            argument: "path",
+            description: "Inline a file (or path:start-end lines) into the message",
+        });
+        registry
+    }
+
+    /// Add a command, replacing any with the same name
+    pub fn register(&mut self, command: AtCommand) {
+        self.commands.retain(|c| c.name != command.name);
+        self.commands.push(command);
+    }
+
+    pub fn get(&self, name: &str) -> Option<&AtCommand> {
+        self.commands
+            .iter()
+            .find(|c| c.name.eq_ignore_ascii_case(name))
+    }
+
+    pub fn commands(&self) -> &[AtCommand] {
+        &self.commands
+    }
+
+    /// Parse @command syntax and extract tool name and argument.
+    /// Command names match case-insensitively. A known command missing its
+    /// argument or a near-miss typo adds a hint; other `@mentions` pass through
+    /// untouched.
+    pub fn parse(&self, message: &str) -> ParsedCommand {
+        let mut parsed = ParsedCommand {
+            message: message.to_string(),
+            forced_tool: None,
+            hint: None,
+        };
+        let Some(rest) = message.trim().strip_prefix('@') else {
+            return parsed;
+        };
+        let word_end = rest.find(char::is_whitespace).unwrap_or(rest.len());
+        let (word, argument) = (&rest[..word_end], rest[word_end..].trim());
+
+        if let Some(command) = self.get(word) {
+            if argument.is_empty() {
+                parsed.hint = Some(CommandHint {
+                    input: format!("@{}", word),
+                    suggestion: command.usage(),
+                    message: format!(
+                        "@{} needs a {}: {}",
+                        word,
+                        command.argument,
+                        command.usage()
+                    ),
+                });
+            } else {
+                parsed.forced_tool = command
+                    .tool
+                    .map(|tool| (tool.to_string(), argument.to_string()));
+            }
+            return parsed;
+        }
+
+        // One edit away from a longer command name is a typo rather than a mention;
+        // allowing more (or shorter words) catches names like @sarah or @Deb
+        let lowered = word.to_lowercase();
+        let closest = self
+            .commands
+            .iter()
+            .map(|c| (c, edit_distance(&lowered, c.name)))
+            .min_by_key(|(_, distance)| *distance);
+        if let Some((command, distance)) = closest {
+            if word.len() >= 5 && distance <= 1 {
+                parsed.hint = Some(CommandHint {
+                    input: format!("@{}", word),
+                    suggestion: command.usage(),
+                    message: format!(
+                        "Unknown command @{}. Did you mean {}?",
+                        word,
+                        command.usage()
+                    ),
+                });
+            }
+        }
+
+        parsed
+    }
+}
+
+/// Parse a message against the built-in commands
+pub fn parse_command(message: &str) -> ParsedCommand {
+    BUILTIN_COMMANDS.parse(message)
+}
+
+/// Built-in commands, for autocomplete
+pub fn builtin_commands() -> &'static [AtCommand] {
+    BUILTIN_COMMANDS.commands()
+}
+
+lazy_static::lazy_static! {
+    static ref FILE_REFERENCE: regex::Regex =
+        regex::Regex::new(r"(?:^|\s)@file\s+(\S+)").unwrap();
+}
+
+/// Append the content of every `@file <path>` reference in the message as a
+/// fenced block. `path:10-40` (or `path:10`) inlines only those lines. Paths must
+/// resolve inside the workspace; failures are noted inline so the model knows
+/// the file wasn't included. Total content is capped at `MAX_INLINE_FILE_BYTES`.
+pub fn expand_file_references(message: &str, workspace_root: Option<&Path>) -> String {
+    let mut references: Vec<&str> = Vec::new();
+    for capture in FILE_REFERENCE.captures_iter(message) {
+        let reference = capture.get(1).unwrap().as_str();
+        if !references.contains(&reference) {
+            references.push(reference);
+        }
+    }
+    if references.is_empty() {
+        return message.to_string();
+    }
+
+    let mut expanded = message.to_string();
+    let mut remaining = MAX_INLINE_FILE_BYTES;
+    for reference in references {
+        if remaining == 0 {
+            expanded.push_str(&format!(
+                "\n\n[@file {} skipped: inline size limit of {} bytes reached]",
+                reference, MAX_INLINE_FILE_BYTES
+            ));
+            continue;
+        }
+
+        match read_file_reference(reference, workspace_root) {
+            Ok((header, mut content)) => {
+                let truncated = content.len() > remaining;
+                if truncated {
+                    let mut end = remaining;
+                    while !content.is_char_boundary(end) {
+                        end -= 1;
+                    }
+                    content.truncate(end);
+                }
+                remaining -= content.len();
+
+                let fence = crate::utils::code_fence(&content);
+                expanded.push_str(&format!(
+                    "\n\n{}\n{}\n{}\n{}",
+                    header,
+                    fence,
+                    content.trim_end_matches('\n'),
+                    fence
+                ));
+                if truncated {
+                    expanded.push_str(&format!(
+                        "\n[... @file content truncated: inline size limit of {} bytes reached ...]",
+                        MAX_INLINE_FILE_BYTES
+                    ));
+                }
+            }
+            Err(e) => {
+                eprintln!("[COMMAND] @file {}: {}", reference, e);
+                expanded.push_str(&format!("\n\n[@file {}: {}]", reference, e));
+            }
+        }
+    }
+    expanded
+}
+
+/// Read one `@file` reference, returning its header line and content
+fn read_file_reference(
+    reference: &str,
+    workspace_root: Option<&Path>,
+) -> Result<(String, String), String> {
+    let workspace_root = workspace_root.ok_or("no workspace open")?;
+    let (path, range) = split_line_range(reference);
+    let abs = crate::tools::validate_path_under_workspace(workspace_root, Path::new(path))?;
+    if abs.is_dir() {
+        return Err("is a directory".to_string());
+    }
+    let (content, _) = crate::encoding::read_text(&abs)?;
+
+    let Some((start, end)) = range else {
+        return Ok((format!("File: {}", path), content));
+    };
+    let lines: Vec<&str> = content.lines().collect();
+    if start == 0 || start > end || start > lines.len() {
+        return Err(format!(
+            "invalid line range {}-{} (file has {} lines)",
+            start,
+            end,
+            lines.len()
+        ));
+    }
+    let end = end.min(lines.len());
+    Ok((
+        format!("File: {} (lines {}-{})", path, start, end),
+        lines[start - 1..end].join("\n"),
+    ))
+}
+
+/// Split `path:10-40` or `path:10` into the path and a 1-based inclusive line range
+fn split_line_range(reference: &str) -> (&str, Option<(usize, usize)>) {
+    let Some((path, range)) = reference.rsplit_once(':') else {
+        return (reference, None);
+    };
+    let parsed: Option<(usize, usize)> = match range.split_once('-') {
+        Some((start, end)) => start.parse().ok().zip(end.parse().ok()),
+        None => range.parse().ok().map(|line| (line, line)),
+    };
+    match parsed {
+        Some(range) if !path.is_empty() => (path, Some(range)),
+        _ => (reference, None),
+    }
+}
+
+/// Levenshtein distance between two strings
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut prev: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut row = vec![i + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let substitute = prev[j] + usize::from(ca != *cb);
+            row.push(substitute.min(prev[j + 1] + 1).min(row[j] + 1));
+        }
+        prev = row;
+    }
+    prev[b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_command() {
+        let parsed = parse_command("@research rust async");
+        assert_eq!(parsed.message, "@research rust async");
+        assert_eq!(
+            parsed.forced_tool,
+            Some(("research".to_string(), "rust async".to_string()))
+        );
+        assert_eq!(
+            parse_command("@search tokio select").forced_tool,
+            Some(("search".to_string(), "tokio select".to_string()))
+        );
+        assert_eq!(
+            parse_command("@web https://example.com").forced_tool,
+            Some(("fetch_url".to_string(), "https://example.com".to_string()))
+        );
+        assert_eq!(
+            parse_command("@Research rust").forced_tool,
+            Some(("research".to_string(), "rust".to_string()))
+        );
+        assert_eq!(
+            parse_command("@Web https://example.com").forced_tool,
+            Some(("fetch_url".to_string(), "https://example.com".to_string()))
+        );
+
+        // Mentions and plain messages pass through
+        for message in [
+            "@alice can you look",
+            "@sarah thoughts?",
+            "@Deb can you review",
+            "@fine by me",
+            "fix @research",
+        ] {
+            assert_eq!(
+                parse_command(message),
+                ParsedCommand {
+                    message: message.to_string(),
+                    forced_tool: None,
+                    hint: None,
+                }
+            );
+        }
+
+        // Hints don't drop the message
+        let parsed = parse_command("@research");
+        assert_eq!(parsed.message, "@research");
+        assert_eq!(parsed.forced_tool, None);
+        assert_eq!(parsed.hint.unwrap().suggestion, "@research <query>");
+        let parsed = parse_command("@reserch tokio");
+        assert_eq!(parsed.message, "@reserch tokio");
+        assert_eq!(parsed.forced_tool, None);
+        let hint = parsed.hint.unwrap();
+        assert_eq!(hint.input, "@reserch");
+        assert_eq!(
+            hint.message,
+            "Unknown command @reserch. Did you mean @research <query>?"
+        );
+    }
+
+    #[test]
+    fn test_expand_file_references() {
+        let dir = tempfile::tempdir().unwrap();
+        let ws_dir = dir.path().join("ws");
+        let ws = ws_dir.as_path();
+        std::fs::create_dir_all(ws.join("src")).unwrap();
+        std::fs::write(dir.path().join("secret.txt"), "secret").unwrap();
+        std::fs::write(ws.join("src/lib.rs"), "one\ntwo\nthree\nfour\n").unwrap();
+
+        assert_eq!(parse_command("@file src/lib.rs").forced_tool, None);
+        assert_eq!(
+            parse_command("@file").hint.unwrap().suggestion,
+            "@file <path>"
+        );
+
+        let expanded = expand_file_references("explain @file src/lib.rs:2-3 please", Some(ws));
+        assert_eq!(
+            expanded,
+            "explain @file src/lib.rs:2-3 please\n\nFile: src/lib.rs (lines 2-3)\n```\ntwo\nthree\n```"
+        );
+
+        let expanded = expand_file_references("@file src/lib.rs and @file ../secret.txt", Some(ws));
+        assert!(expanded.contains("File: src/lib.rs\n```\none\ntwo\nthree\nfour\n```"));
+        assert!(expanded.contains("[@file ../secret.txt: path is outside workspace"));
+        assert!(expand_file_references("@file src/lib.rs:9-12", Some(ws))
+            .contains("invalid line range 9-12 (file has 4 lines)"));
+        assert_eq!(expand_file_references("no refs", Some(ws)), "no refs");
+
+        std::fs::write(ws.join("big.txt"), "x".repeat(MAX_INLINE_FILE_BYTES + 10)).unwrap();
+        let expanded = expand_file_references("@file big.txt @file src/lib.rs", Some(ws));
+        assert!(expanded.contains("@file content truncated"));
+        assert!(expanded.contains("[@file src/lib.rs skipped: inline size limit"));
+    }
+
+    #[test]
+    fn test_register_command() {
+        let mut registry = CommandRegistry::builtin();
+        registry.register(AtCommand {
+            name: "explain",
+            tool: Some("explain"),
+            argument: "symbol",
+            description: "Explain code",
+        });
+        assert_eq!(registry.commands().len(), 5);
+        assert_eq!(
+            registry.parse("@explain parse_command").forced_tool,
+            Some(("explain".to_string(), "parse_command".to_string()))
+        );
+        assert_eq!(
+            registry.parse("@explain").hint.unwrap().suggestion,
+            "@explain <symbol>"
+        );
+    }
+}
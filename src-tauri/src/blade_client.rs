@@ -4,11 +4,40 @@ use serde_json::Value;
 use std::collections::HashMap;
 use tokio::sync::mpsc;
 
+/// Failure of a one-shot Blade HTTP request
+#[derive(Debug)]
+pub enum BladeRequestError {
+    /// The server didn't answer within the HTTP client's timeout
+    Timeout,
+    Failed(String),
+}
+
+impl std::fmt::Display for BladeRequestError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BladeRequestError::Timeout => write!(f, "request timed out"),
+            BladeRequestError::Failed(message) => write!(f, "{}", message),
+        }
+    }
+}
+
+impl BladeRequestError {
+    fn from_reqwest(context: &str, e: reqwest::Error) -> Self {
+        if e.is_timeout() {
+            BladeRequestError::Timeout
+        } else {
+            BladeRequestError::Failed(format!("{}: {}", context, e))
+        }
+    }
+}
+
 /// Blade Protocol client for communicating with zcoderd
 pub struct BladeClient {
     base_url: String,
     http_client: reqwest::Client,
     api_key: String,
+    /// Timeout for one-shot requests (history lookups, summaries); streaming requests aren't bounded
+    request_timeout: Option<std::time::Duration>,
 }
 
 /// Events from the Blade Protocol SSE stream
@@ -125,6 +154,21 @@ impl BladeClient {
             base_url,
             http_client,
             api_key,
+            request_timeout: None,
+        }
+    }
+
+    /// Bound one-shot requests (history lookups, summaries) by `timeout`
+    pub fn with_timeout(mut self, timeout: std::time::Duration) -> Self {
+        self.request_timeout = Some(timeout);
+        self
+    }
+
+    fn get_with_timeout(&self, url: &str) -> reqwest::RequestBuilder {
+        let request = self.http_client.get(url);
+        match self.request_timeout {
+            Some(timeout) => request.timeout(timeout),
+            None => request,
         }
     }
 
@@ -166,6 +210,57 @@ impl BladeClient {
         self.send_blade_request(request).await
     }
 
+    /// Ask zcoderd for a short summary of a conversation transcript.
+    /// Uses a fresh session so the active conversation is left untouched.
+    pub async fn summarize_transcript(
+        &self,
+        model_id: String,
+        transcript: &str,
+    ) -> Result<String, String> {
+        let prompt = format!(
+            "Summarize the following conversation between a user and a coding assistant. \
+             Keep decisions, file paths, open tasks and important facts; drop chit-chat. \
+             Reply with the summary only.\n\n{}",
+            transcript
+        );
+        let workspace = WorkspaceInfo {
+            root: String::new(),
+            project_id: None,
+            active_file: None,
+            cursor_position: None,
+            open_files: Vec::new(),
+        };
+
+        let collect = async {
+            let mut rx = self
+                .send_message(None, model_id, prompt, workspace, HashMap::new())
+                .await?;
+
+            let mut summary = String::new();
+            while let Some(event) = rx.recv().await {
+                match event {
+                    BladeEvent::Text(text) => summary.push_str(&text),
+                    BladeEvent::Done { .. } => break,
+                    BladeEvent::Error { message, .. } => return Err(message),
+                    _ => {}
+                }
+            }
+            Ok::<_, String>(summary)
+        };
+        let summary = match self.request_timeout {
+            Some(timeout) => tokio::time::timeout(timeout, collect)
+                .await
+                .map_err(|_| BladeRequestError::Timeout.to_string())??,
+            None => collect.await?,
+        };
+
+        let summary = summary.trim();
+        if summary.is_empty() {
+            return Err("zcoderd returned an empty summary".to_string());
+        }
+        Ok(summary.to_string())
+    }
+
     /// Send a raw ZLP request to /v1/zlp
     pub async fn send_zlp_request(
         &self,
@@ -230,57 +325,65 @@ impl BladeClient {
     }
 
     /// Get conversation history list
-    pub async fn get_conversation_history(&self, project_id: &str) -> Result<Value, String> {
+    pub async fn get_conversation_history(
+        &self,
+        project_id: &str,
+    ) -> Result<Value, BladeRequestError> {
         let url = format!(
             "{}/v1/blade/history?project_id={}&api_key={}",
             self.base_url, project_id, self.api_key
         );
 
         let response = self
-            .http_client
-            .get(&url)
+            .get_with_timeout(&url)
             .header("Authorization", format!("Bearer {}", self.api_key))
             .send()
             .await
-            .map_err(|e| format!("Failed to fetch conversation history: {}", e))?;
+            .map_err(|e| {
+                BladeRequestError::from_reqwest("Failed to fetch conversation history", e)
+            })?;
 
         if !response.status().is_success() {
             let status = response.status();
             let text = response.text().await.unwrap_or_default();
-            return Err(format!("History API error {}: {}", status, text));
+            return Err(BladeRequestError::Failed(format!(
+                "History API error {}: {}",
+                status, text
+            )));
         }
 
         response
             .json()
             .await
-            .map_err(|e| format!("Failed to parse history response: {}", e))
+            .map_err(|e| BladeRequestError::from_reqwest("Failed to parse history response", e))
     }
 
     /// Get full conversation by session ID
-    pub async fn get_conversation(&self, session_id: &str) -> Result<Value, String> {
+    pub async fn get_conversation(&self, session_id: &str) -> Result<Value, BladeRequestError> {
         let url = format!(
             "{}/v1/blade/history/{}?api_key={}",
             self.base_url, session_id, self.api_key
         );
 
         let response = self
-            .http_client
-            .get(&url)
+            .get_with_timeout(&url)
             .header("Authorization", format!("Bearer {}", self.api_key))
             .send()
             .await
-            .map_err(|e| format!("Failed to fetch conversation: {}", e))?;
+            .map_err(|e| BladeRequestError::from_reqwest("Failed to fetch conversation", e))?;
 
         if !response.status().is_success() {
             let status = response.status();
             let text = response.text().await.unwrap_or_default();
-            return Err(format!("Conversation API error {}: {}", status, text));
+            return Err(BladeRequestError::Failed(format!(
+                "Conversation API error {}: {}",
+                status, text
+            )));
         }
 
-        response
-            .json()
-            .await
-            .map_err(|e| format!("Failed to parse conversation response: {}", e))
+        response.json().await.map_err(|e| {
+            BladeRequestError::from_reqwest("Failed to parse conversation response", e)
+        })
     }
 
     /// Internal method to send any Blade Protocol request
@@ -118,6 +118,10 @@ pub enum EditorIntent {
     UpdateCursor { line: u32, column: u32 },
     /// Update selection (for AI context)
     UpdateSelection { start: u32, end: u32 },
+    /// Move the cursor (scripting clients steering AI context)
+    SetCursor { line: u32, column: u32 },
+    /// Select a line range (scripting clients steering AI context)
+    SelectRange { start_line: u32, end_line: u32 },
     /// Request current editor state snapshot
     GetState {},
     // Tab management (headless)
@@ -223,6 +227,13 @@ pub enum TerminalIntent {
 pub enum HistoryIntent {
     ListConversations { project_id: String },
     LoadConversation { session_id: String },
+    /// Load `limit` messages ending just before `before_message_id` (the newest ones if None)
+    LoadConversationPage {
+        session_id: String,
+        #[serde(default)]
+        before_message_id: Option<String>,
+        limit: usize,
+    },
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -239,6 +250,9 @@ pub enum LanguageIntent {
     // Symbol operations (tree-sitter)
     IndexFile {
         file_path: String,
+        /// Parse as this language instead of detecting it from the path
+        #[serde(default)]
+        language: Option<String>,
     },
     IndexWorkspace {},
     SearchSymbols {
@@ -282,6 +296,7 @@ pub enum BladeEvent {
     History(HistoryEvent),
     System(SystemEvent),
     Language(LanguageEvent), // v1.3: ZLP (tree-sitter based language protocol)
+    Security(SecurityEvent),
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -411,10 +426,26 @@ pub enum WorkflowEvent {
         success: bool,
         result: Option<String>,
     },
+    /// `batch_id` is the history group of every file change in the batch;
+    /// pass it to `undo_batch` to revert them together
     BatchCompleted {
         batch_id: String,
         succeeded: usize,
         failed: usize,
+        /// Tool call ids whose changes were applied under this batch
+        #[serde(default)]
+        change_ids: Vec<String>,
+        /// Tool call ids whose changes failed, were skipped, or were rolled back
+        #[serde(default)]
+        failed_ids: Vec<String>,
+        /// The batch was all-or-nothing and a failure reverted its applied changes
+        #[serde(default)]
+        rolled_back: bool,
+    },
+    /// A guard (max turns, max tool calls per message) stopped the agent;
+    /// `reason` is shown to the user
+    AgentStopped {
+        reason: String,
     },
     // Legacy support
     TaskCompleted {
@@ -454,6 +485,9 @@ pub struct ConversationSummary {
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct HistoryMessage {
+    /// Stable message ID used as the paging cursor; assigned from the position when absent
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
     pub role: String,
     pub content: String,
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -470,6 +504,11 @@ pub enum HistoryEvent {
         conversations: Vec<ConversationSummary>,
     },
     ConversationLoaded(FullConversation),
+    ConversationPage {
+        session_id: String,
+        messages: Vec<HistoryMessage>,
+        has_more: bool,
+    },
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -483,6 +522,36 @@ pub struct FullConversation {
     pub messages: Vec<HistoryMessage>,
 }
 
+impl FullConversation {
+    /// Give messages without a server ID a positional one so they can be paged
+    pub fn assign_message_ids(&mut self) {
+        for (index, msg) in self.messages.iter_mut().enumerate() {
+            if msg.id.is_none() {
+                msg.id = Some(format!("msg-{}", index));
+            }
+        }
+    }
+
+    /// Up to `limit` messages immediately before `before_message_id` (or the newest ones),
+    /// plus whether older messages remain
+    pub fn page(
+        &self,
+        before_message_id: Option<&str>,
+        limit: usize,
+    ) -> (Vec<HistoryMessage>, bool) {
+        let end = match before_message_id {
+            Some(id) => self
+                .messages
+                .iter()
+                .position(|m| m.id.as_deref() == Some(id))
+                .unwrap_or(0),
+            None => self.messages.len(),
+        };
+        let start = end.saturating_sub(limit);
+        (self.messages[start..end].to_vec(), start > 0)
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 #[serde(tag = "type", content = "payload")]
 pub enum SystemEvent {
@@ -506,6 +575,33 @@ pub enum SystemEvent {
     ProcessCompleted {
         intent_id: Uuid,
     },
+    /// A save failed; `scope` is "conversation" or "artifact"
+    PersistenceFailed {
+        scope: String,
+        message: String,
+    },
+    /// Throttled progress of a workspace symbol index run
+    IndexProgress {
+        done: usize,
+        total: usize,
+    },
+    /// zcoderd asked zblade to execute one of its own server-side tools
+    ServerToolMisrouted {
+        tool: String,
+    },
+}
+
+/// Sandbox enforcement, so the UI can warn about refused operations
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(tag = "type", content = "payload")]
+pub enum SecurityEvent {
+    /// A write-type tool targeted a path outside the workspace and was refused;
+    /// `total` counts refusals since launch
+    BlockedWrite {
+        path: String,
+        tool: String,
+        total: u64,
+    },
 }
 
 // v1.3: Language domain events
@@ -686,6 +782,59 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_editor_scripting_intents_deserialize() {
+        let json = r#"{"type":"Editor","payload":{"type":"SelectRange","payload":{"start_line":3,"end_line":9}}}"#;
+        let intent: BladeIntent = serde_json::from_str(json).expect("Failed to parse intent");
+
+        if let BladeIntent::Editor(EditorIntent::SelectRange {
+            start_line,
+            end_line,
+        }) = intent
+        {
+            assert_eq!((start_line, end_line), (3, 9));
+        } else {
+            panic!("Deserialized intent has wrong type");
+        }
+    }
+
+    #[test]
+    fn test_full_conversation_paging() {
+        let mut conversation = FullConversation {
+            session_id: "s1".to_string(),
+            project_id: "p1".to_string(),
+            title: "Paging".to_string(),
+            created_at: String::new(),
+            last_active_at: String::new(),
+            message_count: 5,
+            messages: (0..5)
+                .map(|i| HistoryMessage {
+                    id: None,
+                    role: "user".to_string(),
+                    content: format!("message {}", i),
+                    tool_calls: None,
+                    tool_call_id: None,
+                    created_at: String::new(),
+                })
+                .collect(),
+        };
+        conversation.assign_message_ids();
+
+        let (tail, has_more) = conversation.page(None, 2);
+        assert_eq!(tail.len(), 2);
+        assert_eq!(tail[0].content, "message 3");
+        assert!(has_more);
+
+        let (older, has_more) = conversation.page(tail[0].id.as_deref(), 2);
+        assert_eq!(older[0].content, "message 1");
+        assert_eq!(older[1].content, "message 2");
+        assert!(has_more);
+
+        let (oldest, has_more) = conversation.page(older[0].id.as_deref(), 2);
+        assert_eq!(oldest.len(), 1);
+        assert!(!has_more);
+    }
+
     #[test]
     fn test_error_serialization() {
         let error = BladeError::Timeout { timeout_ms: 5000 };
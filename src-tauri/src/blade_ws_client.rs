@@ -2,15 +2,42 @@ use crate::environment::EnvironmentInfo;
 use futures_util::{SinkExt, StreamExt};
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
+use std::time::Duration;
 use tokio::sync::{mpsc, Mutex};
 use tokio_tungstenite::{connect_async_with_config, tungstenite::protocol::{Message, WebSocketConfig}};
 
+type WsStream =
+    tokio_tungstenite::WebSocketStream<tokio_tungstenite::MaybeTlsStream<tokio::net::TcpStream>>;
+
+/// Reconnect attempts after an unexpected disconnect before giving up
+const RECONNECT_MAX_ATTEMPTS: u32 = 10;
+/// Upper bound for the delay between reconnect attempts
+const RECONNECT_MAX_DELAY: Duration = Duration::from_secs(30);
+
+/// How long to wait for a pong before treating the connection as dead
+const PONG_TIMEOUT: Duration = Duration::from_secs(15);
+
+/// How long to wait for the server to answer a `resume_session` before giving up
+pub const RESUME_REPLY_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Backoff before reconnect `attempt` (1-based): 1s, 2s, 4s, ... capped at `RECONNECT_MAX_DELAY`
+fn reconnect_delay(attempt: u32) -> Duration {
+    let secs = 1u64 << attempt.saturating_sub(1).min(16);
+    Duration::from_secs(secs).min(RECONNECT_MAX_DELAY)
+}
+
 /// WebSocket-based Blade Protocol v2 client
 pub struct BladeWsClient {
-    base_url: String,
+    /// `ws(s)://` base from `ApiConfig::normalized`
+    ws_base: String,
     api_key: String,
     connection: Arc<Mutex<Option<WsConnection>>>,
+    /// Set by `close` so a deliberate shutdown isn't treated as a dropped connection
+    closing: Arc<AtomicBool>,
+    /// Interval between keep-alive pings while a socket is open
+    ping_interval: Duration,
 }
 
 struct WsConnection {
@@ -93,6 +120,11 @@ pub enum BladeWsEvent {
         recovery_hint: Option<String>,
     },
     Disconnected,
+    /// The connection dropped and is being re-established after `delay`
+    Reconnecting {
+        attempt: u32,
+        delay: Duration,
+    },
     ToolActivity {
         tool_name: String,
         file_path: String,
@@ -209,23 +241,31 @@ struct WsIncomingMessage {
 }
 
 impl BladeWsClient {
-    /// Create a new WebSocket Blade Protocol client
-    pub fn new(base_url: String, api_key: String) -> Self {
+    /// Create a new WebSocket Blade Protocol client for a `ws(s)://` base
+    /// (see `ApiConfig::normalized`)
+    pub fn new(ws_base: String, api_key: String) -> Self {
         Self {
-            base_url,
+            ws_base,
             api_key,
             connection: Arc::new(Mutex::new(None)),
+            closing: Arc::new(AtomicBool::new(false)),
+            ping_interval: Duration::from_secs(crate::config::DEFAULT_WS_PING_INTERVAL_SECS),
         }
     }
 
-    /// Connect to the WebSocket server and authenticate with retry logic
+    /// Override the keep-alive ping interval (see `ApiConfig::ws_ping_interval`)
+    pub fn with_ping_interval(mut self, interval: Duration) -> Self {
+        self.ping_interval = interval;
+        self
+    }
+
+    /// Connect to the WebSocket server and authenticate with retry logic.
+    /// Unexpected disconnects are retried in the background with exponential backoff,
+    /// re-authenticating on the same event stream; `Disconnected` is only sent once the
+    /// connection is closed on purpose or reconnecting gives up.
     pub async fn connect(&self) -> Result<mpsc::UnboundedReceiver<BladeWsEvent>, String> {
-        // Convert HTTP URL to WebSocket URL
-        let ws_url = self
-            .base_url
-            .replace("http://", "ws://")
-            .replace("https://", "wss://");
-        let url = format!("{}/v1/blade/v2?api_key={}", ws_url, self.api_key);
+        let url = self.ws_url();
+        self.closing.store(false, Ordering::SeqCst);
 
         let mut retry_count = 0;
         let max_retries = 8; // ~2 minutes total wait time with exponential backoff
@@ -239,16 +279,8 @@ impl BladeWsClient {
                 max_retries + 1
             );
 
-            // Configure WebSocket with larger message size limit (64MB instead of default 16MB)
-            // This prevents "Space limit exceeded" errors for large tool results
-            let ws_config = WebSocketConfig {
-                max_message_size: Some(64 * 1024 * 1024), // 64MB
-                max_frame_size: Some(64 * 1024 * 1024),   // 64MB per frame
-                ..Default::default()
-            };
-
-            match connect_async_with_config(&url, Some(ws_config), false).await {
-                Ok((stream, _)) => {
+            match Self::open_socket(&url).await {
+                Ok(stream) => {
                     eprintln!("[BLADE WS] Connected successfully");
                     ws_stream = stream;
                     break;
@@ -276,18 +308,111 @@ impl BladeWsClient {
             }
         }
 
-        let (mut write, mut read) = ws_stream.split();
-
-        // Create channels
         let (event_tx, event_rx) = mpsc::unbounded_channel();
+        let connection = self.connection.clone();
+        let closing = self.closing.clone();
+        let api_key = self.api_key.clone();
+        let ping_interval = self.ping_interval;
+
+        tokio::spawn(async move {
+            let mut ws_stream = ws_stream;
+            loop {
+                Self::run_connection(
+                    ws_stream,
+                    &connection,
+                    &event_tx,
+                    api_key.clone(),
+                    ping_interval,
+                )
+                .await;
+
+                if closing.load(Ordering::SeqCst) || event_tx.is_closed() {
+                    break;
+                }
+
+                match Self::reconnect(&url, &closing, &event_tx).await {
+                    Some(stream) => ws_stream = stream,
+                    None => break,
+                }
+            }
+
+            let _ = event_tx.send(BladeWsEvent::Disconnected);
+        });
+
+        Ok(event_rx)
+    }
+
+    fn ws_url(&self) -> String {
+        format!("{}/v1/blade/v2?api_key={}", self.ws_base, self.api_key)
+    }
+
+    async fn open_socket(url: &str) -> Result<WsStream, tokio_tungstenite::tungstenite::Error> {
+        // Configure WebSocket with larger message size limit (64MB instead of default 16MB)
+        // This prevents "Space limit exceeded" errors for large tool results
+        let ws_config = WebSocketConfig {
+            max_message_size: Some(64 * 1024 * 1024), // 64MB
+            max_frame_size: Some(64 * 1024 * 1024),   // 64MB per frame
+            ..Default::default()
+        };
+
+        connect_async_with_config(url, Some(ws_config), false)
+            .await
+            .map(|(stream, _)| stream)
+    }
+
+    /// Reopen the socket after an unexpected disconnect, backing off exponentially.
+    /// Returns None if `close` was called meanwhile or every attempt failed.
+    async fn reconnect(
+        url: &str,
+        closing: &AtomicBool,
+        event_tx: &mpsc::UnboundedSender<BladeWsEvent>,
+    ) -> Option<WsStream> {
+        for attempt in 1..=RECONNECT_MAX_ATTEMPTS {
+            let delay = reconnect_delay(attempt);
+            eprintln!(
+                "[BLADE WS] Connection lost, reconnecting in {:?} ({}/{})",
+                delay, attempt, RECONNECT_MAX_ATTEMPTS
+            );
+            let _ = event_tx.send(BladeWsEvent::Reconnecting { attempt, delay });
+            tokio::time::sleep(delay).await;
+
+            if closing.load(Ordering::SeqCst) {
+                return None;
+            }
+            match Self::open_socket(url).await {
+                Ok(stream) => {
+                    eprintln!("[BLADE WS] Reconnected");
+                    return Some(stream);
+                }
+                Err(e) => eprintln!("[BLADE WS] Reconnect attempt {} failed: {}", attempt, e),
+            }
+        }
+
+        eprintln!("[BLADE WS] Giving up after {} reconnect attempts", RECONNECT_MAX_ATTEMPTS);
+        None
+    }
+
+    /// Authenticate over a freshly opened socket and read from it until it closes.
+    /// The session ID from a previous socket is kept so the chat can be resumed.
+    /// A ping is sent every `ping_interval`; if no pong arrives within `PONG_TIMEOUT`
+    /// the socket is dropped so the caller reconnects.
+    async fn run_connection(
+        ws_stream: WsStream,
+        connection: &Arc<Mutex<Option<WsConnection>>>,
+        event_tx: &mpsc::UnboundedSender<BladeWsEvent>,
+        api_key: String,
+        ping_interval: Duration,
+    ) {
+        let (mut write, mut read) = ws_stream.split();
         let (msg_tx, mut msg_rx) = mpsc::unbounded_channel();
 
         // Store connection
         {
-            let mut conn = self.connection.lock().await;
+            let mut conn = connection.lock().await;
+            let session_id = conn.as_ref().and_then(|c| c.session_id.clone());
             *conn = Some(WsConnection {
                 tx: msg_tx.clone(),
-                session_id: None,
+                session_id,
             });
         }
 
@@ -345,130 +470,128 @@ impl BladeWsClient {
             eprintln!("[WS WRITE] Write task exiting");
         });
 
-        // Heartbeat: send websocket ping periodically to keep connection alive
-        // Use 10-second interval for aggressive keep-alive during long-running operations
-        {
-            let hb_tx = msg_tx.clone();
-            tokio::spawn(async move {
-                let interval = std::time::Duration::from_secs(10);
-                loop {
-                    tokio::time::sleep(interval).await;
-                    if hb_tx.send(WsMessage::Ping).is_err() {
-                        break;
-                    }
-                }
+        // Collect environment information for the system prompt
+        let environment = EnvironmentInfo::collect();
+        eprintln!("[BLADE WS] Environment: os={}, arch={:?}, shell={:?}", 
+            environment.os, environment.arch, environment.shell);
+        
+        // Send authentication message
+        let auth_msg = WsBaseMessage {
+            id: "auth-1".to_string(),
+            msg_type: "authenticate".to_string(),
+            timestamp: chrono::Utc::now().timestamp_millis(),
+            payload: Some(
+                serde_json::to_value(AuthenticatePayload {
+                    api_key,
+                    client_name: "zblade".to_string(),
+                    client_version: env!("CARGO_PKG_VERSION").to_string(),
+                    environment: Some(environment),
+                })
+                .unwrap(),
+            ),
+        };
+
+        let auth_json = serde_json::to_string(&auth_msg).unwrap();
+        eprintln!("[BLADE WS] Sending authentication");
+
+        if let Err(e) = msg_tx.send(WsMessage::Send(auth_json)) {
+            eprintln!("[BLADE WS] Failed to send auth: {}", e);
+            let _ = event_tx.send(BladeWsEvent::Error {
+                error_type: "authentication_error".to_string(),
+                code: "auth_failed".to_string(),
+                message: "Failed to send authentication".to_string(),
+                token_count: None,
+                max_tokens: None,
+                excess: None,
+                recoverable: Some(false),
+                recovery_hint: Some("Check your API key and try again".to_string()),
             });
+            return;
         }
 
-        // Spawn read task
-        let event_tx_clone = event_tx.clone();
-        let api_key = self.api_key.clone();
-        let msg_tx_clone = msg_tx.clone();
+        // Heartbeat: ping periodically so idle stretches during long server-side tool runs
+        // don't get the socket closed by intermediaries. Lives as long as this read loop.
+        let mut ping_timer =
+            tokio::time::interval_at(tokio::time::Instant::now() + ping_interval, ping_interval);
+        ping_timer.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        let pong_deadline = tokio::time::sleep(PONG_TIMEOUT);
+        tokio::pin!(pong_deadline);
+        let mut awaiting_pong = false;
 
-        tokio::spawn(async move {
-            // Collect environment information for the system prompt
-            let environment = EnvironmentInfo::collect();
-            eprintln!("[BLADE WS] Environment: os={}, arch={:?}, shell={:?}", 
-                environment.os, environment.arch, environment.shell);
-            
-            // Send authentication message
-            let auth_msg = WsBaseMessage {
-                id: "auth-1".to_string(),
-                msg_type: "authenticate".to_string(),
-                timestamp: chrono::Utc::now().timestamp_millis(),
-                payload: Some(
-                    serde_json::to_value(AuthenticatePayload {
-                        api_key,
-                        client_name: "zblade".to_string(),
-                        client_version: env!("CARGO_PKG_VERSION").to_string(),
-                        environment: Some(environment),
-                    })
-                    .unwrap(),
-                ),
-            };
-
-            let auth_json = serde_json::to_string(&auth_msg).unwrap();
-            eprintln!("[BLADE WS] Sending authentication");
-
-            if let Err(e) = msg_tx_clone.send(WsMessage::Send(auth_json)) {
-                eprintln!("[BLADE WS] Failed to send auth: {}", e);
-                let _ = event_tx_clone.send(BladeWsEvent::Error {
-                    error_type: "authentication_error".to_string(),
-                    code: "auth_failed".to_string(),
-                    message: "Failed to send authentication".to_string(),
-                    token_count: None,
-                    max_tokens: None,
-                    excess: None,
-                    recoverable: Some(false),
-                    recovery_hint: Some("Check your API key and try again".to_string()),
-                });
-                return;
-            }
-
-            // Read messages
-            while let Some(msg_result) = read.next().await {
-                match msg_result {
-                    Ok(Message::Text(text)) => {
-                        if text.len() > 500 {
-                            eprintln!("[BLADE WS] Received: {}... ({} bytes)", &text[..200], text.len());
-                        } else {
-                            eprintln!("[BLADE WS] Received: {}", text);
-                        }
-                        if let Err(e) = Self::parse_message(&text, &event_tx_clone) {
-                            eprintln!("[BLADE WS] Parse error: {}", e);
+        // Read messages
+        loop {
+            let msg_result = tokio::select! {
+                msg = read.next() => match msg {
+                    Some(msg) => msg,
+                    None => break,
+                },
+                _ = ping_timer.tick() => {
+                    if !awaiting_pong {
+                        if msg_tx.send(WsMessage::Ping).is_err() {
+                            break;
                         }
+                        awaiting_pong = true;
+                        pong_deadline
+                            .as_mut()
+                            .reset(tokio::time::Instant::now() + PONG_TIMEOUT);
                     }
-                    Ok(Message::Close(_)) => {
-                        eprintln!("[BLADE WS] Connection closed by server");
-                        let _ = event_tx_clone.send(BladeWsEvent::Disconnected);
-                        break;
+                    continue;
+                }
+                _ = &mut pong_deadline, if awaiting_pong => {
+                    eprintln!("[BLADE WS] No pong within {:?}, dropping connection", PONG_TIMEOUT);
+                    break;
+                }
+            };
+
+            match msg_result {
+                Ok(Message::Text(text)) => {
+                    if text.len() > 500 {
+                        eprintln!("[BLADE WS] Received: {}... ({} bytes)", &text[..200], text.len());
+                    } else {
+                        eprintln!("[BLADE WS] Received: {}", text);
                     }
-                    Ok(Message::Ping(_)) => {
-                        // Pong is handled automatically by tungstenite
+                    if let Err(e) = Self::parse_message(&text, event_tx) {
+                        eprintln!("[BLADE WS] Parse error: {}", e);
                     }
-                    Err(e) => {
-                        eprintln!("[BLADE WS] Read error: {}", e);
-                        let msg = e.to_string();
-                        
-                        // Handle specific error types with appropriate recovery hints
-                        if msg.contains("Connection reset by peer") {
-                            // Treat connection reset as a disconnect so upstream can finish gracefully
-                            let _ = event_tx_clone.send(BladeWsEvent::Disconnected);
-                        } else if msg.contains("Space limit exceeded") || msg.contains("Message too long") {
-                            // Message size limit exceeded - tell the model to use smaller responses
-                            eprintln!("[BLADE WS] Message size limit exceeded, sending recoverable error");
-                            let _ = event_tx_clone.send(BladeWsEvent::Error {
-                                error_type: "message_too_large".to_string(),
-                                code: "size_limit_exceeded".to_string(),
-                                message: "The response was too large to process. Please break your response into smaller parts or use more concise output.".to_string(),
-                                token_count: None,
-                                max_tokens: None,
-                                excess: None,
-                                recoverable: Some(true),
-                                recovery_hint: Some("Your previous response exceeded the message size limit. Please retry with a more concise approach: use smaller code blocks, avoid outputting entire files, and break large changes into multiple smaller tool calls.".to_string()),
-                            });
-                        } else {
-                            let _ = event_tx_clone.send(BladeWsEvent::Error {
-                                error_type: "unknown_error".to_string(),
-                                code: "read_error".to_string(),
-                                message: format!("Read error: {}", msg),
-                                token_count: None,
-                                max_tokens: None,
-                                excess: None,
-                                recoverable: Some(true),
-                                recovery_hint: Some("Connection error. Try again.".to_string()),
-                            });
-                        }
-                        break;
+                }
+                Ok(Message::Close(_)) => {
+                    eprintln!("[BLADE WS] Connection closed by server");
+                    break;
+                }
+                Ok(Message::Ping(_)) => {
+                    // Pong is handled automatically by tungstenite
+                }
+                Ok(Message::Pong(_)) => {
+                    awaiting_pong = false;
+                }
+                Err(e) => {
+                    eprintln!("[BLADE WS] Read error: {}", e);
+                    let msg = e.to_string();
+
+                    // Oversized messages need a change of approach from the model; other read
+                    // errors (e.g. connection reset) are handled by reconnecting
+                    if msg.contains("Space limit exceeded") || msg.contains("Message too long") {
+                        // Message size limit exceeded - tell the model to use smaller responses
+                        eprintln!("[BLADE WS] Message size limit exceeded, sending recoverable error");
+                        let _ = event_tx.send(BladeWsEvent::Error {
+                            error_type: "message_too_large".to_string(),
+                            code: "size_limit_exceeded".to_string(),
+                            message: "The response was too large to process. Please break your response into smaller parts or use more concise output.".to_string(),
+                            token_count: None,
+                            max_tokens: None,
+                            excess: None,
+                            recoverable: Some(true),
+                            recovery_hint: Some("Your previous response exceeded the message size limit. Please retry with a more concise approach: use smaller code blocks, avoid outputting entire files, and break large changes into multiple smaller tool calls.".to_string()),
+                        });
                     }
-                    _ => {}
+                    break;
                 }
+                _ => {}
             }
+        }
 
-            let _ = event_tx_clone.send(BladeWsEvent::Disconnected);
-        });
-
-        Ok(event_rx)
+        // Stop the write task (and with it the heartbeat) for this socket
+        let _ = msg_tx.send(WsMessage::Close);
     }
 
     /// Send a chat message
@@ -624,8 +747,46 @@ impl BladeWsClient {
         conn.as_ref().and_then(|c| c.session_id.clone())
     }
 
+    /// Ask the server to continue an existing session after reconnecting, instead of
+    /// sending the chat request again.
+    ///
+    /// Sent once the new socket is authenticated:
+    ///
+    /// ```json
+    /// { "id": "resume-<ms>", "type": "resume_session", "timestamp": <ms>,
+    ///   "payload": { "session_id": "<session>", "api_key": "<key>" } }
+    /// ```
+    ///
+    /// The server answers by streaming the rest of the turn as usual (`text_chunk`,
+    /// `tool_call`, `chat_done`, ...), or with an `error` if the session can't be
+    /// resumed. Callers give up after `RESUME_REPLY_TIMEOUT` without a reply.
+    pub async fn resume_session(&self, session_id: String) -> Result<(), String> {
+        let conn = self.connection.lock().await;
+        let conn = conn.as_ref().ok_or("Not connected")?;
+
+        let msg = WsBaseMessage {
+            id: format!("resume-{}", chrono::Utc::now().timestamp_millis()),
+            msg_type: "resume_session".to_string(),
+            timestamp: chrono::Utc::now().timestamp_millis(),
+            payload: Some(serde_json::json!({
+                "session_id": session_id,
+                "api_key": self.api_key,
+            })),
+        };
+
+        let json =
+            serde_json::to_string(&msg).map_err(|e| format!("JSON serialization error: {}", e))?;
+
+        conn.tx
+            .send(WsMessage::Send(json))
+            .map_err(|e| format!("Failed to resume session: {}", e))?;
+
+        Ok(())
+    }
+
     /// Close the WebSocket connection
     pub async fn close(&self) {
+        self.closing.store(true, Ordering::SeqCst);
         let conn = self.connection.lock().await;
         if let Some(ref c) = *conn {
             let _ = c.tx.send(WsMessage::Close);
@@ -1011,3 +1172,17 @@ impl BladeWsClient {
         None
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reconnect_delay_backs_off_and_caps() {
+        assert_eq!(reconnect_delay(1), Duration::from_secs(1));
+        assert_eq!(reconnect_delay(2), Duration::from_secs(2));
+        assert_eq!(reconnect_delay(5), Duration::from_secs(16));
+        assert_eq!(reconnect_delay(6), RECONNECT_MAX_DELAY);
+        assert_eq!(reconnect_delay(100), RECONNECT_MAX_DELAY);
+    }
+}
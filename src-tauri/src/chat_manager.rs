@@ -3,15 +3,17 @@ use std::path::PathBuf;
 use std::sync::{mpsc, Arc};
 
 use crate::agentic_loop::AgenticLoop;
-use crate::ai_workflow::get_tool_definitions;
+use crate::ai_workflow::get_enabled_tool_definitions;
 use crate::ai_workflow::{AiWorkflow, PendingToolBatch};
 use crate::blade_ws_client::BladeWsClient;
 use crate::config::ApiConfig;
+use crate::context_assembly::{ContextAssembler, ContextStrategy, TokenBudget, UncommittedContext};
 use crate::conversation::ConversationHistory;
+use crate::language_service::LanguageService;
 use crate::models::registry::ModelInfo;
 use crate::protocol::ToolFunction;
-use crate::protocol::{ChatEvent, ChatMessage, ChatRole, ToolCall};
-use crate::reasoning_parser::ReasoningParser;
+use crate::protocol::{ChatEvent, ChatMessage, ChatRole, MessageMetrics, ToolCall};
+use crate::reasoning_parser::{ReasoningParser, ReasoningSegment};
 use crate::xml_parser;
 use futures_util::StreamExt;
 use serde::{Deserialize, Serialize};
@@ -84,7 +86,10 @@ struct OllamaToolCall {
 
 #[derive(Serialize, Deserialize, Clone)]
 struct OllamaToolFunction {
+    // Streamed fragments may carry only part of the call
+    #[serde(default)]
     name: String,
+    #[serde(default)]
     arguments: Value,
     #[serde(skip_serializing_if = "Option::is_none")]
     index: Option<usize>,
@@ -114,8 +119,12 @@ pub struct ChatManager {
     pub rx: Option<mpsc::Receiver<ChatEvent>>,
     pub xml_buffer: String,
     pub reasoning_parser: ReasoningParser, // v1.2: Multi-format reasoning extraction
+    pub reasoning_models: Vec<String>, // User-registered reasoning model substrings (from ApiConfig)
     pub agentic_loop: AgenticLoop,
     pub session_id: Option<String>,
+    /// Send the earlier turns along with the next message, for a session started
+    /// over an existing history (e.g. after a regenerate or edit)
+    pub replay_history: bool,
     abort_handle: Option<tokio::task::AbortHandle>,
     pub accumulated_tool_calls: Vec<ToolCall>,
     pub updated_assistant_message: Option<ChatMessage>,
@@ -125,13 +134,155 @@ pub struct ChatManager {
     pending_tool_progress: HashMap<String, String>, // tool_call_id -> tool_name from tool_progress (cleared when tool_call arrives)
 }
 
-fn supports_reasoning_tags(model_id: &str) -> bool {
+/// Count streamed chunks toward a message's metrics, starting them if the
+/// message was created without a clock (e.g. restored from storage)
+fn record_chunks(msg: &mut ChatMessage, chunks: usize) {
+    let metrics = msg.metrics.get_or_insert_with(MessageMetrics::started_now);
+    metrics.chunk_count += chunks;
+}
+
+/// Merge streamed Ollama tool call fragments into `buf`, keyed by `function.index`.
+/// String arguments are partial JSON and are appended (like OpenAI deltas); object
+/// arguments are already complete and replace whatever was accumulated.
+fn merge_ollama_tool_calls(buf: &mut Vec<ToolCall>, calls: Vec<OllamaToolCall>) {
+    let base = buf.len();
+    for (position, call) in calls.into_iter().enumerate() {
+        let index = match (call.function.index, &call.function.arguments) {
+            (Some(index), _) => index,
+            // Without an index, a string fragment continues the call at the same position
+            (None, Value::String(_)) => position,
+            (None, _) => base + position,
+        };
+
+        while buf.len() <= index {
+            buf.push(ToolCall {
+                id: uuid::Uuid::new_v4().to_string(),
+                typ: "function".to_string(),
+                function: ToolFunction {
+                    name: String::new(),
+                    arguments: String::new(),
+                },
+                status: Some("executing".to_string()),
+                result: None,
+            });
+        }
+
+        let entry = &mut buf[index];
+        if !call.id.is_empty() {
+            entry.id = call.id;
+        }
+        if !call.typ.is_empty() {
+            entry.typ = call.typ;
+        }
+        if !call.function.name.is_empty() {
+            entry.function.name = call.function.name;
+        }
+        match call.function.arguments {
+            Value::Null => {}
+            Value::String(fragment) => entry.function.arguments.push_str(&fragment),
+            args => entry.function.arguments = serde_json::to_string(&args).unwrap_or_default(),
+        }
+    }
+}
+
+/// Take the completed tool calls accumulated by `merge_ollama_tool_calls`
+fn take_ollama_tool_calls(buf: &mut Vec<ToolCall>) -> Vec<ToolCall> {
+    buf.drain(..)
+        .filter(|call| !call.function.name.is_empty())
+        .map(|mut call| {
+            if call.function.arguments.trim().is_empty() {
+                call.function.arguments = "{}".to_string();
+            }
+            call
+        })
+        .collect()
+}
+
+/// Model-name substrings known to emit `<think>` reasoning tags
+const BUILTIN_REASONING_MODELS: &[&str] = &["deepseek", "qwq", "minimax", "kimi", "r1"];
+
+/// Whether a model's output should go through the reasoning tag parser.
+/// An explicit `ModelInfo::supports_reasoning` wins, then the user's `reasoning_models`
+/// substrings from `ApiConfig`, then the built-in name heuristics.
+fn supports_reasoning_tags(
+    model: Option<&ModelInfo>,
+    model_id: &str,
+    reasoning_models: &[String],
+) -> bool {
+    if let Some(explicit) = model.and_then(|m| m.supports_reasoning) {
+        return explicit;
+    }
+
     let model_lower = model_id.to_lowercase();
-    model_lower.contains("deepseek")
-        || model_lower.contains("qwq")
-        || model_lower.contains("minimax")
-        || model_lower.contains("kimi")
-        || model_lower.contains("r1")
+    reasoning_models
+        .iter()
+        .map(|pattern| pattern.trim().to_lowercase())
+        .any(|pattern| !pattern.is_empty() && model_lower.contains(&pattern))
+        || BUILTIN_REASONING_MODELS
+            .iter()
+            .any(|pattern| model_lower.contains(pattern))
+}
+
+/// Route streamed content through the reasoning parser, enabling it on the fly once a
+/// `<think>`/`<thinking>` tag appears. Until then a trailing partial opening tag is held in
+/// `pending` so a tag split across chunks is still detected.
+fn route_streamed_content(
+    parser: &mut Option<ReasoningParser>,
+    pending: &mut String,
+    content: &str,
+) -> Vec<ReasoningSegment> {
+    if let Some(parser) = parser.as_mut() {
+        return parser.process_segments(content);
+    }
+
+    let mut combined = std::mem::take(pending);
+    combined.push_str(content);
+
+    let mut new_parser = ReasoningParser::new();
+    if combined.contains("<think>") || combined.to_lowercase().contains("<thinking>") {
+        let segments = new_parser.process_segments(&combined);
+        *parser = Some(new_parser);
+        return segments;
+    }
+
+    if let Some(idx) = new_parser.partial_opening_start(&combined) {
+        *pending = combined.split_off(idx);
+    }
+    if combined.is_empty() {
+        Vec::new()
+    } else {
+        vec![ReasoningSegment::Text(combined)]
+    }
+}
+
+/// Flush whatever `route_streamed_content` is still holding at the end of a stream
+fn flush_streamed_content(
+    parser: &mut Option<ReasoningParser>,
+    pending: &mut String,
+) -> Vec<ReasoningSegment> {
+    match parser.as_mut() {
+        Some(parser) => parser.flush(),
+        None if !pending.is_empty() => vec![ReasoningSegment::Text(std::mem::take(pending))],
+        None => Vec::new(),
+    }
+}
+
+/// Forward parsed segments as chunk / reasoning events
+fn send_segments(tx: &mpsc::Sender<ChatEvent>, segments: Vec<ReasoningSegment>) {
+    for segment in segments {
+        match segment {
+            ReasoningSegment::Text(text) => {
+                if !text.is_empty() {
+                    let _ = tx.send(ChatEvent::Chunk(text));
+                }
+            }
+            ReasoningSegment::Reasoning(reasoning) => {
+                if !reasoning.is_empty() {
+                    let _ = tx.send(ChatEvent::ReasoningChunk(reasoning));
+                }
+            }
+        }
+    }
 }
 
 impl ChatManager {
@@ -141,8 +292,10 @@ impl ChatManager {
             rx: None,
             xml_buffer: String::new(),
             reasoning_parser: ReasoningParser::new(),
+            reasoning_models: Vec::new(),
             agentic_loop: AgenticLoop::new(max_turns),
             session_id: None,
+            replay_history: false,
             abort_handle: None,
             accumulated_tool_calls: Vec::new(),
             updated_assistant_message: None,
@@ -166,12 +319,14 @@ impl ChatManager {
         cursor_column: Option<usize>,
         http: reqwest::Client,
         storage_mode: Option<String>,
+        language_service: Option<Arc<LanguageService>>,
     ) -> Result<(), String> {
         self.reasoning_parser.reset();
         self.xml_buffer.clear();
         self.accumulated_tool_calls.clear();
         self.updated_assistant_message = None;
         self.message_seq = 0; // v1.1: reset sequence counter for new message
+        let replay_history = std::mem::take(&mut self.replay_history);
 
         // Get model ID
         let selected_info = models.get(selected_model);
@@ -196,6 +351,10 @@ impl ChatManager {
             })
             .unwrap_or_else(|| "anthropic/claude-sonnet-4-5-20250929".to_string());
 
+        self.reasoning_models = api_config.reasoning_models.clone();
+        let supports_reasoning =
+            supports_reasoning_tags(selected_info, &model_id, &self.reasoning_models);
+
         // Short-circuit for Ollama models
         if selected_info
             .and_then(|m| m.provider.as_deref())
@@ -206,6 +365,7 @@ impl ChatManager {
                 conversation,
                 api_config,
                 &model_id,
+                supports_reasoning,
                 http,
                 workspace,
                 active_file,
@@ -222,15 +382,65 @@ impl ChatManager {
                 conversation,
                 api_config,
                 &model_id,
+                supports_reasoning,
                 http,
                 workspace,
                 active_file,
             );
         }
 
+        // Get last user message
+        let user_message = conversation
+            .get_messages()
+            .iter()
+            .rev()
+            .find(|m| m.role == ChatRole::User)
+            .map(|m| m.content.clone())
+            .unwrap_or_default();
+
+        // Keep the open-file context within the selected model's context window,
+        // dropping the least-relevant files first; uncommitted changes get what's left
+        let (open_files, uncommitted) = match language_service {
+            Some(ls) => {
+                let files = open_files.unwrap_or_default();
+                let budget =
+                    TokenBudget::for_context_window(selected_info.and_then(|m| m.context_window));
+                let available = budget.available_for_context();
+                let assembler = ContextAssembler::new(ls)
+                    .with_budget(budget)
+                    .with_strategy(ContextStrategy::Comprehensive);
+                let (selected, mut allocation) =
+                    assembler.select_open_files(&user_message, active_file.as_deref(), &files);
+                let uncommitted = match workspace
+                    .map(|root| crate::git::collect_uncommitted_context(&root.to_string_lossy()))
+                {
+                    Some(Ok(changes)) => assembler.select_uncommitted(changes, &mut allocation),
+                    Some(Err(e)) => {
+                        eprintln!("[CHAT MGR] Skipping uncommitted changes: {}", e);
+                        UncommittedContext::default()
+                    }
+                    None => UncommittedContext::default(),
+                };
+                eprintln!(
+                    "[CHAT MGR] Context budget: {}/{} tokens (active_file={}, open_files={}, uncommitted={}), kept {}/{} open files",
+                    allocation.total(),
+                    available,
+                    allocation.active_file,
+                    allocation.open_files,
+                    allocation.uncommitted,
+                    selected.len(),
+                    files.len()
+                );
+                (selected, uncommitted)
+            }
+            None => (
+                open_files.unwrap_or_default(),
+                UncommittedContext::default(),
+            ),
+        };
+
         // Build workspace info for Blade Protocol
         let open_file_infos = open_files
-            .unwrap_or_default()
             .into_iter()
             .map(|path| crate::blade_ws_client::OpenFileInfo {
                 path: path.clone(),
@@ -262,14 +472,24 @@ impl ChatManager {
             open_files: open_file_infos,
         };
 
-        // Get last user message
-        let user_message = conversation
-            .get_messages()
-            .iter()
-            .rev()
-            .find(|m| m.role == ChatRole::User)
-            .map(|m| m.content.clone())
-            .unwrap_or_default();
+        let endpoints = api_config.normalized()?;
+
+        // The new session has none of the earlier turns, so spell them out, using
+        // at most half the context budget to leave room for files and the reply
+        let user_message = if replay_history {
+            let budget =
+                TokenBudget::for_context_window(selected_info.and_then(|m| m.context_window));
+            conversation
+                .prompt_with_history(budget.available_for_context() / 2)
+                .unwrap_or(user_message)
+        } else {
+            user_message
+        };
+        let user_message = if uncommitted.is_empty() {
+            user_message
+        } else {
+            format!("{}\n\n{}", uncommitted.prompt.trim_end(), user_message)
+        };
 
         // Close any existing WebSocket connection before starting a new one
         if let Some(old_client) = self.ws_client.take() {
@@ -281,13 +501,15 @@ impl ChatManager {
         }
 
         // Create new WebSocket client for this conversation
-        let blade_url = api_config.blade_url.clone();
         let api_key = api_config.api_key.clone();
-        eprintln!("[BLADE WS] Connecting to: {}", blade_url);
+        eprintln!("[BLADE WS] Connecting to: {}", endpoints.ws_base);
         eprintln!("[BLADE WS] Sending message: {}", user_message);
         eprintln!("[BLADE WS] API key present: {}", !api_key.is_empty());
 
-        let ws_client = Arc::new(BladeWsClient::new(blade_url.clone(), api_key.clone()));
+        let ws_client = Arc::new(
+            BladeWsClient::new(endpoints.ws_base.clone(), api_key.clone())
+                .with_ping_interval(api_config.ws_ping_interval()),
+        );
         self.ws_client = Some(ws_client.clone());
         let session_id = self.session_id.clone();
         
@@ -363,13 +585,65 @@ impl ChatManager {
                     let mut authenticated = false;
                     let mut saw_chat_done = false;
                     let mut saw_content = false;
-                    while let Some(event) = ws_rx.recv().await {
+                    // Set while a resume_session is waiting for the server's reply
+                    let mut resume_deadline: Option<tokio::time::Instant> = None;
+                    loop {
+                        let event = match resume_deadline {
+                            Some(deadline) => {
+                                match tokio::time::timeout_at(deadline, ws_rx.recv()).await {
+                                    Ok(event) => event,
+                                    Err(_) => {
+                                        eprintln!(
+                                            "[CHAT MGR] No reply to resume_session, giving up"
+                                        );
+                                        let _ = tx.send(ChatEvent::Error(
+                                            "Server did not resume the session after reconnecting"
+                                                .to_string(),
+                                        ));
+                                        break;
+                                    }
+                                }
+                            }
+                            None => ws_rx.recv().await,
+                        };
+                        let Some(event) = event else { break };
                         eprintln!(
                             "[CHAT MGR] Received event: {:?}",
                             std::mem::discriminant(&event)
                         );
+                        if !matches!(
+                            event,
+                            crate::blade_ws_client::BladeWsEvent::Connected { .. }
+                                | crate::blade_ws_client::BladeWsEvent::Reconnecting { .. }
+                        ) {
+                            resume_deadline = None;
+                        }
                         match event {
                             crate::blade_ws_client::BladeWsEvent::Connected { .. } => {
+                                // Re-authenticated after a dropped socket: resume the session
+                                // instead of sending the user message a second time
+                                let resume_id = if authenticated {
+                                    ws_client
+                                        .get_session_id()
+                                        .await
+                                        .or_else(|| session_id.clone())
+                                } else {
+                                    None
+                                };
+                                if let Some(sid) = resume_id {
+                                    eprintln!("[CHAT MGR] Reconnected, resuming session {}", sid);
+                                    if let Err(e) = ws_client.resume_session(sid).await {
+                                        eprintln!("[CHAT MGR] Failed to resume session: {}", e);
+                                        let _ = tx.send(ChatEvent::Error(e));
+                                        break;
+                                    }
+                                    resume_deadline = Some(
+                                        tokio::time::Instant::now()
+                                            + crate::blade_ws_client::RESUME_REPLY_TIMEOUT,
+                                    );
+                                    continue;
+                                }
+
                                 eprintln!("[CHAT MGR] Authenticated, sending chat message with session_id: {:?}", session_id);
                                 authenticated = true;
 
@@ -534,7 +808,7 @@ impl ChatManager {
                                 }
                             }
                             crate::blade_ws_client::BladeWsEvent::Disconnected => {
-                                eprintln!("[CHAT MGR] Disconnected - session will be restored from database on reconnect");
+                                eprintln!("[CHAT MGR] Disconnected - reconnect attempts exhausted or connection closed");
                                 if authenticated && (saw_chat_done || saw_content) {
                                     let _ = tx.send(ChatEvent::Done);
                                 } else {
@@ -545,6 +819,17 @@ impl ChatManager {
                                 }
                                 break;
                             }
+                            crate::blade_ws_client::BladeWsEvent::Reconnecting { attempt, delay } => {
+                                eprintln!(
+                                    "[CHAT MGR] Connection lost, reconnecting in {:?} (attempt {})",
+                                    delay, attempt
+                                );
+                                let _ = tx.send(ChatEvent::Progress {
+                                    message: format!("Reconnecting… (attempt {})", attempt),
+                                    stage: "reconnecting".to_string(),
+                                    percent: 0,
+                                });
+                            }
                             crate::blade_ws_client::BladeWsEvent::Progress {
                                 message,
                                 stage,
@@ -685,7 +970,7 @@ impl ChatManager {
         // Will need to handle this differently in production
 
         // Push placeholder for assistant response
-        conversation.push(ChatMessage::new(ChatRole::Assistant, String::new()));
+        conversation.push(ChatMessage::streaming_assistant());
 
         self.rx = Some(rx);
         self.streaming = true;
@@ -698,6 +983,7 @@ impl ChatManager {
         conversation: &mut ConversationHistory,
         api_config: &ApiConfig,
         model_id: &str,
+        supports_reasoning: bool,
         http: reqwest::Client,
         workspace: Option<&PathBuf>,
         active_file: Option<String>,
@@ -825,7 +1111,7 @@ impl ChatManager {
             model: model_name.clone(),
             messages,
             stream: true,
-            tools: Some(get_tool_definitions()),
+            tools: Some(get_enabled_tool_definitions(workspace.map(|p| p.as_path()))),
         };
 
         let (tx, rx) = mpsc::channel();
@@ -839,14 +1125,16 @@ impl ChatManager {
             // The reasoning parser looks for <think> and <thinking> tags in the response.
             // If we run ALL text through it, regular content with angle brackets (HTML, XML, code)
             // gets misinterpreted as reasoning tags, causing garbled output.
-            // Only models like DeepSeek R1, Qwen QwQ, MiniMax, and Kimi use these tags.
-            let supports_reasoning = supports_reasoning_tags(&model_name);
+            // Only models like DeepSeek R1, Qwen QwQ, MiniMax, and Kimi use these tags
+            // (plus any the user registered in `reasoning_models`).
 
             let mut reasoning_parser = if supports_reasoning {
                 Some(ReasoningParser::new())
             } else {
                 None
             };
+            // Content held back while it might be the start of a split reasoning tag
+            let mut pending_content = String::new();
             
             let response = match http.post(&url).json(&request).send().await {
                 Ok(res) => res,
@@ -861,6 +1149,7 @@ impl ChatManager {
 
             let mut stream = response.bytes_stream();
             let mut buffer = String::new();
+            let mut pending_tool_calls: Vec<ToolCall> = Vec::new();
             let saw_done = false;
 
             while let Some(chunk) = stream.next().await {
@@ -914,83 +1203,32 @@ impl ChatManager {
                     if let Some(msg) = parsed.message {
                         if let Some(content) = msg.content {
                             if !content.is_empty() {
-                                // Dynamically enable reasoning parsing if <think>/<thinking> tags appear
-                                if reasoning_parser.is_none()
-                                    && (content.contains("<think>")
-                                        || content.to_lowercase().contains("<thinking>"))
-                                {
-                                    reasoning_parser = Some(ReasoningParser::new());
-                                }
-
-                                if let Some(ref mut parser) = reasoning_parser {
-                                    for segment in parser.process_segments(&content) {
-                                        match segment {
-                                            crate::reasoning_parser::ReasoningSegment::Text(text) => {
-                                                if !text.is_empty() {
-                                                    let _ = tx.send(ChatEvent::Chunk(text));
-                                                }
-                                            }
-                                            crate::reasoning_parser::ReasoningSegment::Reasoning(reasoning) => {
-                                                if !reasoning.is_empty() {
-                                                    let _ = tx.send(ChatEvent::ReasoningChunk(reasoning));
-                                                }
-                                            }
-                                        }
-                                    }
-                                } else {
-                                    // No reasoning parser - send content directly
-                                    let _ = tx.send(ChatEvent::Chunk(content));
-                                }
+                                // Reasoning parsing switches on if <think>/<thinking> tags appear
+                                let segments = route_streamed_content(
+                                    &mut reasoning_parser,
+                                    &mut pending_content,
+                                    &content,
+                                );
+                                send_segments(&tx, segments);
                             }
                         }
 
+                        // Tool calls may be split across chunks; emit them once on completion
                         if let Some(tool_calls) = msg.tool_calls {
-                            let calls: Vec<ToolCall> = tool_calls
-                                .into_iter()
-                                .map(|call| ToolCall {
-                                    id: if call.id.is_empty() {
-                                        uuid::Uuid::new_v4().to_string()
-                                    } else {
-                                        call.id
-                                    },
-                                    typ: if call.typ.is_empty() {
-                                        "function".to_string()
-                                    } else {
-                                        call.typ
-                                    },
-                                    function: ToolFunction {
-                                        name: call.function.name,
-                                        arguments: serde_json::to_string(&call.function.arguments)
-                                            .unwrap_or_default(),
-                                    },
-                                    status: Some("executing".to_string()),
-                                    result: None,
-                                })
-                                .collect();
-                            if !calls.is_empty() {
-                                let _ = tx.send(ChatEvent::ToolCalls(calls));
-                            }
+                            merge_ollama_tool_calls(&mut pending_tool_calls, tool_calls);
                         }
                     }
 
                     if parsed.done.unwrap_or(false) {
-                        // Flush any buffered content from reasoning parser before Done
-                        if let Some(ref mut parser) = reasoning_parser {
-                            for segment in parser.flush() {
-                                match segment {
-                                    crate::reasoning_parser::ReasoningSegment::Text(text) => {
-                                        if !text.is_empty() {
-                                            let _ = tx.send(ChatEvent::Chunk(text));
-                                        }
-                                    }
-                                    crate::reasoning_parser::ReasoningSegment::Reasoning(reasoning) => {
-                                        if !reasoning.is_empty() {
-                                            let _ = tx.send(ChatEvent::ReasoningChunk(reasoning));
-                                        }
-                                    }
-                                }
-                            }
+                        let calls = take_ollama_tool_calls(&mut pending_tool_calls);
+                        if !calls.is_empty() {
+                            let _ = tx.send(ChatEvent::ToolCalls(calls));
                         }
+                        // Flush any buffered content from reasoning parser before Done
+                        send_segments(
+                            &tx,
+                            flush_streamed_content(&mut reasoning_parser, &mut pending_content),
+                        );
                         let _ = tx.send(ChatEvent::Done);
                         return;
                     }
@@ -998,21 +1236,14 @@ impl ChatManager {
             }
 
             // Flush any buffered content before sending Done
-            if let Some(ref mut parser) = reasoning_parser {
-                for segment in parser.flush() {
-                    match segment {
-                        crate::reasoning_parser::ReasoningSegment::Text(text) => {
-                            if !text.is_empty() {
-                                let _ = tx.send(ChatEvent::Chunk(text));
-                            }
-                        }
-                        crate::reasoning_parser::ReasoningSegment::Reasoning(reasoning) => {
-                            if !reasoning.is_empty() {
-                                let _ = tx.send(ChatEvent::ReasoningChunk(reasoning));
-                            }
-                        }
-                    }
-                }
+            send_segments(
+                &tx,
+                flush_streamed_content(&mut reasoning_parser, &mut pending_content),
+            );
+
+            let calls = take_ollama_tool_calls(&mut pending_tool_calls);
+            if !calls.is_empty() {
+                let _ = tx.send(ChatEvent::ToolCalls(calls));
             }
 
             if !saw_done {
@@ -1020,7 +1251,7 @@ impl ChatManager {
             }
         });
 
-        conversation.push(ChatMessage::new(ChatRole::Assistant, String::new()));
+        conversation.push(ChatMessage::streaming_assistant());
         self.rx = Some(rx);
         self.streaming = true;
         self.abort_handle = Some(task.abort_handle());
@@ -1032,6 +1263,7 @@ impl ChatManager {
         conversation: &mut ConversationHistory,
         api_config: &ApiConfig,
         model_id: &str,
+        supports_reasoning: bool,
         http: reqwest::Client,
         workspace: Option<&PathBuf>,
         active_file: Option<String>,
@@ -1182,7 +1414,7 @@ impl ChatManager {
             model: model_name.clone(),
             messages,
             stream: true,
-            tools: Some(get_tool_definitions()),
+            tools: Some(get_enabled_tool_definitions(workspace.map(|p| p.as_path()))),
         };
 
         // OpenAI-compatible servers follow the /v1/chat/completions path; base URL should be versionless
@@ -1214,12 +1446,13 @@ impl ChatManager {
             }
 
             // Reasoning parser is optional and enabled only for models that support it or emit <think>/<thinking> tags.
-            let supports_reasoning = supports_reasoning_tags(&model_name);
             let mut reasoning_parser: Option<ReasoningParser> = if supports_reasoning {
                 Some(ReasoningParser::new())
             } else {
                 None
             };
+            // Content held back while it might be the start of a split reasoning tag
+            let mut pending_content = String::new();
 
             let response = match http
                 .post(&url)
@@ -1277,32 +1510,13 @@ impl ChatManager {
                                 // Handle text / reasoning deltas
                                 if let Some(content) = &choice.delta.content {
                                     if !content.is_empty() {
-                                        // Dynamically enable reasoning parsing if tags appear mid-stream
-                                        if reasoning_parser.is_none()
-                                            && (content.contains("<think>")
-                                                || content.to_lowercase().contains("<thinking>"))
-                                        {
-                                            reasoning_parser = Some(ReasoningParser::new());
-                                        }
-
-                                        if let Some(ref mut parser) = reasoning_parser {
-                                            for segment in parser.process_segments(content) {
-                                                match segment {
-                                                    crate::reasoning_parser::ReasoningSegment::Text(text) => {
-                                                        if !text.is_empty() {
-                                                            let _ = tx.send(ChatEvent::Chunk(text));
-                                                        }
-                                                    }
-                                                    crate::reasoning_parser::ReasoningSegment::Reasoning(reasoning) => {
-                                                        if !reasoning.is_empty() {
-                                                            let _ = tx.send(ChatEvent::ReasoningChunk(reasoning));
-                                                        }
-                                                    }
-                                                }
-                                            }
-                                        } else {
-                                            let _ = tx.send(ChatEvent::Chunk(content.clone()));
-                                        }
+                                        // Reasoning parsing switches on if tags appear mid-stream
+                                        let segments = route_streamed_content(
+                                            &mut reasoning_parser,
+                                            &mut pending_content,
+                                            content,
+                                        );
+                                        send_segments(&tx, segments);
                                     }
                                 }
 
@@ -1345,22 +1559,10 @@ impl ChatManager {
 
                                 if choice.finish_reason.is_some() {
                                     // Flush any buffered content from reasoning parser before Done
-                                    if let Some(ref mut parser) = reasoning_parser {
-                                        for segment in parser.flush() {
-                                            match segment {
-                                                crate::reasoning_parser::ReasoningSegment::Text(text) => {
-                                                    if !text.is_empty() {
-                                                        let _ = tx.send(ChatEvent::Chunk(text));
-                                                    }
-                                                }
-                                                crate::reasoning_parser::ReasoningSegment::Reasoning(reasoning) => {
-                                                    if !reasoning.is_empty() {
-                                                        let _ = tx.send(ChatEvent::ReasoningChunk(reasoning));
-                                                    }
-                                                }
-                                            }
-                                        }
-                                    }
+                                    send_segments(
+                                        &tx,
+                                        flush_streamed_content(&mut reasoning_parser, &mut pending_content),
+                                    );
                                     let _ = tx.send(ChatEvent::Done);
                                     break;
                                 }
@@ -1371,27 +1573,15 @@ impl ChatManager {
             }
 
             // Flush any buffered content before sending Done
-            if let Some(ref mut parser) = reasoning_parser {
-                for segment in parser.flush() {
-                    match segment {
-                        crate::reasoning_parser::ReasoningSegment::Text(text) => {
-                            if !text.is_empty() {
-                                let _ = tx.send(ChatEvent::Chunk(text));
-                            }
-                        }
-                        crate::reasoning_parser::ReasoningSegment::Reasoning(reasoning) => {
-                            if !reasoning.is_empty() {
-                                let _ = tx.send(ChatEvent::ReasoningChunk(reasoning));
-                            }
-                        }
-                    }
-                }
-            }
+            send_segments(
+                &tx,
+                flush_streamed_content(&mut reasoning_parser, &mut pending_content),
+            );
 
             let _ = tx.send(ChatEvent::Done);
         });
 
-        conversation.push(ChatMessage::new(ChatRole::Assistant, String::new()));
+        conversation.push(ChatMessage::streaming_assistant());
         self.rx = Some(rx);
         self.streaming = true;
         self.abort_handle = Some(task.abort_handle());
@@ -1408,28 +1598,33 @@ impl ChatManager {
         workspace: Option<&PathBuf>,
         http: reqwest::Client,
     ) -> Result<(), String> {
-        // RFC: Large Tool Result Handling - determine if we should truncate locally
-        let is_local_mode = workspace
+        // RFC: Large Tool Result Handling - determine if (and how far) we should truncate locally
+        let truncate_limit = workspace
             .map(|ws| {
                 let settings = crate::project_settings::load_project_settings_or_default(ws);
-                matches!(settings.storage.mode, crate::project_settings::StorageMode::Local)
+                let is_local_mode =
+                    matches!(settings.storage.mode, crate::project_settings::StorageMode::Local);
+                is_local_mode.then_some(settings.context.max_tool_result_bytes)
             })
-            .unwrap_or(true); // Default to local mode if no workspace
+            // Default to local mode if no workspace
+            .unwrap_or(Some(crate::tools::DEFAULT_MAX_TOOL_RESULT_BYTES));
         // Agentic Loop Check
         if self.agentic_loop.is_active() {
             self.agentic_loop.increment_turn();
             if !self.agentic_loop.is_active() {
-                return Err("Agentic loop stopped: max turns reached".to_string());
+                return Err(format!(
+                    "Agentic loop stopped: max turns ({}) reached",
+                    self.agentic_loop.max_turns()
+                ));
             }
         }
 
         // Store tool results in conversation history
         // RFC: Large Tool Result Handling - truncate in local mode
         for (_call, result) in batch.file_results.iter() {
-            let content = if is_local_mode {
-                result.to_tool_content_truncated()
-            } else {
-                result.to_tool_content()
+            let content = match truncate_limit {
+                Some(max_bytes) => result.to_tool_content_truncated(max_bytes),
+                None => result.to_tool_content(),
             };
             let mut tool_msg = ChatMessage::new(ChatRole::Tool, content);
             tool_msg.tool_call_id = Some(_call.id.clone());
@@ -1438,7 +1633,7 @@ impl ChatManager {
 
         // Update tool call status in the assistant message and store for emission
         // RFC: Large Tool Result Handling - truncate in local mode
-        let updated_assistant = conversation.update_tool_call_status_with_truncation(&batch.file_results, is_local_mode);
+        let updated_assistant = conversation.update_tool_call_status_with_truncation(&batch.file_results, truncate_limit);
         self.updated_assistant_message = updated_assistant;
 
         let is_ollama = models
@@ -1452,10 +1647,16 @@ impl ChatManager {
                 .get(selected_model)
                 .map(|m| m.api_id.as_ref().unwrap_or(&m.id).clone())
                 .unwrap_or_else(|| "ollama/unknown".to_string());
+            let supports_reasoning = supports_reasoning_tags(
+                models.get(selected_model),
+                &model_id,
+                &self.reasoning_models,
+            );
             return self.start_ollama_stream(
                 conversation,
                 api_config,
                 &model_id,
+                supports_reasoning,
                 http,
                 workspace,
                 None,
@@ -1473,11 +1674,17 @@ impl ChatManager {
                 .get(selected_model)
                 .map(|m| m.id.clone())
                 .unwrap_or_else(|| "openai-compat/unknown".to_string());
+            let supports_reasoning = supports_reasoning_tags(
+                models.get(selected_model),
+                &model_id,
+                &self.reasoning_models,
+            );
             // Keep rx open; start a fresh openai-compat stream to continue after tools
             return self.start_openai_compat_stream(
                 conversation,
                 api_config,
                 &model_id,
+                supports_reasoning,
                 http,
                 workspace,
                 None,
@@ -1503,7 +1710,7 @@ impl ChatManager {
             .ok_or_else(|| "No WebSocket client available".to_string())?
             .clone();
         let results = batch.file_results.clone(); // Clone for the task
-        let is_local_mode_clone = is_local_mode; // Clone for async task
+        let truncate_limit_clone = truncate_limit; // Clone for async task
 
         // RFC-002: Clone conversation messages for local storage mode context retrieval
         // Convert to BladeMessage format that zcoderd expects
@@ -1556,10 +1763,9 @@ impl ChatManager {
             // Send ALL results sequentially
             // RFC: Large Tool Result Handling - truncate in local mode
             for (call, result) in &results {
-                let tool_content = if is_local_mode_clone {
-                    result.to_tool_content_truncated()
-                } else {
-                    result.to_tool_content()
+                let tool_content = match truncate_limit_clone {
+                    Some(max_bytes) => result.to_tool_content_truncated(max_bytes),
+                    None => result.to_tool_content(),
                 };
                 eprintln!(
                     "[TOOL RESULT SEND] call_id={}, success={}",
@@ -1643,9 +1849,12 @@ impl ChatManager {
             || model_id.contains("gpt-5.2")
             || model_id.contains("codex")
             || is_blade_protocol;
-        let use_reasoning_parser = supports_reasoning_tags(&model_id) && !is_blade_protocol;
+        let use_reasoning_parser =
+            supports_reasoning_tags(models.get(selected_model), &model_id, &self.reasoning_models)
+                && !is_blade_protocol;
 
         let mut batched_chunk = String::new();
+        let mut batched_chunk_count = 0;
         let mut done = false;
         let mut error_msg: Option<String> = None;
 
@@ -1703,7 +1912,7 @@ impl ChatManager {
                             }
                         }
                     } else {
-                        conversation.push(ChatMessage::new(ChatRole::Assistant, String::new()));
+                        conversation.push(ChatMessage::streaming_assistant());
                         if let Some(new_last) = conversation.last_mut() {
                             if is_openai_text {
                                 new_last.content.push_str(&s);
@@ -1744,6 +1953,10 @@ impl ChatManager {
                             }
                         }
                     }
+                    let chunk_count = std::mem::take(&mut batched_chunk_count);
+                    if let Some(assistant_msg) = conversation.last_assistant_mut() {
+                        record_chunks(assistant_msg, chunk_count);
+                    }
                     batched_chunk.clear();
                 }
             };
@@ -1769,6 +1982,7 @@ impl ChatManager {
                         }
                     }
                     batched_chunk.push_str(&s);
+                    batched_chunk_count += 1;
                 }
                 ChatEvent::ToolActivity(payload) => {
                     flush_batch!();
@@ -1803,6 +2017,7 @@ impl ChatManager {
                     flush_batch!();
                     // Emit reasoning immediately for incremental display and proper separation from text
                     if let Some(assistant_msg) = conversation.last_assistant_mut() {
+                        record_chunks(assistant_msg, 1);
                         let r = assistant_msg.reasoning.get_or_insert_with(String::new);
                         r.push_str(&s);
                         let mid = assistant_msg.id.clone().unwrap_or_default();
@@ -2044,13 +2259,7 @@ impl ChatManager {
                 "[DRAIN] Calling finalize_turn with tool_calls: {:?}",
                 tool_calls.as_ref().map(|c| c.len())
             );
-            self.finalize_turn(
-                conversation,
-                tool_calls.clone(),
-                &error_msg,
-                models,
-                selected_model,
-            );
+            self.finalize_turn(conversation, tool_calls.clone(), &error_msg);
 
             // Set streaming=false to reduce CPU usage during tool execution.
             // IMPORTANT: Do NOT clear rx if we expect more events (e.g., additional tool calls).
@@ -2177,17 +2386,7 @@ impl ChatManager {
         conversation: &mut ConversationHistory,
         tool_calls: Option<Vec<ToolCall>>,
         error_msg: &Option<String>,
-        models: &[ModelInfo],
-        selected_model: usize,
     ) {
-        let is_qwen = models
-            .get(selected_model)
-            .map(|m| {
-                let id = m.id.to_lowercase();
-                id.contains("qwen") || id.contains("mercury")
-            })
-            .unwrap_or(false);
-
         let has_tool_calls = tool_calls.as_ref().map(|t| !t.is_empty()).unwrap_or(false);
 
         // 1. Agentic Loop Logic
@@ -2205,8 +2404,8 @@ impl ChatManager {
                     }
                 }
             }
-        } else if (is_qwen) && has_tool_calls {
-            // Auto-start loop for Qwen if tools are used
+        } else if has_tool_calls {
+            // Count tool-result round trips against max_turns from the first tool call
             eprintln!("[AGENTIC LOOP] Auto-starting for tool execution");
             self.agentic_loop.start();
         }
@@ -2225,6 +2424,11 @@ impl ChatManager {
                 // If barely anything happened, mark it
                 last.content = "[no content]".to_string();
             }
+
+            // Tool round trips keep the same message, so the last completion wins
+            let metrics = last.metrics.get_or_insert_with(MessageMetrics::started_now);
+            metrics.tool_call_count += tool_calls.as_ref().map_or(0, Vec::len);
+            metrics.complete();
         }
     }
 
@@ -2266,6 +2470,7 @@ impl ChatManager {
             selection_start_line: None,
             selection_end_line: None,
             app_handle: None,
+            conversation_id: None,
         };
         ai.handle_tool_calls(workspace, calls, content, &context)
     }
@@ -2284,6 +2489,7 @@ mod tests {
         conversation.push(ChatMessage::new(ChatRole::User, "Test".to_string()));
 
         let api_config = ApiConfig {
+            blade_url: "https://coder.zaguanai.com".to_string(),
             api_key: "test_key".to_string(),
             ..Default::default()
         };
@@ -2308,6 +2514,7 @@ mod tests {
                 None, // cursor_column
                 http,
                 None, // storage_mode
+                None, // language_service
             );
 
             // Verify conversation has Assistant placeholder
@@ -2327,4 +2534,136 @@ mod tests {
             );
         });
     }
+
+    #[test]
+    fn test_drain_events_records_message_metrics() {
+        let mut chat_manager = ChatManager::new(50);
+        let mut conversation = ConversationHistory::new();
+        conversation.push(ChatMessage::new(ChatRole::User, "Test".to_string()));
+        conversation.push(ChatMessage::streaming_assistant());
+
+        let (tx, rx) = mpsc::channel();
+        chat_manager.rx = Some(rx);
+        tx.send(ChatEvent::Chunk("Hel".to_string())).unwrap();
+        tx.send(ChatEvent::Chunk("lo".to_string())).unwrap();
+        tx.send(ChatEvent::ToolCalls(vec![ToolCall {
+            id: "call_1".to_string(),
+            typ: "function".to_string(),
+            function: ToolFunction {
+                name: "read_file".to_string(),
+                arguments: "{}".to_string(),
+            },
+            status: None,
+            result: None,
+        }]))
+        .unwrap();
+        tx.send(ChatEvent::Done).unwrap();
+
+        chat_manager.drain_events(&mut conversation, &[], 0);
+
+        let metrics = conversation.last().unwrap().metrics.clone().unwrap();
+        assert_eq!(metrics.chunk_count, 2);
+        assert_eq!(metrics.tool_call_count, 1);
+        assert!(metrics.duration_ms().is_some());
+    }
+
+    fn model(id: &str, supports_reasoning: Option<bool>) -> ModelInfo {
+        ModelInfo {
+            id: id.to_string(),
+            name: id.to_string(),
+            description: String::new(),
+            provider: Some("ollama".to_string()),
+            reasoning_effort: None,
+            api_id: None,
+            context_window: None,
+            supports_reasoning,
+            supports_tools: true,
+            max_output_tokens: None,
+        }
+    }
+
+    #[test]
+    fn test_supports_reasoning_tags_precedence() {
+        let custom = vec!["my-thinker".to_string()];
+
+        // Built-in heuristics still apply
+        assert!(supports_reasoning_tags(None, "ollama/deepseek-coder", &[]));
+        assert!(!supports_reasoning_tags(None, "ollama/My-Thinker:7b", &[]));
+
+        // User-registered substrings match case-insensitively
+        assert!(supports_reasoning_tags(None, "ollama/My-Thinker:7b", &custom));
+
+        // An explicit per-model override beats both
+        let opted_out = model("ollama/deepseek-coder", Some(false));
+        assert!(!supports_reasoning_tags(Some(&opted_out), &opted_out.id, &custom));
+        let opted_in = model("ollama/llama3", Some(true));
+        assert!(supports_reasoning_tags(Some(&opted_in), &opted_in.id, &[]));
+    }
+
+    #[test]
+    fn test_ollama_tool_call_split_across_chunks() {
+        let chunks = [
+            r#"{"message":{"role":"assistant","tool_calls":[{"function":{"index":0,"name":"read_file","arguments":"{\"path\": \"src/"}}]},"done":false}"#,
+            r#"{"message":{"role":"assistant","tool_calls":[{"function":{"index":0,"arguments":"main.rs\"}"}}]},"done":true}"#,
+        ];
+
+        let mut pending = Vec::new();
+        for line in chunks {
+            let chunk: OllamaChatChunk = serde_json::from_str(line).unwrap();
+            if let Some(calls) = chunk.message.and_then(|m| m.tool_calls) {
+                merge_ollama_tool_calls(&mut pending, calls);
+            }
+        }
+
+        let calls = take_ollama_tool_calls(&mut pending);
+        assert_eq!(calls.len(), 1);
+        assert_eq!(calls[0].function.name, "read_file");
+        assert_eq!(calls[0].function.arguments, r#"{"path": "src/main.rs"}"#);
+        assert!(pending.is_empty());
+    }
+
+    #[test]
+    fn test_ollama_complete_tool_calls_are_not_merged() {
+        let line = r#"{"message":{"role":"assistant","tool_calls":[{"function":{"name":"read_file","arguments":{"path":"a.rs"}}},{"function":{"name":"read_file","arguments":{"path":"b.rs"}}}]},"done":true}"#;
+        let chunk: OllamaChatChunk = serde_json::from_str(line).unwrap();
+
+        let mut pending = Vec::new();
+        merge_ollama_tool_calls(&mut pending, chunk.message.unwrap().tool_calls.unwrap());
+
+        let calls = take_ollama_tool_calls(&mut pending);
+        assert_eq!(calls.len(), 2);
+        assert_eq!(calls[0].function.arguments, r#"{"path":"a.rs"}"#);
+        assert_eq!(calls[1].function.arguments, r#"{"path":"b.rs"}"#);
+        assert_ne!(calls[0].id, calls[1].id);
+    }
+
+    #[test]
+    fn test_split_think_tag_enables_reasoning_parser() {
+        let mut parser = None;
+        let mut pending = String::new();
+        let mut segments = Vec::new();
+
+        for chunk in ["Hi <thi", "nk>plan</think>", "answer"] {
+            segments.extend(route_streamed_content(&mut parser, &mut pending, chunk));
+        }
+        segments.extend(flush_streamed_content(&mut parser, &mut pending));
+
+        assert!(parser.is_some());
+        assert_eq!(
+            segments,
+            vec![
+                ReasoningSegment::Text("Hi ".to_string()),
+                ReasoningSegment::Reasoning("plan".to_string()),
+                ReasoningSegment::Text("answer".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_thinking_tag_detection_ignores_case() {
+        let mut parser = None;
+        let mut pending = String::new();
+        route_streamed_content(&mut parser, &mut pending, "<Thinking>plan</Thinking>");
+        assert!(parser.is_some());
+    }
 }
@@ -1,16 +1,17 @@
 use crate::app_state::AppState;
+use crate::at_commands::{expand_file_references, parse_command};
 use crate::chat_manager::DrainResult;
 use crate::models::registry::get_models;
 use crate::project_settings;
-use crate::utils::{extract_root_command, is_cwd_outside_workspace, parse_command};
+use crate::utils::{allowlistable_root_command, extract_root_command, is_cwd_outside_workspace};
 use crate::{blade_protocol, local_artifacts};
 use tauri::{AppHandle, Emitter, Manager, Runtime, State};
 
-async fn load_available_models(state: &State<'_, AppState>) -> Vec<crate::models::registry::ModelInfo> {
-    let (blade_url, api_key, ollama_enabled, ollama_url, openai_compat_enabled, openai_compat_url) = {
+pub(crate) async fn load_available_models(state: &State<'_, AppState>) -> Vec<crate::models::registry::ModelInfo> {
+    let (endpoints, api_key, ollama_enabled, ollama_url, openai_compat_enabled, openai_compat_url) = {
         let config = state.config.lock().unwrap();
         (
-            config.blade_url.clone(),
+            config.normalized(),
             config.api_key.clone(),
             config.ollama_enabled,
             config.ollama_url.clone(),
@@ -19,7 +20,13 @@ async fn load_available_models(state: &State<'_, AppState>) -> Vec<crate::models
         )
     };
 
-    let mut models = get_models(&blade_url, &api_key).await;
+    let mut models = match endpoints {
+        Ok(endpoints) => get_models(&endpoints.http_base, &api_key).await,
+        Err(e) => {
+            eprintln!("[MODEL REGISTRY] Skipping Blade models: {}", e);
+            Vec::new()
+        }
+    };
     if ollama_enabled {
         let mut ollama_models = crate::models::ollama::get_models(&ollama_url).await;
         models.append(&mut ollama_models);
@@ -33,6 +40,69 @@ async fn load_available_models(state: &State<'_, AppState>) -> Vec<crate::models
     models
 }
 
+/// Write a copy of `value` to the system temp dir after a regular save failed. The file
+/// gets a random name and owner-only permissions, since the temp dir is shared.
+fn save_to_temp<T: serde::Serialize>(
+    prefix: &str,
+    value: &T,
+) -> Result<std::path::PathBuf, String> {
+    let mut file = tempfile::Builder::new()
+        .prefix(prefix)
+        .suffix(".json")
+        .tempfile()
+        .map_err(|e| format!("Failed to create backup: {}", e))?;
+    serde_json::to_writer_pretty(&mut file, value)
+        .map_err(|e| format!("Failed to write backup: {}", e))?;
+    let (_, path) = file
+        .keep()
+        .map_err(|e| format!("Failed to keep backup: {}", e))?;
+    Ok(path)
+}
+
+/// Retry a failed save to a temp location, then tell the frontend the save failed
+fn report_persistence_failure<R: Runtime, T: serde::Serialize>(
+    window: &tauri::Window<R>,
+    scope: &str,
+    backup_prefix: &str,
+    value: &T,
+    error: String,
+) {
+    let message = match save_to_temp(backup_prefix, value) {
+        Ok(path) => format!("{} (backup written to {})", error, path.display()),
+        Err(backup_error) => format!("{} (backup also failed: {})", error, backup_error),
+    };
+    eprintln!("[PERSIST] Failed to save {}: {}", scope, message);
+
+    let _ = window.emit(
+        "blade-event",
+        blade_protocol::BladeEventEnvelope {
+            id: uuid::Uuid::new_v4(),
+            timestamp: std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_millis() as u64,
+            causality_id: None,
+            event: blade_protocol::BladeEvent::System(
+                blade_protocol::SystemEvent::PersistenceFailed {
+                    scope: scope.to_string(),
+                    message,
+                },
+            ),
+        },
+    );
+}
+
+/// How the poll loop handles tool calls that need user approval
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ApprovalMode {
+    /// Ask the frontend and wait for its decision
+    Interactive,
+    /// Run commands and confirm-gated tools without asking (scripted callers)
+    AutoApprove,
+    /// Fail the turn instead of asking (scripted callers without auto-approve)
+    Reject,
+}
+
 pub async fn handle_send_message<R: Runtime>(
     message: String,
     images: Option<Vec<crate::protocol::ChatImage>>,
@@ -47,6 +117,46 @@ pub async fn handle_send_message<R: Runtime>(
     state: State<'_, AppState>,
     app: AppHandle<R>,
 ) -> Result<(), String> {
+    start_message_turn(
+        message,
+        images,
+        false,
+        model_id,
+        active_file,
+        open_files,
+        cursor_line,
+        cursor_column,
+        selection_start_line,
+        selection_end_line,
+        window,
+        state,
+        app,
+        ApprovalMode::Interactive,
+    )
+    .await
+    .map(|_| ())
+}
+
+/// Start a chat turn and its background poll loop. The returned handle
+/// resolves when the turn has fully finished (including tool round-trips),
+/// with the chat error if it failed. `files_expanded` marks a message whose
+/// `@file` references were already inlined (e.g. one resent from history).
+pub async fn start_message_turn<R: Runtime>(
+    message: String,
+    images: Option<Vec<crate::protocol::ChatImage>>,
+    files_expanded: bool,
+    model_id: Option<String>,
+    active_file: Option<String>,
+    open_files: Option<Vec<String>>,
+    cursor_line: Option<usize>,
+    cursor_column: Option<usize>,
+    selection_start_line: Option<usize>,
+    selection_end_line: Option<usize>,
+    window: tauri::Window<R>,
+    state: State<'_, AppState>,
+    app: AppHandle<R>,
+    approval: ApprovalMode,
+) -> Result<tauri::async_runtime::JoinHandle<Result<(), String>>, String> {
     println!("Received message from frontend: {}", message);
     eprintln!(
         "[SEND MSG] active_file={:?}, cursor_line={:?}, cursor_column={:?}",
@@ -63,8 +173,28 @@ pub async fn handle_send_message<R: Runtime>(
         *state.selection_end_line.lock().unwrap() = selection_end_line;
     }
 
+    // Fresh cancel token per message so a stop aimed at an earlier loop can't leak into this one
+    let cancel = {
+        let token = std::sync::Arc::new(std::sync::atomic::AtomicBool::new(false));
+        *state.generation_cancel.lock().unwrap() = token.clone();
+        token
+    };
+
     // Parse @commands and convert to tool calls
-    let (actual_message, forced_tool) = parse_command(&message);
+    let parsed = parse_command(&message);
+    if let Some(hint) = &parsed.hint {
+        eprintln!("[COMMAND] {}", hint.message);
+        let _ = window.emit(crate::events::event_names::COMMAND_HINT, hint);
+    }
+    let (actual_message, forced_tool) = (parsed.message, parsed.forced_tool);
+
+    // Inline @file references before the message is stored or sent
+    let actual_message = if files_expanded {
+        actual_message
+    } else {
+        let workspace_root = state.workspace.lock().unwrap().workspace.clone();
+        expand_file_references(&actual_message, workspace_root.as_deref())
+    };
 
     // Check for pending error feedback from previous turn (e.g. message too large)
     // Prepend it as a system note so the model knows what happened
@@ -86,6 +216,7 @@ pub async fn handle_send_message<R: Runtime>(
             actual_message.clone(),
         );
         chat_msg.images = images.clone();
+        chat_msg.files_expanded = true;
         conversation.push(chat_msg);
     }
 
@@ -99,6 +230,9 @@ pub async fn handle_send_message<R: Runtime>(
         eprintln!("[COMMAND] zcoderd will handle this directly");
     }
 
+    // The tool call budget is per user message
+    state.workflow.lock().unwrap().start_message();
+
     // 2. Start Stream
     let models = load_available_models(&state).await;
     {
@@ -157,6 +291,11 @@ pub async fn handle_send_message<R: Runtime>(
 
         // Store active model index for use in continue_tool_batch
         *state.selected_model_index.lock().unwrap() = selected_model;
+        if let Some(model) = models.get(selected_model) {
+            *state.selected_model_id.lock().unwrap() = Some(model.id.clone());
+        }
+        // Editor state was stored above; persist it along with the model
+        crate::project_state::schedule_autosave(&app);
 
         // We use reqwest Client
         let http = reqwest::Client::new();
@@ -175,6 +314,10 @@ pub async fn handle_send_message<R: Runtime>(
             })
             .unwrap_or_else(|| "local".to_string()),
         );
+        mgr.agentic_loop.set_max_turns(
+            ws.map(|p| project_settings::load_project_settings_or_default(p).max_turns)
+                .unwrap_or(project_settings::DEFAULT_MAX_TURNS),
+        );
 
         mgr.start_stream(
             message,
@@ -189,6 +332,7 @@ pub async fn handle_send_message<R: Runtime>(
             cursor_column,
             http,
             storage_mode,
+            Some(state.language_service.clone()),
         )
         .map_err(|e| e.to_string())?;
     }
@@ -196,8 +340,10 @@ pub async fn handle_send_message<R: Runtime>(
     // 3. Event-Driven Processing (Background Task)
     // Only processes events when there's actual streaming activity
     let app_handle = app.clone();
+    // Tool calls made during this turn are logged under the conversation it started in
+    let conversation_id = state.conversation.lock().unwrap().metadata.id.clone();
 
-    tauri::async_runtime::spawn(async move {
+    let turn = tauri::async_runtime::spawn(async move {
         let mut last_session_id: Option<String> = None;
         
         // Fetch models once at the start instead of every iteration
@@ -205,6 +351,15 @@ pub async fn handle_send_message<R: Runtime>(
         let models = load_available_models(&state).await;
 
         loop {
+            // stop_generation was requested: bail out before draining so no late event
+            // (e.g. a stray MessageDelta) reaches the UI after the stop
+            if cancel.load(std::sync::atomic::Ordering::SeqCst) {
+                eprintln!("[ORCHESTRATOR] Generation cancelled, stopping poll loop");
+                app_handle.state::<AppState>().reload_command_allowlist();
+                window.emit("chat-done", ()).unwrap_or_default();
+                break Err("Generation cancelled".to_string());
+            }
+
             // Check if we're actually streaming before processing
             let (is_streaming, has_rx, has_pending) = {
                 let state = app_handle.state::<AppState>();
@@ -229,6 +384,11 @@ pub async fn handle_send_message<R: Runtime>(
             }
             // If has_pending, process immediately without sleeping
 
+            // A stop may have arrived while we slept
+            if cancel.load(std::sync::atomic::Ordering::SeqCst) {
+                continue;
+            }
+
             let state = app_handle.state::<AppState>();
 
             let (result, is_streaming, session_id) = {
@@ -254,10 +414,7 @@ pub async fn handle_send_message<R: Runtime>(
                     let mut workflow = state.workflow.lock().unwrap();
                     workflow.clear_history();
                 }
-                {
-                    let mut cache = state.approved_command_roots.lock().unwrap();
-                    cache.clear();
-                }
+                state.reload_command_allowlist();
                 last_session_id = session_id.clone();
             }
 
@@ -281,7 +438,13 @@ pub async fn handle_send_message<R: Runtime>(
                         // Persist the current session ID to the stored metadata
                         stored.metadata.session_id = session_id.clone();
                         if let Err(e) = store.save_conversation(&stored) {
-                            eprintln!("Failed to auto-save conversation: {}", e);
+                            report_persistence_failure(
+                                &window,
+                                "conversation",
+                                &format!("zblade-conversation-{}-", stored.metadata.id),
+                                &stored,
+                                e,
+                            );
                         } else {
                             println!("Auto-saved conversation: {}", stored.metadata.id);
                         }
@@ -313,7 +476,9 @@ pub async fn handle_send_message<R: Runtime>(
                                         role: msg.role.clone(),
                                         content: msg.content.clone(),
                                         timestamp: chrono::Utc::now().to_rfc3339(),
-                                        code_references: vec![], // TODO: Extract from content
+                                        code_references: local_artifacts::extract_code_references(
+                                            &msg.content,
+                                        ),
                                     };
                                     artifact.messages.push(local_msg);
                                 }
@@ -322,7 +487,13 @@ pub async fn handle_send_message<R: Runtime>(
                                 let artifact_store =
                                     local_artifacts::LocalArtifactStore::new(ws_path);
                                 if let Err(e) = artifact_store.save_conversation(&artifact) {
-                                    eprintln!("[LOCAL] Failed to save local artifact: {}", e);
+                                    report_persistence_failure(
+                                        &window,
+                                        "artifact",
+                                        &format!("zblade-artifact-{}-", stored.metadata.id),
+                                        &artifact,
+                                        e,
+                                    );
                                 } else {
                                     eprintln!("[LOCAL] Saved conversation to .zblade/artifacts/conversations/{}.json", stored.metadata.id);
                                 }
@@ -363,7 +534,7 @@ pub async fn handle_send_message<R: Runtime>(
                     }
 
                     window.emit("chat-done", ()).unwrap_or_default();
-                    break;
+                    break Ok(());
                 }
             } else if let DrainResult::Research {
                 content,
@@ -455,8 +626,8 @@ pub async fn handle_send_message<R: Runtime>(
                     },
                 );
             } else if let DrainResult::Error(e) = result {
-                window.emit("chat-error", e).unwrap_or_default();
-                break;
+                window.emit("chat-error", &e).unwrap_or_default();
+                break Err(e);
             } else if let DrainResult::ToolCreated(msg, new_calls) = result {
                 let msg_id = msg.id.clone().unwrap_or_else(|| "unknown".to_string());
                 for tc in new_calls {
@@ -649,7 +820,8 @@ pub async fn handle_send_message<R: Runtime>(
                     selection_start_line,
                     selection_end_line,
                     Some(app_handle.clone()),
-                );
+                )
+                .with_conversation_id(Some(conversation_id.clone()));
 
                 let batch_opt = {
                     let mut workflow = state.workflow.lock().unwrap();
@@ -690,6 +862,62 @@ pub async fn handle_send_message<R: Runtime>(
                     if !has_pending_actions {
                         // No approval needed - set batch to run and let it fall through
                         batch_to_run = Some(batch);
+                    } else if approval == ApprovalMode::Reject {
+                        let mut batch = batch;
+                        let pending: Vec<String> = batch
+                            .commands
+                            .iter()
+                            .map(|c| c.command.clone())
+                            .chain(batch.confirms.iter().map(|c| c.tool_name.clone()))
+                            .collect();
+                        let error =
+                            format!("Tool call requires approval: {}", pending.join(", "));
+
+                        // Answer every call so the stored assistant message doesn't keep
+                        // tool_calls without results, then end the turn
+                        let rejected: Vec<_> = batch
+                            .commands
+                            .iter()
+                            .map(|c| (c.call.clone(), format!("'{}'", c.command)))
+                            .chain(
+                                batch
+                                    .confirms
+                                    .iter()
+                                    .map(|c| (c.call.clone(), format!("'{}'", c.description))),
+                            )
+                            .collect();
+                        for (call, label) in rejected {
+                            if !batch.file_results.iter().any(|(c, _)| c.id == call.id) {
+                                let msg = format!(
+                                    "Rejected {}: this session can't approve commands or confirm-gated tools.",
+                                    label
+                                );
+                                batch
+                                    .file_results
+                                    .push((call, crate::tools::ToolResult::skipped(msg)));
+                            }
+                        }
+                        {
+                            let mut conversation = state.conversation.lock().unwrap();
+                            for (call, result) in &batch.file_results {
+                                let mut tool_msg = crate::protocol::ChatMessage::new(
+                                    crate::protocol::ChatRole::Tool,
+                                    result.to_tool_content(),
+                                );
+                                tool_msg.tool_call_id = Some(call.id.clone());
+                                conversation.push(tool_msg);
+                            }
+                            conversation
+                                .update_tool_call_status_with_truncation(&batch.file_results, None);
+                        }
+
+                        state.reload_command_allowlist();
+                        window.emit("chat-error", &error).unwrap_or_default();
+                        break Err(error);
+                    } else if approval == ApprovalMode::AutoApprove {
+                        let mut batch = batch;
+                        run_approval_free(&mut batch, ws_root.as_deref(), &context).await;
+                        batch_to_run = Some(batch);
                     } else {
                         // If we reach here, there ARE pending items that need approval
                         // MUST go through the approval flow
@@ -729,6 +957,12 @@ pub async fn handle_send_message<R: Runtime>(
                                 },
                                 #[serde(rename = "delete_file")]
                                 DeleteFile { id: String, path: String },
+                                #[serde(rename = "semantic")]
+                                Semantic {
+                                    id: String,
+                                    path: String,
+                                    diff: String,
+                                },
                             }
 
                             let proposals: Vec<ChangeProposal> = batch
@@ -764,6 +998,24 @@ pub async fn handle_send_message<R: Runtime>(
                                             path: change.path.clone(),
                                         }
                                     }
+                                    crate::ai_workflow::ChangeType::Semantic { patch } => {
+                                        let applier = crate::semantic_patch::PatchApplier::new(
+                                            state.language_service.clone(),
+                                        );
+                                        let diff = match applier.preview(patch) {
+                                            Ok(hunks) => hunks
+                                                .iter()
+                                                .map(|h| h.to_string())
+                                                .collect::<Vec<_>>()
+                                                .join("\n"),
+                                            Err(e) => format!("Preview unavailable: {}", e),
+                                        };
+                                        ChangeProposal::Semantic {
+                                            id: change.call.id.clone(),
+                                            path: change.path.clone(),
+                                            diff,
+                                        }
+                                    }
                                 })
                                 .collect();
 
@@ -773,7 +1025,10 @@ pub async fn handle_send_message<R: Runtime>(
                         }
 
                         // Handle Commands and Confirms
+                        // Simple commands whose root is allowlisted run without a confirmation prompt
+                        let mut auto_approved = Vec::new();
                         if !batch.commands.is_empty() || !batch.confirms.is_empty() {
+                            let approved_roots = state.approved_command_roots.lock().unwrap().clone();
                             let mut actions = Vec::new();
                             for cmd in &batch.commands {
                                 if batch.file_results.iter().any(|(c, _)| c.id == cmd.call.id) {
@@ -784,6 +1039,19 @@ pub async fn handle_send_message<R: Runtime>(
                                     ws_root.as_deref(),
                                     cmd.cwd.as_deref(),
                                 );
+                                // Chained or redirected commands always need a prompt, whatever their root
+                                let is_allowlisted = allowlistable_root_command(&cmd.command)
+                                    .is_some_and(|root| approved_roots.contains(&root));
+                                if is_allowlisted && cwd_outside_workspace != Some(true) {
+                                    eprintln!("[ORCHESTRATOR] Auto-approving allowlisted command: {}", cmd.command);
+                                    auto_approved.push(crate::events::CommandExecutionStartedPayload {
+                                        command_id: format!("cmd-{}", cmd.call.id),
+                                        call_id: cmd.call.id.clone(),
+                                        command: cmd.command.clone(),
+                                        cwd: cmd.cwd.clone(),
+                                    });
+                                    continue;
+                                }
                                 actions.push(crate::events::StructuredAction {
                                     id: cmd.call.id.clone(),
                                     command: cmd.command.clone(),
@@ -829,6 +1097,26 @@ pub async fn handle_send_message<R: Runtime>(
                             *guard = Some(tx);
                         }
 
+                        // Start allowlisted commands only once the sender is in place, so a
+                        // fast submit_command_result can't complete the batch before we wait
+                        for payload in auto_approved {
+                            if let Err(e) = crate::utils::ensure_command_cwd(payload.cwd.as_deref())
+                            {
+                                crate::commands::tools::record_command_result(
+                                    &state,
+                                    &app_handle,
+                                    &payload.call_id,
+                                    &e,
+                                    1,
+                                    None,
+                                );
+                                continue;
+                            }
+                            window
+                                .emit(crate::events::event_names::COMMAND_EXECUTION_STARTED, payload)
+                                .unwrap_or_default();
+                        }
+
                         // Wait for the signal (sent by approve_change, approve_tool, or approve_all_changes)
                         let _ = rx.await.unwrap_or(false);
 
@@ -847,7 +1135,9 @@ pub async fn handle_send_message<R: Runtime>(
                     }
                 }
 
-                if let Some(batch) = batch_to_run {
+                if let Some(mut batch) = batch_to_run {
+                    redact_batch_results(&mut batch, ws_root.as_deref(), &window);
+
                     // Auto-open files in editor when file-modifying tools are called
                     // (read_file operations don't auto-open to avoid disruptive UX)
                     for (call, result) in &batch.file_results {
@@ -864,7 +1154,9 @@ pub async fn handle_send_message<R: Runtime>(
                                 | "apply_edit"
                                 | "apply_patch"
                                 | "edit_file"
+                                | "edit_lines"
                                 | "multi_replace_file_content"
+                                | "semantic_edit"
                         );
 
                         if is_file_modifying_tool {
@@ -901,21 +1193,21 @@ pub async fn handle_send_message<R: Runtime>(
                         }
                     }
 
-                    // Check if loop was detected - if so, stop the agentic loop
-                    if batch.loop_detected {
-                        eprintln!("[AGENTIC LOOP] Stopping due to loop detection");
+                    // Check if the batch ends the agentic loop (loop detection, tool call limit)
+                    if let Some(reason) = batch.stop_reason {
+                        eprintln!("[AGENTIC LOOP] Stopping: {}", reason.describe());
 
                         let models = load_available_models(&state).await;
 
                         {
                             let mut mgr = state.chat_manager.lock().unwrap();
-                            mgr.agentic_loop.stop("loop detected");
+                            mgr.agentic_loop.stop(reason.describe());
                             // Still send the tool results back to the model so it can respond
                             let mut conversation = state.conversation.lock().unwrap();
                             let config = state.config.lock().unwrap();
                             let selected_model_idx = *state.selected_model_index.lock().unwrap();
                             let ws = state.workspace.lock().unwrap();
-                            let http = reqwest::Client::new();
+                            let http = state.blade_http.clone();
 
                             mgr.continue_tool_batch(
                                 batch,
@@ -926,14 +1218,18 @@ pub async fn handle_send_message<R: Runtime>(
                                 ws.workspace.as_ref(),
                                 http,
                             )
-                            .unwrap_or_else(|e| eprintln!("Continue batch failed: {}", e));
+                            .unwrap_or_else(|e| {
+                                eprintln!("Continue batch failed: {}", e);
+                                if mgr.agentic_loop.reached_max_turns() {
+                                    crate::ai_workflow::emit_agent_stopped(&window, &e);
+                                } else {
+                                    window.emit("chat-error", &e).unwrap_or_default();
+                                }
+                            });
                         }
 
-                        // Clear approved command roots after loop detection
-                        {
-                            let mut cache = state.approved_command_roots.lock().unwrap();
-                            cache.clear();
-                        }
+                        // Drop session approvals once the loop stops (persisted allowlist stays)
+                        state.reload_command_allowlist();
 
                         // Don't continue the loop - let it finish naturally
                     } else {
@@ -945,7 +1241,7 @@ pub async fn handle_send_message<R: Runtime>(
                             let config = state.config.lock().unwrap();
                             let selected_model_idx = *state.selected_model_index.lock().unwrap();
                             let ws = state.workspace.lock().unwrap();
-                            let http = reqwest::Client::new();
+                            let http = state.blade_http.clone();
 
                             mgr.continue_tool_batch(
                                 batch,
@@ -956,15 +1252,19 @@ pub async fn handle_send_message<R: Runtime>(
                                 ws.workspace.as_ref(), // Ensure this matches Option<&PathBuf>
                                 http,
                             )
-                            .unwrap_or_else(|e| eprintln!("Continue batch failed: {}", e));
+                            .unwrap_or_else(|e| {
+                                eprintln!("Continue batch failed: {}", e);
+                                if mgr.agentic_loop.reached_max_turns() {
+                                    crate::ai_workflow::emit_agent_stopped(&window, &e);
+                                } else {
+                                    window.emit("chat-error", &e).unwrap_or_default();
+                                }
+                            });
                         }
 
-                        // Clear approved command roots after each AI response completes
-                        // This ensures each command batch requires fresh approval
-                        {
-                            let mut cache = state.approved_command_roots.lock().unwrap();
-                            cache.clear();
-                        }
+                        // Reset approved command roots after each AI response completes
+                        // Only roots persisted via "approve always" skip the next confirmation
+                        state.reload_command_allowlist();
 
                         // Continue polling for the new response
                         continue;
@@ -974,5 +1274,97 @@ pub async fn handle_send_message<R: Runtime>(
         }
     });
 
-    Ok(())
+    Ok(turn)
+}
+
+/// Mask secrets in a finished batch's command output before it is stored or
+/// sent to the model, and tell the UI how many were hidden. File reads are left
+/// alone so the model edits the source as it really is.
+fn redact_batch_results<R: Runtime>(
+    batch: &mut crate::ai_workflow::PendingToolBatch,
+    ws_root: Option<&str>,
+    window: &tauri::Window<R>,
+) {
+    let Some(redactor) = project_settings::output_redactor(ws_root.map(std::path::Path::new))
+    else {
+        return;
+    };
+    let count: usize = batch
+        .file_results
+        .iter_mut()
+        .filter(|(call, _)| crate::tools::canonical_tool_name(&call.function.name) == "run_command")
+        .map(|(_, result)| result.redact_secrets(&redactor))
+        .sum();
+    if count > 0 {
+        eprintln!("[REDACT] Masked {} secret(s) in tool output", count);
+        let _ = window.emit(
+            crate::events::event_names::SECRETS_REDACTED,
+            crate::events::SecretsRedactedPayload { count },
+        );
+    }
+}
+
+/// Execute a batch's approval-gated commands and tools without asking, for
+/// scripted turns that opted into auto-approval
+async fn run_approval_free<R: Runtime>(
+    batch: &mut crate::ai_workflow::PendingToolBatch,
+    ws_root: Option<&str>,
+    context: &crate::tool_execution::ToolExecutionContext<R>,
+) {
+    let root = std::path::Path::new(ws_root.unwrap_or("."));
+    let shell = context
+        .app_handle
+        .as_ref()
+        .and_then(|app| app.try_state::<AppState>())
+        .map(|state| state.config.lock().unwrap().shell.clone())
+        .unwrap_or_default();
+    for cmd in batch.commands.clone() {
+        if batch.file_results.iter().any(|(c, _)| c.id == cmd.call.id) {
+            continue;
+        }
+        eprintln!("[ORCHESTRATOR] Auto-approved command: {}", cmd.command);
+        if let Err(e) = crate::utils::ensure_command_cwd(cmd.cwd.as_deref()) {
+            batch
+                .file_results
+                .push((cmd.call.clone(), crate::tools::ToolResult::err(e)));
+            continue;
+        }
+        let started = std::time::SystemTime::now();
+        let result = crate::ai_workflow::run_command_in_workspace(
+            root,
+            &cmd.command,
+            cmd.cwd.as_deref(),
+            &shell,
+        )
+        .await;
+        let state = context
+            .app_handle
+            .as_ref()
+            .and_then(|app| app.try_state::<AppState>());
+        if let Some(state) = state {
+            crate::tool_execution::record_tool_call(
+                &state,
+                batch.conversation_id.as_deref(),
+                &cmd.call.id,
+                &cmd.call.function.name,
+                &cmd.call.function.arguments,
+                result.success,
+                started,
+            );
+        }
+        batch.file_results.push((cmd.call.clone(), result));
+    }
+    for conf in batch.confirms.clone() {
+        if batch.file_results.iter().any(|(c, _)| c.id == conf.call.id) {
+            continue;
+        }
+        eprintln!("[ORCHESTRATOR] Auto-approved tool: {}", conf.tool_name);
+        let result = crate::tool_execution::execute_tool_with_context(
+            context,
+            &conf.call.id,
+            &conf.call.function.name,
+            &conf.call.function.arguments,
+        );
+        batch.file_results.push((conf.call.clone(), result));
+    }
 }
@@ -17,13 +17,16 @@ pub async fn warmup_cache(
         _ => warmup::WarmupTrigger::Launch,
     };
 
-    state
-        .warmup_client
-        .warmup(&session_id, &model, trigger)
-        .await
+    let client = state.warmup();
+    client.warmup(&session_id, &model, trigger).await
 }
 
 #[command]
 pub fn should_rewarm_cache(state: State<'_, AppState>) -> bool {
-    state.warmup_client.should_rewarm()
+    state.warmup().should_rewarm()
+}
+
+#[command]
+pub fn warmup_status(state: State<'_, AppState>) -> warmup::WarmupStatus {
+    state.warmup().status()
 }
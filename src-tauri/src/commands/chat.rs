@@ -1,9 +1,12 @@
 use crate::app_state::AppState;
-use crate::chat_orchestrator::handle_send_message;
+use crate::chat_orchestrator::{
+    handle_send_message, load_available_models, start_message_turn, ApprovalMode,
+};
 use crate::conversation::ConversationHistory;
 use crate::conversation_store;
 use crate::models::registry::get_models;
-use tauri::{AppHandle, Emitter, Runtime, State, Window};
+use crate::protocol::ChatRole;
+use tauri::{AppHandle, Emitter, Manager, Runtime, State, Window};
 
 #[tauri::command]
 pub async fn send_message<R: Runtime>(
@@ -37,14 +40,86 @@ pub async fn send_message<R: Runtime>(
     .await
 }
 
+/// Result of a `send_message_sync` turn
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct SyncMessageResult {
+    /// Final assistant message of the turn
+    pub message: crate::protocol::ChatMessage,
+    /// Every tool call the model made during the turn, with results
+    pub tool_calls: Vec<crate::protocol::ToolCall>,
+}
+
+/// Send a message and wait for the whole turn, including tool round-trips,
+/// to finish. Tool calls that need approval run when `auto_approve` is set
+/// and fail the turn otherwise, so scripted callers never block on a prompt.
+#[tauri::command]
+pub async fn send_message_sync<R: Runtime>(
+    message: String,
+    model_id: Option<String>,
+    auto_approve: Option<bool>,
+    window: Window<R>,
+    state: State<'_, AppState>,
+    app: AppHandle<R>,
+) -> Result<SyncMessageResult, String> {
+    let approval = if auto_approve.unwrap_or(false) {
+        ApprovalMode::AutoApprove
+    } else {
+        ApprovalMode::Reject
+    };
+    let start = state.conversation.lock().unwrap().len();
+
+    let turn = start_message_turn(
+        message,
+        None,
+        false,
+        model_id,
+        None,
+        None,
+        None,
+        None,
+        None,
+        None,
+        window,
+        state,
+        app.clone(),
+        approval,
+    )
+    .await?;
+    turn.await
+        .map_err(|e| format!("Chat turn aborted: {}", e))??;
+
+    let state = app.state::<AppState>();
+    let conversation = state.conversation.lock().unwrap();
+    let turn_messages: Vec<_> = conversation
+        .iter()
+        .skip(start.min(conversation.len()))
+        .collect();
+    let tool_calls = turn_messages
+        .iter()
+        .filter_map(|m| m.tool_calls.clone())
+        .flatten()
+        .collect();
+    let message = turn_messages
+        .iter()
+        .rev()
+        .find(|m| m.role == ChatRole::Assistant)
+        .map(|m| (*m).clone())
+        .ok_or_else(|| "Turn finished without an assistant message".to_string())?;
+
+    Ok(SyncMessageResult {
+        message,
+        tool_calls,
+    })
+}
+
 #[tauri::command]
 pub async fn list_models(
     state: State<'_, AppState>,
 ) -> Result<Vec<crate::models::registry::ModelInfo>, String> {
-    let (blade_url, api_key, ollama_enabled, ollama_url, openai_compat_enabled, openai_compat_url) = {
+    let (endpoints, api_key, ollama_enabled, ollama_url, openai_compat_enabled, openai_compat_url) = {
         let config = state.config.lock().unwrap();
         (
-            config.blade_url.clone(),
+            config.normalized(),
             config.api_key.clone(),
             config.ollama_enabled,
             config.ollama_url.clone(),
@@ -53,7 +128,13 @@ pub async fn list_models(
         )
     };
 
-    let mut models = crate::models::registry::get_models(&blade_url, &api_key).await;
+    let mut models = match endpoints {
+        Ok(endpoints) => crate::models::registry::get_models(&endpoints.http_base, &api_key).await,
+        Err(e) => {
+            eprintln!("[MODEL REGISTRY] Skipping Blade models: {}", e);
+            Vec::new()
+        }
+    };
     if ollama_enabled {
         let mut ollama_models = crate::models::ollama::get_models(&ollama_url).await;
         models.append(&mut ollama_models);
@@ -66,6 +147,174 @@ pub async fn list_models(
     Ok(models)
 }
 
+/// Refetch the model lists now instead of waiting for the caches to expire
+#[tauri::command]
+pub async fn refresh_models(
+    state: State<'_, AppState>,
+) -> Result<Vec<crate::models::registry::ModelInfo>, String> {
+    let (endpoints, api_key) = {
+        let config = state.config.lock().unwrap();
+        (config.normalized(), config.api_key.clone())
+    };
+    if let Ok(endpoints) = endpoints {
+        crate::models::registry::refresh_models(&endpoints.http_base, &api_key).await;
+    }
+    crate::models::ollama::clear_cache();
+    crate::models::openai_compat::clear_cache();
+
+    list_models(state).await
+}
+
+/// Recent turns kept verbatim when older turns are summarized
+const SUMMARIZE_KEEP_RECENT_TURNS: usize = 2;
+/// Tool result bodies larger than this are stripped by `drop_tool_results`
+const MAX_KEPT_TOOL_RESULT_BYTES: usize = 512;
+
+#[derive(serde::Serialize)]
+pub struct ContextRecoveryResult {
+    pub strategy: String,
+    /// Messages removed (or, for `drop_tool_results`, tool results stripped)
+    pub affected_messages: usize,
+    pub estimated_tokens: usize,
+    /// The prompt that hit the limit, removed from history so it can be resent as-is
+    pub retry_prompt: Option<String>,
+}
+
+/// Rewrite the conversation after a context-length error so the last prompt can be retried.
+/// `strategy` is one of "summarize_old", "drop_tool_results" or "truncate".
+#[tauri::command]
+pub async fn recover_context(
+    strategy: String,
+    state: State<'_, AppState>,
+) -> Result<ContextRecoveryResult, String> {
+    if !matches!(
+        strategy.as_str(),
+        "summarize_old" | "drop_tool_results" | "truncate"
+    ) {
+        return Err(format!("Unknown context recovery strategy: {}", strategy));
+    }
+
+    let models = load_available_models(&state).await;
+    let selected = models
+        .get(*state.selected_model_index.lock().unwrap())
+        .cloned();
+
+    // Summarize before touching the history so a failed call leaves it intact
+    let summary = if strategy == "summarize_old" {
+        let (turns, transcript, original_len) = {
+            let conversation = state.conversation.lock().unwrap();
+            // The last turn is the failed prompt; it is retried rather than summarized
+            let turns = conversation
+                .turn_count()
+                .saturating_sub(1 + SUMMARIZE_KEEP_RECENT_TURNS);
+            let transcript = conversation.transcript(conversation.oldest_turns_len(turns));
+            (turns, transcript, conversation.len())
+        };
+        if turns == 0 {
+            return Err("Not enough conversation history to summarize".to_string());
+        }
+
+        let model = selected
+            .as_ref()
+            .filter(|m| {
+                !matches!(m.provider.as_deref(), Some("ollama") | Some("openai-compat"))
+            })
+            .ok_or_else(|| "Summarizing requires a zcoderd model to be selected".to_string())?;
+        let model_id = model.api_id.clone().unwrap_or_else(|| model.id.clone());
+
+        let (endpoints, api_key, timeout) = {
+            let config = state.config.lock().unwrap();
+            (
+                config.normalized(),
+                config.api_key.clone(),
+                config.http_timeout(),
+            )
+        };
+        let client = crate::blade_client::BladeClient::new(
+            endpoints?.http_base,
+            state.blade_http.clone(),
+            api_key,
+        )
+        .with_timeout(timeout);
+        let summary = client.summarize_transcript(model_id, &transcript).await?;
+        Some((turns, summary, original_len))
+    } else {
+        None
+    };
+
+    let mut conversation = state.conversation.lock().unwrap();
+    if let Some((_, _, original_len)) = summary {
+        if conversation.len() != original_len {
+            return Err("Conversation changed while summarizing; please try again".to_string());
+        }
+    }
+
+    let retry_prompt = conversation.pop_last_turn().map(|m| m.content);
+
+    let affected_messages = match (strategy.as_str(), summary) {
+        ("summarize_old", Some((turns, summary, _))) => {
+            conversation.replace_oldest_turns(turns, summary)
+        }
+        ("drop_tool_results", _) => conversation.drop_tool_results(MAX_KEPT_TOOL_RESULT_BYTES),
+        _ => {
+            let budget = crate::context_assembly::TokenBudget::for_context_window(
+                selected.as_ref().and_then(|m| m.context_window),
+            );
+            conversation.truncate_to_tokens(budget.available_for_context())
+        }
+    };
+
+    let estimated_tokens = conversation.estimate_tokens();
+    eprintln!(
+        "[CHAT] Context recovery '{}': {} messages affected, ~{} tokens remain",
+        strategy, affected_messages, estimated_tokens
+    );
+
+    Ok(ContextRecoveryResult {
+        strategy,
+        affected_messages,
+        estimated_tokens,
+        retry_prompt,
+    })
+}
+
+#[derive(serde::Serialize)]
+pub struct TokenUsage {
+    pub used_tokens: usize,
+    /// The model's advertised window, or the default budget when it is unknown
+    pub context_window: usize,
+    pub percent: f32,
+}
+
+/// Estimate how much of `model_id`'s context window the current conversation uses,
+/// tool result bodies included
+#[tauri::command]
+pub async fn get_token_usage(
+    model_id: String,
+    state: State<'_, AppState>,
+) -> Result<TokenUsage, String> {
+    let models = load_available_models(&state).await;
+    let window = models
+        .iter()
+        .find(|m| m.id == model_id || m.api_id.as_deref() == Some(model_id.as_str()))
+        .and_then(|m| m.context_window);
+    let context_window =
+        crate::context_assembly::TokenBudget::for_context_window(window).total;
+
+    let used_tokens = state.conversation.lock().unwrap().estimate_tokens();
+    let percent = if context_window == 0 {
+        0.0
+    } else {
+        used_tokens as f32 / context_window as f32 * 100.0
+    };
+
+    Ok(TokenUsage {
+        used_tokens,
+        context_window,
+        percent,
+    })
+}
+
 #[tauri::command]
 pub fn get_conversation(state: State<'_, AppState>) -> Vec<crate::protocol::ChatMessage> {
     let conversation = state.conversation.lock().unwrap();
@@ -74,10 +323,68 @@ pub fn get_conversation(state: State<'_, AppState>) -> Vec<crate::protocol::Chat
 
 #[tauri::command]
 pub fn list_conversations(
+    offset: Option<usize>,
+    limit: Option<usize>,
     state: State<'_, AppState>,
-) -> Result<Vec<conversation_store::ConversationMetadata>, String> {
+) -> Result<conversation_store::Page<conversation_store::ConversationMetadata>, String> {
     let store = state.conversation_store.lock().unwrap();
-    Ok(store.list_conversations())
+    Ok(store.list_conversations_page(offset.unwrap_or(0), limit))
+}
+
+/// Render a stored conversation as Markdown. With `write_to`, also save it at
+/// that path inside the workspace.
+#[tauri::command]
+pub fn export_conversation_markdown<R: Runtime>(
+    id: String,
+    write_to: Option<String>,
+    state: State<'_, AppState>,
+    app_handle: AppHandle<R>,
+) -> Result<String, String> {
+    let markdown = state
+        .conversation_store
+        .lock()
+        .unwrap()
+        .load_conversation(&id)?
+        .to_markdown();
+
+    if let Some(path) = write_to {
+        let workspace_root = state
+            .workspace
+            .lock()
+            .unwrap()
+            .workspace
+            .clone()
+            .ok_or_else(|| "No workspace open".to_string())?;
+        let target =
+            crate::tools::resolve_path_in_workspace(&workspace_root, std::path::Path::new(&path))?;
+        if let Some(parent) = target.parent() {
+            std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+        }
+        crate::utils::atomic_write(&target, &markdown).map_err(|e| e.to_string())?;
+        eprintln!("[CHAT] Exported conversation {} to {}", id, target.display());
+        let _ = app_handle.emit(crate::events::event_names::REFRESH_EXPLORER, ());
+    }
+
+    Ok(markdown)
+}
+
+/// Import a "json" or "markdown" transcript as a new conversation, returning its id
+#[tauri::command]
+pub fn import_conversation(
+    format: String,
+    content: String,
+    state: State<'_, AppState>,
+) -> Result<String, String> {
+    let metadata = state
+        .conversation_store
+        .lock()
+        .unwrap()
+        .import_conversation(&format, &content)?;
+    eprintln!(
+        "[CHAT] Imported conversation {} ({} messages)",
+        metadata.id, metadata.message_count
+    );
+    Ok(metadata.id)
 }
 
 #[tauri::command]
@@ -141,10 +448,197 @@ pub fn new_conversation(model_id: String, state: State<'_, AppState>) -> Result<
     Ok(id)
 }
 
+/// Fork a stored conversation at `up_to_message_index`, returning the new conversation's ID
+#[tauri::command]
+pub fn fork_conversation(
+    from_id: String,
+    up_to_message_index: usize,
+    state: State<'_, AppState>,
+) -> Result<String, String> {
+    // Make sure messages not yet auto-saved are part of the fork source
+    let current = {
+        let conversation = state.conversation.lock().unwrap();
+        (conversation.metadata.id == from_id && conversation.len() > 0)
+            .then(|| conversation.to_stored())
+    };
+
+    let mut store = state.conversation_store.lock().unwrap();
+    if let Some(stored) = current {
+        store.save_conversation(&stored)?;
+    }
+
+    let metadata = store.fork_conversation(&from_id, up_to_message_index)?;
+    eprintln!(
+        "[CHAT] Forked conversation {} at message {} -> {}",
+        from_id, up_to_message_index, metadata.id
+    );
+    Ok(metadata.id)
+}
+
+/// Retry the last turn: drop the last assistant response (and its tool messages) and
+/// resend the user message that prompted it, optionally on another model. The original
+/// response is kept in a fork of the conversation, whose ID is returned.
+#[tauri::command]
+pub async fn regenerate_response<R: Runtime>(
+    model_id: Option<String>,
+    window: Window<R>,
+    state: State<'_, AppState>,
+    app: AppHandle<R>,
+) -> Result<Option<String>, String> {
+    if state.chat_manager.lock().unwrap().streaming {
+        return Err("Cannot regenerate while a response is streaming".to_string());
+    }
+
+    let (prompt, original) = {
+        let mut conversation = state.conversation.lock().unwrap();
+        let original = conversation.to_stored();
+        let prompt = conversation
+            .pop_last_turn()
+            .ok_or_else(|| "No message to regenerate".to_string())?;
+        (prompt, original)
+    };
+
+    // Nothing to preserve if the prompt never got a response (e.g. it errored)
+    let has_response = original.messages.last().is_some_and(|m| m.role != "user");
+    let fork_id = if has_response {
+        let mut store = state.conversation_store.lock().unwrap();
+        store.save_conversation(&original)?;
+        let fork = store.fork_conversation(&original.metadata.id, original.messages.len() - 1)?;
+        eprintln!("[CHAT] Regenerating; original response kept in {}", fork.id);
+        Some(fork.id)
+    } else {
+        None
+    };
+
+    if let Err(e) = resend_user_message(prompt, model_id, window, state.clone(), app).await {
+        // Put the turn back so a failed retry doesn't lose it
+        let mut restored = ConversationHistory::from_stored(original);
+        restored.metadata.session_id = None;
+        *state.conversation.lock().unwrap() = restored;
+        if let Some(ref id) = fork_id {
+            let _ = state
+                .conversation_store
+                .lock()
+                .unwrap()
+                .delete_conversation(id);
+        }
+        return Err(e);
+    }
+    Ok(fork_id)
+}
+
+/// Rewrite the user message at `message_index` and resend it, dropping everything
+/// that followed it; the turns before it are sent along as context. Only user
+/// messages can be edited.
+#[tauri::command]
+pub async fn edit_and_resend<R: Runtime>(
+    message_index: usize,
+    new_content: String,
+    model_id: Option<String>,
+    window: Window<R>,
+    state: State<'_, AppState>,
+    app: AppHandle<R>,
+) -> Result<(), String> {
+    if state.chat_manager.lock().unwrap().streaming {
+        return Err("Cannot edit a message while a response is streaming".to_string());
+    }
+    if new_content.trim().is_empty() {
+        return Err("Edited message cannot be empty".to_string());
+    }
+
+    let prompt = {
+        let mut conversation = state.conversation.lock().unwrap();
+        match conversation.get(message_index) {
+            None => {
+                return Err(format!(
+                    "Message {} does not exist (conversation has {} messages)",
+                    message_index,
+                    conversation.len()
+                ))
+            }
+            Some(msg) if msg.role != ChatRole::User => {
+                return Err(format!(
+                    "Only user messages can be edited (message {} is a {:?} message)",
+                    message_index, msg.role
+                ))
+            }
+            Some(_) => {}
+        }
+        let removed = conversation.truncate_from(message_index);
+        eprintln!(
+            "[CHAT] Editing message {}; dropped {} messages",
+            message_index,
+            removed.len()
+        );
+        let mut prompt = removed.into_iter().next().expect("checked above");
+        prompt.content = new_content;
+        // New text, so its @file references are inlined again
+        prompt.files_expanded = false;
+        prompt
+    };
+
+    resend_user_message(prompt, model_id, window, state, app).await
+}
+
+/// Send a user message taken out of the history again, with the editor state of the
+/// last send. The server session still remembers the dropped turns, so a fresh one is
+/// started and the request carries the trimmed history as a transcript.
+async fn resend_user_message<R: Runtime>(
+    prompt: crate::protocol::ChatMessage,
+    model_id: Option<String>,
+    window: Window<R>,
+    state: State<'_, AppState>,
+    app: AppHandle<R>,
+) -> Result<(), String> {
+    {
+        let mut mgr = state.chat_manager.lock().unwrap();
+        mgr.session_id = None;
+        mgr.replay_history = true;
+    }
+    state.conversation.lock().unwrap().metadata.session_id = None;
+
+    let active_file = state.active_file.lock().unwrap().clone();
+    let open_files = state.open_files.lock().unwrap().clone();
+    let cursor_line = *state.cursor_line.lock().unwrap();
+    let cursor_column = *state.cursor_column.lock().unwrap();
+    let selection_start_line = *state.selection_start_line.lock().unwrap();
+    let selection_end_line = *state.selection_end_line.lock().unwrap();
+
+    start_message_turn(
+        prompt.content,
+        prompt.images,
+        prompt.files_expanded,
+        model_id,
+        active_file,
+        Some(open_files),
+        cursor_line,
+        cursor_column,
+        selection_start_line,
+        selection_end_line,
+        window,
+        state,
+        app,
+        ApprovalMode::Interactive,
+    )
+    .await
+    .map(|_| ())
+}
+
 #[tauri::command]
 pub fn delete_conversation(id: String, state: State<'_, AppState>) -> Result<(), String> {
     let mut store = state.conversation_store.lock().unwrap();
-    store.delete_conversation(&id)
+    store.delete_conversation(&id)?;
+    state.tool_log.remove(&id);
+    Ok(())
+}
+
+/// Tool calls executed in a conversation, oldest first
+#[tauri::command]
+pub fn get_tool_log(
+    conversation_id: String,
+    state: State<'_, AppState>,
+) -> Vec<crate::tool_execution::ToolInvocation> {
+    state.tool_log.entries(&conversation_id)
 }
 
 #[tauri::command]
@@ -157,6 +651,13 @@ pub fn save_conversation(state: State<'_, AppState>) -> Result<(), String> {
 
 #[tauri::command]
 pub fn stop_generation(state: State<'_, AppState>, app_handle: tauri::AppHandle) -> bool {
+    // Break the send_message poll loop on its next iteration
+    state
+        .generation_cancel
+        .lock()
+        .unwrap()
+        .store(true, std::sync::atomic::Ordering::SeqCst);
+
     let mut mgr = state.chat_manager.lock().unwrap();
     let stopped = mgr.request_stop();
 
@@ -185,17 +686,42 @@ pub fn stop_generation(state: State<'_, AppState>, app_handle: tauri::AppHandle)
     stopped
 }
 
+/// Agentic loop state, so users can see why the agent halted
+#[tauri::command]
+pub fn get_agentic_status(state: State<'_, AppState>) -> crate::agentic_loop::AgenticStatus {
+    state.chat_manager.lock().unwrap().agentic_loop.status()
+}
+
+/// Stop the agentic loop and forget recent tool calls, so a stuck agent can
+/// restart without loop detection firing on its old calls
+#[tauri::command]
+pub fn reset_agentic_loop(state: State<'_, AppState>) {
+    state
+        .chat_manager
+        .lock()
+        .unwrap()
+        .agentic_loop
+        .stop("reset by user");
+    state.workflow.lock().unwrap().clear_history();
+}
+
+/// Available @commands, for autocomplete in the chat input
+#[tauri::command]
+pub fn list_commands() -> Vec<crate::at_commands::AtCommand> {
+    crate::at_commands::builtin_commands().to_vec()
+}
+
 #[tauri::command]
 pub async fn set_selected_model(
     model_id: String,
     state: State<'_, AppState>,
 ) -> Result<(), String> {
-    // Update the selected model index (in-memory only)
-    let (blade_url, api_key) = {
+    // Update the selected model index
+    let (endpoints, api_key) = {
         let config = state.config.lock().unwrap();
-        (config.blade_url.clone(), config.api_key.clone())
+        (config.normalized(), config.api_key.clone())
     };
-    let models = get_models(&blade_url, &api_key).await;
+    let models = get_models(&endpoints?.http_base, &api_key).await;
 
     // Use smart matching logic identical to handle_send_message
     let matched_idx = models
@@ -220,19 +746,34 @@ pub async fn set_selected_model(
 
     if let Some(idx) = matched_idx {
         *state.selected_model_index.lock().unwrap() = idx;
+        *state.selected_model_id.lock().unwrap() = Some(models[idx].id.clone());
         eprintln!(
             "[MODEL] Set selected model index to {} for {} (Registry ID: {})",
             idx, model_id, models[idx].id
         );
+
+        // Saved right away rather than with the editor autosave, so the choice
+        // survives even an immediate restart
+        let workspace_root = state.workspace.lock().unwrap().workspace.clone();
+        if let Some(root) = workspace_root {
+            if let Err(e) =
+                crate::project_state::save_selected_model(&root.to_string_lossy(), &models[idx].id)
+            {
+                eprintln!("[MODEL] Failed to save selected model: {}", e);
+            }
+        }
         Ok(())
     } else {
         Err(format!("Model not found: {}", model_id))
     }
 }
 
+/// The workspace's saved model, falling back to the configured default
 #[tauri::command]
-pub fn get_selected_model(_state: State<'_, AppState>) -> Option<String> {
-    // Model is now stored in project state only, not main config
-    // Return None to let the frontend use project state or default
-    None
+pub fn get_selected_model(state: State<'_, AppState>) -> Option<String> {
+    let workspace_root = state.workspace.lock().unwrap().workspace.clone();
+    workspace_root
+        .and_then(|root| crate::project_state::load_project_state(&root.to_string_lossy()))
+        .and_then(|project| project.selected_model_id)
+        .or_else(|| state.config.lock().unwrap().default_model.clone())
 }
@@ -6,32 +6,115 @@ pub async fn open_workspace_logic(
     state: &AppState,
     app_handle: &tauri::AppHandle,
 ) -> Result<(), String> {
-    let mut ws = state.workspace.lock().unwrap();
-    ws.set_workspace(std::path::PathBuf::from(&path));
-    drop(ws);
-    crate::fs_watcher::restart_fs_watcher(app_handle);
-    let _ = app_handle.emit(crate::events::event_names::REFRESH_EXPLORER, ());
+    // Write the previous workspace's pending editor state before switching
+    state.project_state_autosave.flush();
 
-    let language_service = state.language_service.clone();
-    let workspace_path = path.clone();
-    tokio::task::spawn_blocking(move || {
+    // The guard must be gone before the first await, or the future isn't Send
+    let (root, trusted) = {
+        let mut ws = state.workspace.lock().unwrap();
+        ws.set_workspace(std::path::PathBuf::from(&path));
+        // Watch paths belong to the previous workspace
+        state.watch_paths.lock().unwrap().clear();
+        // set_workspace canonicalizes the path; everything below keys off that root
+        let root = ws
+            .workspace
+            .clone()
+            .ok_or_else(|| format!("Not a usable workspace: {}", path))?;
+        (root, ws.is_current_trusted())
+    };
+    if !trusted {
         eprintln!(
-            "[LanguageService] Starting background workspace indexing: {}",
-            workspace_path
+            "[WORKSPACE] Not trusted yet, commands disabled: {}",
+            root.display()
         );
-        match language_service.index_directory(".") {
-            Ok(stats) => {
-                eprintln!(
-                    "[LanguageService] Workspace indexed: {} files, {} symbols in {}ms",
-                    stats.files_indexed, stats.symbols_extracted, stats.duration_ms
-                );
-            }
-            Err(e) => {
-                eprintln!("[LanguageService] Workspace indexing failed: {}", e);
-            }
+        let _ = app_handle.emit(
+            crate::events::event_names::WORKSPACE_TRUST_REQUIRED,
+            crate::events::WorkspaceChangedPayload {
+                workspace_path: root.to_string_lossy().to_string(),
+            },
+        );
+    }
+    state.reload_command_allowlist();
+
+    // A trusted workspace may point at its own Blade backend via .zblade/.env
+    if state.reload_api_config() {
+        state.update_ws_credentials().await;
+    }
+
+    // Cancels indexing of the previous workspace before its symbols are cleared
+    match state.language_service.set_workspace_root(root.clone()) {
+        Ok(true) => {
+            *state.indexer_manager.lock().unwrap() = None;
+            crate::commands::index::spawn_project_indexer(app_handle);
+        }
+        Ok(false) => {}
+        Err(e) => eprintln!("[LanguageService] Failed to switch workspace: {}", e),
+    }
+
+    crate::fs_watcher::restart_fs_watcher(app_handle);
+    let _ = app_handle.emit(crate::events::event_names::REFRESH_EXPLORER, ());
+
+    eprintln!(
+        "[LanguageService] Starting background workspace indexing: {}",
+        root.display()
+    );
+    crate::commands::index::spawn_workspace_index(app_handle, false);
+
+    Ok(())
+}
+
+/// Canonical form of a watch path; relative paths are taken from the workspace root
+fn resolve_watch_path(path: &str, state: &AppState) -> Result<std::path::PathBuf, String> {
+    let candidate = std::path::PathBuf::from(path);
+    let candidate = if candidate.is_absolute() {
+        candidate
+    } else {
+        let ws = state.workspace.lock().unwrap();
+        ws.workspace
+            .as_ref()
+            .ok_or_else(|| "No workspace open".to_string())?
+            .join(candidate)
+    };
+    std::fs::canonicalize(&candidate).map_err(|e| format!("{}: {}", candidate.display(), e))
+}
+
+/// Watch `path` for file changes. A directory inside the workspace narrows
+/// watching to it (and any other such directories) instead of the whole root;
+/// one outside the workspace is watched in addition to it.
+#[tauri::command]
+pub fn add_watch_path(
+    path: String,
+    state: tauri::State<'_, AppState>,
+    app_handle: tauri::AppHandle,
+) -> Result<(), String> {
+    let resolved = resolve_watch_path(&path, &state)?;
+    {
+        let mut watch_paths = state.watch_paths.lock().unwrap();
+        if watch_paths.contains(&resolved) {
+            return Ok(());
         }
-    });
+        watch_paths.push(resolved);
+    }
+    crate::fs_watcher::restart_fs_watcher(&app_handle);
+    Ok(())
+}
 
+#[tauri::command]
+pub fn remove_watch_path(
+    path: String,
+    state: tauri::State<'_, AppState>,
+    app_handle: tauri::AppHandle,
+) -> Result<(), String> {
+    let resolved = resolve_watch_path(&path, &state).unwrap_or_else(|_| path.clone().into());
+    {
+        let mut watch_paths = state.watch_paths.lock().unwrap();
+        let before = watch_paths.len();
+        watch_paths.retain(|p| *p != resolved);
+        if watch_paths.len() == before {
+            return Err(format!("Not watching {}", path));
+        }
+    }
+    crate::fs_watcher::restart_fs_watcher(&app_handle);
     Ok(())
 }
 
@@ -57,7 +140,13 @@ pub fn list_files_logic(
         return Err("No workspace open".to_string());
     };
 
-    Ok(crate::explorer::list_directory(&root))
+    // git reports ignored entries itself; the filter is only needed outside a repo
+    let filter = ws
+        .workspace
+        .as_deref()
+        .filter(|w| !w.join(".git").exists())
+        .map(crate::gitignore_filter::GitignoreFilter::new);
+    Ok(crate::explorer::list_directory(&root, filter.as_ref()))
 }
 
 #[tauri::command]
@@ -68,6 +157,34 @@ pub async fn list_files(
     list_files_logic(path, &*state)
 }
 
+/// Directory tree up to `max_depth` levels (default 2, capped at
+/// `explorer::MAX_TREE_DEPTH`) for lazy expansion in the file tree
+#[tauri::command]
+pub async fn list_directory_depth(
+    path: Option<String>,
+    max_depth: Option<usize>,
+    state: tauri::State<'_, AppState>,
+) -> Result<Vec<crate::explorer::FileEntry>, String> {
+    let workspace_root = state.workspace.lock().unwrap().workspace.clone();
+    let root = match (path, &workspace_root) {
+        (Some(p), _) => std::path::PathBuf::from(p),
+        (None, Some(w)) => w.clone(),
+        (None, None) => return Err("No workspace open".to_string()),
+    };
+    let max_depth = max_depth
+        .unwrap_or(2)
+        .clamp(1, crate::explorer::MAX_TREE_DEPTH);
+
+    let filter = workspace_root
+        .as_deref()
+        .map(crate::gitignore_filter::GitignoreFilter::new);
+    Ok(crate::explorer::list_directory_depth(
+        &root,
+        max_depth,
+        filter.as_ref(),
+    ))
+}
+
 pub fn read_file_content_logic(path: String, state: &AppState) -> Result<String, String> {
     // Virtual buffers removal - surgically removed.
 
@@ -87,8 +204,17 @@ pub fn read_file_content_logic(path: String, state: &AppState) -> Result<String,
     };
 
     // No virtual content, read from disk
-    match std::fs::read_to_string(&resolved_path) {
-        Ok(content) => {
+    match std::fs::read(&resolved_path) {
+        Ok(bytes) => {
+            let (content, encoding) = crate::encoding::decode(&bytes)
+                .map_err(|e| format!("{}: {}", resolved_path.display(), e))?;
+            if encoding != crate::encoding::TextEncoding::Utf8 {
+                println!(
+                    "[READ FILE CONTENT] Decoded {} as {}",
+                    resolved_path.display(),
+                    encoding.label()
+                );
+            }
             if content.is_empty() {
                 println!(
                     "[READ FILE CONTENT] Read empty content from: {} (requested: {})",
@@ -118,6 +244,74 @@ pub async fn read_file_content(
     read_file_content_logic(path, &*state)
 }
 
+/// Nested symbol outline of a file, for the outline/breadcrumb panel.
+/// `language` overrides detection; files in languages without a tree-sitter
+/// grammar get an empty outline, even when they can't be read as text.
+#[tauri::command]
+pub async fn get_file_outline(
+    path: String,
+    language: Option<String>,
+    state: tauri::State<'_, AppState>,
+) -> Result<Vec<crate::tree_sitter::OutlineNode>, String> {
+    // Binary files of unsupported types have no outline rather than a read error
+    let unsupported =
+        language.is_none() && crate::tree_sitter::Language::from_path(&path).is_none();
+    let content = match read_file_content_logic(path.clone(), &state) {
+        Ok(content) => content,
+        Err(_) if unsupported => return Ok(Vec::new()),
+        Err(e) => return Err(e),
+    };
+    let language = match language.as_deref() {
+        Some(name) => Some(
+            crate::tree_sitter::Language::from_name(name)
+                .ok_or_else(|| format!("Unsupported language: {}", name))?,
+        ),
+        None => crate::tree_sitter::Language::detect(&path, content.lines().next()),
+    };
+    let Some(language) = language else {
+        return Ok(Vec::new());
+    };
+
+    let mut parser = crate::tree_sitter::TreeSitterParser::new().map_err(|e| e.to_string())?;
+    let tree = parser
+        .parse(&content, language)
+        .map_err(|e| e.to_string())?;
+    let symbols = crate::tree_sitter::extract_symbols(&tree, &content, language, &path);
+    Ok(crate::tree_sitter::build_outline(&symbols))
+}
+
+/// Unified diff of two workspace files, or of `path` against git HEAD when
+/// `against` is "head". Empty when nothing changed.
+#[tauri::command]
+pub async fn diff_files(
+    left_path: Option<String>,
+    right_path: Option<String>,
+    path: Option<String>,
+    against: Option<String>,
+    state: tauri::State<'_, AppState>,
+) -> Result<String, String> {
+    let workspace = state
+        .workspace
+        .lock()
+        .unwrap()
+        .workspace
+        .clone()
+        .ok_or_else(|| "No workspace open".to_string())?;
+
+    let against_head = match against.as_deref() {
+        Some(other) if !other.eq_ignore_ascii_case("head") => {
+            return Err(format!("Unsupported diff target: {}", other));
+        }
+        other => other.is_some(),
+    };
+    let target = match (against_head, &path, &left_path, &right_path) {
+        (true, Some(path), _, _) => crate::tools::DiffTarget::Head { path },
+        (false, _, Some(left), Some(right)) => crate::tools::DiffTarget::Files { left, right },
+        _ => return Err("Expected left_path and right_path, or path with against".to_string()),
+    };
+    crate::tools::diff_in_workspace(&workspace, target)
+}
+
 pub fn write_file_content_logic(
     path: String,
     content: String,
@@ -135,7 +329,43 @@ pub fn write_file_content_logic(
         }
     };
 
-    std::fs::write(&resolved_path, content).map_err(|e| e.to_string())
+    // Keep the file's existing encoding (Latin-1, UTF-16, BOM) on save
+    let encoding = crate::encoding::detect_file_encoding(&resolved_path);
+    let bytes = crate::encoding::encode(&content, encoding)?;
+    crate::utils::atomic_write(&resolved_path, bytes).map_err(|e| e.to_string())?;
+
+    // Saved content replaces the file's embeddings even where the fs watcher isn't looking
+    let config = crate::tools::EmbeddingConfig::from_api_config(&state.config.lock().unwrap());
+    let root = state.workspace.lock().unwrap().workspace.clone();
+    if let (Some(config), Some(root)) = (config, root) {
+        let change = crate::fs_watcher::FileChange {
+            path: resolved_path.to_string_lossy().to_string(),
+            kind: crate::fs_watcher::FileChangeKind::Modified,
+        };
+        if let Err(e) = crate::tools::refresh_file_embeddings(&root, &config, vec![change]) {
+            eprintln!("[EMBEDDINGS] Failed to refresh {}: {}", path, e);
+        }
+    }
+    Ok(())
+}
+
+/// Encoding a file is stored in; reads are transcoded to UTF-8 and saves convert back
+#[tauri::command]
+pub async fn get_file_encoding(
+    path: String,
+    state: tauri::State<'_, AppState>,
+) -> Result<crate::encoding::TextEncoding, String> {
+    let p = std::path::PathBuf::from(&path);
+    let resolved_path = if p.is_absolute() {
+        p
+    } else {
+        let ws = state.workspace.lock().unwrap();
+        match ws.workspace.as_ref() {
+            Some(root) => root.join(&path),
+            None => p,
+        }
+    };
+    crate::encoding::read_text(&resolved_path).map(|(_, encoding)| encoding)
 }
 
 #[tauri::command]
@@ -1,6 +1,7 @@
 use crate::app_state::AppState;
+use crate::events::event_names;
 use std::path::PathBuf;
-use tauri::State;
+use tauri::{Emitter, State};
 
 #[tauri::command]
 pub fn get_file_history(
@@ -11,15 +12,27 @@ pub fn get_file_history(
     Ok(state.history_service.get_history(&path_buf))
 }
 
+/// Revert a single file to the given snapshot
 #[tauri::command]
 pub fn revert_file_to_snapshot(
     snapshot_id: String,
+    app_handle: tauri::AppHandle,
     state: State<'_, AppState>,
 ) -> Result<(), String> {
-    state.history_service.revert_to(&snapshot_id)
+    state.history_service.revert_to(&snapshot_id)?;
+    let _ = app_handle.emit(event_names::REFRESH_EXPLORER, ());
+    Ok(())
 }
 
+/// Revert every file touched by an AI tool batch to its state before the batch.
+/// Returns the reverted paths.
 #[tauri::command]
-pub fn undo_batch(group_id: String, state: State<'_, AppState>) -> Result<Vec<String>, String> {
-    state.history_service.undo_batch(&group_id)
+pub fn undo_batch(
+    group_id: String,
+    app_handle: tauri::AppHandle,
+    state: State<'_, AppState>,
+) -> Result<Vec<String>, String> {
+    let reverted = state.history_service.undo_batch(&group_id)?;
+    let _ = app_handle.emit(event_names::REFRESH_EXPLORER, ());
+    Ok(reverted)
 }
@@ -0,0 +1,95 @@
+use crate::app_state::AppState;
+use crate::blade_protocol::{BladeEvent, BladeEventEnvelope, SystemEvent};
+use crate::language_service::{IndexState, IndexStatus};
+use tauri::{command, AppHandle, Emitter, Manager, Runtime, State};
+
+/// Index the workspace symbols on a background thread, reporting progress as
+/// `SystemEvent::IndexProgress`. `rebuild` drops the existing index first.
+pub fn spawn_workspace_index<R: Runtime>(app_handle: &AppHandle<R>, rebuild: bool) {
+    let app_handle = app_handle.clone();
+    std::thread::spawn(move || {
+        let service = app_handle.state::<AppState>().language_service.clone();
+        let report = |done: usize, total: usize| emit_index_progress(&app_handle, done, total);
+
+        let result = if rebuild {
+            service.rebuild_index(report)
+        } else {
+            service.index_directory_with_progress(".", report)
+        };
+        match result {
+            Ok(stats) => eprintln!(
+                "[LanguageService] Workspace indexed: {} files, {} symbols in {}ms",
+                stats.files_indexed, stats.symbols_extracted, stats.duration_ms
+            ),
+            Err(e) => eprintln!("[LanguageService] Workspace indexing failed: {}", e),
+        }
+    });
+}
+
+/// Build the project index (`IndexerManager`) for the current workspace on a
+/// background thread. The result is dropped if the workspace changed while
+/// it was being built.
+pub fn spawn_project_indexer<R: Runtime>(app_handle: &AppHandle<R>) {
+    let app_handle = app_handle.clone();
+    std::thread::spawn(move || {
+        let state = app_handle.state::<AppState>();
+        let Some(path) = state.workspace.lock().unwrap().workspace.clone() else {
+            return;
+        };
+
+        eprintln!(
+            "[Indexer] Starting background initialization for {:?}",
+            path
+        );
+        match crate::indexer::IndexerManager::new(&path) {
+            Ok(manager) => {
+                let current = state.workspace.lock().unwrap().workspace.clone();
+                if current.as_ref() != Some(&path) {
+                    eprintln!(
+                        "[Indexer] Discarding index for {:?}: workspace changed",
+                        path
+                    );
+                    return;
+                }
+                eprintln!("[Indexer] Initialized with {} files", manager.file_count());
+                *state.indexer_manager.lock().unwrap() = Some(manager);
+            }
+            Err(e) => {
+                eprintln!("[Indexer] Failed to initialize: {}", e);
+            }
+        }
+    });
+}
+
+fn emit_index_progress<R: Runtime>(app_handle: &AppHandle<R>, done: usize, total: usize) {
+    let _ = app_handle.emit(
+        "blade-event",
+        BladeEventEnvelope {
+            id: uuid::Uuid::new_v4(),
+            timestamp: std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_millis() as u64,
+            causality_id: None,
+            event: BladeEvent::System(SystemEvent::IndexProgress { done, total }),
+        },
+    );
+}
+
+#[command]
+pub fn index_status(state: State<'_, AppState>) -> IndexStatus {
+    state.language_service.index_status()
+}
+
+/// Force a full rebuild of the symbol index
+#[command]
+pub fn reindex_workspace<R: Runtime>(
+    app_handle: AppHandle<R>,
+    state: State<'_, AppState>,
+) -> Result<(), String> {
+    if state.language_service.index_status().state == IndexState::Indexing {
+        return Err("Workspace indexing already in progress".to_string());
+    }
+    spawn_workspace_index(&app_handle, true);
+    Ok(())
+}
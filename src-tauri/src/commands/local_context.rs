@@ -1,3 +1,4 @@
+use crate::conversation_store::Page;
 use crate::local_artifacts;
 use crate::local_index;
 use tauri::command;
@@ -5,10 +6,12 @@ use tauri::command;
 #[command]
 pub fn list_local_conversations(
     project_path: String,
-) -> Result<Vec<local_index::ConversationIndex>, String> {
+    offset: Option<usize>,
+    limit: Option<usize>,
+) -> Result<Page<local_index::ConversationIndex>, String> {
     let path = std::path::PathBuf::from(project_path);
     let store = local_artifacts::LocalArtifactStore::new(&path);
-    store.list_conversations()
+    store.list_conversations_page(offset.unwrap_or(0), limit)
 }
 
 #[command]
@@ -51,3 +54,12 @@ pub fn delete_local_conversation(
     let store = local_artifacts::LocalArtifactStore::new(&path);
     store.delete_conversation(&conversation_id)
 }
+
+#[command]
+pub fn rebuild_local_index(
+    project_path: String,
+) -> Result<local_artifacts::IndexRebuildStats, String> {
+    let path = std::path::PathBuf::from(project_path);
+    let store = local_artifacts::LocalArtifactStore::new(&path);
+    store.rebuild_index()
+}
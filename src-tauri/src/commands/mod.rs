@@ -3,11 +3,13 @@ pub mod cache;
 pub mod chat;
 pub mod files;
 pub mod history;
+pub mod index;
 pub mod local_context;
 pub mod misc;
 pub mod project;
 pub mod screenshot;
 pub mod settings;
 pub mod state;
+pub mod terminal;
 pub mod tools;
 pub mod uncommitted;
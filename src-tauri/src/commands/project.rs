@@ -15,6 +15,32 @@ pub fn get_current_workspace(state: State<'_, AppState>) -> Option<String> {
     workspace.get_workspace_root()
 }
 
+/// Whether `path` (default: the open workspace) is trusted to run commands
+#[tauri::command]
+pub fn is_workspace_trusted(path: Option<String>, state: State<'_, AppState>) -> bool {
+    let workspace = state.workspace.lock().unwrap();
+    match path {
+        Some(path) => workspace.is_trusted(std::path::Path::new(&path)),
+        None => workspace.is_current_trusted(),
+    }
+}
+
+/// Trust `path`, enabling commands, the terminal and its `.zblade/.env` Blade
+/// overrides there from now on
+#[tauri::command]
+pub async fn trust_workspace(path: String, state: State<'_, AppState>) -> Result<(), String> {
+    eprintln!("[WORKSPACE] Trusted: {}", path);
+    state
+        .workspace
+        .lock()
+        .unwrap()
+        .trust(std::path::Path::new(&path))?;
+    if state.reload_api_config() {
+        state.update_ws_credentials().await;
+    }
+    Ok(())
+}
+
 #[tauri::command]
 pub fn load_project_state(project_path: String) -> Option<project_state::ProjectState> {
     project_state::load_project_state(&project_path)
@@ -25,11 +51,20 @@ pub fn save_project_state(state_data: project_state::ProjectState) -> Result<(),
     project_state::save_project_state(&state_data)
 }
 
+/// Turn the debounced backend save of editor state on or off
+#[tauri::command]
+pub fn set_autosave_enabled(enabled: bool, state: State<'_, AppState>) {
+    state.project_state_autosave.set_enabled(enabled);
+}
+
 #[tauri::command]
 pub fn graceful_shutdown_with_state(
     app_handle: tauri::AppHandle,
     state_data: project_state::ProjectState,
+    state: State<'_, AppState>,
 ) -> Result<(), String> {
+    // A waiting autosave runs first so it can't overwrite the state saved below
+    state.project_state_autosave.flush();
     if let Err(e) = project_state::save_project_state(&state_data) {
         println!("[Backend] Failed to save state during shutdown: {}", e);
         // We continue to exit even if save fails, to prevent hanging
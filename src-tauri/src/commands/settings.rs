@@ -1,6 +1,6 @@
 use crate::app_state::AppState;
 use crate::config::{self, ApiConfig};
-use tauri::State;
+use tauri::{Emitter, State};
 
 #[tauri::command]
 pub fn get_global_settings(state: State<'_, AppState>) -> ApiConfig {
@@ -9,6 +9,7 @@ pub fn get_global_settings(state: State<'_, AppState>) -> ApiConfig {
 
 #[tauri::command]
 pub fn save_global_settings(settings: ApiConfig, state: State<'_, AppState>) -> Result<(), String> {
+    let overrides = state.project_api_overrides();
     let mut config = state.config.lock().unwrap();
 
     if let Err(e) = config::ensure_global_prompts_dir() {
@@ -17,17 +18,76 @@ pub fn save_global_settings(settings: ApiConfig, state: State<'_, AppState>) ->
 
     // Enforce hardcoded Blade URL
     let mut safe_settings = settings.clone();
-    safe_settings.blade_url = "https://coder.zaguanai.com".to_string();
+    safe_settings.blade_url = config::DEFAULT_BLADE_URL.to_string();
+    // Managed through get/set_reasoning_models; the settings form doesn't carry it
+    safe_settings.reasoning_models = config.reasoning_models.clone();
+    // Edited in the config file only
+    safe_settings.shell = config.shell.clone();
 
     *config = safe_settings.clone();
 
     // Persist to disk
-    let path = config::default_api_config_path();
-    config::save_api_config(&path, &config)?;
+    persist_global_config(&config, &overrides)?;
+    overrides.apply(&mut config);
 
     Ok(())
 }
 
+/// Save the global config file, keeping the on-disk Blade URL / API key for any
+/// field the open workspace overrides so project values don't leak into it
+fn persist_global_config(
+    cfg: &ApiConfig,
+    overrides: &config::ProjectApiOverrides,
+) -> Result<(), String> {
+    let path = config::default_api_config_path();
+    let mut on_disk = cfg.clone();
+    if overrides.blade_url.is_some() || overrides.api_key.is_some() {
+        let previous = config::load_api_config(&path);
+        if overrides.blade_url.is_some() {
+            on_disk.blade_url = previous.blade_url;
+        }
+        if overrides.api_key.is_some() {
+            on_disk.api_key = previous.api_key;
+        }
+    }
+    config::save_api_config(&path, &on_disk)
+}
+
+/// Re-read the API config from disk (e.g. after rotating the API key or changing the
+/// Blade URL) without restarting, keeping the open workspace and conversation
+#[tauri::command]
+pub async fn reload_api_config(
+    state: State<'_, AppState>,
+    app_handle: tauri::AppHandle,
+) -> Result<ApiConfig, String> {
+    if state.reload_api_config() {
+        state.update_ws_credentials().await;
+    }
+
+    let config = state.config.lock().unwrap().clone();
+    let _ = app_handle.emit(crate::events::event_names::CONFIG_RELOADED, &config);
+    Ok(config)
+}
+
+/// Model-name substrings the user registered as emitting `<think>` reasoning tags
+#[tauri::command]
+pub fn get_reasoning_models(state: State<'_, AppState>) -> Vec<String> {
+    state.config.lock().unwrap().reasoning_models.clone()
+}
+
+#[tauri::command]
+pub fn set_reasoning_models(models: Vec<String>, state: State<'_, AppState>) -> Result<(), String> {
+    let overrides = state.project_api_overrides();
+    let mut config = state.config.lock().unwrap();
+    config.reasoning_models = models
+        .into_iter()
+        .map(|m| m.trim().to_string())
+        .filter(|m| !m.is_empty())
+        .collect();
+
+    persist_global_config(&config, &overrides)
+}
+
 #[tauri::command]
 pub async fn test_ollama_connection(
     state: State<'_, AppState>,
@@ -11,6 +11,7 @@ use crate::core_state::{
     TerminalStateSnapshot, WorkspaceStateSnapshot,
 };
 use crate::feature_flags::FeatureFlagsSnapshot;
+use crate::idempotency::IdempotencyStats;
 
 /// Returns a complete snapshot of the core application state.
 /// Used for UI initialization, reload recovery, and debugging.
@@ -104,3 +105,31 @@ pub fn set_feature_flag(
         _ => Err(format!("Unknown feature flag: {}", flag)),
     }
 }
+
+/// Turns read-only (plan) mode on or off. While on, the agent may read and
+/// search but every file-mutating tool and `run_command` is refused.
+#[tauri::command]
+pub fn set_read_only_mode(enabled: bool, state: State<'_, AppState>) {
+    eprintln!("[READ ONLY] {}", if enabled { "Enabled" } else { "Disabled" });
+    state
+        .read_only_mode
+        .store(enabled, std::sync::atomic::Ordering::SeqCst);
+}
+
+/// Returns whether read-only (plan) mode is on.
+#[tauri::command]
+pub fn get_read_only_mode(state: State<'_, AppState>) -> bool {
+    state.read_only_mode.load(std::sync::atomic::Ordering::SeqCst)
+}
+
+/// Returns idempotency cache statistics, for debugging why `dispatch` replayed a result.
+#[tauri::command]
+pub fn get_idempotency_stats(state: State<'_, AppState>) -> IdempotencyStats {
+    state.idempotency_cache.stats()
+}
+
+/// Clears the idempotency cache so re-sent intents are executed again.
+#[tauri::command]
+pub fn clear_idempotency_cache(state: State<'_, AppState>) {
+    state.idempotency_cache.clear();
+}
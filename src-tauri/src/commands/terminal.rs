@@ -0,0 +1,19 @@
+use crate::terminal::TerminalManager;
+use tauri::State;
+
+/// Recent output of terminal `id`, the last `max_lines` lines (default: all
+/// kept), so a reconnecting frontend can repaint and the agent can see what ran
+#[tauri::command]
+pub fn get_terminal_scrollback(
+    id: String,
+    max_lines: Option<usize>,
+    state: State<'_, TerminalManager>,
+) -> Result<String, String> {
+    state
+        .scrollback
+        .lock()
+        .unwrap()
+        .get(&id)
+        .map(|scrollback| scrollback.tail(max_lines))
+        .ok_or_else(|| format!("Terminal not found: {}", id))
+}
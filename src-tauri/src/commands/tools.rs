@@ -1,6 +1,6 @@
 use crate::app_state::AppState;
 use crate::events;
-use crate::utils::extract_root_command;
+use crate::utils::allowlistable_root_command;
 use crate::workflow_controller::check_batch_completion;
 use regex::Regex;
 use tauri::{Emitter, Manager, Runtime, State, Window};
@@ -58,6 +58,20 @@ fn strip_ansi_codes(input: &str) -> String {
     cleanup_regex.replace_all(&result, "\n\n").trim().to_string()
 }
 
+/// Keep the last `max_lines` lines of command output; 0 keeps everything
+pub(crate) fn tail_lines(output: &str, max_lines: usize) -> String {
+    let total = output.lines().count();
+    if max_lines == 0 || total <= max_lines {
+        return output.to_string();
+    }
+    let kept: Vec<&str> = output.lines().skip(total - max_lines).collect();
+    format!(
+        "[... {} earlier lines omitted ...]\n{}",
+        total - max_lines,
+        kept.join("\n")
+    )
+}
+
 // #[tauri::command]
 pub fn approve_tool<R: Runtime>(approved: bool, window: Window<R>, state: State<'_, AppState>) {
     let app_handle = window.app_handle();
@@ -74,10 +88,30 @@ pub fn approve_tool<R: Runtime>(approved: bool, window: Window<R>, state: State<
 
             if approved {
                 eprintln!("[APPROVAL] User APPROVED - executing commands");
+                let trusted = state.workspace.lock().unwrap().is_current_trusted();
                 // 1. Emit events for shell commands to be executed with terminal display
                 for cmd in batch.commands.clone() {
                     // Only emit if not already result
                     if !batch.file_results.iter().any(|(c, _)| c.id == cmd.call.id) {
+                        if !trusted {
+                            eprintln!(
+                                "[APPROVAL] Refusing command in untrusted workspace: {}",
+                                cmd.command
+                            );
+                            batch.file_results.push((
+                                cmd.call.clone(),
+                                crate::tools::ToolResult::err(
+                                    crate::workspace_manager::UNTRUSTED_WORKSPACE_ERROR,
+                                ),
+                            ));
+                            continue;
+                        }
+                        if let Err(e) = crate::utils::ensure_command_cwd(cmd.cwd.as_deref()) {
+                            batch
+                                .file_results
+                                .push((cmd.call.clone(), crate::tools::ToolResult::err(e)));
+                            continue;
+                        }
                         let command_id = format!("cmd-{}", cmd.call.id);
                         eprintln!(
                             "[COMMAND EXEC] Emitting command-execution-started for: {}",
@@ -92,6 +126,11 @@ pub fn approve_tool<R: Runtime>(approved: bool, window: Window<R>, state: State<
                                 cwd: cmd.cwd.clone(),
                             },
                         );
+                        if let Some(pending) =
+                            batch.commands.iter_mut().find(|c| c.call.id == cmd.call.id)
+                        {
+                            pending.started_at = Some(std::time::SystemTime::now());
+                        }
                     }
                 }
 
@@ -119,10 +158,12 @@ pub fn approve_tool<R: Runtime>(approved: bool, window: Window<R>, state: State<
                             selection_start_line,
                             selection_end_line,
                             Some(app_handle.clone()),
-                        );
+                        )
+                        .with_conversation_id(batch.conversation_id.clone());
 
                         let res = crate::tool_execution::execute_tool_with_context(
                             &context,
+                            &conf.call.id,
                             &conf.call.function.name,
                             &conf.call.function.arguments,
                         );
@@ -203,13 +244,32 @@ pub fn approve_tool_decision<R: Runtime>(
     let approved = decision == "approve_once" || decision == "approve_always";
 
     if decision == "approve_always" {
-        let mut cache = state.approved_command_roots.lock().unwrap();
-        let batch_guard = state.pending_batch.lock().unwrap();
-        if let Some(batch) = batch_guard.as_ref() {
-            for cmd in &batch.commands {
-                if let Some(root) = extract_root_command(&cmd.command) {
-                    cache.insert(root);
-                }
+        let roots: Vec<String> = {
+            let batch_guard = state.pending_batch.lock().unwrap();
+            batch_guard
+                .as_ref()
+                .map(|batch| {
+                    batch
+                        .commands
+                        .iter()
+                        // Wrapped or compound commands are approved once, never remembered
+                        .filter_map(|cmd| allowlistable_root_command(&cmd.command))
+                        .collect()
+                })
+                .unwrap_or_default()
+        };
+
+        state
+            .approved_command_roots
+            .lock()
+            .unwrap()
+            .extend(roots.iter().cloned());
+
+        // Persist to the user's allowlist for this project so the approval survives restarts
+        let workspace = state.workspace.lock().unwrap().workspace.clone();
+        if let Some(root_path) = workspace {
+            if let Err(e) = crate::project_settings::add_to_command_allowlist(&root_path, roots) {
+                eprintln!("[APPROVAL] Failed to persist command allowlist: {}", e);
             }
         }
     }
@@ -237,17 +297,23 @@ pub fn approve_single_command<R: Runtime>(
                 if !batch.file_results.iter().any(|(c, _)| c.id == call_id) {
                     if approved {
                         eprintln!("[SINGLE APPROVAL] User APPROVED command: {}", cmd.command);
-                        // Emit event for this specific command to be executed
-                        let command_id = format!("cmd-{}", cmd.call.id);
-                        let _ = window.emit(
-                            crate::events::event_names::COMMAND_EXECUTION_STARTED,
-                            crate::events::CommandExecutionStartedPayload {
-                                command_id,
-                                call_id: cmd.call.id.clone(),
-                                command: cmd.command.clone(),
-                                cwd: cmd.cwd.clone(),
-                            },
-                        );
+                        if let Err(e) = crate::utils::ensure_command_cwd(cmd.cwd.as_deref()) {
+                            batch
+                                .file_results
+                                .push((cmd.call.clone(), crate::tools::ToolResult::err(e)));
+                        } else {
+                            // Emit event for this specific command to be executed
+                            let command_id = format!("cmd-{}", cmd.call.id);
+                            let _ = window.emit(
+                                crate::events::event_names::COMMAND_EXECUTION_STARTED,
+                                crate::events::CommandExecutionStartedPayload {
+                                    command_id,
+                                    call_id: cmd.call.id.clone(),
+                                    command: cmd.command.clone(),
+                                    cwd: cmd.cwd.clone(),
+                                },
+                            );
+                        }
                     } else {
                         eprintln!("[SINGLE APPROVAL] User SKIPPED command: {}", cmd.command);
                         // Add skip result immediately
@@ -282,11 +348,121 @@ pub fn submit_command_result(
     call_id: String,
     output: String,
     exit_code: i32,
+    cancelled: Option<bool>,
     state: State<'_, AppState>,
     app_handle: tauri::AppHandle,
 ) -> Result<(), String> {
-    // Strip ANSI codes from output for clean display in chat and AI context
-    let clean_output = strip_ansi_codes(&output);
+    record_command_result(&state, &app_handle, &call_id, &output, exit_code, cancelled);
+    Ok(())
+}
+
+/// Exit code reported for commands killed by the timeout (as coreutils `timeout`)
+pub const COMMAND_TIMEOUT_EXIT_CODE: i32 = 124;
+
+/// Kill an AI-run command and fail it with `COMMAND_TIMEOUT_EXIT_CODE` if it
+/// hasn't reported a result within the project's `command_timeout_secs`.
+/// Called when the command's start sentinel shows up in `terminal_id`.
+pub fn spawn_command_timeout<R: Runtime>(
+    app_handle: &tauri::AppHandle<R>,
+    terminal_id: String,
+    call_id: String,
+) {
+    let workspace = app_handle
+        .state::<AppState>()
+        .workspace
+        .lock()
+        .unwrap()
+        .workspace
+        .clone();
+    let timeout_secs = workspace
+        .map(|root| crate::project_settings::load_project_settings_or_default(&root))
+        .unwrap_or_default()
+        .command_timeout_secs;
+    if timeout_secs == 0 {
+        return;
+    }
+
+    let app_handle = app_handle.clone();
+    std::thread::spawn(move || {
+        std::thread::sleep(std::time::Duration::from_secs(timeout_secs));
+
+        let state = app_handle.state::<AppState>();
+        let still_running = state
+            .pending_batch
+            .lock()
+            .unwrap()
+            .as_ref()
+            .is_some_and(|batch| {
+                batch.commands.iter().any(|c| c.call.id == call_id)
+                    && !batch.file_results.iter().any(|(c, _)| c.id == call_id)
+            });
+        if !still_running {
+            return;
+        }
+
+        eprintln!(
+            "[COMMAND EXEC] Command {} timed out after {}s, killing it",
+            call_id, timeout_secs
+        );
+        let terminals = app_handle.state::<crate::terminal::TerminalManager>();
+        crate::terminal::kill_foreground_job(&terminals, &terminal_id);
+
+        let message = command_timeout_message(timeout_secs);
+        record_command_result(
+            &state,
+            &app_handle,
+            &call_id,
+            &message,
+            COMMAND_TIMEOUT_EXIT_CODE,
+            Some(false),
+        );
+        crate::terminal::emit_command_exited(
+            &app_handle,
+            &terminal_id,
+            &call_id,
+            COMMAND_TIMEOUT_EXIT_CODE,
+            message,
+            Some(false),
+        );
+    });
+}
+
+/// Result text for a command killed after `timeout_secs`
+pub fn command_timeout_message(timeout_secs: u64) -> String {
+    format!(
+        "Command timed out after {}s and was killed. Long-running processes such as \
+         dev servers, watchers and REPLs never exit on their own: run them in the \
+         background or ask the user to start them.",
+        timeout_secs
+    )
+}
+
+/// Store a finished command's result in the pending batch (first result wins)
+/// and complete the batch once nothing is outstanding.
+///
+/// `cancelled` is set by whoever killed the command; when absent, exit code 130
+/// (SIGINT, e.g. Ctrl+C in the terminal) is taken as a cancellation.
+pub fn record_command_result<R: Runtime>(
+    state: &AppState,
+    app_handle: &tauri::AppHandle<R>,
+    call_id: &str,
+    output: &str,
+    exit_code: i32,
+    cancelled: Option<bool>,
+) {
+    let call_id = call_id.to_string();
+    // Strip ANSI codes from output for clean display in chat and AI context,
+    // keeping only the tail of chatty commands
+    let tail = state
+        .workspace
+        .lock()
+        .unwrap()
+        .workspace
+        .as_deref()
+        .map(crate::project_settings::load_project_settings_or_default)
+        .unwrap_or_default()
+        .command_output_tail_lines;
+    let clean_output = tail_lines(&strip_ansi_codes(output), tail);
     
     let mut batch_guard = state.pending_batch.lock().unwrap();
     if let Some(batch) = batch_guard.as_mut() {
@@ -296,12 +472,11 @@ pub fn submit_command_result(
             if !batch.file_results.iter().any(|(c, _)| c.id == call_id) {
                 let result = if exit_code == 0 {
                     crate::tools::ToolResult::ok(clean_output.clone())
-                } else if exit_code == 130 {
-                    // Exit code 130 means the command was cancelled (SIGINT)
-                    // Treat it as a skip
+                } else if cancelled.unwrap_or(exit_code == 130) {
+                    // Cancelled commands are treated as a skip
                     eprintln!(
-                        "[SUBMIT] Command {} was cancelled (exit 130), treating as skip",
-                        call_id
+                        "[SUBMIT] Command {} was cancelled (exit {}), treating as skip",
+                        call_id, exit_code
                     );
                     crate::tools::ToolResult::skipped(format!(
                         "User cancelled: '{}'. This command was not executed.",
@@ -317,6 +492,15 @@ pub fn submit_command_result(
                     crate::tools::ToolResult::err(error_msg)
                 };
                 let is_skipped = result.skipped;
+                crate::tool_execution::record_tool_call(
+                    state,
+                    batch.conversation_id.as_deref(),
+                    &call_id,
+                    &cmd.call.function.name,
+                    &cmd.call.function.arguments,
+                    result.success,
+                    cmd.started_at.unwrap_or_else(std::time::SystemTime::now),
+                );
                 batch.file_results.push((cmd.call.clone(), result));
 
                 // Emit tool-execution-completed event for UI to update status
@@ -337,7 +521,7 @@ pub fn submit_command_result(
                     events::CommandExecutedPayload {
                         command: cmd.command.clone(),
                         cwd: cmd.cwd.clone(),
-                        output: output.clone(), // Use raw output with ANSI codes for UI
+                        output: output.to_string(), // Use raw output with ANSI codes for UI
                         exit_code,
                         duration: None,
                         call_id: call_id.clone(),
@@ -348,6 +532,116 @@ pub fn submit_command_result(
     }
     drop(batch_guard);
 
-    check_batch_completion(&*state);
-    Ok(())
+    check_batch_completion(state);
+}
+
+/// A tool zblade can execute, as described to the model
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct ToolInfo {
+    pub name: String,
+    pub description: String,
+    /// JSON schema of the tool's arguments
+    pub parameters: serde_json::Value,
+}
+
+/// Tool definitions the open project allows, for the /tools panel and integrators
+#[tauri::command]
+pub fn list_tools(state: State<'_, AppState>) -> Vec<ToolInfo> {
+    let settings = state
+        .workspace
+        .lock()
+        .unwrap()
+        .workspace
+        .as_deref()
+        .map(crate::project_settings::load_project_settings_or_default)
+        .unwrap_or_default()
+        .tools;
+    enabled_tools(&settings)
+}
+
+fn enabled_tools(settings: &crate::project_settings::ToolSettings) -> Vec<ToolInfo> {
+    let definitions: Vec<ToolInfo> = crate::ai_workflow::get_tool_definitions()
+        .iter()
+        .filter_map(|def| {
+            let function = def.get("function")?;
+            Some(ToolInfo {
+                name: function.get("name")?.as_str()?.to_string(),
+                description: function
+                    .get("description")
+                    .and_then(|d| d.as_str())
+                    .unwrap_or_default()
+                    .to_string(),
+                parameters: function
+                    .get("parameters")
+                    .cloned()
+                    .unwrap_or(serde_json::Value::Null),
+            })
+        })
+        .collect();
+    let defined = |name: &str| {
+        definitions
+            .iter()
+            .any(|def| crate::tools::canonical_tool_name(&def.name) == name)
+    };
+
+    // Every tool zblade dispatches, described from its argument schema when the
+    // model isn't sent a hand-written definition for it
+    let undefined: Vec<ToolInfo> = crate::tools::LOCAL_TOOLS
+        .iter()
+        .filter(|&&name| !defined(name))
+        .map(|name| ToolInfo {
+            name: name.to_string(),
+            description: String::new(),
+            parameters: crate::tool_execution::args_json_schema(
+                &crate::tool_execution::tool_arg_specs(name).unwrap_or_default(),
+            ),
+        })
+        .collect();
+
+    definitions
+        .into_iter()
+        .chain(undefined)
+        .filter(|tool| settings.is_enabled(&tool.name))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tail_lines() {
+        let output = "one\ntwo\nthree\nfour\n";
+        assert_eq!(tail_lines(output, 0), output);
+        assert_eq!(tail_lines(output, 4), output);
+        assert_eq!(
+            tail_lines(output, 2),
+            "[... 2 earlier lines omitted ...]\nthree\nfour"
+        );
+    }
+
+    #[test]
+    fn test_enabled_tools_respects_project_settings() {
+        let all = enabled_tools(&Default::default());
+        let rg = all.iter().find(|t| t.name == "rg").unwrap();
+        assert!(!rg.description.is_empty());
+        assert_eq!(rg.parameters["required"][0], "pattern");
+        // Dispatched tools without a definition are listed from their argument schema
+        let copy = all.iter().find(|t| t.name == "copy_file").unwrap();
+        assert_eq!(copy.parameters["properties"]["source"]["type"], "string");
+        assert!(all.iter().all(|t| t.name != "list_directory"));
+
+        let settings = crate::project_settings::ToolSettings {
+            deny: vec!["grep_search".to_string()],
+            disable_destructive: true,
+            ..Default::default()
+        };
+        let names: Vec<String> = enabled_tools(&settings)
+            .into_iter()
+            .map(|t| t.name)
+            .collect();
+        assert_eq!(names.len(), all.len() - 4);
+        assert!(!names.iter().any(|n| n == "rg" || n == "run_command"));
+        assert!(!names.iter().any(|n| n == "delete_file" || n == "move_file"));
+    }
 }
@@ -16,23 +16,233 @@ pub struct ApiConfig {
     pub ollama_enabled: bool,
     #[serde(default = "default_ollama_url")]
     pub ollama_url: String,
+    #[serde(default = "default_ollama_embedding_model")]
+    pub ollama_embedding_model: String,
+    /// Extra model-name substrings whose output uses `<think>` reasoning tags
+    #[serde(default)]
+    pub reasoning_models: Vec<String>,
     #[serde(default)]
     pub openai_compat_enabled: bool,
     #[serde(default = "default_openai_compat_url")]
     pub openai_compat_url: String,
+    /// Seconds between WebSocket pings to the Blade server (0 uses the default)
+    #[serde(default = "default_ws_ping_interval_secs")]
+    pub ws_ping_interval_secs: u64,
+    /// Timeout in seconds for HTTP requests to the Blade server (0 uses the default)
+    #[serde(default = "default_http_timeout_secs")]
+    pub http_timeout_secs: u64,
+    /// Model to select in workspaces that have no saved selection
+    #[serde(default)]
+    pub default_model: Option<String>,
+    /// Shell for terminals and AI-run commands. User-level only: a shell path
+    /// from a cloned repository's settings would run as soon as a terminal opens
+    #[serde(default)]
+    pub shell: ShellSettings,
     pub theme: String,
     pub markdown_view: String,
 }
 
+/// Shell used for terminals and AI-run commands, so commands resolve PATH the
+/// same way the user's terminal does.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ShellSettings {
+    /// Shell executable. Default: `$SHELL` (or `/bin/bash`) on Unix,
+    /// `powershell.exe` on Windows
+    #[serde(default)]
+    pub shell_path: Option<String>,
+    /// Start Unix shells as login shells (`-l`) so profile-managed PATH entries
+    /// (nvm, rbenv, ...) are loaded. On by default
+    #[serde(default = "default_login_shell")]
+    pub login_shell: bool,
+    /// Arguments passed to the shell before anything else
+    #[serde(default)]
+    pub extra_args: Vec<String>,
+}
+
+impl Default for ShellSettings {
+    fn default() -> Self {
+        Self {
+            shell_path: None,
+            login_shell: true,
+            extra_args: Vec::new(),
+        }
+    }
+}
+
+impl ShellSettings {
+    pub fn program(&self) -> String {
+        if let Some(path) = self.shell_path.as_ref().filter(|p| !p.trim().is_empty()) {
+            return path.clone();
+        }
+        if cfg!(windows) {
+            "powershell.exe".to_string()
+        } else {
+            std::env::var("SHELL")
+                .ok()
+                .filter(|s| !s.is_empty())
+                .unwrap_or_else(|| "/bin/bash".to_string())
+        }
+    }
+
+    /// Executable name without directory or `.exe`, e.g. `bash`, `pwsh`, `cmd`
+    pub fn name(&self) -> String {
+        let program = self.program();
+        // Either separator, so Windows paths in a synced config parse anywhere
+        let file_name = program
+            .rsplit(['/', '\\'])
+            .next()
+            .unwrap_or("sh")
+            .to_lowercase();
+        file_name
+            .strip_suffix(".exe")
+            .unwrap_or(&file_name)
+            .to_string()
+    }
+
+    /// Arguments for an interactive session
+    pub fn interactive_args(&self) -> Vec<String> {
+        let mut args = self.extra_args.clone();
+        if self.login_shell && !matches!(self.name().as_str(), "cmd" | "powershell" | "pwsh") {
+            args.push("-l".to_string());
+        }
+        args
+    }
+
+    /// Arguments that make the shell run `command` and exit
+    pub fn command_args(&self, command: &str) -> Vec<String> {
+        let mut args = self.interactive_args();
+        match self.name().as_str() {
+            "cmd" => args.push("/C".to_string()),
+            "powershell" | "pwsh" => {
+                args.push("-NoLogo".to_string());
+                args.push("-Command".to_string());
+            }
+            _ => args.push("-c".to_string()),
+        }
+        args.push(command.to_string());
+        args
+    }
+}
+
+fn default_login_shell() -> bool {
+    true
+}
+
+/// Canonical Blade endpoints derived from the configured `blade_url`
+#[derive(Debug, Clone, PartialEq)]
+pub struct BladeEndpoints {
+    /// `http(s)://host[:port][/path]` without a trailing slash, for REST calls
+    pub http_base: String,
+    /// The same origin as `ws(s)://`, for the Blade Protocol WebSocket
+    pub ws_base: String,
+}
+
+/// Validate a Blade URL given with any of http/https/ws/wss and derive both bases
+/// (http↔ws, https↔wss) from it
+pub fn normalize_blade_url(raw: &str) -> Result<BladeEndpoints, String> {
+    let trimmed = raw.trim();
+    let url = reqwest::Url::parse(trimmed)
+        .map_err(|e| format!("Invalid Blade URL '{}': {}", trimmed, e))?;
+
+    let (http_scheme, ws_scheme) = match url.scheme() {
+        "http" | "ws" => ("http", "ws"),
+        "https" | "wss" => ("https", "wss"),
+        other => {
+            return Err(format!(
+                "Invalid Blade URL '{}': unsupported scheme '{}' (use http, https, ws or wss)",
+                trimmed, other
+            ))
+        }
+    };
+    let host = url
+        .host_str()
+        .ok_or_else(|| format!("Invalid Blade URL '{}': missing host", trimmed))?;
+    if url.query().is_some() || url.fragment().is_some() {
+        return Err(format!(
+            "Invalid Blade URL '{}': query strings and fragments aren't allowed",
+            trimmed
+        ));
+    }
+
+    let authority = match url.port() {
+        Some(port) => format!("{}:{}", host, port),
+        None => host.to_string(),
+    };
+    let path = url.path().trim_end_matches('/');
+
+    Ok(BladeEndpoints {
+        http_base: format!("{}://{}{}", http_scheme, authority, path),
+        ws_base: format!("{}://{}{}", ws_scheme, authority, path),
+    })
+}
+
+impl ApiConfig {
+    /// Canonical HTTP and WebSocket bases for `blade_url`; use these instead of the raw value
+    pub fn normalized(&self) -> Result<BladeEndpoints, String> {
+        normalize_blade_url(&self.blade_url)
+    }
+
+    /// Ollama embedding model for local semantic search, falling back to the default
+    /// when unset (`ApiConfig::default()` leaves it empty)
+    pub fn embedding_model(&self) -> &str {
+        if self.ollama_embedding_model.trim().is_empty() {
+            DEFAULT_OLLAMA_EMBEDDING_MODEL
+        } else {
+            self.ollama_embedding_model.trim()
+        }
+    }
+
+    /// Interval between Blade WebSocket keep-alive pings, falling back to the default
+    /// when unset (`ApiConfig::default()` leaves it at 0)
+    pub fn ws_ping_interval(&self) -> std::time::Duration {
+        let secs = if self.ws_ping_interval_secs == 0 {
+            DEFAULT_WS_PING_INTERVAL_SECS
+        } else {
+            self.ws_ping_interval_secs
+        };
+        std::time::Duration::from_secs(secs)
+    }
+
+    /// Timeout for Blade HTTP requests, falling back to the default when unset
+    pub fn http_timeout(&self) -> std::time::Duration {
+        let secs = if self.http_timeout_secs == 0 {
+            DEFAULT_HTTP_TIMEOUT_SECS
+        } else {
+            self.http_timeout_secs
+        };
+        std::time::Duration::from_secs(secs)
+    }
+}
+
+pub const DEFAULT_BLADE_URL: &str = "https://coder.zaguanai.com";
+
 fn default_blade_url() -> String {
     // Check environment variable first, then fall back to fidelity
-    std::env::var("BLADE_URL").unwrap_or_else(|_| "https://coder.zaguanai.com".to_string())
+    std::env::var("BLADE_URL").unwrap_or_else(|_| DEFAULT_BLADE_URL.to_string())
 }
 
 fn default_ollama_url() -> String {
     "http://localhost:11434".to_string()
 }
 
+pub const DEFAULT_OLLAMA_EMBEDDING_MODEL: &str = "nomic-embed-text";
+
+fn default_ollama_embedding_model() -> String {
+    DEFAULT_OLLAMA_EMBEDDING_MODEL.to_string()
+}
+
+pub const DEFAULT_WS_PING_INTERVAL_SECS: u64 = 25;
+
+fn default_ws_ping_interval_secs() -> u64 {
+    DEFAULT_WS_PING_INTERVAL_SECS
+}
+
+pub const DEFAULT_HTTP_TIMEOUT_SECS: u64 = 30;
+
+fn default_http_timeout_secs() -> u64 {
+    DEFAULT_HTTP_TIMEOUT_SECS
+}
+
 fn default_openai_compat_url() -> String {
     // Use base URL (no version); callers append /v1 paths
     "http://localhost:8080".to_string()
@@ -84,6 +294,80 @@ pub fn save_api_config(path: &Path, cfg: &ApiConfig) -> Result<(), String> {
     fs::write(path, json).map_err(|e| e.to_string())
 }
 
+/// Load the global config, filling an empty Blade URL / API key from the process
+/// environment (`BLADE_URL`, `BLADE_API_KEY`) and then the default URL
+pub fn resolve_api_config(path: &Path) -> ApiConfig {
+    let mut config = load_api_config(path);
+
+    if config.blade_url.trim().is_empty() {
+        config.blade_url =
+            std::env::var("BLADE_URL").unwrap_or_else(|_| DEFAULT_BLADE_URL.to_string());
+    }
+    if config.api_key.trim().is_empty() {
+        if let Ok(key) = std::env::var("BLADE_API_KEY") {
+            config.api_key = key;
+        }
+    }
+
+    config
+}
+
+/// Per-workspace Blade endpoint overrides read from `.zblade/.env`
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct ProjectApiOverrides {
+    pub blade_url: Option<String>,
+    pub api_key: Option<String>,
+}
+
+impl ProjectApiOverrides {
+    /// Overrides take precedence over the config file and process environment.
+    /// A project URL only ever gets the project's own key: the global key is never
+    /// sent to a server the repository chose.
+    pub fn apply(&self, config: &mut ApiConfig) {
+        if let Some(url) = &self.blade_url {
+            config.blade_url = url.clone();
+            config.api_key = self.api_key.clone().unwrap_or_default();
+        } else if let Some(key) = &self.api_key {
+            config.api_key = key.clone();
+        }
+    }
+}
+
+/// Read `BLADE_URL` / `BLADE_API_KEY` from the project's `.zblade/.env` without
+/// touching the process environment
+pub fn load_project_api_overrides(project_path: &Path) -> ProjectApiOverrides {
+    let mut overrides = ProjectApiOverrides::default();
+    let env_path = crate::project_settings::get_zblade_dir(project_path).join(".env");
+    let Ok(entries) = dotenvy::from_path_iter(&env_path) else {
+        return overrides;
+    };
+
+    for entry in entries {
+        let (key, value) = match entry {
+            Ok(pair) => pair,
+            Err(e) => {
+                eprintln!(
+                    "[CONFIG] Skipping invalid line in {}: {}",
+                    env_path.display(),
+                    e
+                );
+                continue;
+            }
+        };
+        let value = value.trim();
+        if value.is_empty() {
+            continue;
+        }
+        match key.as_str() {
+            "BLADE_URL" => overrides.blade_url = Some(value.to_string()),
+            "BLADE_API_KEY" => overrides.api_key = Some(value.to_string()),
+            _ => {}
+        }
+    }
+
+    overrides
+}
+
 /// Generate or get user_id from config
 /// If user_id doesn't exist, generate one and save it
 pub fn get_or_create_user_id(config_path: &Path) -> String {
@@ -147,3 +431,92 @@ pub fn get_or_create_user_id(config_path: &Path) -> String {
 
     config.user_id
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize_blade_url_derives_both_schemes() {
+        let endpoints = normalize_blade_url(" https://coder.zaguanai.com/ ").unwrap();
+        assert_eq!(endpoints.http_base, "https://coder.zaguanai.com");
+        assert_eq!(endpoints.ws_base, "wss://coder.zaguanai.com");
+
+        let endpoints = normalize_blade_url("ws://localhost:8080/blade").unwrap();
+        assert_eq!(endpoints.http_base, "http://localhost:8080/blade");
+        assert_eq!(endpoints.ws_base, "ws://localhost:8080/blade");
+
+        assert!(normalize_blade_url("coder.zaguanai.com").is_err());
+        assert!(normalize_blade_url("ftp://coder.zaguanai.com").is_err());
+        assert!(normalize_blade_url("https://coder.zaguanai.com?api_key=x").is_err());
+    }
+
+    #[test]
+    fn test_project_api_overrides_take_precedence() {
+        let dir = tempfile::tempdir().unwrap();
+        let zblade = crate::project_settings::get_zblade_dir(dir.path());
+        fs::create_dir_all(&zblade).unwrap();
+        fs::write(
+            zblade.join(".env"),
+            "BLADE_URL=https://staging.example.com\nBLADE_API_KEY=\nOTHER=1\n",
+        )
+        .unwrap();
+
+        let overrides = load_project_api_overrides(dir.path());
+        assert_eq!(
+            overrides.blade_url.as_deref(),
+            Some("https://staging.example.com")
+        );
+        assert_eq!(overrides.api_key, None);
+
+        let mut config = ApiConfig {
+            blade_url: "https://coder.zaguanai.com".to_string(),
+            api_key: "global-key".to_string(),
+            ..Default::default()
+        };
+        overrides.apply(&mut config);
+        assert_eq!(config.blade_url, "https://staging.example.com");
+        assert_eq!(config.api_key, "");
+
+        let with_key = ProjectApiOverrides {
+            api_key: Some("project-key".to_string()),
+            ..overrides
+        };
+        with_key.apply(&mut config);
+        assert_eq!(config.api_key, "project-key");
+    }
+
+    #[test]
+    fn test_shell_settings() {
+        let bash = ShellSettings {
+            shell_path: Some("/usr/local/bin/bash".to_string()),
+            login_shell: true,
+            extra_args: vec!["--norc".to_string()],
+        };
+        assert_eq!(bash.name(), "bash");
+        assert_eq!(bash.interactive_args(), ["--norc", "-l"]);
+        assert_eq!(bash.command_args("make"), ["--norc", "-l", "-c", "make"]);
+
+        let cmd = ShellSettings {
+            shell_path: Some(r"C:\Windows\System32\cmd.exe".to_string()),
+            login_shell: true,
+            extra_args: Vec::new(),
+        };
+        assert_eq!(cmd.command_args("dir"), ["/C", "dir"]);
+
+        let pwsh = ShellSettings {
+            shell_path: Some("pwsh".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(pwsh.command_args("ls"), ["-NoLogo", "-Command", "ls"]);
+
+        // Login shell unless turned off
+        let zsh: ShellSettings = serde_json::from_str(r#"{"shell_path": "/bin/zsh"}"#).unwrap();
+        assert_eq!(zsh.command_args("ls"), ["-l", "-c", "ls"]);
+        let zsh = ShellSettings {
+            login_shell: false,
+            ..zsh
+        };
+        assert_eq!(zsh.command_args("ls"), ["-c", "ls"]);
+    }
+}
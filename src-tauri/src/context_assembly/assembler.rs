@@ -4,13 +4,14 @@
 //! by combining symbol data, file content, and related code.
 
 use std::collections::HashSet;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 
 use serde::{Deserialize, Serialize};
 
 use super::budget::{estimate_tokens, truncate_to_tokens, BudgetAllocation, TokenBudget};
 use super::strategy::{ContextStrategy, StrategyConfig};
+use super::uncommitted::UncommittedContext;
 use crate::language_service::LanguageService;
 use crate::tree_sitter::Symbol;
 
@@ -27,6 +28,9 @@ pub struct AssembledContext {
     pub files_included: Vec<String>,
     /// Symbols included in context
     pub symbols_included: Vec<SymbolInfo>,
+    /// Uncommitted changes that made it into the context
+    #[serde(default)]
+    pub uncommitted: UncommittedContext,
 }
 
 /// Summary of assembled context
@@ -61,6 +65,7 @@ pub struct ContextAssembler {
     budget: TokenBudget,
     strategy: ContextStrategy,
     config: StrategyConfig,
+    uncommitted: Option<UncommittedContext>,
 }
 
 impl ContextAssembler {
@@ -72,6 +77,7 @@ impl ContextAssembler {
             budget: TokenBudget::default(),
             strategy,
             config: StrategyConfig::for_strategy(strategy),
+            uncommitted: None,
         }
     }
 
@@ -95,6 +101,12 @@ impl ContextAssembler {
         self
     }
 
+    /// Include the user's uncommitted changes (see `crate::git::collect_uncommitted_context`)
+    pub fn with_uncommitted(mut self, changes: UncommittedContext) -> Self {
+        self.uncommitted = Some(changes);
+        self
+    }
+
     /// Assemble context for a cursor position
     pub fn assemble_for_cursor(
         &self,
@@ -201,6 +213,9 @@ impl ContextAssembler {
             }
         }
 
+        // 5. Include uncommitted changes with whatever budget is left
+        let uncommitted = self.push_uncommitted(&mut allocation, &mut context_parts);
+
         // Sort by priority and build final context
         context_parts.sort_by(|a, b| {
             b.priority
@@ -227,6 +242,7 @@ impl ContextAssembler {
             },
             files_included: files_included.into_iter().collect(),
             symbols_included,
+            uncommitted,
         })
     }
 
@@ -285,6 +301,8 @@ impl ContextAssembler {
             }
         }
 
+        let uncommitted = self.push_uncommitted(&mut allocation, &mut context_parts);
+
         context_parts.sort_by(|a, b| {
             b.priority
                 .partial_cmp(&a.priority)
@@ -310,13 +328,141 @@ impl ContextAssembler {
             },
             files_included: files_included.into_iter().collect(),
             symbols_included,
+            uncommitted,
         })
     }
 
+    /// Select the open files that fit in the token budget.
+    ///
+    /// Files are ranked by relevance (active file first, then files defining
+    /// symbols that match the query, then the rest in their original order)
+    /// and the least-relevant ones are dropped once the budget is exhausted.
+    /// The returned files keep their original ordering.
+    pub fn select_open_files(
+        &self,
+        query: &str,
+        active_file: Option<&str>,
+        open_files: &[String],
+    ) -> (Vec<String>, BudgetAllocation) {
+        let mut allocation = BudgetAllocation::default();
+
+        let matching_files: Vec<(PathBuf, f32)> = if query.trim().is_empty() {
+            Vec::new()
+        } else {
+            self.language_service
+                .search_symbols(query, 20)
+                .unwrap_or_default()
+                .into_iter()
+                .map(|r| (self.language_service.resolve_path(&r.symbol.file_path), r.score))
+                .collect()
+        };
+
+        let active_path = active_file.map(|f| self.language_service.resolve_path(f));
+
+        let mut ranked: Vec<(usize, f32)> = open_files
+            .iter()
+            .enumerate()
+            .map(|(idx, file)| {
+                let path = self.language_service.resolve_path(file);
+                let priority = if active_path.as_ref() == Some(&path) {
+                    self.config.weights.active_file
+                } else if let Some((_, score)) = matching_files.iter().find(|(p, _)| *p == path) {
+                    self.config.weights.definitions * score
+                } else {
+                    self.config.weights.open_files
+                };
+                (idx, priority)
+            })
+            .collect();
+
+        // Stable sort keeps the original order among equally relevant files
+        ranked.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(std::cmp::Ordering::Equal));
+
+        let mut selected = Vec::new();
+        let mut other_files = 0;
+        for (idx, _) in ranked {
+            let file = &open_files[idx];
+            let path = self.language_service.resolve_path(file);
+            let is_active = active_path.as_ref() == Some(&path);
+
+            if !is_active && other_files >= self.config.max_open_files {
+                continue;
+            }
+
+            let content = std::fs::read_to_string(&path).unwrap_or_default();
+            let tokens = estimate_tokens(&content);
+            if allocation.remaining(&self.budget) < tokens {
+                continue;
+            }
+
+            if is_active {
+                allocation.active_file += tokens;
+            } else {
+                allocation.open_files += tokens;
+                other_files += 1;
+            }
+            selected.push(idx);
+        }
+
+        selected.sort_unstable();
+        let files = selected.into_iter().map(|idx| open_files[idx].clone()).collect();
+        (files, allocation)
+    }
+
+    /// Fit uncommitted changes into what's left of the budget.
+    ///
+    /// Tracked modifications come before untracked files, and diffs are kept in
+    /// that order until the budget runs out. Files whose diff doesn't fit are
+    /// still listed, just without it; strategies that exclude uncommitted
+    /// changes keep only the file list.
+    pub fn select_uncommitted(
+        &self,
+        changes: UncommittedContext,
+        allocation: &mut BudgetAllocation,
+    ) -> UncommittedContext {
+        let mut files = changes.files;
+        // Stable sort keeps git's order within each group
+        files.sort_by_key(|f| f.untracked);
+
+        for file in &mut files {
+            let Some(diff) = &file.diff else {
+                continue;
+            };
+            let tokens = estimate_tokens(diff);
+            if !self.config.include_uncommitted || allocation.remaining(&self.budget) < tokens {
+                file.diff = None;
+                file.truncated = false;
+                continue;
+            }
+            allocation.uncommitted += tokens;
+        }
+
+        UncommittedContext::new(files)
+    }
+
     // =========================================================================
     // Helper Methods
     // =========================================================================
 
+    fn push_uncommitted(
+        &self,
+        allocation: &mut BudgetAllocation,
+        context_parts: &mut Vec<ContextPart>,
+    ) -> UncommittedContext {
+        let Some(changes) = &self.uncommitted else {
+            return UncommittedContext::default();
+        };
+        let selected = self.select_uncommitted(changes.clone(), allocation);
+        if !selected.is_empty() {
+            context_parts.push(ContextPart {
+                content: selected.prompt.clone(),
+                priority: self.config.weights.uncommitted,
+                source: ContextSource::Uncommitted,
+            });
+        }
+        selected
+    }
+
     fn get_cursor_context(&self, file_path: &str, line: u32) -> Result<String, ContextError> {
         // Read file and extract lines around cursor
         let full_path = Path::new(file_path);
@@ -437,6 +583,7 @@ enum ContextSource {
     Import(String),
     OpenFile(String),
     SearchResult(String),
+    Uncommitted,
 }
 
 /// Context assembly errors
@@ -534,6 +681,101 @@ function authorize(user: User, resource: string): boolean {
         assert!(ctx.summary.total_symbols > 0);
     }
 
+    #[test]
+    fn test_select_open_files_drops_least_relevant() {
+        let (assembler, temp_dir) = create_test_assembler();
+
+        let active = temp_dir.path().join("active.ts");
+        let auth = temp_dir.path().join("auth.ts");
+        let other = temp_dir.path().join("other.ts");
+        fs::write(&active, "const a = 1;\n".repeat(10)).unwrap();
+        fs::write(&auth, "function authenticate(): boolean { return true; }\n").unwrap();
+        fs::write(&other, "const x = 1;\n".repeat(200)).unwrap();
+        let _ = assembler.language_service.index_file("auth.ts");
+
+        let open_files: Vec<String> = [&other, &auth, &active]
+            .iter()
+            .map(|p| p.to_string_lossy().to_string())
+            .collect();
+
+        // Budget fits the active file and auth.ts, but not other.ts
+        let budget = TokenBudget {
+            total: 200,
+            system_reserve: 0,
+            user_reserve: 0,
+            response_reserve: 0,
+        };
+        let assembler = assembler.with_budget(budget);
+        let (selected, allocation) =
+            assembler.select_open_files("authenticate", Some(&open_files[2]), &open_files);
+
+        assert_eq!(selected, vec![open_files[1].clone(), open_files[2].clone()]);
+        assert!(allocation.active_file > 0);
+        assert!(allocation.total() <= 200);
+    }
+
+    #[test]
+    fn test_select_uncommitted_prefers_tracked_changes() {
+        use crate::context_assembly::UncommittedFile;
+
+        let (assembler, _temp) = create_test_assembler();
+        let file = |path: &str, untracked: bool, diff: String| UncommittedFile {
+            path: path.to_string(),
+            status_code: if untracked { "??" } else { ".M" }.to_string(),
+            untracked,
+            diff: Some(diff),
+            truncated: false,
+        };
+        let changes = UncommittedContext::new(vec![
+            file("notes.md", true, "+note\n".repeat(100)),
+            file("src/lib.rs", false, "-old\n+new\n".to_string()),
+        ]);
+
+        let budget = TokenBudget {
+            total: 100,
+            system_reserve: 0,
+            user_reserve: 0,
+            response_reserve: 0,
+        };
+        let assembler = assembler.with_budget(budget);
+        let mut allocation = BudgetAllocation::default();
+        let selected = assembler.select_uncommitted(changes.clone(), &mut allocation);
+
+        assert_eq!(selected.files[0].path, "src/lib.rs");
+        assert!(selected.files[0].diff.is_some());
+        assert!(selected.files[1].diff.is_none());
+        assert!(allocation.uncommitted > 0 && allocation.total() <= 100);
+        let omitted = "### notes.md (new, untracked)\n(diff omitted)";
+        assert!(selected.prompt.contains(omitted));
+
+        let minimal = assembler.with_strategy(ContextStrategy::Minimal);
+        let selected = minimal.select_uncommitted(changes, &mut BudgetAllocation::default());
+        assert!(selected.files.iter().all(|f| f.diff.is_none()));
+    }
+
+    #[test]
+    fn test_assemble_includes_uncommitted() {
+        use crate::context_assembly::UncommittedFile;
+
+        let (assembler, _temp) = create_test_assembler();
+        let changes = UncommittedContext::new(vec![UncommittedFile {
+            path: "src/lib.rs".to_string(),
+            status_code: ".M".to_string(),
+            untracked: false,
+            diff: Some("-old\n+new\n".to_string()),
+            truncated: false,
+        }]);
+
+        let ctx = assembler
+            .with_uncommitted(changes)
+            .assemble_for_query("nothing", &[])
+            .unwrap();
+
+        assert_eq!(ctx.uncommitted.files.len(), 1);
+        assert!(ctx.context.contains("### src/lib.rs (.M)"));
+        assert!(ctx.context.contains("+new"));
+    }
+
     #[test]
     fn test_strategy_configuration() {
         let (assembler, _temp) = create_test_assembler();
@@ -78,6 +78,15 @@ impl TokenBudget {
             response_reserve: total / 10,
         }
     }
+
+    /// Create a budget for a model's advertised context window,
+    /// falling back to the default when the window is unknown
+    pub fn for_context_window(context_window: Option<u32>) -> Self {
+        match context_window {
+            Some(window) if window > 0 => Self::custom(window as usize),
+            _ => Self::default(),
+        }
+    }
 }
 
 /// Allocation of budget across context types
@@ -95,6 +104,8 @@ pub struct BudgetAllocation {
     pub imports: usize,
     /// Tokens used for open files context
     pub open_files: usize,
+    /// Tokens used for uncommitted change diffs
+    pub uncommitted: usize,
 }
 
 impl BudgetAllocation {
@@ -106,6 +117,7 @@ impl BudgetAllocation {
             + self.related_types
             + self.imports
             + self.open_files
+            + self.uncommitted
     }
 
     /// Check if under a given limit
@@ -179,4 +191,14 @@ mod tests {
         let truncated = truncate_to_tokens(text, 4); // ~16 chars
         assert!(truncated.len() <= 20);
     }
+
+    #[test]
+    fn test_budget_for_context_window() {
+        let budget = TokenBudget::for_context_window(Some(32_000));
+        assert_eq!(budget.total, 32_000);
+        assert!(budget.available_for_context() < 32_000);
+
+        assert_eq!(TokenBudget::for_context_window(None).total, 128_000);
+        assert_eq!(TokenBudget::for_context_window(Some(0)).total, 128_000);
+    }
 }
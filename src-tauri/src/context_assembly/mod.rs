@@ -8,11 +8,14 @@
 //! - Symbol-based: Include related definitions, usages, and types
 //! - File-based: Include relevant portions of open files
 //! - Semantic: Use symbol relationships for smart selection
+//! - Uncommitted: Include the user's work-in-progress diffs against HEAD
 
 mod assembler;
 mod budget;
 mod strategy;
+mod uncommitted;
 
 pub use assembler::{AssembledContext, ContextAssembler};
-pub use budget::{BudgetAllocation, TokenBudget};
+pub use budget::{estimate_tokens, BudgetAllocation, TokenBudget};
 pub use strategy::{ContextStrategy, StrategyConfig};
+pub use uncommitted::{cap_diff, UncommittedContext, UncommittedFile};
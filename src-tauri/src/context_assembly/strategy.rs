@@ -39,6 +39,8 @@ pub struct StrategyConfig {
     pub include_types: bool,
     /// Include imports from the current file
     pub include_imports: bool,
+    /// Include diffs of uncommitted changes (the user's work in progress)
+    pub include_uncommitted: bool,
     /// Maximum files to include from open files
     pub max_open_files: usize,
     /// Priority weights for different context types
@@ -60,6 +62,8 @@ pub struct ContextWeights {
     pub imports: f32,
     /// Weight for open files
     pub open_files: f32,
+    /// Weight for uncommitted change diffs
+    pub uncommitted: f32,
 }
 
 impl Default for ContextWeights {
@@ -71,6 +75,7 @@ impl Default for ContextWeights {
             types: 0.6,
             imports: 0.3,
             open_files: 0.4,
+            uncommitted: 0.9,
         }
     }
 }
@@ -84,6 +89,7 @@ impl StrategyConfig {
             include_references: false,
             include_types: true,
             include_imports: true,
+            include_uncommitted: true,
             max_open_files: 0,
             weights: ContextWeights {
                 active_file: 1.0,
@@ -92,6 +98,7 @@ impl StrategyConfig {
                 types: 0.7,
                 imports: 0.3,
                 open_files: 0.0,
+                uncommitted: 0.9,
             },
         }
     }
@@ -104,6 +111,7 @@ impl StrategyConfig {
             include_references: true,
             include_types: true,
             include_imports: true,
+            include_uncommitted: true,
             max_open_files: 3,
             weights: ContextWeights::default(),
         }
@@ -117,6 +125,7 @@ impl StrategyConfig {
             include_references: true,
             include_types: true,
             include_imports: true,
+            include_uncommitted: true,
             max_open_files: 10,
             weights: ContextWeights {
                 active_file: 1.0,
@@ -125,6 +134,7 @@ impl StrategyConfig {
                 types: 0.8,
                 imports: 0.5,
                 open_files: 0.7,
+                uncommitted: 0.9,
             },
         }
     }
@@ -137,6 +147,7 @@ impl StrategyConfig {
             include_references: false,
             include_types: false,
             include_imports: false,
+            include_uncommitted: false,
             max_open_files: 0,
             weights: ContextWeights {
                 active_file: 1.0,
@@ -145,6 +156,7 @@ impl StrategyConfig {
                 types: 0.0,
                 imports: 0.0,
                 open_files: 0.0,
+                uncommitted: 0.0,
             },
         }
     }
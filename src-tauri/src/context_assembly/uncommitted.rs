@@ -0,0 +1,106 @@
+//! Uncommitted changes context
+//!
+//! The user's work in progress (files changed since HEAD and their diffs),
+//! formatted for the prompt so the model can focus on it.
+
+use serde::{Deserialize, Serialize};
+
+/// Largest diff kept for a single file; longer diffs are cut at a line boundary
+pub const MAX_FILE_DIFF_BYTES: usize = 8 * 1024;
+
+/// One changed file in the working tree
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UncommittedFile {
+    /// Path relative to the workspace
+    pub path: String,
+    /// Two-letter git status code (e.g. ".M", "A.", "??")
+    pub status_code: String,
+    pub untracked: bool,
+    /// Unified diff against HEAD; None when it was skipped (binary, or over budget)
+    pub diff: Option<String>,
+    /// The diff was cut at `MAX_FILE_DIFF_BYTES`
+    pub truncated: bool,
+}
+
+/// Changed files plus the prompt text describing them
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UncommittedContext {
+    pub files: Vec<UncommittedFile>,
+    pub prompt: String,
+}
+
+impl UncommittedContext {
+    pub fn new(files: Vec<UncommittedFile>) -> Self {
+        let prompt = format_prompt(&files);
+        Self { files, prompt }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.files.is_empty()
+    }
+}
+
+/// Cap a diff at `MAX_FILE_DIFF_BYTES`, returning whether it was cut
+pub fn cap_diff(diff: String) -> (String, bool) {
+    if diff.len() <= MAX_FILE_DIFF_BYTES {
+        return (diff, false);
+    }
+    let mut end = MAX_FILE_DIFF_BYTES;
+    while !diff.is_char_boundary(end) {
+        end -= 1;
+    }
+    let end = diff[..end].rfind('\n').map_or(end, |i| i + 1);
+    (diff[..end].to_string(), true)
+}
+
+fn format_prompt(files: &[UncommittedFile]) -> String {
+    if files.is_empty() {
+        return String::new();
+    }
+
+    let mut prompt = String::from("Uncommitted changes (work in progress):\n");
+    for file in files {
+        let label = if file.untracked {
+            "new, untracked"
+        } else {
+            file.status_code.as_str()
+        };
+        prompt.push_str(&format!("\n### {} ({})\n", file.path, label));
+        match &file.diff {
+            Some(diff) => {
+                prompt.push_str("```diff\n");
+                prompt.push_str(diff);
+                if !diff.ends_with('\n') {
+                    prompt.push('\n');
+                }
+                if file.truncated {
+                    prompt.push_str("... (diff truncated)\n");
+                }
+                prompt.push_str("```\n");
+            }
+            None => prompt.push_str("(diff omitted)\n"),
+        }
+    }
+    prompt
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cap_diff_cuts_at_line_boundary() {
+        let line = "+".to_string() + &"x".repeat(99) + "\n";
+        let diff = line.repeat(200);
+        let (capped, truncated) = cap_diff(diff);
+        assert!(truncated);
+        assert!(capped.len() <= MAX_FILE_DIFF_BYTES);
+        assert!(capped.ends_with('\n'));
+
+        let (small, truncated) = cap_diff("+a\n".to_string());
+        assert_eq!(small, "+a\n");
+        assert!(!truncated);
+    }
+}
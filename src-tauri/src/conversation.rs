@@ -1,3 +1,4 @@
+use crate::context_assembly::estimate_tokens;
 use crate::protocol::{ChatMessage, ChatRole, OpenAiMessage, ToolCall};
 
 use crate::conversation_store::{generate_title, ConversationMetadata, StoredConversation};
@@ -22,6 +23,7 @@ impl ConversationHistory {
                 model_id: "claude-sonnet".to_string(), // Default
                 message_count: 0,
                 session_id: None,
+                forked_from: None,
             },
         }
     }
@@ -60,14 +62,15 @@ impl ConversationHistory {
         &mut self,
         results: &[(ToolCall, crate::tools::ToolResult)],
     ) -> Option<ChatMessage> {
-        self.update_tool_call_status_with_truncation(results, false)
+        self.update_tool_call_status_with_truncation(results, None)
     }
 
     /// RFC: Large Tool Result Handling - Update tool call status with optional truncation
+    /// `max_tool_result_bytes` of `None` stores results untruncated.
     pub fn update_tool_call_status_with_truncation(
         &mut self,
         results: &[(ToolCall, crate::tools::ToolResult)],
-        truncate: bool,
+        max_tool_result_bytes: Option<usize>,
     ) -> Option<ChatMessage> {
         // Update tool call status in assistant messages when results arrive
         let mut updated_assistant: Option<ChatMessage> = None;
@@ -86,10 +89,9 @@ impl ConversationHistory {
                                     "error".to_string()
                                 });
                                 // RFC: Large Tool Result Handling - truncate in local mode
-                                tc.result = Some(if truncate {
-                                    result.to_tool_content_truncated()
-                                } else {
-                                    result.to_tool_content()
+                                tc.result = Some(match max_tool_result_bytes {
+                                    Some(max_bytes) => result.to_tool_content_truncated(max_bytes),
+                                    None => result.to_tool_content(),
                                 });
                                 break;
                             }
@@ -236,6 +238,162 @@ impl ConversationHistory {
         self.metadata.updated_at = Utc::now();
     }
 
+    /// Rough token estimate for the whole conversation, including reasoning
+    /// and tool results stored on assistant tool calls
+    pub fn estimate_tokens(&self) -> usize {
+        self.messages.iter().map(estimate_message_tokens).sum()
+    }
+
+    /// Indices of the messages that start each turn (every user message)
+    fn turn_starts(&self) -> Vec<usize> {
+        self.messages
+            .iter()
+            .enumerate()
+            .filter(|(_, m)| m.role == ChatRole::User)
+            .map(|(i, _)| i)
+            .collect()
+    }
+
+    /// Number of turns (user messages) in the conversation
+    pub fn turn_count(&self) -> usize {
+        self.turn_starts().len()
+    }
+
+    /// Number of messages covered by the oldest `turns` turns
+    pub fn oldest_turns_len(&self, turns: usize) -> usize {
+        let starts = self.turn_starts();
+        match starts.get(turns) {
+            Some(&end) => end,
+            None => self.messages.len(),
+        }
+    }
+
+    /// The first `end` messages as a plain "role: content" transcript
+    pub fn transcript(&self, end: usize) -> String {
+        self.messages
+            .iter()
+            .take(end)
+            .map(transcript_line)
+            .collect::<Vec<_>>()
+            .join("\n\n")
+    }
+
+    /// The last user message, prefixed with a transcript of the messages before it
+    /// for a server session that has never seen them. Only the most recent
+    /// messages that fit in `max_tokens` are included.
+    pub fn prompt_with_history(&self, max_tokens: usize) -> Option<String> {
+        let start = *self.turn_starts().last()?;
+        let prompt = &self.messages[start].content;
+
+        let mut lines = Vec::new();
+        let mut used = 0;
+        for message in self.messages[..start].iter().rev() {
+            let line = transcript_line(message);
+            used += estimate_tokens(&line);
+            if used > max_tokens {
+                break;
+            }
+            lines.push(line);
+        }
+        if lines.is_empty() {
+            return Some(prompt.clone());
+        }
+        lines.reverse();
+
+        let omitted = start - lines.len();
+        let mut transcript = lines.join("\n\n");
+        if omitted > 0 {
+            transcript = format!("[{} earlier messages omitted]\n\n{}", omitted, transcript);
+        }
+        Some(format!(
+            "[Earlier conversation, for context]\n\n{}\n\n[Current message]\n\n{}",
+            transcript, prompt
+        ))
+    }
+
+    /// Remove the last turn (the final user message and everything after it),
+    /// returning the user message so the prompt can be retried
+    pub fn pop_last_turn(&mut self) -> Option<ChatMessage> {
+        let start = *self.turn_starts().last()?;
+        let user_message = self.messages.drain(start..).next();
+        self.touch();
+        user_message
+    }
+
+    /// Remove the message at `index` and everything after it, returning the
+    /// removed messages (oldest first)
+    pub fn truncate_from(&mut self, index: usize) -> Vec<ChatMessage> {
+        let index = index.min(self.messages.len());
+        let removed = self.messages.drain(index..).collect();
+        self.touch();
+        removed
+    }
+
+    /// Replace the oldest `turns` turns with a single system summary message.
+    /// Returns the number of messages removed.
+    pub fn replace_oldest_turns(&mut self, turns: usize, summary: String) -> usize {
+        let end = self.oldest_turns_len(turns);
+        if end == 0 {
+            return 0;
+        }
+        self.messages.drain(..end);
+        self.messages.insert(
+            0,
+            ChatMessage::new(
+                ChatRole::System,
+                format!("Summary of earlier conversation:\n{}", summary),
+            ),
+        );
+        self.touch();
+        end
+    }
+
+    /// Replace tool result bodies larger than `max_bytes` with a short placeholder,
+    /// keeping the call and its status. Returns the number of results stripped.
+    pub fn drop_tool_results(&mut self, max_bytes: usize) -> usize {
+        let mut stripped = 0;
+        for msg in self.messages.iter_mut() {
+            if msg.role == ChatRole::Tool && msg.content.len() > max_bytes {
+                msg.content = dropped_tool_result(msg.content.len());
+                stripped += 1;
+            }
+            if let Some(ref mut tool_calls) = msg.tool_calls {
+                for tc in tool_calls.iter_mut() {
+                    if let Some(len) = tc.result.as_ref().map(|r| r.len()) {
+                        if len > max_bytes {
+                            tc.result = Some(dropped_tool_result(len));
+                            stripped += 1;
+                        }
+                    }
+                }
+            }
+        }
+        if stripped > 0 {
+            self.touch();
+        }
+        stripped
+    }
+
+    /// Remove the oldest turns until the estimated token count fits in `max_tokens`.
+    /// The most recent turn is always kept. Returns the number of messages removed.
+    pub fn truncate_to_tokens(&mut self, max_tokens: usize) -> usize {
+        let mut removed = 0;
+        while self.estimate_tokens() > max_tokens && self.turn_starts().len() > 1 {
+            let end = self.oldest_turns_len(1).max(1);
+            self.messages.drain(..end);
+            removed += end;
+        }
+        if removed > 0 {
+            self.touch();
+        }
+        removed
+    }
+
+    fn touch(&mut self) {
+        self.metadata.message_count = self.messages.len();
+        self.metadata.updated_at = Utc::now();
+    }
+
     /// Convert to StoredConversation for persistence
     pub fn to_stored(&self) -> StoredConversation {
         StoredConversation {
@@ -252,3 +410,154 @@ impl ConversationHistory {
         }
     }
 }
+
+fn estimate_message_tokens(msg: &ChatMessage) -> usize {
+    let mut tokens = estimate_tokens(&msg.content);
+    if let Some(ref reasoning) = msg.reasoning {
+        tokens += estimate_tokens(reasoning);
+    }
+    if let Some(ref tool_calls) = msg.tool_calls {
+        for tc in tool_calls {
+            tokens += estimate_tokens(&tc.function.arguments);
+            if let Some(ref result) = tc.result {
+                tokens += estimate_tokens(result);
+            }
+        }
+    }
+    tokens
+}
+
+/// A message as one "role: content" transcript entry
+fn transcript_line(msg: &ChatMessage) -> String {
+    let role = match msg.role {
+        ChatRole::User => "user",
+        ChatRole::Assistant => "assistant",
+        ChatRole::System => "system",
+        ChatRole::Tool => "tool",
+    };
+    format!("{}: {}", role, msg.content)
+}
+
+fn dropped_tool_result(len: usize) -> String {
+    format!("[tool result removed to recover context ({} bytes)]", len)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn conversation_with_turns(turns: usize, body: &str) -> ConversationHistory {
+        let mut conversation = ConversationHistory::new();
+        for i in 0..turns {
+            conversation.push(ChatMessage::new(ChatRole::User, format!("question {}", i)));
+            conversation.push(ChatMessage::new(ChatRole::Assistant, body.to_string()));
+        }
+        conversation
+    }
+
+    #[test]
+    fn test_replace_oldest_turns_with_summary() {
+        let mut conversation = conversation_with_turns(3, "answer");
+        let removed = conversation.replace_oldest_turns(2, "earlier stuff".to_string());
+
+        assert_eq!(removed, 4);
+        assert_eq!(conversation.len(), 3);
+        assert_eq!(conversation.get(0).unwrap().role, ChatRole::System);
+        assert!(conversation.get(0).unwrap().content.contains("earlier stuff"));
+        assert_eq!(conversation.get(1).unwrap().content, "question 2");
+    }
+
+    #[test]
+    fn test_drop_tool_results_keeps_small_bodies() {
+        let mut conversation = conversation_with_turns(1, "answer");
+        let mut big = ChatMessage::new(ChatRole::Tool, "x".repeat(2000));
+        big.tool_call_id = Some("call_1".to_string());
+        conversation.push(big);
+        conversation.push(ChatMessage::new(ChatRole::Tool, "ok".to_string()));
+
+        assert_eq!(conversation.drop_tool_results(512), 1);
+        assert!(conversation.get(2).unwrap().content.contains("2000 bytes"));
+        assert_eq!(conversation.get(2).unwrap().tool_call_id.as_deref(), Some("call_1"));
+        assert_eq!(conversation.get(3).unwrap().content, "ok");
+    }
+
+    #[test]
+    fn test_truncate_to_tokens_keeps_last_turn() {
+        let mut conversation = conversation_with_turns(4, &"y".repeat(400));
+        assert!(conversation.estimate_tokens() > 250);
+
+        conversation.truncate_to_tokens(250);
+        assert!(conversation.estimate_tokens() <= 250);
+        assert_eq!(conversation.get(0).unwrap().content, "question 2");
+
+        // Never drops the most recent turn, even if it alone is over budget
+        conversation.truncate_to_tokens(1);
+        assert_eq!(conversation.len(), 2);
+        assert_eq!(conversation.get(0).unwrap().content, "question 3");
+    }
+
+    #[test]
+    fn test_pop_last_turn_returns_prompt() {
+        let mut conversation = conversation_with_turns(2, "answer");
+        let popped = conversation.pop_last_turn().unwrap();
+        assert_eq!(popped.content, "question 1");
+        assert_eq!(conversation.len(), 2);
+    }
+
+    #[test]
+    fn test_truncate_from_removes_later_turns() {
+        let mut conversation = conversation_with_turns(3, "answer");
+        let removed = conversation.truncate_from(2);
+        assert_eq!(removed.len(), 4);
+        assert_eq!(removed[0].content, "question 1");
+        assert_eq!(conversation.len(), 2);
+        assert_eq!(conversation.metadata.message_count, 2);
+
+        assert!(conversation.truncate_from(10).is_empty());
+    }
+
+    #[test]
+    fn test_prompt_with_history_carries_earlier_turns() {
+        let mut conversation = conversation_with_turns(2, "answer");
+        let prompt = conversation.pop_last_turn().unwrap();
+        conversation.push(prompt);
+
+        let replayed = conversation.prompt_with_history(1000).unwrap();
+        assert!(replayed.starts_with(
+            "[Earlier conversation, for context]\n\nuser: question 0\n\nassistant: answer"
+        ));
+        assert!(replayed.ends_with("[Current message]\n\nquestion 1"));
+
+        let mut first = ConversationHistory::new();
+        first.push(ChatMessage::new(ChatRole::User, "question 0".to_string()));
+        assert_eq!(first.prompt_with_history(1000).unwrap(), "question 0");
+        assert_eq!(ConversationHistory::new().prompt_with_history(1000), None);
+    }
+
+    #[test]
+    fn test_prompt_with_history_keeps_recent_messages_within_budget() {
+        let mut conversation = conversation_with_turns(4, &"y".repeat(400));
+        conversation.push(ChatMessage::new(ChatRole::User, "latest".to_string()));
+
+        // Room for the last turn (~107 tokens) but not the one before it
+        let replayed = conversation.prompt_with_history(150).unwrap();
+        assert!(replayed.contains("[6 earlier messages omitted]\n\nuser: question 3"));
+        assert!(!replayed.contains("question 2"));
+        assert!(replayed.ends_with("[Current message]\n\nlatest"));
+
+        assert_eq!(conversation.prompt_with_history(0).unwrap(), "latest");
+    }
+
+    #[test]
+    fn test_edited_prompt_carries_turns_before_it() {
+        let mut conversation = conversation_with_turns(3, "answer");
+        let mut edited = conversation.truncate_from(2).into_iter().next().unwrap();
+        edited.content = "question 1, rephrased".to_string();
+        conversation.push(edited);
+
+        let replayed = conversation.prompt_with_history(1000).unwrap();
+        assert!(replayed.contains("user: question 0\n\nassistant: answer"));
+        assert!(replayed.ends_with("[Current message]\n\nquestion 1, rephrased"));
+        assert!(!replayed.contains("question 2"));
+    }
+}
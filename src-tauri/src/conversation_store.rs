@@ -17,6 +17,9 @@ pub struct ConversationMetadata {
     pub message_count: usize,
     #[serde(default)]
     pub session_id: Option<String>,
+    /// ID of the conversation this one was forked from
+    #[serde(default)]
+    pub forked_from: Option<String>,
 }
 
 /// A complete conversation with metadata and messages
@@ -37,6 +40,16 @@ pub struct SerializableChatMessage {
     pub reasoning: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub tool_call_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tool_calls: Option<Vec<crate::protocol::ToolCall>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub content_before_tools: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub content_after_tools: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub metrics: Option<crate::protocol::MessageMetrics>,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub files_expanded: bool,
 }
 
 impl From<&ChatMessage> for SerializableChatMessage {
@@ -52,6 +65,11 @@ impl From<&ChatMessage> for SerializableChatMessage {
             images: msg.images.clone(),
             reasoning: msg.reasoning.clone(),
             tool_call_id: msg.tool_call_id.clone(),
+            tool_calls: msg.tool_calls.clone(),
+            content_before_tools: msg.content_before_tools.clone(),
+            content_after_tools: msg.content_after_tools.clone(),
+            metrics: msg.metrics.clone(),
+            files_expanded: msg.files_expanded,
         }
     }
 }
@@ -71,10 +89,268 @@ impl From<SerializableChatMessage> for ChatMessage {
         chat_msg.images = msg.images;
         chat_msg.reasoning = msg.reasoning;
         chat_msg.tool_call_id = msg.tool_call_id;
+        chat_msg.tool_calls = msg.tool_calls;
+        chat_msg.content_before_tools = msg.content_before_tools;
+        chat_msg.content_after_tools = msg.content_after_tools;
+        chat_msg.metrics = msg.metrics;
+        chat_msg.files_expanded = msg.files_expanded;
         chat_msg
     }
 }
 
+impl StoredConversation {
+    /// Render the conversation as Markdown for sharing or archiving. Tool calls
+    /// appear where they happened in the assistant's reply, each with its result;
+    /// reasoning and results are collapsible.
+    pub fn to_markdown(&self) -> String {
+        let mut out = format!(
+            "# {}\n\n_Model: {} · {}_\n",
+            self.metadata.title,
+            self.metadata.model_id,
+            self.metadata.created_at.format("%Y-%m-%d %H:%M UTC")
+        );
+
+        // Results rendered under their call aren't repeated as separate messages
+        let mut rendered_results: Vec<&str> = Vec::new();
+        for msg in &self.messages {
+            match msg.role.as_str() {
+                "tool" => {
+                    if msg
+                        .tool_call_id
+                        .as_deref()
+                        .is_some_and(|id| rendered_results.contains(&id))
+                    {
+                        continue;
+                    }
+                    out.push_str("\n### Tool result\n\n");
+                    push_collapsible_result(&mut out, &msg.content);
+                }
+                "assistant" => {
+                    out.push_str("\n## Assistant\n");
+                    if let Some(reasoning) =
+                        msg.reasoning.as_deref().filter(|r| !r.trim().is_empty())
+                    {
+                        out.push_str(&format!(
+                            "\n<details>\n<summary>Reasoning</summary>\n\n{}\n\n</details>\n",
+                            reasoning.trim()
+                        ));
+                    }
+
+                    let calls: Vec<&crate::protocol::ToolCall> = msg
+                        .tool_calls
+                        .iter()
+                        .flatten()
+                        .filter(|call| call.function.name != "todo_write")
+                        .collect();
+                    // Same split as the chat view: explicit fields when present, otherwise
+                    // the content all came before the tools
+                    let (before, after) = if msg.content_before_tools.is_some()
+                        || msg.content_after_tools.is_some()
+                    {
+                        let before = msg.content_before_tools.clone().unwrap_or_default();
+                        let after = msg.content_after_tools.clone().unwrap_or_else(|| {
+                            msg.content
+                                .strip_prefix(before.as_str())
+                                .unwrap_or_default()
+                                .to_string()
+                        });
+                        (before, after)
+                    } else {
+                        (msg.content.clone(), String::new())
+                    };
+
+                    push_text(&mut out, &before);
+                    for call in calls {
+                        out.push_str(&format!(
+                            "\n> **Tool:** `{}`{}\n",
+                            call.function.name,
+                            tool_arguments_summary(&call.function.arguments)
+                        ));
+                        if let Some(status) = call.status.as_deref() {
+                            out.push_str(&format!("> _{}_\n", status));
+                        }
+                        let result = call.result.as_deref().or_else(|| {
+                            self.messages
+                                .iter()
+                                .find(|m| {
+                                    m.role == "tool"
+                                        && m.tool_call_id.as_deref() == Some(call.id.as_str())
+                                })
+                                .map(|m| m.content.as_str())
+                        });
+                        if let Some(result) = result {
+                            out.push('\n');
+                            push_collapsible_result(&mut out, result);
+                            rendered_results.push(&call.id);
+                        }
+                    }
+                    push_text(&mut out, &after);
+                }
+                role => {
+                    let heading = if role == "user" { "User" } else { "System" };
+                    out.push_str(&format!("\n## {}\n", heading));
+                    push_text(&mut out, &msg.content);
+                }
+            }
+        }
+        out
+    }
+}
+
+fn push_text(out: &mut String, text: &str) {
+    let text = text.trim();
+    if !text.is_empty() {
+        out.push_str(&format!("\n{}\n", text));
+    }
+}
+
+fn push_collapsible_result(out: &mut String, result: &str) {
+    let fence = crate::utils::code_fence(result);
+    out.push_str(&format!(
+        "<details>\n<summary>Result</summary>\n\n{}\n{}\n{}\n\n</details>\n",
+        fence,
+        result.trim_end(),
+        fence
+    ));
+}
+
+/// Tool arguments on one line, shortened for the call summary
+fn tool_arguments_summary(arguments: &str) -> String {
+    const MAX_CHARS: usize = 120;
+    let compact = serde_json::from_str::<serde_json::Value>(arguments)
+        .map(|v| v.to_string())
+        .unwrap_or_else(|_| arguments.split_whitespace().collect::<Vec<_>>().join(" "));
+    if compact.is_empty() || compact == "{}" {
+        return String::new();
+    }
+    let shortened = if compact.chars().count() > MAX_CHARS {
+        format!("{}…", compact.chars().take(MAX_CHARS).collect::<String>())
+    } else {
+        compact
+    };
+    if shortened.contains('`') {
+        format!(" `` {} ``", shortened)
+    } else {
+        format!(" `{}`", shortened)
+    }
+}
+
+/// Message role for a `## ...` transcript header
+fn transcript_role(header: &str) -> Option<&'static str> {
+    match header.trim().trim_end_matches(':').to_lowercase().as_str() {
+        "user" | "human" | "you" => Some("user"),
+        "assistant" | "ai" => Some("assistant"),
+        "system" => Some("system"),
+        _ => None,
+    }
+}
+
+/// Parse a Markdown transcript into its title, model and messages. Role headers
+/// inside code fences are treated as content.
+fn parse_markdown_transcript(
+    content: &str,
+) -> Result<(Option<String>, Option<String>, Vec<SerializableChatMessage>), String> {
+    let mut title = None;
+    let mut model_id = None;
+    let mut messages = Vec::new();
+    let mut current: Option<(&'static str, Vec<&str>)> = None;
+    // Length of the open fence, and the line that opened it
+    let mut fence: Option<(usize, usize)> = None;
+
+    for (i, line) in content.lines().enumerate() {
+        let line_number = i + 1;
+        let trimmed = line.trim_start();
+
+        if trimmed.starts_with("```") {
+            let ticks = trimmed.chars().take_while(|c| *c == '`').count();
+            match fence {
+                Some((open, _)) if ticks >= open && trimmed[ticks..].trim().is_empty() => {
+                    fence = None
+                }
+                Some(_) => {}
+                None => fence = Some((ticks, line_number)),
+            }
+        } else if fence.is_none() {
+            if let Some(role) = trimmed.strip_prefix("## ").and_then(transcript_role) {
+                if let Some((role, lines)) = current.take() {
+                    messages.push(transcript_message(role, &lines));
+                }
+                current = Some((role, Vec::new()));
+                continue;
+            }
+            if current.is_none() {
+                if let Some(text) = trimmed.strip_prefix("# ").filter(|_| title.is_none()) {
+                    title = Some(text.trim().to_string());
+                } else if let Some(meta) = trimmed.strip_prefix("_Model: ") {
+                    let model = meta.split(" · ").next().unwrap_or(meta);
+                    model_id = Some(model.trim_end_matches('_').trim().to_string());
+                } else if !trimmed.is_empty() {
+                    return Err(format!(
+                        "line {}: expected a `## User` or `## Assistant` header before message text",
+                        line_number
+                    ));
+                }
+                continue;
+            }
+        }
+
+        if let Some((_, lines)) = current.as_mut() {
+            lines.push(line);
+        } else if !trimmed.is_empty() {
+            return Err(format!(
+                "line {}: code block before the first `## User` or `## Assistant` header",
+                line_number
+            ));
+        }
+    }
+
+    if let Some((_, opened_at)) = fence {
+        return Err(format!("line {}: code fence is never closed", opened_at));
+    }
+    if let Some((role, lines)) = current {
+        messages.push(transcript_message(role, &lines));
+    }
+    if messages.is_empty() {
+        return Err("no `## User` or `## Assistant` sections found".to_string());
+    }
+    Ok((title, model_id, messages))
+}
+
+/// Build a message from a transcript section, lifting out the collapsible
+/// reasoning block that `to_markdown` writes for assistant messages
+fn transcript_message(role: &str, lines: &[&str]) -> SerializableChatMessage {
+    let text = lines.join("\n");
+    let mut content = text.trim();
+    let mut reasoning = None;
+    if let Some(rest) = content.strip_prefix("<details>\n<summary>Reasoning</summary>") {
+        if let Some((inner, after)) = rest.split_once("</details>") {
+            reasoning = Some(inner.trim().to_string());
+            content = after.trim();
+        }
+    }
+
+    SerializableChatMessage {
+        role: role.to_string(),
+        content: content.to_string(),
+        images: None,
+        reasoning,
+        tool_call_id: None,
+        tool_calls: None,
+        content_before_tools: None,
+        content_after_tools: None,
+        metrics: None,
+        // A transcript records prompts as they were sent
+        files_expanded: role == "user",
+    }
+}
+
+/// One page of a listing plus the total number of items available
+#[derive(Clone, Debug, Serialize)]
+pub struct Page<T> {
+    pub items: Vec<T>,
+    pub total: usize,
+}
+
 /// Index of all conversations
 #[derive(Clone, Debug, Serialize, Deserialize)]
 struct ConversationIndex {
@@ -123,6 +399,22 @@ impl ConversationStore {
         conversations
     }
 
+    /// List a page of conversations, most recent first, reading only index metadata
+    pub fn list_conversations_page(
+        &self,
+        offset: usize,
+        limit: Option<usize>,
+    ) -> Page<ConversationMetadata> {
+        let conversations = self.list_conversations();
+        let total = conversations.len();
+        let items = conversations
+            .into_iter()
+            .skip(offset)
+            .take(limit.unwrap_or(usize::MAX))
+            .collect();
+        Page { items, total }
+    }
+
     /// Load a conversation by ID
     pub fn load_conversation(&self, id: &str) -> Result<StoredConversation, String> {
         let path = self.storage_path.join(format!("{}.json", id));
@@ -170,6 +462,7 @@ impl ConversationStore {
             model_id,
             message_count: 0,
             session_id: None,
+            forked_from: None,
         };
 
         self.index.conversations.push(metadata.clone());
@@ -181,6 +474,106 @@ impl ConversationStore {
         metadata
     }
 
+    /// Fork a conversation, copying messages `0..=up_to_message_index` into a new one.
+    /// The fork starts a fresh zcoderd session since its history diverges from the original.
+    pub fn fork_conversation(
+        &mut self,
+        from_id: &str,
+        up_to_message_index: usize,
+    ) -> Result<ConversationMetadata, String> {
+        let source = self.load_conversation(from_id)?;
+        if up_to_message_index >= source.messages.len() {
+            return Err(format!(
+                "Message index {} out of range for conversation {} ({} messages)",
+                up_to_message_index,
+                from_id,
+                source.messages.len()
+            ));
+        }
+
+        let messages: Vec<SerializableChatMessage> = source
+            .messages
+            .into_iter()
+            .take(up_to_message_index + 1)
+            .collect();
+        let now = Utc::now();
+        let metadata = ConversationMetadata {
+            id: Uuid::new_v4().to_string(),
+            title: format!("{} (fork)", source.metadata.title),
+            created_at: now,
+            updated_at: now,
+            model_id: source.metadata.model_id,
+            message_count: messages.len(),
+            session_id: None,
+            forked_from: Some(from_id.to_string()),
+        };
+
+        self.save_conversation(&StoredConversation {
+            metadata: metadata.clone(),
+            messages,
+        })?;
+
+        Ok(metadata)
+    }
+
+    /// Import a transcript as a new conversation. `format` is "json" (this app's
+    /// stored conversation schema) or "markdown" (`## User` / `## Assistant`
+    /// sections, as written by `StoredConversation::to_markdown`).
+    pub fn import_conversation(
+        &mut self,
+        format: &str,
+        content: &str,
+    ) -> Result<ConversationMetadata, String> {
+        let now = Utc::now();
+        let (title, model_id, created_at, messages) = match format {
+            "json" => {
+                let source: StoredConversation = serde_json::from_str(content)
+                    .map_err(|e| format!("Invalid JSON transcript: {}", e))?;
+                for (i, msg) in source.messages.iter().enumerate() {
+                    if !matches!(msg.role.as_str(), "user" | "assistant" | "system" | "tool") {
+                        return Err(format!("message {}: unknown role '{}'", i + 1, msg.role));
+                    }
+                }
+                (
+                    Some(source.metadata.title),
+                    Some(source.metadata.model_id),
+                    source.metadata.created_at,
+                    source.messages,
+                )
+            }
+            "markdown" => {
+                let (title, model_id, messages) = parse_markdown_transcript(content)?;
+                (title, model_id, now, messages)
+            }
+            other => return Err(format!("Unsupported transcript format: {}", other)),
+        };
+
+        let title = title.unwrap_or_else(|| {
+            messages
+                .iter()
+                .find(|m| m.role == "user")
+                .map(|m| generate_title(&m.content))
+                .unwrap_or_else(|| "Imported conversation".to_string())
+        });
+        let metadata = ConversationMetadata {
+            id: Uuid::new_v4().to_string(),
+            title,
+            created_at,
+            updated_at: now,
+            model_id: model_id.unwrap_or_else(|| "imported".to_string()),
+            message_count: messages.len(),
+            session_id: None,
+            forked_from: None,
+        };
+
+        self.save_conversation(&StoredConversation {
+            metadata: metadata.clone(),
+            messages,
+        })?;
+
+        Ok(metadata)
+    }
+
     /// Delete a conversation
     pub fn delete_conversation(&mut self, id: &str) -> Result<(), String> {
         // Delete file
@@ -273,4 +666,161 @@ mod tests {
         assert_eq!(generate_title("/fix the bug"), "Fix the bug");
         assert_eq!(generate_title("/help"), "Help");
     }
+
+    #[test]
+    fn test_fork_conversation() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let mut store = ConversationStore::new(dir.path().to_path_buf()).unwrap();
+
+        let mut metadata = store.create_new_conversation("model".to_string());
+        metadata.session_id = Some("session-1".to_string());
+        let messages = ["first", "answer", "second", "answer"]
+            .iter()
+            .enumerate()
+            .map(|(i, content)| {
+                let role = if i % 2 == 0 { ChatRole::User } else { ChatRole::Assistant };
+                SerializableChatMessage::from(&ChatMessage::new(role, content.to_string()))
+            })
+            .collect();
+        store
+            .save_conversation(&StoredConversation {
+                metadata: metadata.clone(),
+                messages,
+            })
+            .unwrap();
+
+        let fork = store.fork_conversation(&metadata.id, 1).unwrap();
+        assert_ne!(fork.id, metadata.id);
+        assert_eq!(fork.forked_from.as_deref(), Some(metadata.id.as_str()));
+        assert_eq!(fork.session_id, None);
+
+        let stored = store.load_conversation(&fork.id).unwrap();
+        assert_eq!(stored.messages.len(), 2);
+        assert_eq!(stored.messages[1].content, "answer");
+        assert_eq!(store.load_conversation(&metadata.id).unwrap().messages.len(), 4);
+
+        assert!(store.fork_conversation(&metadata.id, 4).is_err());
+    }
+
+    #[test]
+    fn test_to_markdown() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let mut store = ConversationStore::new(dir.path().to_path_buf()).unwrap();
+        let mut metadata = store.create_new_conversation("model-x".to_string());
+        metadata.title = "Fix the bug".to_string();
+
+        let mut assistant = ChatMessage::new(ChatRole::Assistant, String::new());
+        assistant.reasoning = Some("Need to look at the file".to_string());
+        assistant.content = "Let me check.The bug is fixed.".to_string();
+        assistant.content_before_tools = Some("Let me check.".to_string());
+        assistant.tool_calls = Some(vec![crate::protocol::ToolCall {
+            id: "call-1".to_string(),
+            typ: "function".to_string(),
+            function: crate::protocol::ToolFunction {
+                name: "read_file".to_string(),
+                arguments: r#"{"path": "src/lib.rs"}"#.to_string(),
+            },
+            status: None,
+            result: None,
+        }]);
+        let mut tool = ChatMessage::new(ChatRole::Tool, "fn main() {}".to_string());
+        tool.tool_call_id = Some("call-1".to_string());
+
+        let conversation = StoredConversation {
+            metadata,
+            messages: [
+                ChatMessage::new(ChatRole::User, "Fix it".to_string()),
+                assistant,
+                tool,
+            ]
+            .iter()
+            .map(SerializableChatMessage::from)
+            .collect(),
+        };
+        let markdown = conversation.to_markdown();
+
+        assert!(markdown.starts_with("# Fix the bug\n\n_Model: model-x · "));
+        assert!(markdown.contains("\n## User\n\nFix it\n"));
+        assert!(markdown.contains("<summary>Reasoning</summary>\n\nNeed to look at the file"));
+        let before = markdown.find("Let me check.").unwrap();
+        let call = markdown
+            .find("> **Tool:** `read_file` `{\"path\":\"src/lib.rs\"}`")
+            .unwrap();
+        let result = markdown.find("```\nfn main() {}\n```").unwrap();
+        let after = markdown.find("The bug is fixed.").unwrap();
+        assert!(before < call && call < result && result < after);
+        // The tool message was rendered under its call
+        assert!(!markdown.contains("### Tool result"));
+    }
+
+    #[test]
+    fn test_import_conversation() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let mut store = ConversationStore::new(dir.path().to_path_buf()).unwrap();
+
+        let markdown = "# Rust help\n\n_Model: model-x · 2025-01-01 10:00 UTC_\n\n## User\n\nHow do I do this?\n\n## Assistant\n\n<details>\n<summary>Reasoning</summary>\n\nThink first\n\n</details>\n\nLike this:\n\n```md\n## User\n```\n";
+        let metadata = store.import_conversation("markdown", markdown).unwrap();
+        assert_eq!(metadata.title, "Rust help");
+        assert_eq!(metadata.model_id, "model-x");
+        let stored = store.load_conversation(&metadata.id).unwrap();
+        assert_eq!(stored.messages.len(), 2);
+        assert_eq!(stored.messages[0].content, "How do I do this?");
+        assert_eq!(stored.messages[1].reasoning.as_deref(), Some("Think first"));
+        assert_eq!(
+            stored.messages[1].content,
+            "Like this:\n\n```md\n## User\n```"
+        );
+
+        // Our own export imports back as the same conversation
+        let exported = stored.to_markdown();
+        let reimported = store.import_conversation("markdown", &exported).unwrap();
+        let restored = store.load_conversation(&reimported.id).unwrap();
+        assert_eq!(restored.metadata.title, "Rust help");
+        assert_eq!(restored.messages[1].content, stored.messages[1].content);
+
+        let json = serde_json::to_string(&stored).unwrap();
+        let from_json = store.import_conversation("json", &json).unwrap();
+        assert_ne!(from_json.id, metadata.id);
+        assert_eq!(store.list_conversations().len(), 3);
+
+        let err = store
+            .import_conversation("markdown", "# Title\nstray text\n## User\nhi")
+            .unwrap_err();
+        assert!(err.starts_with("line 2:"), "{}", err);
+        let err = store
+            .import_conversation("markdown", "## User\n```\nunclosed")
+            .unwrap_err();
+        assert!(err.starts_with("line 2:"), "{}", err);
+        let err = store
+            .import_conversation("json", "{\n  \"metadata\": 1\n}")
+            .unwrap_err();
+        assert!(err.contains("line 2"), "{}", err);
+    }
+
+    #[test]
+    fn test_list_conversations_page() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let mut store = ConversationStore::new(dir.path().to_path_buf()).unwrap();
+        let ids: Vec<String> = (0..5)
+            .map(|i| {
+                let mut metadata = store.create_new_conversation("model".to_string());
+                metadata.updated_at = metadata.updated_at + chrono::Duration::seconds(i);
+                store
+                    .save_conversation(&StoredConversation {
+                        metadata: metadata.clone(),
+                        messages: vec![],
+                    })
+                    .unwrap();
+                metadata.id
+            })
+            .collect();
+
+        let page = store.list_conversations_page(1, Some(2));
+        assert_eq!(page.total, 5);
+        let page_ids: Vec<&str> = page.items.iter().map(|m| m.id.as_str()).collect();
+        assert_eq!(page_ids, vec![ids[3].as_str(), ids[2].as_str()]);
+
+        assert!(store.list_conversations_page(10, Some(2)).items.is_empty());
+        assert_eq!(store.list_conversations_page(0, None).items.len(), 5);
+    }
 }
@@ -0,0 +1,217 @@
+//! Text encoding detection
+//!
+//! Source files aren't always UTF-8: legacy code is often Latin-1 and Windows
+//! tools write UTF-16. Reads are transcoded to UTF-8 and report the encoding
+//! they found, so writes can encode back to it.
+
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+/// Leading bytes inspected for the BOM-less UTF-16 heuristic
+const SNIFF_BYTES: usize = 4096;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum TextEncoding {
+    #[default]
+    Utf8,
+    Utf8Bom,
+    /// UTF-16 without a BOM (detected from NUL-byte patterns)
+    Utf16Le,
+    Utf16LeBom,
+    Utf16Be,
+    Utf16BeBom,
+    /// ISO-8859-1: every byte maps to the code point of the same value
+    Latin1,
+}
+
+impl TextEncoding {
+    pub fn label(self) -> &'static str {
+        match self {
+            TextEncoding::Utf8 => "utf-8",
+            TextEncoding::Utf8Bom => "utf-8 with BOM",
+            TextEncoding::Utf16Le => "utf-16le",
+            TextEncoding::Utf16LeBom => "utf-16le with BOM",
+            TextEncoding::Utf16Be => "utf-16be",
+            TextEncoding::Utf16BeBom => "utf-16be with BOM",
+            TextEncoding::Latin1 => "latin-1",
+        }
+    }
+}
+
+/// Decode file bytes to UTF-8 text, detecting the encoding from a BOM, UTF-8
+/// validity, and NUL-byte patterns. Content that fits none of them is binary.
+pub fn decode(bytes: &[u8]) -> Result<(String, TextEncoding), String> {
+    if let Some(rest) = bytes.strip_prefix(&[0xEF, 0xBB, 0xBF]) {
+        return String::from_utf8(rest.to_vec())
+            .map(|s| (s, TextEncoding::Utf8Bom))
+            .map_err(|_| "file has a UTF-8 BOM but invalid UTF-8 content".to_string());
+    }
+    if let Some(rest) = bytes.strip_prefix(&[0xFF, 0xFE]) {
+        return decode_utf16(rest, TextEncoding::Utf16LeBom);
+    }
+    if let Some(rest) = bytes.strip_prefix(&[0xFE, 0xFF]) {
+        return decode_utf16(rest, TextEncoding::Utf16BeBom);
+    }
+    // Before the UTF-8 check: ASCII-only UTF-16 is also valid UTF-8
+    if let Some(encoding) = sniff_utf16(bytes) {
+        return decode_utf16(bytes, encoding);
+    }
+    if let Ok(s) = std::str::from_utf8(bytes) {
+        return Ok((s.to_string(), TextEncoding::Utf8));
+    }
+    if bytes.contains(&0) {
+        return Err("file appears to be binary and can't be read as text".to_string());
+    }
+    Ok((
+        bytes.iter().map(|&b| b as char).collect(),
+        TextEncoding::Latin1,
+    ))
+}
+
+/// Encode UTF-8 text back into `encoding`, failing for characters it can't represent
+pub fn encode(text: &str, encoding: TextEncoding) -> Result<Vec<u8>, String> {
+    Ok(match encoding {
+        TextEncoding::Utf8 => text.as_bytes().to_vec(),
+        TextEncoding::Utf8Bom => [&[0xEF, 0xBB, 0xBF][..], text.as_bytes()].concat(),
+        TextEncoding::Utf16Le => text.encode_utf16().flat_map(u16::to_le_bytes).collect(),
+        TextEncoding::Utf16LeBom => std::iter::once(0xFEFF)
+            .chain(text.encode_utf16())
+            .flat_map(u16::to_le_bytes)
+            .collect(),
+        TextEncoding::Utf16Be => text.encode_utf16().flat_map(u16::to_be_bytes).collect(),
+        TextEncoding::Utf16BeBom => std::iter::once(0xFEFF)
+            .chain(text.encode_utf16())
+            .flat_map(u16::to_be_bytes)
+            .collect(),
+        TextEncoding::Latin1 => text
+            .chars()
+            .map(|c| {
+                u8::try_from(u32::from(c))
+                    .map_err(|_| format!("'{}' can't be saved in a latin-1 file", c))
+            })
+            .collect::<Result<_, _>>()?,
+    })
+}
+
+/// Read a file as text in whatever encoding it uses
+pub fn read_text(path: &Path) -> Result<(String, TextEncoding), String> {
+    let bytes = std::fs::read(path).map_err(|e| e.to_string())?;
+    decode(&bytes)
+}
+
+/// Encoding of an existing file, so a write can keep it; missing or unreadable
+/// files are treated as UTF-8
+pub fn detect_file_encoding(path: &Path) -> TextEncoding {
+    std::fs::read(path)
+        .ok()
+        .and_then(|bytes| decode(&bytes).ok())
+        .map(|(_, encoding)| encoding)
+        .unwrap_or_default()
+}
+
+fn decode_utf16(bytes: &[u8], encoding: TextEncoding) -> Result<(String, TextEncoding), String> {
+    if bytes.len() % 2 != 0 {
+        return Err(format!(
+            "{} file has an odd number of bytes",
+            encoding.label()
+        ));
+    }
+    let units: Vec<u16> = bytes
+        .chunks_exact(2)
+        .map(|pair| match encoding {
+            TextEncoding::Utf16Be | TextEncoding::Utf16BeBom => {
+                u16::from_be_bytes([pair[0], pair[1]])
+            }
+            _ => u16::from_le_bytes([pair[0], pair[1]]),
+        })
+        .collect();
+    String::from_utf16(&units)
+        .map(|s| (s, encoding))
+        .map_err(|_| format!("invalid {} content", encoding.label()))
+}
+
+/// BOM-less UTF-16 text that is mostly ASCII has a NUL in every other byte
+fn sniff_utf16(bytes: &[u8]) -> Option<TextEncoding> {
+    let sample = &bytes[..bytes.len().min(SNIFF_BYTES) & !1];
+    if sample.len() < 4 || bytes.len() % 2 != 0 {
+        return None;
+    }
+    let pairs = sample.len() / 2;
+    let even_nuls = sample.iter().step_by(2).filter(|&&b| b == 0).count();
+    let odd_nuls = sample
+        .iter()
+        .skip(1)
+        .step_by(2)
+        .filter(|&&b| b == 0)
+        .count();
+    if odd_nuls * 10 >= pairs * 7 && even_nuls * 10 <= pairs {
+        Some(TextEncoding::Utf16Le)
+    } else if even_nuls * 10 >= pairs * 7 && odd_nuls * 10 <= pairs {
+        Some(TextEncoding::Utf16Be)
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decode_detects_encodings() {
+        assert_eq!(
+            decode(b"fn main() {}").unwrap(),
+            ("fn main() {}".to_string(), TextEncoding::Utf8)
+        );
+        assert_eq!(
+            decode(b"\xEF\xBB\xBFhi").unwrap(),
+            ("hi".to_string(), TextEncoding::Utf8Bom)
+        );
+        assert_eq!(
+            decode(b"caf\xE9").unwrap(),
+            ("café".to_string(), TextEncoding::Latin1)
+        );
+
+        let le: Vec<u8> = "hi".encode_utf16().flat_map(u16::to_le_bytes).collect();
+        assert_eq!(
+            decode(&le).unwrap(),
+            ("hi".to_string(), TextEncoding::Utf16Le)
+        );
+        let be_bom = encode("x = 1", TextEncoding::Utf16BeBom).unwrap();
+        assert_eq!(
+            decode(&be_bom).unwrap(),
+            ("x = 1".to_string(), TextEncoding::Utf16BeBom)
+        );
+
+        let err = decode(&[0x89, b'P', b'N', b'G', 0x00, 0x00, 0x1A, 0xFF, 0x03]).unwrap_err();
+        assert!(err.contains("binary"), "{}", err);
+    }
+
+    #[test]
+    fn test_encode_round_trips() {
+        for encoding in [
+            TextEncoding::Utf8,
+            TextEncoding::Utf8Bom,
+            TextEncoding::Utf16LeBom,
+            TextEncoding::Utf16BeBom,
+            TextEncoding::Latin1,
+        ] {
+            let bytes = encode("déjà vu\n", encoding).unwrap();
+            assert_eq!(decode(&bytes).unwrap(), ("déjà vu\n".to_string(), encoding));
+        }
+        assert!(encode("€", TextEncoding::Latin1).is_err());
+    }
+
+    #[test]
+    fn test_utf16_without_bom_round_trips_without_bom() {
+        for encoding in [TextEncoding::Utf16Le, TextEncoding::Utf16Be] {
+            let bytes = encode("let x = 1;\n", encoding).unwrap();
+            assert_eq!(bytes.len(), "let x = 1;\n".len() * 2);
+            assert_eq!(
+                decode(&bytes).unwrap(),
+                ("let x = 1;\n".to_string(), encoding)
+            );
+        }
+    }
+}
@@ -1,6 +1,21 @@
-use crate::ephemeral_documents::EphemeralDocument;
+use crate::ephemeral_documents::{self, EphemeralDocument};
 use crate::AppState;
-use tauri::State;
+use tauri::{AppHandle, Emitter, Runtime, State};
+
+/// Spill documents to the workspace when `persist_ephemeral_documents` is on.
+/// Returns whether they are being spilled.
+fn sync_spill_dir(state: &State<'_, AppState>) -> bool {
+    let workspace_root = state.workspace.lock().unwrap().workspace.clone();
+    let dir = workspace_root
+        .filter(|root| {
+            crate::project_settings::load_project_settings_or_default(root)
+                .persist_ephemeral_documents
+        })
+        .map(|root| ephemeral_documents::spill_dir(&root));
+    let persist = dir.is_some();
+    state.ephemeral_docs.set_spill_dir(dir);
+    persist
+}
 
 #[tauri::command]
 pub fn create_ephemeral_document(
@@ -13,6 +28,7 @@ pub fn create_ephemeral_document(
         suggested_name,
         content.len()
     );
+    sync_spill_dir(&state);
     let id = state.ephemeral_docs.create(content, suggested_name);
     println!("[EPHEMERAL RUST] Document created with ID: {}", id);
     Ok(id)
@@ -35,6 +51,7 @@ pub fn update_ephemeral_document(
     content: String,
     state: State<'_, AppState>,
 ) -> Result<(), String> {
+    sync_spill_dir(&state);
     if state.ephemeral_docs.update_content(&id, content) {
         Ok(())
     } else {
@@ -58,6 +75,35 @@ pub fn list_ephemeral_documents(
     Ok(state.ephemeral_docs.list())
 }
 
+/// Reload ephemeral documents left unsaved by an earlier session (e.g. one that
+/// crashed), so the frontend can reopen them
+#[tauri::command]
+pub fn recover_ephemeral_documents(
+    state: State<'_, AppState>,
+) -> Result<Vec<EphemeralDocument>, String> {
+    let workspace_root = state.workspace.lock().unwrap().workspace.clone();
+    let Some(root) = workspace_root else {
+        return Ok(Vec::new());
+    };
+    let persist = sync_spill_dir(&state);
+    let dir = ephemeral_documents::spill_dir(&root);
+    let recovered = state.ephemeral_docs.recover(&dir);
+    if !persist {
+        // Persistence was turned off since these were written; they now live
+        // in memory like any other document
+        for doc in &recovered {
+            let _ = std::fs::remove_file(dir.join(format!("{}.json", doc.id)));
+        }
+    }
+    if !recovered.is_empty() {
+        println!(
+            "[EPHEMERAL RUST] Recovered {} unsaved document(s)",
+            recovered.len()
+        );
+    }
+    Ok(recovered)
+}
+
 #[tauri::command]
 pub async fn save_ephemeral_document(
     id: String,
@@ -123,3 +169,56 @@ pub async fn save_ephemeral_document_to_workspace(
     // Return the relative path (just the filename since it's in root)
     Ok(timestamped_filename)
 }
+
+/// Write an ephemeral document into the workspace as a real file and open it.
+/// `relative_path` defaults to the document's suggested name; existing files
+/// are not overwritten. Returns the absolute path written.
+#[tauri::command]
+pub fn save_ephemeral_to_workspace<R: Runtime>(
+    doc_id: String,
+    relative_path: Option<String>,
+    state: State<'_, AppState>,
+    app_handle: AppHandle<R>,
+) -> Result<String, String> {
+    let doc = state
+        .ephemeral_docs
+        .get(&doc_id)
+        .ok_or_else(|| "Document not found".to_string())?;
+
+    let workspace_root = state
+        .workspace
+        .lock()
+        .unwrap()
+        .workspace
+        .clone()
+        .ok_or_else(|| "No workspace open".to_string())?;
+
+    let relative_path = relative_path
+        .filter(|p| !p.trim().is_empty())
+        .unwrap_or_else(|| doc.suggested_name.clone());
+    let file_path = crate::tools::resolve_path_in_workspace(
+        &workspace_root,
+        std::path::Path::new(relative_path.trim()),
+    )?;
+    if file_path.exists() {
+        return Err(format!("{} already exists", relative_path));
+    }
+
+    if let Some(parent) = file_path.parent() {
+        std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+    }
+    crate::utils::atomic_write(&file_path, &doc.content).map_err(|e| e.to_string())?;
+
+    let file_path_str = file_path.to_string_lossy().to_string();
+    println!(
+        "[EPHEMERAL] Saved document {} to workspace: {}",
+        doc_id, file_path_str
+    );
+
+    state.ephemeral_docs.remove(&doc_id);
+
+    let _ = app_handle.emit(crate::events::event_names::REFRESH_EXPLORER, ());
+    let _ = app_handle.emit("open-file", &file_path_str);
+
+    Ok(file_path_str)
+}
@@ -1,6 +1,8 @@
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
 use std::sync::Mutex;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -12,14 +14,43 @@ pub struct EphemeralDocument {
     pub modified: bool,
 }
 
+/// Where unsaved ephemeral documents are spilled for a project
+pub fn spill_dir(project_path: &Path) -> PathBuf {
+    crate::project_settings::get_zblade_dir(project_path).join("ephemeral")
+}
+
 pub struct EphemeralDocumentStore {
     documents: Mutex<HashMap<String, EphemeralDocument>>,
+    /// When set, documents are also written here so they survive a crash
+    spill_dir: Mutex<Option<PathBuf>>,
 }
 
 impl EphemeralDocumentStore {
     pub fn new() -> Self {
         Self {
             documents: Mutex::new(HashMap::new()),
+            spill_dir: Mutex::new(None),
+        }
+    }
+
+    /// Enable (or with `None`, disable) spilling documents to disk
+    pub fn set_spill_dir(&self, dir: Option<PathBuf>) {
+        *self.spill_dir.lock().unwrap() = dir;
+    }
+
+    fn spill(&self, doc: &EphemeralDocument) {
+        let Some(dir) = self.spill_dir.lock().unwrap().clone() else {
+            return;
+        };
+        let result = fs::create_dir_all(&dir)
+            .map_err(|e| e.to_string())
+            .and_then(|_| serde_json::to_vec(doc).map_err(|e| e.to_string()))
+            .and_then(|json| {
+                crate::utils::atomic_write(&dir.join(format!("{}.json", doc.id)), json)
+                    .map_err(|e| e.to_string())
+            });
+        if let Err(e) = result {
+            eprintln!("[EPHEMERAL] Failed to spill {}: {}", doc.id, e);
         }
     }
 
@@ -33,6 +64,7 @@ impl EphemeralDocumentStore {
             modified: false,
         };
 
+        self.spill(&doc);
         let mut docs = self.documents.lock().unwrap();
         docs.insert(id.clone(), doc);
         id
@@ -48,6 +80,7 @@ impl EphemeralDocumentStore {
         if let Some(doc) = docs.get_mut(id) {
             doc.content = content;
             doc.modified = true;
+            self.spill(doc);
             true
         } else {
             false
@@ -55,10 +88,46 @@ impl EphemeralDocumentStore {
     }
 
     pub fn remove(&self, id: &str) -> bool {
+        if let Some(dir) = self.spill_dir.lock().unwrap().as_ref() {
+            let _ = fs::remove_file(dir.join(format!("{}.json", id)));
+        }
         let mut docs = self.documents.lock().unwrap();
         docs.remove(id).is_some()
     }
 
+    /// Load documents spilled to `dir` by an earlier session back into the
+    /// store, returning them. Documents already in memory are left as they are.
+    pub fn recover(&self, dir: &Path) -> Vec<EphemeralDocument> {
+        let Ok(entries) = fs::read_dir(dir) else {
+            return Vec::new();
+        };
+
+        let mut docs = self.documents.lock().unwrap();
+        let mut recovered = Vec::new();
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.extension().and_then(|e| e.to_str()) != Some("json") {
+                continue;
+            }
+            let doc: EphemeralDocument = match fs::read(&path)
+                .map_err(|e| e.to_string())
+                .and_then(|bytes| serde_json::from_slice(&bytes).map_err(|e| e.to_string()))
+            {
+                Ok(doc) => doc,
+                Err(e) => {
+                    eprintln!("[EPHEMERAL] Skipping unreadable {}: {}", path.display(), e);
+                    continue;
+                }
+            };
+            if !docs.contains_key(&doc.id) {
+                docs.insert(doc.id.clone(), doc.clone());
+                recovered.push(doc);
+            }
+        }
+        recovered.sort_by_key(|doc| doc.created_at);
+        recovered
+    }
+
     pub fn list(&self) -> Vec<EphemeralDocument> {
         let docs = self.documents.lock().unwrap();
         docs.values().cloned().collect()
@@ -70,3 +139,43 @@ impl Default for EphemeralDocumentStore {
         Self::new()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_spilled_documents_are_recovered() {
+        let dir = tempfile::tempdir().unwrap();
+        let spill = dir.path().join("ephemeral");
+
+        let store = EphemeralDocumentStore::new();
+        store.set_spill_dir(Some(spill.clone()));
+        let kept = store.create("draft".to_string(), "research.md".to_string());
+        assert!(store.update_content(&kept, "final".to_string()));
+
+        // Closed documents leave nothing to recover
+        let closed = format!("{}-closed", kept);
+        store.documents.lock().unwrap().insert(
+            closed.clone(),
+            EphemeralDocument {
+                id: closed.clone(),
+                ..store.get(&kept).unwrap()
+            },
+        );
+        store.spill(&store.get(&closed).unwrap());
+        assert!(store.remove(&closed));
+
+        // After a restart
+        let store = EphemeralDocumentStore::new();
+        let recovered = store.recover(&spill);
+        assert_eq!(recovered.len(), 1);
+        assert_eq!(recovered[0].id, kept);
+        assert_eq!(recovered[0].content, "final");
+        assert!(recovered[0].modified);
+        assert_eq!(store.get(&kept).unwrap().suggested_name, "research.md");
+
+        // Recovering again doesn't duplicate what's already loaded
+        assert!(store.recover(&spill).is_empty());
+    }
+}
@@ -27,6 +27,9 @@ pub mod event_names {
     /// Tool execution completed successfully
     pub const TOOL_EXECUTION_COMPLETED: &str = "tool-execution-completed";
 
+    /// Secrets were masked in tool output before it was sent to the model
+    pub const SECRETS_REDACTED: &str = "secrets-redacted";
+
     /// AI model changed
     pub const MODEL_CHANGED: &str = "model-changed";
 
@@ -36,6 +39,10 @@ pub mod event_names {
     /// Command execution started (with terminal)
     pub const COMMAND_EXECUTION_STARTED: &str = "command-execution-started";
 
+    /// A message looked like a mistyped or incomplete @command (it is still sent)
+    /// (payload: `at_commands::CommandHint`)
+    pub const COMMAND_HINT: &str = "command-hint";
+
     // === File Edit Workflow ===
 
     /// File edit proposed by AI, needs user review
@@ -70,11 +77,19 @@ pub mod event_names {
     /// Active file/tab changed
     pub const ACTIVE_FILE_CHANGED: &str = "active-file-changed";
 
+    /// A file or directory with open tabs was renamed or moved
+    /// (payload: `FileMovedPayload`)
+    pub const FILE_MOVED: &str = "file-moved";
+
     // === Workspace ===
 
     /// Workspace folder changed
     pub const WORKSPACE_CHANGED: &str = "workspace-changed";
 
+    /// Opened workspace isn't trusted yet; commands and the terminal stay
+    /// disabled until `trust_workspace` (payload: `WorkspaceChangedPayload`)
+    pub const WORKSPACE_TRUST_REQUIRED: &str = "workspace-trust-required";
+
     /// Project files changed (added/deleted)
     pub const PROJECT_FILES_CHANGED: &str = "project-files-changed";
 
@@ -89,6 +104,9 @@ pub mod event_names {
     /// General backend error
     pub const BACKEND_ERROR: &str = "backend-error";
 
+    /// API config was re-read from disk
+    pub const CONFIG_RELOADED: &str = "config-reloaded";
+
     // === Documents ===
 
     /// Open ephemeral document (research results, etc)
@@ -104,6 +122,9 @@ pub mod event_names {
     /// Terminal reported a cwd change
     pub const TERMINAL_CWD_CHANGED: &str = "terminal-cwd-changed";
 
+    /// Terminal was killed and removed from the manager
+    pub const TERMINAL_CLOSED: &str = "terminal-closed";
+
     // === History ===
 
     /// History entry added (snapshot created)
@@ -184,6 +205,12 @@ pub struct TerminalCwdChangedPayload {
     pub cwd: String,
 }
 
+/// Payload for terminal-closed event
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TerminalClosedPayload {
+    pub id: String,
+}
+
 /// Payload for tool-execution-started event
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ToolExecutionStartedPayload {
@@ -201,6 +228,12 @@ pub struct ToolExecutionCompletedPayload {
     pub skipped: bool,
 }
 
+/// Payload for secrets-redacted event
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SecretsRedactedPayload {
+    pub count: usize,
+}
+
 /// Payload for model-changed event
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ModelChangedPayload {
@@ -281,6 +314,13 @@ pub struct ActiveFileChangedPayload {
     pub previous_file_path: Option<String>,
 }
 
+/// Payload for file-moved event
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FileMovedPayload {
+    pub old_path: String,
+    pub new_path: String,
+}
+
 /// Payload for workspace-changed event
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct WorkspaceChangedPayload {
@@ -1,5 +1,28 @@
+use crate::gitignore_filter::GitignoreFilter;
 use serde::{Deserialize, Serialize};
-use std::path::Path;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// Deepest tree `list_directory_depth` builds in one call
+pub const MAX_TREE_DEPTH: usize = 8;
+
+/// Longest a repository's git status scan is reused. The fs watcher drops a
+/// scan as soon as anything in its repository changes (`.git` included, so
+/// staging and commits count); this only bounds how stale it gets for changes
+/// the watcher doesn't see, e.g. outside the scoped watch paths.
+const GIT_STATUS_MAX_AGE: Duration = Duration::from_secs(60);
+
+/// Changed paths in a repository (absolute, canonical) with their state.
+/// `None` when the directory isn't in a git work tree.
+type GitScan = Option<Arc<Vec<(PathBuf, &'static str)>>>;
+
+lazy_static::lazy_static! {
+    /// Keyed by repository root, so every directory of a repo shares one scan
+    static ref GIT_STATUS_CACHE: Mutex<HashMap<PathBuf, (Instant, GitScan)>> =
+        Mutex::new(HashMap::new());
+}
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct FileEntry {
@@ -7,10 +30,26 @@ pub struct FileEntry {
     pub path: String,
     pub is_dir: bool,
     pub children: Option<Vec<FileEntry>>,
+    /// For directories in a depth-limited tree: whether there is anything to
+    /// expand, so the UI can show an arrow without loading the children
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub has_children: Option<bool>,
+    /// modified/untracked/staged/ignored/clean; `None` outside a git work tree
+    /// unless the gitignore filter says the entry is ignored
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub git_status: Option<String>,
+    /// File size in bytes (files only)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub size: Option<u64>,
 }
 
-pub fn list_directory(path: &Path) -> Vec<FileEntry> {
+/// List a directory, directories first. Entries carry their git status from a
+/// cached `git status` scan of the directory, falling back to `filter` for
+/// ignored entries when the directory isn't in a git work tree.
+pub fn list_directory(path: &Path, filter: Option<&GitignoreFilter>) -> Vec<FileEntry> {
     let mut entries = Vec::new();
+    let scan = git_status_scan(path);
+    let canonical_dir = std::fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
 
     if let Ok(read_dir) = std::fs::read_dir(path) {
         for entry in read_dir.flatten() {
@@ -32,11 +71,30 @@ pub fn list_directory(path: &Path) -> Vec<FileEntry> {
                 // Actually, let's effectively do purely shallow here.
             }
 
+            let git_status = match &scan {
+                Some(changes) => Some(entry_git_status(
+                    changes,
+                    &canonical_dir.join(&name),
+                    is_dir,
+                )),
+                None => filter
+                    .is_some_and(|f| f.should_ignore(&path))
+                    .then_some("ignored"),
+            };
+            let size = if is_dir {
+                None
+            } else {
+                path.metadata().ok().map(|m| m.len())
+            };
+
             entries.push(FileEntry {
                 name,
                 path: path.to_string_lossy().to_string(),
                 is_dir,
                 children,
+                has_children: None,
+                git_status: git_status.map(str::to_string),
+                size,
             });
         }
     }
@@ -50,3 +108,219 @@ pub fn list_directory(path: &Path) -> Vec<FileEntry> {
 
     entries
 }
+
+/// List `path` as a tree `max_depth` levels deep (1 is a shallow listing), so the
+/// explorer can expand lazily. Gitignored directories and `.git` are listed but
+/// not descended into; directories left unexpanded get a `has_children` hint.
+pub fn list_directory_depth(
+    path: &Path,
+    max_depth: usize,
+    filter: Option<&GitignoreFilter>,
+) -> Vec<FileEntry> {
+    let mut entries = list_directory(path, filter);
+    for entry in entries.iter_mut().filter(|e| e.is_dir) {
+        let dir = Path::new(&entry.path);
+        let ignored = entry.name == ".git" || filter.is_some_and(|f| f.should_ignore(dir));
+        if max_depth > 1 && !ignored {
+            let children = list_directory_depth(dir, max_depth - 1, filter);
+            entry.has_children = Some(!children.is_empty());
+            entry.children = Some(children);
+        } else {
+            entry.has_children = Some(
+                std::fs::read_dir(dir)
+                    .map(|mut r| r.next().is_some())
+                    .unwrap_or(false),
+            );
+        }
+    }
+    entries
+}
+
+/// Git status of the repository containing `dir`, reused until the repository
+/// changes (see `invalidate_git_status`) or `GIT_STATUS_MAX_AGE` passes
+fn git_status_scan(dir: &Path) -> GitScan {
+    let dir = std::fs::canonicalize(dir).ok()?;
+    let repo_root = find_repo_root(&dir)?;
+    if let Some((scanned_at, scan)) = GIT_STATUS_CACHE.lock().unwrap().get(&repo_root) {
+        if scanned_at.elapsed() < GIT_STATUS_MAX_AGE {
+            return scan.clone();
+        }
+    }
+
+    // Not under the lock: other directories' lookups shouldn't wait on git
+    let scan = run_git_status(&repo_root).map(Arc::new);
+    let mut cache = GIT_STATUS_CACHE.lock().unwrap();
+    cache.retain(|_, (scanned_at, _)| scanned_at.elapsed() < GIT_STATUS_MAX_AGE);
+    cache.insert(repo_root, (Instant::now(), scan.clone()));
+    scan
+}
+
+/// Drop the cached git status of every repository containing one of `paths`,
+/// so the next listing rescans it. Called by the fs watcher for each event.
+pub fn invalidate_git_status(paths: &[PathBuf]) {
+    let mut cache = GIT_STATUS_CACHE.lock().unwrap();
+    if cache.is_empty() {
+        return;
+    }
+    cache.retain(|repo_root, _| !paths.iter().any(|p| p.starts_with(repo_root)));
+}
+
+/// Nearest ancestor of `dir` (inclusive) with a `.git` directory or file
+fn find_repo_root(dir: &Path) -> Option<PathBuf> {
+    dir.ancestors()
+        .find(|d| d.join(".git").exists())
+        .map(Path::to_path_buf)
+}
+
+fn run_git_status(repo_root: &Path) -> Option<Vec<(PathBuf, &'static str)>> {
+    let output = std::process::Command::new("git")
+        .arg("-C")
+        .arg(repo_root)
+        .args(["status", "--porcelain=v1", "--ignored", "-z"])
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    Some(parse_porcelain_z(
+        repo_root,
+        &String::from_utf8_lossy(&output.stdout),
+    ))
+}
+
+/// Parse `git status --porcelain=v1 -z` into absolute paths and their state
+fn parse_porcelain_z(repo_root: &Path, porcelain: &str) -> Vec<(PathBuf, &'static str)> {
+    let mut changes = Vec::new();
+    let mut records = porcelain.split('\0').filter(|r| !r.is_empty());
+    while let Some(record) = records.next() {
+        if record.len() < 4 {
+            continue;
+        }
+        // Renames and copies are followed by the original path
+        if matches!(record.as_bytes()[0], b'R' | b'C') {
+            records.next();
+        }
+        changes.push((
+            repo_root.join(&record[3..]),
+            crate::tools::classify_git_status(record),
+        ));
+    }
+    changes
+}
+
+/// State of one entry: its own status, or that of an untracked/ignored parent git
+/// reported as a whole. Directories with changes below them count as modified.
+fn entry_git_status(
+    changes: &[(PathBuf, &'static str)],
+    path: &Path,
+    is_dir: bool,
+) -> &'static str {
+    if let Some((_, status)) = changes.iter().find(|(p, _)| path.starts_with(p)) {
+        return status;
+    }
+    let changed_below = changes
+        .iter()
+        .any(|(p, status)| *status != "ignored" && p.starts_with(path));
+    if is_dir && changed_below {
+        "modified"
+    } else {
+        "clean"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn test_list_directory_depth() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        fs::create_dir_all(root.join("src/nested/deep")).unwrap();
+        fs::create_dir_all(root.join("target/debug")).unwrap();
+        fs::create_dir(root.join("empty")).unwrap();
+        fs::write(root.join("src/main.rs"), "").unwrap();
+        fs::write(root.join(".gitignore"), "target/\n").unwrap();
+        let filter = GitignoreFilter::new(root);
+
+        let tree = list_directory_depth(root, 2, Some(&filter));
+        let names: Vec<&str> = tree.iter().map(|e| e.name.as_str()).collect();
+        assert_eq!(names, vec!["empty", "src", "target", ".gitignore"]);
+
+        let empty = &tree[0];
+        assert_eq!(empty.has_children, Some(false));
+        assert_eq!(empty.children.as_ref().map(Vec::len), Some(0));
+
+        // Second level is loaded; the third is only hinted at
+        let src = &tree[1];
+        let src_children = src.children.as_ref().unwrap();
+        assert_eq!(src_children[0].name, "nested");
+        assert!(src_children[0].children.is_none());
+        assert_eq!(src_children[0].has_children, Some(true));
+        assert_eq!(src_children[1].name, "main.rs");
+        assert_eq!(src_children[1].has_children, None);
+
+        // Ignored directories are listed but not expanded
+        let target = &tree[2];
+        assert!(target.children.is_none());
+        assert_eq!(target.has_children, Some(true));
+    }
+
+    #[test]
+    fn test_find_repo_root() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        fs::create_dir_all(root.join("repo/.git")).unwrap();
+        fs::create_dir_all(root.join("repo/src/nested")).unwrap();
+        // Worktrees and submodules have a `.git` file
+        fs::create_dir_all(root.join("repo/vendor/lib")).unwrap();
+        fs::write(root.join("repo/vendor/lib/.git"), "gitdir: elsewhere").unwrap();
+
+        assert_eq!(
+            find_repo_root(&root.join("repo/src/nested")),
+            Some(root.join("repo"))
+        );
+        assert_eq!(
+            find_repo_root(&root.join("repo/vendor/lib")),
+            Some(root.join("repo/vendor/lib"))
+        );
+    }
+
+    #[test]
+    fn test_invalidate_git_status() {
+        let repo = PathBuf::from("/invalidate-test/repo");
+        let other = PathBuf::from("/invalidate-test/other");
+        {
+            let mut cache = GIT_STATUS_CACHE.lock().unwrap();
+            cache.insert(repo.clone(), (Instant::now(), None));
+            cache.insert(other.clone(), (Instant::now(), None));
+        }
+
+        invalidate_git_status(&[repo.join(".git/index")]);
+        let cache = GIT_STATUS_CACHE.lock().unwrap();
+        assert!(!cache.contains_key(&repo));
+        assert!(cache.contains_key(&other));
+    }
+
+    #[test]
+    fn test_entry_git_status() {
+        let root = Path::new("/repo");
+        let changes = parse_porcelain_z(
+            root,
+            " M src/lib.rs\0A  src/new.rs\0R  src/b.rs\0src/a.rs\0?? notes/\0!! target/\0",
+        );
+        assert_eq!(changes.len(), 5);
+
+        let status = |path: &str, is_dir| entry_git_status(&changes, &root.join(path), is_dir);
+        assert_eq!(status("src/lib.rs", false), "modified");
+        assert_eq!(status("src/new.rs", false), "staged");
+        assert_eq!(status("src/b.rs", false), "staged");
+        assert_eq!(status("src/a.rs", false), "clean");
+        assert_eq!(status("src", true), "modified");
+        assert_eq!(status("notes/todo.md", false), "untracked");
+        assert_eq!(status("target/debug", true), "ignored");
+        assert_eq!(status("README.md", false), "clean");
+        assert_eq!(status("docs", true), "clean");
+    }
+}
@@ -1,13 +1,125 @@
 use crate::app_state::AppState;
+use crate::gitignore_filter::GitignoreFilter;
+use crate::language_service::IndexQueue;
 use notify::{event::ModifyKind, EventKind, RecursiveMode, Watcher};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
 use std::sync::{Arc, Mutex};
-use std::time::{Duration, Instant};
+use std::time::Duration;
 use tauri::{Emitter, Manager, Runtime};
 
+/// Window during which watcher events are accumulated into one emission
+const DEBOUNCE_WINDOW: Duration = Duration::from_millis(250);
+
+/// Per-path classification so the frontend can patch individual tree nodes
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FileChangeKind {
+    Created,
+    Modified,
+    Removed,
+    Renamed,
+}
+
+impl FileChangeKind {
+    fn from_event_kind(kind: &EventKind) -> Self {
+        match kind {
+            EventKind::Create(_) => FileChangeKind::Created,
+            EventKind::Remove(_) => FileChangeKind::Removed,
+            EventKind::Modify(ModifyKind::Name(_)) => FileChangeKind::Renamed,
+            _ => FileChangeKind::Modified,
+        }
+    }
+
+    /// Combine two events seen for the same path within one debounce window.
+    fn merge(self, next: FileChangeKind) -> FileChangeKind {
+        match (self, next) {
+            (_, FileChangeKind::Removed) => FileChangeKind::Removed,
+            // Removed and re-created (e.g. editors saving via rename) is a modification
+            (FileChangeKind::Removed, FileChangeKind::Created) => FileChangeKind::Modified,
+            (FileChangeKind::Modified, next) => next,
+            (prev, _) => prev,
+        }
+    }
+}
+
+#[derive(Clone, serde::Serialize)]
+pub struct FileChange {
+    pub path: String,
+    pub kind: FileChangeKind,
+}
+
 #[derive(Clone, serde::Serialize)]
 pub struct FileChangeEvent {
     pub count: usize,
     pub paths: Vec<String>,
+    pub changes: Vec<FileChange>,
+}
+
+impl FileChangeEvent {
+    fn from_pending(pending: HashMap<String, FileChangeKind>) -> Self {
+        let mut changes: Vec<FileChange> = pending
+            .into_iter()
+            .map(|(path, kind)| FileChange { path, kind })
+            .collect();
+        changes.sort_by(|a, b| a.path.cmp(&b.path));
+        let paths: Vec<String> = changes.iter().map(|c| c.path.clone()).collect();
+
+        Self {
+            count: paths.len(),
+            paths,
+            changes,
+        }
+    }
+}
+
+/// Whether a watcher event path is noise that shouldn't trigger an explorer refresh.
+/// `.git/` is always skipped, even if the workspace has no .gitignore.
+fn is_ignored_event_path(path: &Path, filter: &GitignoreFilter) -> bool {
+    path.components().any(|c| c.as_os_str() == ".git") || filter.should_ignore_with_parents(path)
+}
+
+/// Keep local semantic-search embeddings in sync with changed files
+fn refresh_embeddings<R: Runtime>(app_handle: &tauri::AppHandle<R>, changes: &[FileChange]) {
+    let state = app_handle.state::<AppState>();
+    let Some(config) =
+        crate::tools::EmbeddingConfig::from_api_config(&state.config.lock().unwrap())
+    else {
+        return;
+    };
+    let Some(root) = state.workspace.lock().unwrap().workspace.clone() else {
+        return;
+    };
+
+    if let Err(e) = crate::tools::refresh_file_embeddings(&root, &config, changes.to_vec()) {
+        eprintln!("[WATCHER] Failed to refresh embeddings: {}", e);
+    }
+}
+
+/// Keep the symbol index in sync with changed files
+fn reindex_symbols(queue: &IndexQueue, changes: &[FileChange]) {
+    for change in changes {
+        queue.enqueue(Path::new(&change.path));
+    }
+}
+
+/// What to watch recursively. Watch paths inside the workspace scope watching
+/// down to them instead of the whole root; paths outside it are watched as well.
+/// Paths are compared canonicalized, so symlinks and `..` don't hide a scoped one.
+fn watch_targets(root: &Path, watch_paths: &[PathBuf]) -> Vec<PathBuf> {
+    let canonical = |p: &Path| std::fs::canonicalize(p).unwrap_or_else(|_| p.to_path_buf());
+    let root = canonical(root);
+    let (scoped, extra): (Vec<PathBuf>, Vec<PathBuf>) = watch_paths
+        .iter()
+        .map(|p| canonical(p))
+        .partition(|p| p.starts_with(&root));
+    let mut targets = if scoped.is_empty() {
+        vec![root]
+    } else {
+        scoped
+    };
+    targets.extend(extra);
+    targets
 }
 
 pub fn restart_fs_watcher<R: Runtime>(app_handle: &tauri::AppHandle<R>) {
@@ -30,9 +142,16 @@ pub fn restart_fs_watcher<R: Runtime>(app_handle: &tauri::AppHandle<R>) {
                 return;
             }
 
+            // Rebuilt on every restart so a workspace change picks up its own .gitignore files
+            let gitignore_filter = GitignoreFilter::new(&root);
+            // Dropped together with the watcher, which stops its workers
+            let index_queue = Arc::new(IndexQueue::start(state.language_service.clone()));
+
             let app_handle_clone = app_handle.clone();
-            let last_emit = Arc::new(Mutex::new(Instant::now() - Duration::from_secs(1)));
-            let last_emit_ref = last_emit.clone();
+            // `Some` while a debounce window is open; paths seen during the window are
+            // coalesced here and emitted together when it closes.
+            let pending: Arc<Mutex<Option<HashMap<String, FileChangeKind>>>> =
+                Arc::new(Mutex::new(None));
 
             let mut watcher =
                 match notify::recommended_watcher(move |res: notify::Result<notify::Event>| {
@@ -53,29 +172,49 @@ pub fn restart_fs_watcher<R: Runtime>(app_handle: &tauri::AppHandle<R>) {
                             if !relevant {
                                 return;
                             }
+                            // Before the filter: .git changes (staging, commits) move git status too
+                            crate::explorer::invalidate_git_status(&event.paths);
 
-                            let now = Instant::now();
-                            let mut last = last_emit_ref.lock().unwrap();
-                            if now.duration_since(*last) < Duration::from_millis(250) {
-                                return;
-                            }
-                            *last = now;
-
+                            // Drop build/VCS churn before it can open a debounce window
                             let paths: Vec<String> = event
                                 .paths
                                 .iter()
+                                .filter(|p| !is_ignored_event_path(p, &gitignore_filter))
                                 .map(|p| p.display().to_string())
                                 .collect();
+                            if paths.is_empty() {
+                                return;
+                            }
+
+                            let kind = FileChangeKind::from_event_kind(&event.kind);
+                            let mut guard = pending.lock().unwrap();
+                            let window_open = guard.is_some();
+                            let batch = guard.get_or_insert_with(HashMap::new);
+                            for path in paths {
+                                batch
+                                    .entry(path)
+                                    .and_modify(|prev| *prev = prev.merge(kind))
+                                    .or_insert(kind);
+                            }
 
-                            let file_change_event = FileChangeEvent {
-                                count: paths.len(),
-                                paths: paths.clone(),
-                            };
+                            if !window_open {
+                                let pending = pending.clone();
+                                let app_handle = app_handle_clone.clone();
+                                let index_queue = index_queue.clone();
+                                std::thread::spawn(move || {
+                                    std::thread::sleep(DEBOUNCE_WINDOW);
+                                    let Some(batch) = pending.lock().unwrap().take() else {
+                                        return;
+                                    };
 
-                            let _ =
-                                app_handle_clone.emit("file-changes-detected", file_change_event);
-                            let _ = app_handle_clone
-                                .emit(crate::events::event_names::REFRESH_EXPLORER, ());
+                                    let event = FileChangeEvent::from_pending(batch);
+                                    let _ = app_handle.emit("file-changes-detected", &event);
+                                    let _ = app_handle
+                                        .emit(crate::events::event_names::REFRESH_EXPLORER, ());
+                                    reindex_symbols(&index_queue, &event.changes);
+                                    refresh_embeddings(&app_handle, &event.changes);
+                                });
+                            }
                         }
                         Err(e) => eprintln!("[WATCHER] error: {}", e),
                     }
@@ -87,14 +226,88 @@ pub fn restart_fs_watcher<R: Runtime>(app_handle: &tauri::AppHandle<R>) {
                     }
                 };
 
-            // This is the blocking call (recursive watch crawl)
-            if let Err(e) = watcher.watch(&root, RecursiveMode::Recursive) {
-                eprintln!("[WATCHER] Failed to watch {}: {}", root.display(), e);
+            let watch_paths = state.watch_paths.lock().unwrap().clone();
+            let mut watching = 0;
+            for target in watch_targets(&root, &watch_paths) {
+                // This is the blocking call (recursive watch crawl)
+                match watcher.watch(&target, RecursiveMode::Recursive) {
+                    Ok(()) => {
+                        watching += 1;
+                        eprintln!("[WATCHER] Watching: {}", target.display());
+                    }
+                    Err(e) => eprintln!("[WATCHER] Failed to watch {}: {}", target.display(), e),
+                }
+            }
+            if watching == 0 {
                 return;
             }
 
             *watcher_guard = Some(watcher);
-            eprintln!("[WATCHER] Watching workspace: {}", root.display());
         }
     });
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_change_kind_merge() {
+        use FileChangeKind::*;
+
+        assert_eq!(Created.merge(Modified), Created);
+        assert_eq!(Modified.merge(Renamed), Renamed);
+        assert_eq!(Created.merge(Removed), Removed);
+        assert_eq!(Removed.merge(Created), Modified);
+    }
+
+    #[test]
+    fn test_watch_targets() {
+        let root = Path::new("/ws");
+        assert_eq!(watch_targets(root, &[]), vec![PathBuf::from("/ws")]);
+
+        let extra = vec![PathBuf::from("/generated/docs")];
+        assert_eq!(
+            watch_targets(root, &extra),
+            vec![PathBuf::from("/ws"), PathBuf::from("/generated/docs")]
+        );
+
+        // An in-workspace path replaces the root
+        let scoped = vec![
+            PathBuf::from("/generated/docs"),
+            PathBuf::from("/ws/packages/app"),
+        ];
+        assert_eq!(
+            watch_targets(root, &scoped),
+            vec![
+                PathBuf::from("/ws/packages/app"),
+                PathBuf::from("/generated/docs")
+            ]
+        );
+    }
+
+    #[test]
+    fn test_watch_targets_compares_canonical_paths() {
+        let dir = tempfile::tempdir().unwrap();
+        let ws = std::fs::canonicalize(dir.path()).unwrap().join("ws");
+        std::fs::create_dir_all(ws.join("pkg")).unwrap();
+
+        let root = ws.join("../ws");
+        assert_eq!(
+            watch_targets(&root, &[ws.join("pkg")]),
+            vec![ws.join("pkg")]
+        );
+    }
+
+    #[test]
+    fn test_event_coalesces_paths() {
+        let mut pending = HashMap::new();
+        pending.insert("/ws/b.rs".to_string(), FileChangeKind::Modified);
+        pending.insert("/ws/a.rs".to_string(), FileChangeKind::Created);
+
+        let event = FileChangeEvent::from_pending(pending);
+        assert_eq!(event.count, 2);
+        assert_eq!(event.paths, vec!["/ws/a.rs", "/ws/b.rs"]);
+        assert_eq!(event.changes[0].kind, FileChangeKind::Created);
+    }
+}
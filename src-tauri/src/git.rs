@@ -1,10 +1,17 @@
 use serde::Serialize;
+use std::path::Path;
 use std::process::Command;
 use tauri::State;
 
+use crate::context_assembly::{
+    cap_diff, BudgetAllocation, ContextAssembler, TokenBudget, UncommittedContext, UncommittedFile,
+};
 use crate::AppState;
 use crate::models::{ollama, openai_compat, registry};
 
+/// Files beyond this many keep their status but skip the diff
+const MAX_UNCOMMITTED_DIFFS: usize = 50;
+
 #[derive(Debug, Clone, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct GitStatusSummary {
@@ -24,6 +31,9 @@ pub struct GitStatusSummary {
 pub struct GitFileStatus {
     pub path: String,
     pub display_path: Option<String>,
+    /// Path before a rename or copy
+    #[serde(skip)]
+    pub orig_path: Option<String>,
     pub staged: bool,
     pub unstaged: bool,
     pub untracked: bool,
@@ -185,10 +195,10 @@ struct CommitContext {
 }
 
 async fn load_available_models(state: &State<'_, AppState>) -> Vec<registry::ModelInfo> {
-    let (blade_url, api_key, ollama_enabled, ollama_url, openai_compat_enabled, openai_compat_url) = {
+    let (endpoints, api_key, ollama_enabled, ollama_url, openai_compat_enabled, openai_compat_url) = {
         let config = state.config.lock().unwrap();
         (
-            config.blade_url.clone(),
+            config.normalized(),
             config.api_key.clone(),
             config.ollama_enabled,
             config.ollama_url.clone(),
@@ -197,7 +207,13 @@ async fn load_available_models(state: &State<'_, AppState>) -> Vec<registry::Mod
         )
     };
 
-    let mut models = registry::get_models(&blade_url, &api_key).await;
+    let mut models = match endpoints {
+        Ok(endpoints) => registry::get_models(&endpoints.http_base, &api_key).await,
+        Err(e) => {
+            eprintln!("[MODEL REGISTRY] Skipping Blade models: {}", e);
+            Vec::new()
+        }
+    };
     if ollama_enabled {
         let mut ollama_models = ollama::get_models(&ollama_url).await;
         models.append(&mut ollama_models);
@@ -245,27 +261,48 @@ fn resolve_model_id(models: &[registry::ModelInfo], requested_id: &str) -> Strin
     }
 }
 
-fn collect_changes_for_message(root: &str) -> Result<CommitContext, String> {
-    let staged_files = run_git(root, &["diff", "--cached", "--name-only"])?;
-    let mut files: Vec<String> = staged_files
+/// Hash of git's empty tree, to diff against in a repository without commits
+const EMPTY_TREE: &str = "4b825dc642cb6eb9a060e54bf8d69288fbee4904";
+
+/// Changes to describe in a commit message: the staged ones if any, else the
+/// working tree. With `paths`, exactly what `git commit -- <paths>` records:
+/// those paths' working-tree state against HEAD.
+fn collect_changes_for_message(root: &str, paths: &[String]) -> Result<CommitContext, String> {
+    let git_paths = |args: &[&str]| {
+        let mut args = args.to_vec();
+        if !paths.is_empty() {
+            args.push("--");
+            args.extend(paths.iter().map(String::as_str));
+        }
+        run_git(root, &args)
+    };
+
+    let head = if run_git(root, &["rev-parse", "--verify", "-q", "HEAD"]).is_ok() {
+        "HEAD"
+    } else {
+        EMPTY_TREE
+    };
+    let staged = paths.is_empty()
+        && !run_git(root, &["diff", "--cached", "--name-only"])?
+            .trim()
+            .is_empty();
+    let diff_base: Vec<&str> = if staged {
+        vec!["diff", "--cached"]
+    } else if !paths.is_empty() {
+        vec!["diff", head]
+    } else {
+        vec!["diff"]
+    };
+    let with_base = |extra: &str| [diff_base.as_slice(), &[extra]].concat();
+
+    let mut files: Vec<String> = git_paths(&with_base("--name-only"))?
         .lines()
         .map(|l| l.trim().to_string())
         .filter(|l| !l.is_empty())
         .collect();
 
-    let staged = !files.is_empty();
-
-    if !staged {
-        let unstaged_files = run_git(root, &["diff", "--name-only"])?;
-        files = unstaged_files
-            .lines()
-            .map(|l| l.trim().to_string())
-            .filter(|l| !l.is_empty())
-            .collect();
-    }
-
     // Get untracked files
-    let untracked_output = run_git(root, &["ls-files", "--others", "--exclude-standard"])?;
+    let untracked_output = git_paths(&["ls-files", "--others", "--exclude-standard"])?;
     let untracked: Vec<String> = untracked_output
         .lines()
         .map(|l| l.trim().to_string())
@@ -277,20 +314,10 @@ fn collect_changes_for_message(root: &str) -> Result<CommitContext, String> {
         files.extend(untracked.clone());
     }
 
-    let diff_args = if staged {
-        vec!["diff", "--cached", "--unified=3"]
-    } else {
-        vec!["diff", "--unified=3"]
-    };
-    let mut diff = run_git(root, &diff_args)?;
+    let mut diff = git_paths(&with_base("--unified=3"))?;
 
     // Get diff stats (insertions/deletions summary)
-    let diff_stat_args = if staged {
-        vec!["diff", "--cached", "--stat"]
-    } else {
-        vec!["diff", "--stat"]
-    };
-    let diff_stat = run_git(root, &diff_stat_args).unwrap_or_default();
+    let diff_stat = git_paths(&with_base("--stat")).unwrap_or_default();
 
     // Get current branch
     let branch = run_git(root, &["rev-parse", "--abbrev-ref", "HEAD"])
@@ -351,93 +378,61 @@ fn collect_changes_for_message(root: &str) -> Result<CommitContext, String> {
     })
 }
 
+/// Parse `git status --porcelain=v2 -z`. Records are NUL-separated, so paths keep
+/// their spaces; a rename's original path follows it as a record of its own.
 fn parse_git_status_files(output: &str) -> Vec<GitFileStatus> {
     let mut files = Vec::new();
+    let mut records = output.split('\0').filter(|r| !r.is_empty());
 
-    for line in output.lines() {
-        if line.starts_with("# ") {
-            continue;
-        }
-
-        if let Some(rest) = line.strip_prefix("1 ") {
-            let parts: Vec<&str> = rest.split_whitespace().collect();
-            if parts.len() < 2 {
-                continue;
-            }
-            let xy = parts[0];
-            let path = parts.last().unwrap_or(&"").to_string();
-            let mut chars = xy.chars();
-            let x = chars.next().unwrap_or('.');
-            let y = chars.next().unwrap_or('.');
-            files.push(GitFileStatus {
-                path,
-                display_path: None,
-                staged: x != '.',
-                unstaged: y != '.',
-                untracked: false,
-                conflicted: false,
-                status_code: format!("{}{}", x, y),
-            });
+    while let Some(record) = records.next() {
+        let Some((kind, rest)) = record.split_once(' ') else {
             continue;
-        }
-
-        if let Some(rest) = line.strip_prefix("2 ") {
-            let parts: Vec<&str> = rest.split_whitespace().collect();
-            if parts.len() < 3 {
+        };
+        // Fields before the path: XY, submodule state, modes and object names
+        let fields = match kind {
+            "1" => 7,
+            "2" => 8,
+            "u" => 9,
+            "?" => {
+                files.push(GitFileStatus {
+                    path: rest.to_string(),
+                    display_path: None,
+                    orig_path: None,
+                    staged: false,
+                    unstaged: true,
+                    untracked: true,
+                    conflicted: false,
+                    status_code: "??".to_string(),
+                });
                 continue;
             }
-            let xy = parts[0];
-            let new_path = parts.get(parts.len().saturating_sub(2)).unwrap_or(&"");
-            let old_path = parts.get(parts.len().saturating_sub(1)).unwrap_or(&"");
-            let mut chars = xy.chars();
-            let x = chars.next().unwrap_or('.');
-            let y = chars.next().unwrap_or('.');
-            files.push(GitFileStatus {
-                path: new_path.to_string(),
-                display_path: Some(format!("{} → {}", old_path, new_path)),
-                staged: x != '.',
-                unstaged: y != '.',
-                untracked: false,
-                conflicted: false,
-                status_code: format!("{}{}", x, y),
-            });
-            continue;
-        }
+            _ => continue,
+        };
 
-        if let Some(rest) = line.strip_prefix("u ") {
-            let parts: Vec<&str> = rest.split_whitespace().collect();
-            if parts.len() < 2 {
-                continue;
-            }
-            let xy = parts[0];
-            let path = parts.last().unwrap_or(&"").to_string();
-            let mut chars = xy.chars();
-            let x = chars.next().unwrap_or('.');
-            let y = chars.next().unwrap_or('.');
-            files.push(GitFileStatus {
-                path,
-                display_path: None,
-                staged: x != '.',
-                unstaged: y != '.',
-                untracked: false,
-                conflicted: true,
-                status_code: format!("{}{}", x, y),
-            });
+        let parts: Vec<&str> = rest.splitn(fields + 1, ' ').collect();
+        if parts.len() <= fields {
             continue;
         }
-
-        if let Some(rest) = line.strip_prefix("? ") {
-            let path = rest.trim().to_string();
-            files.push(GitFileStatus {
-                path,
-                display_path: None,
-                staged: false,
-                unstaged: true,
-                untracked: true,
-                conflicted: false,
-                status_code: "??".to_string(),
-            });
-        }
+        let xy = parts[0];
+        let path = parts[fields].to_string();
+        let orig_path = if kind == "2" {
+            records.next().map(str::to_string)
+        } else {
+            None
+        };
+        let mut chars = xy.chars();
+        let x = chars.next().unwrap_or('.');
+        let y = chars.next().unwrap_or('.');
+        files.push(GitFileStatus {
+            display_path: orig_path.as_ref().map(|old| format!("{} → {}", old, path)),
+            path,
+            orig_path,
+            staged: x != '.',
+            unstaged: y != '.',
+            untracked: false,
+            conflicted: kind == "u",
+            status_code: format!("{}{}", x, y),
+        });
     }
 
     files
@@ -483,6 +478,7 @@ pub fn git_status_files(state: State<'_, AppState>) -> Result<Vec<GitFileStatus>
         .arg("status")
         .arg("--porcelain=v2")
         .arg("-uall")
+        .arg("-z")
         .output()
         .map_err(|e| format!("failed to run git status: {}", e))?;
 
@@ -590,23 +586,79 @@ pub fn git_unstage_all(state: State<'_, AppState>) -> Result<(), String> {
     Ok(())
 }
 
+/// Commit the staged changes, or only `files` when given (each must be inside the
+/// workspace; other staged changes stay staged). With `generate_message`, zcoderd
+/// summarizes the changes being committed into a conventional-commit message using
+/// `model_id` or the selected model, and `message` is ignored. The message is settled
+/// before anything is staged. Returns the new commit hash.
 #[tauri::command]
-pub fn git_commit(state: State<'_, AppState>, message: String) -> Result<String, String> {
+pub async fn git_commit(
+    state: State<'_, AppState>,
+    message: Option<String>,
+    files: Option<Vec<String>>,
+    generate_message: Option<bool>,
+    model_id: Option<String>,
+) -> Result<String, String> {
     let Some(root) = workspace_root(&state) else {
         return Err("No workspace open".to_string());
     };
 
+    if run_git(&root, &["rev-parse", "--git-dir"]).is_err() {
+        return Err(format!("Not a git repository: {}", root));
+    }
+
+    // Check every path before touching the index; deleted files are fine
+    let workspace = Path::new(&root);
+    let mut paths = Vec::new();
+    for file in files.unwrap_or_default() {
+        let path = crate::tools::resolve_path_in_workspace(workspace, Path::new(&file))
+            .map_err(|e| format!("Refusing to commit {}: {}", file, e))?;
+        paths.push(path.to_string_lossy().to_string());
+    }
+
+    // Settle the message first, so a failure leaves the index as it was
+    let message = if generate_message.unwrap_or(false) {
+        let model_id = model_id
+            .or_else(|| state.selected_model_id.lock().unwrap().clone())
+            .ok_or_else(|| "No model selected to generate the commit message".to_string())?;
+        generate_commit_message_ai(&state, &root, &model_id, &paths).await?
+    } else {
+        message.unwrap_or_default()
+    };
     let message = message.trim();
     if message.is_empty() {
         return Err("Commit message is required".to_string());
     }
 
-    let output = Command::new("git")
+    if !paths.is_empty() {
+        // New files must be in the index before a pathspec commit can include them
+        let output = Command::new("git")
+            .arg("-C")
+            .arg(&root)
+            .arg("add")
+            .arg("-A")
+            .arg("--")
+            .args(&paths)
+            .output()
+            .map_err(|e| format!("failed to run git add: {}", e))?;
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr).to_string();
+            return Err(format!("git add failed: {}", stderr.trim()));
+        }
+    }
+
+    let mut commit = Command::new("git");
+    commit
         .arg("-C")
         .arg(&root)
         .arg("commit")
         .arg("-m")
-        .arg(message)
+        .arg(message);
+    if !paths.is_empty() {
+        // Only these paths are committed; anything else staged stays staged
+        commit.arg("--").args(&paths);
+    }
+    let output = commit
         .output()
         .map_err(|e| format!("failed to run git commit: {}", e))?;
 
@@ -615,8 +667,8 @@ pub fn git_commit(state: State<'_, AppState>, message: String) -> Result<String,
         return Err(format!("git commit failed: {}", stderr.trim()));
     }
 
-    let stdout = String::from_utf8_lossy(&output.stdout).to_string();
-    Ok(stdout)
+    let hash = run_git(&root, &["rev-parse", "HEAD"])?;
+    Ok(hash.trim().to_string())
 }
 
 #[tauri::command]
@@ -767,13 +819,97 @@ pub fn git_diff(state: State<'_, AppState>, path: String, staged: bool) -> Resul
     Ok(stdout)
 }
 
+/// Work-in-progress context: modified and untracked files with their diffs against
+/// HEAD, fitted to `max_tokens` (the default context budget when omitted).
+#[tauri::command]
+pub fn get_uncommitted_context(
+    state: State<'_, AppState>,
+    max_tokens: Option<usize>,
+) -> Result<UncommittedContext, String> {
+    let Some(root) = workspace_root(&state) else {
+        return Ok(UncommittedContext::default());
+    };
+
+    let changes = collect_uncommitted_context(&root)?;
+    let budget = match max_tokens {
+        Some(total) => TokenBudget {
+            total,
+            system_reserve: 0,
+            user_reserve: 0,
+            response_reserve: 0,
+        },
+        None => TokenBudget::default(),
+    };
+    let assembler = ContextAssembler::new(state.language_service.clone()).with_budget(budget);
+    Ok(assembler.select_uncommitted(changes, &mut BudgetAllocation::default()))
+}
+
+/// Changed and untracked files inside the workspace, each with a size-capped diff
+/// against HEAD. Empty outside a git repository.
+pub(crate) fn collect_uncommitted_context(root: &str) -> Result<UncommittedContext, String> {
+    let status = match run_git(root, &["status", "--porcelain=v2", "-uall", "-z"]) {
+        Ok(status) => status,
+        Err(e) if e.contains("not a git repository") => return Ok(UncommittedContext::default()),
+        Err(e) => return Err(e),
+    };
+    // Status paths are relative to the repository root, which may sit above the workspace
+    let toplevel = run_git(root, &["rev-parse", "--show-toplevel"])?;
+    let toplevel = Path::new(toplevel.trim());
+    let workspace = std::fs::canonicalize(root).map_err(|e| e.to_string())?;
+
+    let mut files = Vec::new();
+    for status in parse_git_status_files(&status) {
+        let absolute = toplevel.join(&status.path);
+        let Ok(relative) = absolute.strip_prefix(&workspace) else {
+            continue;
+        };
+        let path = relative.to_string_lossy().replace('\\', "/");
+        // A rename from outside the workspace can only be shown as a new file
+        let from = status.orig_path.as_ref().and_then(|orig| {
+            let absolute = toplevel.join(orig);
+            let relative = absolute.strip_prefix(&workspace).ok()?;
+            Some(relative.to_string_lossy().replace('\\', "/"))
+        });
+
+        let (diff, truncated) = if files.len() < MAX_UNCOMMITTED_DIFFS {
+            let target = match &from {
+                Some(from) => crate::tools::DiffTarget::Renamed { from, path: &path },
+                None => crate::tools::DiffTarget::Head { path: &path },
+            };
+            match crate::tools::diff_in_workspace(&workspace, target) {
+                Ok(diff) if !diff.is_empty() => {
+                    let (diff, truncated) = cap_diff(diff);
+                    (Some(diff), truncated)
+                }
+                Ok(_) => (None, false),
+                Err(e) => {
+                    eprintln!("[GIT] No diff for {}: {}", path, e);
+                    (None, false)
+                }
+            }
+        } else {
+            (None, false)
+        };
+
+        files.push(UncommittedFile {
+            path,
+            status_code: status.status_code,
+            untracked: status.untracked,
+            diff,
+            truncated,
+        });
+    }
+
+    Ok(UncommittedContext::new(files))
+}
+
 #[tauri::command]
 pub fn git_generate_commit_message(state: State<'_, AppState>) -> Result<String, String> {
     let Some(root) = workspace_root(&state) else {
         return Err("No workspace open".to_string());
     };
 
-    let ctx = collect_changes_for_message(&root)?;
+    let ctx = collect_changes_for_message(&root, &[])?;
 
     if ctx.files.is_empty() {
         return Err("No changes to commit".to_string());
@@ -799,7 +935,18 @@ pub async fn git_generate_commit_message_ai(
         return Err("No workspace open".to_string());
     };
 
-    let ctx = collect_changes_for_message(&root)?;
+    generate_commit_message_ai(&state, &root, &model_id, &[]).await
+}
+
+/// Ask zcoderd to summarize the pending changes (staged ones if any, or those
+/// of `paths`) into a conventional-commit message
+async fn generate_commit_message_ai(
+    state: &State<'_, AppState>,
+    root: &str,
+    model_id: &str,
+    paths: &[String],
+) -> Result<String, String> {
+    let ctx = collect_changes_for_message(root, paths)?;
     if ctx.files.is_empty() {
         return Err("No changes to commit".to_string());
     }
@@ -862,15 +1009,15 @@ Respond with ONLY the commit message, nothing else."#,
     );
 
     let workspace_info = crate::blade_ws_client::WorkspaceInfo {
-        root: root.clone(),
+        root: root.to_string(),
         project_id: None,
         active_file: None,
         cursor_position: None,
         open_files: Vec::new(),
     };
 
-    let available_models = load_available_models(&state).await;
-    let resolved_model_id = resolve_model_id(&available_models, &model_id);
+    let available_models = load_available_models(state).await;
+    let resolved_model_id = resolve_model_id(&available_models, model_id);
 
     // Use shared WebSocket connection manager from AppState
     let ws_manager = state.ws_connection.clone();
@@ -924,3 +1071,33 @@ Respond with ONLY the commit message, nothing else."#,
         Ok(message.to_string())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_git_status_files_z() {
+        let output = concat!(
+            "1 .M N... 100644 100644 100644 abc abc src/my file.rs\0",
+            "2 R. N... 100644 100644 100644 abc abc R100 new name.rs\0old name.rs\0",
+            "u UU N... 100644 100644 100644 100644 a b c both.rs\0",
+            "? notes dir/todo.md\0",
+        );
+
+        let files = parse_git_status_files(output);
+        assert_eq!(files.len(), 4);
+        assert_eq!(files[0].path, "src/my file.rs");
+        assert_eq!(files[0].status_code, ".M");
+        assert_eq!(files[1].path, "new name.rs");
+        assert_eq!(files[1].orig_path.as_deref(), Some("old name.rs"));
+        assert_eq!(
+            files[1].display_path.as_deref(),
+            Some("old name.rs → new name.rs")
+        );
+        assert!(files[2].conflicted);
+        assert_eq!(files[2].path, "both.rs");
+        assert!(files[3].untracked);
+        assert_eq!(files[3].path, "notes dir/todo.md");
+    }
+}
@@ -156,6 +156,31 @@ impl GitignoreFilter {
         }
     }
 
+    /// Like `should_ignore`, but also returns true when any parent directory is ignored.
+    /// Watcher events report deep paths (e.g. `target/debug/foo.o`) that a `target/`
+    /// pattern doesn't match on its own.
+    pub fn should_ignore_with_parents(&self, path: &Path) -> bool {
+        let guard = self.inner.read().unwrap();
+
+        let Some(ref gitignore) = *guard else {
+            return false;
+        };
+
+        let abs_path = if path.is_absolute() {
+            path.to_path_buf()
+        } else {
+            self.workspace_root.join(path)
+        };
+
+        let Ok(rel_path) = abs_path.strip_prefix(&self.workspace_root) else {
+            return false;
+        };
+
+        gitignore
+            .matched_path_or_any_parents(rel_path, abs_path.is_dir())
+            .is_ignore()
+    }
+
     /// Helper to filter a list of paths, removing those that should be ignored
     pub fn filter_paths<P: AsRef<Path>>(&self, paths: Vec<P>) -> Vec<PathBuf> {
         paths
@@ -232,6 +257,23 @@ build/
         assert!(!filter.should_ignore(&root.join("important.log")));
     }
 
+    #[test]
+    fn test_gitignore_with_parents() {
+        let temp = tempdir().unwrap();
+        let root = temp.path();
+
+        fs::write(root.join(".gitignore"), "target/\n").unwrap();
+        fs::create_dir_all(root.join("target").join("debug")).unwrap();
+        fs::create_dir_all(root.join("src")).unwrap();
+
+        let filter = GitignoreFilter::new(root);
+
+        let deep = root.join("target").join("debug").join("foo.o");
+        assert!(!filter.should_ignore(&deep));
+        assert!(filter.should_ignore_with_parents(&deep));
+        assert!(!filter.should_ignore_with_parents(&root.join("src").join("main.rs")));
+    }
+
     #[test]
     fn test_no_gitignore() {
         let temp = tempdir().unwrap();
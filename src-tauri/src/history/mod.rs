@@ -92,7 +92,8 @@ impl HistoryService {
         Ok(entry)
     }
 
-    pub fn revert_to(&self, entry_id: &str) -> Result<(), String> {
+    /// Restore the snapshot with the given ID, returning the path that was reverted.
+    pub fn revert_to(&self, entry_id: &str) -> Result<PathBuf, String> {
         let entry = {
             let index = self.index.lock().unwrap();
             let mut found = None;
@@ -107,7 +108,7 @@ impl HistoryService {
 
         if let Some(entry) = entry {
             fs::copy(&entry.snapshot_path, &entry.file_path).map_err(|e| e.to_string())?;
-            Ok(())
+            Ok(entry.file_path)
         } else {
             Err("Snapshot not found".to_string())
         }
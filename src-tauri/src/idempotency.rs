@@ -1,14 +1,60 @@
-use std::collections::HashMap;
+use serde::Serialize;
+use std::collections::{HashMap, VecDeque};
 use std::sync::Mutex;
 use std::time::{Duration, Instant};
 use uuid::Uuid;
 
+/// Default maximum number of cached idempotency keys
+pub const DEFAULT_MAX_ENTRIES: usize = 10_000;
+
 /// Idempotency cache entry
 #[derive(Clone)]
 struct CacheEntry {
     intent_id: Uuid,
     result: IdempotencyResult,
     expires_at: Instant,
+    /// Insertion sequence, used to tell stale `order` slots from live ones
+    seq: u64,
+}
+
+/// Entries plus their insertion order, oldest first
+#[derive(Default)]
+struct CacheInner {
+    entries: HashMap<String, CacheEntry>,
+    order: VecDeque<(u64, String)>,
+    next_seq: u64,
+    hits: u64,
+    misses: u64,
+}
+
+impl CacheInner {
+    fn insert(&mut self, key: String, intent_id: Uuid, result: IdempotencyResult, ttl: Duration) {
+        let seq = self.next_seq;
+        self.next_seq += 1;
+        self.order.push_back((seq, key.clone()));
+        self.entries.insert(
+            key,
+            CacheEntry {
+                intent_id,
+                result,
+                expires_at: Instant::now() + ttl,
+                seq,
+            },
+        );
+    }
+
+    /// Evict the oldest entries until at most `max_entries` remain
+    fn evict_to(&mut self, max_entries: usize) {
+        while self.entries.len() > max_entries {
+            let Some((seq, key)) = self.order.pop_front() else {
+                break;
+            };
+            // Keys re-stored since this slot was queued have a newer seq
+            if self.entries.get(&key).map(|e| e.seq) == Some(seq) {
+                self.entries.remove(&key);
+            }
+        }
+    }
 }
 
 /// Result of an idempotent operation
@@ -18,18 +64,35 @@ pub enum IdempotencyResult {
     Failed { error: String },
 }
 
-/// Idempotency cache with TTL
+/// Snapshot of the cache for debugging replayed intents
+#[derive(Debug, Clone, Serialize)]
+pub struct IdempotencyStats {
+    pub entries: usize,
+    pub hits: u64,
+    pub misses: u64,
+    /// Age of the oldest live entry, if any
+    pub oldest_age_secs: Option<u64>,
+}
+
+/// Idempotency cache with TTL and a max-entries cap that evicts the oldest keys
 pub struct IdempotencyCache {
-    cache: Mutex<HashMap<String, CacheEntry>>,
+    cache: Mutex<CacheInner>,
     ttl: Duration,
+    max_entries: usize,
 }
 
 impl IdempotencyCache {
     /// Create a new idempotency cache with the specified TTL
     pub fn new(ttl: Duration) -> Self {
+        Self::with_max_entries(ttl, DEFAULT_MAX_ENTRIES)
+    }
+
+    /// Create a new idempotency cache with the specified TTL and size cap
+    pub fn with_max_entries(ttl: Duration, max_entries: usize) -> Self {
         Self {
-            cache: Mutex::new(HashMap::new()),
+            cache: Mutex::new(CacheInner::default()),
             ttl,
+            max_entries,
         }
     }
 
@@ -37,59 +100,86 @@ impl IdempotencyCache {
     /// Returns Some(intent_id) if found and not expired, None otherwise
     pub fn check(&self, key: &str) -> Option<(Uuid, IdempotencyResult)> {
         let mut cache = self.cache.lock().unwrap();
-        
-        // Clean up expired entries while we're here
-        let now = Instant::now();
-        cache.retain(|_, entry| entry.expires_at > now);
-        
-        // Check if key exists and is not expired
-        if let Some(entry) = cache.get(key) {
-            if entry.expires_at > now {
-                return Some((entry.intent_id, entry.result.clone()));
+
+        let cached = match cache.entries.get(key) {
+            Some(entry) if entry.expires_at > Instant::now() => {
+                Some((entry.intent_id, entry.result.clone()))
             }
+            Some(_) => {
+                cache.entries.remove(key);
+                None
+            }
+            None => None,
+        };
+
+        if cached.is_some() {
+            cache.hits += 1;
+        } else {
+            cache.misses += 1;
         }
-        
-        None
+        cached
     }
 
     /// Store a successful result for an idempotency key
     pub fn store_success(&self, key: String, intent_id: Uuid) {
-        let mut cache = self.cache.lock().unwrap();
-        cache.insert(
-            key,
-            CacheEntry {
-                intent_id,
-                result: IdempotencyResult::Success,
-                expires_at: Instant::now() + self.ttl,
-            },
-        );
+        self.store(key, intent_id, IdempotencyResult::Success);
     }
 
     /// Store a failed result for an idempotency key
     pub fn store_failure(&self, key: String, intent_id: Uuid, error: String) {
+        self.store(key, intent_id, IdempotencyResult::Failed { error });
+    }
+
+    fn store(&self, key: String, intent_id: Uuid, result: IdempotencyResult) {
         let mut cache = self.cache.lock().unwrap();
-        cache.insert(
-            key,
-            CacheEntry {
-                intent_id,
-                result: IdempotencyResult::Failed { error },
-                expires_at: Instant::now() + self.ttl,
-            },
-        );
+        cache.insert(key, intent_id, result, self.ttl);
+        cache.evict_to(self.max_entries);
     }
 
-    /// Clear all entries (useful for testing)
-    #[allow(dead_code)]
-    pub fn clear(&self) {
+    /// Drop expired entries; called periodically so idle keys don't linger until the cap
+    pub fn purge_expired(&self) -> usize {
         let mut cache = self.cache.lock().unwrap();
-        cache.clear();
+        let now = Instant::now();
+        let before = cache.entries.len();
+        cache.entries.retain(|_, entry| entry.expires_at > now);
+        let CacheInner { entries, order, .. } = &mut *cache;
+        order.retain(|(seq, key)| entries.get(key).map(|e| e.seq) == Some(*seq));
+        before - entries.len()
+    }
+
+    /// Entry count, hit/miss counters and the age of the oldest entry
+    pub fn stats(&self) -> IdempotencyStats {
+        let cache = self.cache.lock().unwrap();
+        let now = Instant::now();
+        let oldest_age_secs = cache
+            .entries
+            .values()
+            .filter(|entry| entry.expires_at > now)
+            .map(|entry| entry.expires_at)
+            .min()
+            .map(|expires_at| {
+                let inserted_at = expires_at.checked_sub(self.ttl).unwrap_or(now);
+                now.saturating_duration_since(inserted_at).as_secs()
+            });
+
+        IdempotencyStats {
+            entries: cache.entries.len(),
+            hits: cache.hits,
+            misses: cache.misses,
+            oldest_age_secs,
+        }
+    }
+
+    /// Clear all entries and reset the hit/miss counters
+    pub fn clear(&self) {
+        *self.cache.lock().unwrap() = CacheInner::default();
     }
 
     /// Get the number of cached entries (useful for monitoring)
     #[allow(dead_code)]
     pub fn len(&self) -> usize {
         let cache = self.cache.lock().unwrap();
-        cache.len()
+        cache.entries.len()
     }
 }
 
@@ -158,4 +248,57 @@ mod tests {
             _ => panic!("Expected Failed result"),
         }
     }
+
+    #[test]
+    fn test_idempotency_cache_evicts_oldest_beyond_cap() {
+        let cache = IdempotencyCache::with_max_entries(Duration::from_secs(60), 3);
+        for i in 0..5 {
+            cache.store_success(format!("key-{}", i), Uuid::new_v4());
+        }
+
+        assert_eq!(cache.len(), 3);
+        assert!(cache.check("key-0").is_none());
+        assert!(cache.check("key-1").is_none());
+        for i in 2..5 {
+            assert!(cache.check(&format!("key-{}", i)).is_some());
+        }
+
+        // Re-storing a key makes it the newest
+        cache.store_success("key-2".to_string(), Uuid::new_v4());
+        cache.store_success("key-5".to_string(), Uuid::new_v4());
+        assert!(cache.check("key-2").is_some());
+        assert!(cache.check("key-3").is_none());
+    }
+
+    #[test]
+    fn test_idempotency_cache_purge_expired() {
+        let cache = IdempotencyCache::new(Duration::from_millis(50));
+        cache.store_success("old".to_string(), Uuid::new_v4());
+        thread::sleep(Duration::from_millis(80));
+        cache.store_success("new".to_string(), Uuid::new_v4());
+
+        assert_eq!(cache.purge_expired(), 1);
+        assert_eq!(cache.len(), 1);
+        assert!(cache.check("new").is_some());
+    }
+
+    #[test]
+    fn test_idempotency_cache_stats() {
+        let cache = IdempotencyCache::new(Duration::from_secs(60));
+        assert!(cache.check("missing").is_none());
+        cache.store_success("key".to_string(), Uuid::new_v4());
+        assert!(cache.check("key").is_some());
+        assert!(cache.check("key").is_some());
+
+        let stats = cache.stats();
+        assert_eq!(stats.entries, 1);
+        assert_eq!(stats.hits, 2);
+        assert_eq!(stats.misses, 1);
+        assert_eq!(stats.oldest_age_secs, Some(0));
+
+        cache.clear();
+        let stats = cache.stats();
+        assert_eq!((stats.entries, stats.hits, stats.misses), (0, 0, 0));
+        assert_eq!(stats.oldest_age_secs, None);
+    }
 }
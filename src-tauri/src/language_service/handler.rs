@@ -36,10 +36,22 @@ impl LanguageHandler {
     ) -> BladeResult<Option<BladeEventEnvelope>> {
         let service = self.service.clone();
         let event_payload = match intent {
-            LanguageIntent::IndexFile { file_path } => {
+            LanguageIntent::IndexFile {
+                file_path,
+                language,
+            } => {
+                let language = match language.as_deref() {
+                    Some(name) => Some(crate::tree_sitter::Language::from_name(name).ok_or_else(
+                        || BladeError::ValidationError {
+                            field: "language".to_string(),
+                            message: format!("Unsupported language: {}", name),
+                        },
+                    )?),
+                    None => None,
+                };
                 let s = service.clone();
                 let f = file_path.clone();
-                let symbols = spawn_blocking(move || s.index_file(&f))
+                let symbols = spawn_blocking(move || s.index_file_as(&f, language))
                     .await
                     .map_err(|e| BladeError::Internal {
                         trace_id: Uuid::new_v4().to_string(),
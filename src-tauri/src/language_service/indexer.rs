@@ -5,11 +5,18 @@
 
 use std::collections::HashSet;
 use std::path::{Path, PathBuf};
-use std::sync::RwLock;
+use std::sync::mpsc::{self, SyncSender, TrySendError};
+use std::sync::{Arc, Mutex, RwLock};
 use std::time::{Duration, Instant};
 
+use super::LanguageService;
 use crate::tree_sitter::Language;
 
+/// Worker threads re-indexing files reported by the fs watcher
+const INDEX_WORKERS: usize = 2;
+/// Pending re-index jobs before new ones are dropped
+const INDEX_QUEUE_CAPACITY: usize = 1024;
+
 /// Events emitted during indexing
 #[derive(Debug, Clone)]
 pub enum IndexEvent {
@@ -121,6 +128,12 @@ impl FileIndexer {
         Language::from_path(path).is_some()
     }
 
+    /// Check if a watcher path should be (re-)indexed: a supported file
+    /// outside hidden, dependency and build directories
+    pub fn should_index(&self, rel_path: &str) -> bool {
+        self.is_supported(rel_path) && !rel_path.split('/').any(Self::should_ignore)
+    }
+
     /// Check if a file change should be processed (debouncing)
     pub fn should_process(&self, path: &str) -> bool {
         let mut changes = self.last_changes.write().unwrap();
@@ -178,6 +191,85 @@ impl FileIndexer {
     }
 }
 
+/// Bounded worker pool that keeps the symbol index in sync with watcher
+/// events. A queued file is only read when its job runs, so repeated events
+/// for it collapse into one job; jobs beyond the queue capacity are dropped
+/// and left for the next workspace index.
+pub struct IndexQueue {
+    /// Tracks queued paths through `start_indexing`/`finish_indexing`
+    indexer: Arc<FileIndexer>,
+    sender: SyncSender<String>,
+}
+
+impl IndexQueue {
+    /// Start the worker pool; workers exit once the queue is dropped
+    pub fn start(service: Arc<LanguageService>) -> Self {
+        Self::with_workers(service, INDEX_WORKERS, INDEX_QUEUE_CAPACITY)
+    }
+
+    fn with_workers(service: Arc<LanguageService>, workers: usize, capacity: usize) -> Self {
+        let indexer = Arc::new(FileIndexer::new(service.workspace_root()));
+        let (sender, receiver) = mpsc::sync_channel::<String>(capacity);
+        let receiver = Arc::new(Mutex::new(receiver));
+
+        for _ in 0..workers {
+            let receiver = receiver.clone();
+            let indexer = indexer.clone();
+            let service = service.clone();
+            std::thread::spawn(move || loop {
+                let Ok(rel_path) = receiver.lock().unwrap().recv() else {
+                    break;
+                };
+                // Unqueue before reading so a change made mid-parse queues a new job
+                indexer.finish_indexing(&rel_path);
+                sync_file(&service, &indexer, &rel_path);
+            });
+        }
+
+        Self { indexer, sender }
+    }
+
+    /// Queue a created, modified or removed file. Returns false when the
+    /// path isn't indexable or the queue is full.
+    pub fn enqueue(&self, abs_path: &Path) -> bool {
+        let Some(rel_path) = self.indexer.to_relative(abs_path) else {
+            return false;
+        };
+        if !self.indexer.should_index(&rel_path) {
+            return false;
+        }
+        if !self.indexer.start_indexing(&rel_path) {
+            // Already queued; the pending job will read the latest content
+            return true;
+        }
+
+        match self.sender.try_send(rel_path) {
+            Ok(()) => true,
+            Err(TrySendError::Full(rel_path)) => {
+                eprintln!("[INDEXER] Queue full, skipping re-index of {}", rel_path);
+                self.indexer.finish_indexing(&rel_path);
+                false
+            }
+            Err(TrySendError::Disconnected(rel_path)) => {
+                self.indexer.finish_indexing(&rel_path);
+                false
+            }
+        }
+    }
+}
+
+/// Re-index a file that still exists, or drop it from the index
+fn sync_file(service: &LanguageService, indexer: &FileIndexer, rel_path: &str) {
+    let result = if indexer.to_absolute(rel_path).is_file() {
+        service.index_file(rel_path).map(|_| ())
+    } else {
+        service.remove_file(rel_path).map(|_| ())
+    };
+    if let Err(e) = result {
+        eprintln!("[INDEXER] Failed to sync {}: {}", rel_path, e);
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -263,4 +355,45 @@ mod tests {
         assert!(!indexer.is_indexing("test.ts"));
         assert!(indexer.start_indexing("test.ts"));
     }
+
+    #[test]
+    fn test_should_index() {
+        let dir = TempDir::new().unwrap();
+        let indexer = FileIndexer::new(dir.path().to_path_buf());
+
+        assert!(indexer.should_index("src/main.ts"));
+        assert!(!indexer.should_index("target/debug/build.rs"));
+        assert!(!indexer.should_index("node_modules/pkg/index.js"));
+        assert!(!indexer.should_index("src/notes.md"));
+    }
+
+    #[test]
+    fn test_index_queue_syncs_changes() {
+        use crate::symbol_index::store::SymbolStore;
+
+        let dir = create_test_workspace();
+        let store = Arc::new(SymbolStore::in_memory().unwrap());
+        let service = Arc::new(LanguageService::new(dir.path().to_path_buf(), store).unwrap());
+        let queue = IndexQueue::with_workers(service.clone(), 2, 16);
+
+        let wait_for = |expect_symbols: bool| {
+            for _ in 0..200 {
+                let found = !service.get_file_symbols("main.ts").unwrap().is_empty();
+                if found == expect_symbols {
+                    return true;
+                }
+                std::thread::sleep(Duration::from_millis(10));
+            }
+            false
+        };
+
+        assert!(queue.enqueue(&dir.path().join("main.ts")));
+        assert!(wait_for(true));
+
+        fs::remove_file(dir.path().join("main.ts")).unwrap();
+        assert!(queue.enqueue(&dir.path().join("main.ts")));
+        assert!(wait_for(false));
+
+        assert!(!queue.enqueue(&dir.path().join("node_modules/package.ts")));
+    }
 }
@@ -11,7 +11,9 @@
 pub mod handler;
 mod indexer;
 mod service;
+mod status;
 
 pub use handler::LanguageHandler;
-pub use indexer::{FileIndexer, IndexEvent};
+pub use indexer::{FileIndexer, IndexEvent, IndexQueue};
 pub use service::LanguageService;
+pub use status::{IndexState, IndexStatus};
@@ -5,8 +5,9 @@
 
 use std::collections::HashMap;
 use std::path::{Path, PathBuf};
-use std::sync::{Arc, Mutex, RwLock};
+use std::sync::{Arc, Mutex, RwLock, RwLockReadGuard};
 
+use super::status::{IndexStatus, IndexTracker};
 use crate::gitignore_filter::GitignoreFilter;
 use crate::project_settings;
 use crate::symbol_index::{SearchQuery, SearchResult, SymbolStore};
@@ -15,7 +16,10 @@ use crate::tree_sitter::{extract_symbols, Language, Symbol, SymbolType, TreeSitt
 /// Unified language service
 pub struct LanguageService {
     /// Workspace root path
-    workspace_root: PathBuf,
+    workspace_root: RwLock<PathBuf>,
+    /// Bumped on workspace switch; work started under an older generation
+    /// is cancelled and never writes to the store
+    generation: RwLock<u64>,
     /// Tree-sitter parser for AST analysis
     parser: Mutex<TreeSitterParser>,
     /// Symbol index for persistent storage
@@ -23,6 +27,8 @@ pub struct LanguageService {
 
     /// In-memory cache of recently parsed files
     file_cache: RwLock<HashMap<String, CachedFile>>,
+    /// Progress of the current/last workspace index
+    index_status: IndexTracker,
 }
 
 /// Cached file data
@@ -42,6 +48,8 @@ pub enum LanguageError {
 
     Io(std::io::Error),
     NotSupported(String),
+    /// The workspace changed while the operation was running
+    Cancelled,
 }
 
 impl std::fmt::Display for LanguageError {
@@ -52,6 +60,7 @@ impl std::fmt::Display for LanguageError {
 
             LanguageError::Io(e) => write!(f, "IO error: {}", e),
             LanguageError::NotSupported(msg) => write!(f, "Not supported: {}", msg),
+            LanguageError::Cancelled => write!(f, "Cancelled by workspace change"),
         }
     }
 }
@@ -79,11 +88,13 @@ impl LanguageService {
         let parser = TreeSitterParser::new().map_err(|e| LanguageError::Parse(e.to_string()))?;
 
         Ok(Self {
-            workspace_root,
+            workspace_root: RwLock::new(workspace_root),
+            generation: RwLock::new(0),
             parser: Mutex::new(parser),
             symbol_store,
 
             file_cache: RwLock::new(HashMap::new()),
+            index_status: IndexTracker::default(),
         })
     }
 
@@ -93,9 +104,39 @@ impl LanguageService {
 
     /// Index a single file
     pub fn index_file(&self, file_path: &str) -> Result<Vec<Symbol>, LanguageError> {
+        self.index_file_as(file_path, None)
+    }
+
+    /// Index a single file, parsing it as `language` when given instead of
+    /// detecting the language from its path and first line
+    pub fn index_file_as(
+        &self,
+        file_path: &str,
+        language: Option<Language>,
+    ) -> Result<Vec<Symbol>, LanguageError> {
+        self.index_file_in(self.generation(), file_path, language)
+    }
+
+    fn index_file_in(
+        &self,
+        generation: u64,
+        file_path: &str,
+        language: Option<Language>,
+    ) -> Result<Vec<Symbol>, LanguageError> {
         let full_path = self.resolve_path(file_path);
         let content = std::fs::read_to_string(&full_path)?;
-        let hash = compute_hash(&content);
+
+        // Resolve the language first: symbols cached from parsing the file as
+        // another language must not be returned for an override
+        let detected = Language::detect(file_path, content.lines().next());
+        let language = language.or(detected).ok_or_else(|| {
+            LanguageError::NotSupported(format!("Unknown language for: {}", file_path))
+        })?;
+        let hash = if Some(language) == detected {
+            compute_hash(&content)
+        } else {
+            compute_hash(&format!("{:?}\0{}", language, content))
+        };
 
         // Check if reindexing is needed
         if !self.symbol_store.needs_reindex(file_path, &hash)? {
@@ -103,11 +144,6 @@ impl LanguageService {
             return Ok(self.symbol_store.get_symbols_in_file(file_path)?);
         }
 
-        // Detect language and parse
-        let language = Language::from_path(file_path).ok_or_else(|| {
-            LanguageError::NotSupported(format!("Unknown language for: {}", file_path))
-        })?;
-
         let tree = {
             let mut parser = self.parser.lock().unwrap();
             parser
@@ -119,6 +155,7 @@ impl LanguageService {
         let symbols = extract_symbols(&tree, &content, language, file_path);
 
         // Delete old symbols and insert new ones
+        let _generation = self.lock_generation(generation)?;
         self.symbol_store.delete_file_symbols(file_path)?;
         self.symbol_store.upsert_symbols(&symbols)?;
         self.symbol_store
@@ -144,8 +181,111 @@ impl LanguageService {
         Ok(symbols)
     }
 
+    /// Drop a deleted file from the index and cache
+    pub fn remove_file(&self, file_path: &str) -> Result<usize, LanguageError> {
+        self.file_cache.write().unwrap().remove(file_path);
+        Ok(self.symbol_store.remove_file(file_path)?)
+    }
+
     /// Index an entire directory recursively
     pub fn index_directory(&self, dir_path: &str) -> Result<IndexStats, LanguageError> {
+        self.index_directory_with_progress(dir_path, |_, _| {})
+    }
+
+    /// Index a directory, calling `on_progress(done, total)` at a throttled
+    /// rate and once more at the end. Fails if another run is in progress.
+    pub fn index_directory_with_progress(
+        &self,
+        dir_path: &str,
+        on_progress: impl FnMut(usize, usize),
+    ) -> Result<IndexStats, LanguageError> {
+        self.run_tracked_index(dir_path, false, on_progress)
+    }
+
+    /// Drop every indexed symbol and index the workspace from scratch
+    pub fn rebuild_index(
+        &self,
+        on_progress: impl FnMut(usize, usize),
+    ) -> Result<IndexStats, LanguageError> {
+        self.run_tracked_index(".", true, on_progress)
+    }
+
+    /// Status of the current/last workspace index run
+    pub fn index_status(&self) -> IndexStatus {
+        self.index_status.status()
+    }
+
+    fn run_tracked_index(
+        &self,
+        dir_path: &str,
+        rebuild: bool,
+        mut on_progress: impl FnMut(usize, usize),
+    ) -> Result<IndexStats, LanguageError> {
+        let generation = self.generation();
+        if !self.index_status.start(generation) {
+            return Err(LanguageError::Index(
+                "workspace indexing already in progress".to_string(),
+            ));
+        }
+
+        let cleared = if rebuild {
+            self.clear_index(generation)
+        } else {
+            Ok(())
+        };
+        let result = cleared
+            .and_then(|()| self.index_directory_inner(generation, dir_path, &mut on_progress));
+        let error = match &result {
+            Ok(_) | Err(LanguageError::Cancelled) => None,
+            Err(e) => Some(e.to_string()),
+        };
+        self.index_status.finish(generation, error);
+        result
+    }
+
+    fn clear_index(&self, generation: u64) -> Result<(), LanguageError> {
+        let _generation = self.lock_generation(generation)?;
+        self.symbol_store.clear()?;
+        self.file_cache.write().unwrap().clear();
+        Ok(())
+    }
+
+    /// Point the service at another workspace. Cancels in-flight indexing
+    /// and clears the previous workspace's symbols; returns false if `root`
+    /// is already the current workspace.
+    pub fn set_workspace_root(&self, root: PathBuf) -> Result<bool, LanguageError> {
+        let mut generation = self.generation.write().unwrap();
+        if *self.workspace_root.read().unwrap() == root {
+            return Ok(false);
+        }
+
+        *generation += 1;
+        *self.workspace_root.write().unwrap() = root;
+        self.file_cache.write().unwrap().clear();
+        self.symbol_store.clear()?;
+        Ok(true)
+    }
+
+    fn generation(&self) -> u64 {
+        *self.generation.read().unwrap()
+    }
+
+    /// Hold the generation steady for a store write, failing if the
+    /// workspace changed since `generation` was read
+    fn lock_generation(&self, generation: u64) -> Result<RwLockReadGuard<'_, u64>, LanguageError> {
+        let current = self.generation.read().unwrap();
+        if *current != generation {
+            return Err(LanguageError::Cancelled);
+        }
+        Ok(current)
+    }
+
+    fn index_directory_inner(
+        &self,
+        generation: u64,
+        dir_path: &str,
+        on_progress: &mut impl FnMut(usize, usize),
+    ) -> Result<IndexStats, LanguageError> {
         let full_path = self.resolve_path(dir_path);
         let mut stats = IndexStats::default();
         let start = std::time::Instant::now();
@@ -153,7 +293,33 @@ impl LanguageService {
         // Create gitignore filter if enabled
         let gitignore_filter = self.create_gitignore_filter();
 
-        self.index_directory_recursive(&full_path, "", &mut stats, gitignore_filter.as_ref())?;
+        // Discover first so progress has a total to report against
+        let mut files = Vec::new();
+        self.collect_index_files(&full_path, "", &mut files, gitignore_filter.as_ref())?;
+        let total = files.len();
+        self.index_status.set_total(generation, total);
+
+        for (done, relative) in files.iter().enumerate() {
+            if self.generation() != generation {
+                eprintln!("[LanguageService] Indexing cancelled by workspace change");
+                return Err(LanguageError::Cancelled);
+            }
+            match self.index_file_in(generation, relative, None) {
+                Ok(symbols) => {
+                    stats.files_indexed += 1;
+                    stats.symbols_extracted += symbols.len();
+                }
+                Err(LanguageError::Cancelled) => return Err(LanguageError::Cancelled),
+                Err(e) => {
+                    stats.files_failed += 1;
+                    eprintln!("[LanguageService] Failed to index {}: {}", relative, e);
+                }
+            }
+            if self.index_status.file_done(generation) {
+                on_progress(done + 1, total);
+            }
+        }
+        on_progress(total, total);
 
         stats.duration_ms = start.elapsed().as_millis() as u64;
         eprintln!(
@@ -166,7 +332,8 @@ impl LanguageService {
 
     /// Create a GitignoreFilter if gitignore filtering is enabled
     fn create_gitignore_filter(&self) -> Option<GitignoreFilter> {
-        let settings = project_settings::load_project_settings_or_default(&self.workspace_root);
+        let workspace_root = self.workspace_root();
+        let settings = project_settings::load_project_settings_or_default(&workspace_root);
 
         // If allow_gitignored_files is true, don't create a filter (allow all files)
         if settings.allow_gitignored_files {
@@ -175,19 +342,19 @@ impl LanguageService {
         }
 
         // Create filter to respect .gitignore
-        let filter = GitignoreFilter::new(&self.workspace_root);
+        let filter = GitignoreFilter::new(&workspace_root);
         eprintln!(
             "[LanguageService] Gitignore filtering enabled for workspace: {}",
-            self.workspace_root.display()
+            workspace_root.display()
         );
         Some(filter)
     }
 
-    fn index_directory_recursive(
+    fn collect_index_files(
         &self,
         base_path: &Path,
         relative_path: &str,
-        stats: &mut IndexStats,
+        files: &mut Vec<String>,
         gitignore_filter: Option<&GitignoreFilter>,
     ) -> Result<(), LanguageError> {
         let dir_path = if relative_path.is_empty() {
@@ -224,21 +391,9 @@ impl LanguageService {
             };
 
             if path.is_dir() {
-                self.index_directory_recursive(base_path, &relative, stats, gitignore_filter)?;
-            } else if path.is_file() {
-                // Check if it's a supported language
-                if Language::from_path(&relative).is_some() {
-                    match self.index_file(&relative) {
-                        Ok(symbols) => {
-                            stats.files_indexed += 1;
-                            stats.symbols_extracted += symbols.len();
-                        }
-                        Err(e) => {
-                            stats.files_failed += 1;
-                            eprintln!("[LanguageService] Failed to index {}: {}", relative, e);
-                        }
-                    }
-                }
+                self.collect_index_files(base_path, &relative, files, gitignore_filter)?;
+            } else if path.is_file() && Language::from_path(&relative).is_some() {
+                files.push(relative);
             }
         }
 
@@ -337,11 +492,16 @@ impl LanguageService {
     // Helper Methods
     // =========================================================================
 
+    /// Root that relative index paths are resolved against
+    pub fn workspace_root(&self) -> PathBuf {
+        self.workspace_root.read().unwrap().clone()
+    }
+
     pub fn resolve_path(&self, file_path: &str) -> PathBuf {
         if Path::new(file_path).is_absolute() {
             PathBuf::from(file_path)
         } else {
-            self.workspace_root.join(file_path)
+            self.workspace_root.read().unwrap().join(file_path)
         }
     }
 
@@ -350,6 +510,7 @@ impl LanguageService {
         file_path: &str,
         content: &str,
     ) -> Result<Vec<Symbol>, LanguageError> {
+        let generation = self.generation();
         let hash = compute_hash(content);
 
         // Check cache first
@@ -363,7 +524,7 @@ impl LanguageService {
         }
 
         // Detect language and parse
-        let language = Language::from_path(file_path).ok_or_else(|| {
+        let language = Language::detect(file_path, content.lines().next()).ok_or_else(|| {
             LanguageError::NotSupported(format!("Unknown language for: {}", file_path))
         })?;
 
@@ -378,6 +539,7 @@ impl LanguageService {
         let symbols = extract_symbols(&tree, content, language, file_path);
 
         // Delete old symbols and insert new ones
+        let _generation = self.lock_generation(generation)?;
         self.symbol_store.delete_file_symbols(file_path)?;
         self.symbol_store.upsert_symbols(&symbols)?;
         self.symbol_store
@@ -471,6 +633,27 @@ mod tests {
         assert!(symbols.iter().any(|s| s.name == "UserService"));
     }
 
+    #[test]
+    fn test_language_override_bypasses_cached_symbols() {
+        let (service, temp_dir) = create_test_service();
+        fs::write(
+            temp_dir.path().join("greet.py"),
+            "def greet():\n    return 1\n",
+        )
+        .unwrap();
+
+        let symbols = service.index_file("greet.py").unwrap();
+        assert!(symbols.iter().any(|s| s.name == "greet"));
+
+        let symbols = service
+            .index_file_as("greet.py", Some(Language::Rust))
+            .unwrap();
+        assert!(!symbols.iter().any(|s| s.name == "greet"));
+
+        let symbols = service.index_file("greet.py").unwrap();
+        assert!(symbols.iter().any(|s| s.name == "greet"));
+    }
+
     #[test]
     fn test_search_symbols() {
         let (service, temp_dir) = create_test_service();
@@ -513,4 +696,50 @@ mod tests {
         assert_eq!(stats.files_indexed, 1);
         assert!(stats.symbols_extracted > 0);
     }
+
+    #[test]
+    fn test_workspace_switch_cancels_indexing() {
+        let (service, temp_dir) = create_test_service();
+        for i in 0..20 {
+            let source = format!("function old{}() {{}}", i);
+            fs::write(temp_dir.path().join(format!("old{}.ts", i)), source).unwrap();
+        }
+        let next_workspace = TempDir::new().unwrap();
+        fs::write(next_workspace.path().join("new.ts"), "function fresh() {}").unwrap();
+
+        // Switch workspaces as soon as the first file has been indexed
+        let mut switched = false;
+        let result = service.index_directory_with_progress(".", |_, _| {
+            if !switched {
+                switched = service
+                    .set_workspace_root(next_workspace.path().to_path_buf())
+                    .unwrap();
+            }
+        });
+
+        assert!(matches!(result, Err(LanguageError::Cancelled)));
+        assert_eq!(
+            service.stats().unwrap().files_indexed,
+            0,
+            "the old run must not write after the switch"
+        );
+
+        let stats = service.index_directory(".").unwrap();
+        assert_eq!(stats.files_indexed, 1);
+        assert_eq!(service.search_symbols("fresh", 10).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_remove_file() {
+        let (service, temp_dir) = create_test_service();
+
+        fs::write(temp_dir.path().join("gone.ts"), "function gone() {}").unwrap();
+        service.index_file("gone.ts").unwrap();
+        assert!(service.remove_file("gone.ts").unwrap() > 0);
+        assert!(service.get_file_symbols("gone.ts").unwrap().is_empty());
+
+        // Re-creating it with identical content must index it again
+        let symbols = service.index_file("gone.ts").unwrap();
+        assert!(symbols.iter().any(|s| s.name == "gone"));
+    }
 }
@@ -0,0 +1,202 @@
+//! Workspace indexing status
+//!
+//! Tracks progress of a full workspace index so the UI can tell whether
+//! symbol search is ready or still warming up.
+
+use serde::Serialize;
+use std::sync::Mutex;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+/// Minimum interval between progress reports
+const REPORT_INTERVAL: Duration = Duration::from_millis(250);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum IndexState {
+    Idle,
+    Indexing,
+    Error,
+}
+
+/// Snapshot returned by the `index_status` command
+#[derive(Debug, Clone, Serialize)]
+pub struct IndexStatus {
+    pub state: IndexState,
+    pub files_total: usize,
+    pub files_done: usize,
+    /// Unix millis when the current/last run started
+    pub started_at: Option<u64>,
+    pub eta_secs: Option<u64>,
+    pub error: Option<String>,
+}
+
+#[derive(Debug)]
+struct Run {
+    /// Workspace generation the run belongs to
+    generation: u64,
+    state: IndexState,
+    files_total: usize,
+    files_done: usize,
+    started_at: Option<(u64, Instant)>,
+    last_report: Option<Instant>,
+    error: Option<String>,
+}
+
+/// Progress of the current (or last) workspace index run
+#[derive(Debug)]
+pub struct IndexTracker {
+    run: Mutex<Run>,
+}
+
+impl Default for IndexTracker {
+    fn default() -> Self {
+        Self {
+            run: Mutex::new(Run {
+                generation: 0,
+                state: IndexState::Idle,
+                files_total: 0,
+                files_done: 0,
+                started_at: None,
+                last_report: None,
+                error: None,
+            }),
+        }
+    }
+}
+
+impl IndexTracker {
+    /// Begin a run; returns false if one is already in progress for the
+    /// same workspace generation. A run from an older generation is superseded.
+    pub fn start(&self, generation: u64) -> bool {
+        let mut run = self.run.lock().unwrap();
+        if run.state == IndexState::Indexing && run.generation == generation {
+            return false;
+        }
+
+        let now_ms = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_millis() as u64;
+        *run = Run {
+            generation,
+            state: IndexState::Indexing,
+            files_total: 0,
+            files_done: 0,
+            started_at: Some((now_ms, Instant::now())),
+            last_report: None,
+            error: None,
+        };
+        true
+    }
+
+    /// Set the number of files discovered for this run
+    pub fn set_total(&self, generation: u64, total: usize) {
+        let mut run = self.run.lock().unwrap();
+        if run.generation == generation {
+            run.files_total = total;
+        }
+    }
+
+    /// Record one processed file; returns true when a throttled progress
+    /// report is due
+    pub fn file_done(&self, generation: u64) -> bool {
+        let mut run = self.run.lock().unwrap();
+        if run.generation != generation {
+            return false;
+        }
+        run.files_done += 1;
+
+        let due = run
+            .last_report
+            .is_none_or(|last| last.elapsed() >= REPORT_INTERVAL);
+        if due {
+            run.last_report = Some(Instant::now());
+        }
+        due
+    }
+
+    /// End the run, recording the error if it failed. Ignored when a newer
+    /// run has superseded it.
+    pub fn finish(&self, generation: u64, error: Option<String>) {
+        let mut run = self.run.lock().unwrap();
+        if run.generation != generation {
+            return;
+        }
+        run.state = if error.is_some() {
+            IndexState::Error
+        } else {
+            IndexState::Idle
+        };
+        run.error = error;
+    }
+
+    pub fn status(&self) -> IndexStatus {
+        let run = self.run.lock().unwrap();
+        let eta_secs = match (run.state, run.started_at) {
+            (IndexState::Indexing, Some((_, started))) if run.files_done > 0 => {
+                let remaining = run.files_total.saturating_sub(run.files_done) as f64;
+                let per_file = started.elapsed().as_secs_f64() / run.files_done as f64;
+                Some((remaining * per_file).ceil() as u64)
+            }
+            _ => None,
+        };
+
+        IndexStatus {
+            state: run.state,
+            files_total: run.files_total,
+            files_done: run.files_done,
+            started_at: run.started_at.map(|(ms, _)| ms),
+            eta_secs,
+            error: run.error.clone(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tracker_lifecycle() {
+        let tracker = IndexTracker::default();
+        assert_eq!(tracker.status().state, IndexState::Idle);
+
+        assert!(tracker.start(0));
+        assert!(
+            !tracker.start(0),
+            "a second run must not start concurrently"
+        );
+        tracker.set_total(0, 3);
+
+        // First file reports immediately, the next one is throttled
+        assert!(tracker.file_done(0));
+        assert!(!tracker.file_done(0));
+
+        let status = tracker.status();
+        assert_eq!(status.state, IndexState::Indexing);
+        assert_eq!((status.files_done, status.files_total), (2, 3));
+        assert!(status.started_at.is_some());
+        assert!(status.eta_secs.is_some());
+
+        tracker.finish(0, Some("disk full".to_string()));
+        let status = tracker.status();
+        assert_eq!(status.state, IndexState::Error);
+        assert_eq!(status.eta_secs, None);
+        assert!(tracker.start(0));
+    }
+
+    #[test]
+    fn test_tracker_superseded_run() {
+        let tracker = IndexTracker::default();
+        assert!(tracker.start(0));
+        // A workspace switch starts a new run while the old one winds down
+        assert!(tracker.start(1));
+        assert!(!tracker.file_done(0));
+        tracker.finish(0, None);
+        assert_eq!(tracker.status().files_done, 0);
+        assert_eq!(tracker.status().state, IndexState::Indexing);
+
+        tracker.finish(1, None);
+        assert_eq!(tracker.status().state, IndexState::Idle);
+    }
+}
@@ -1,6 +1,7 @@
 pub mod agentic_loop;
 pub mod ai_workflow;
 pub mod app_state;
+pub mod at_commands;
 pub mod blade_client;
 pub mod blade_protocol;
 pub mod blade_ws_client;
@@ -13,6 +14,7 @@ pub mod context_assembly;
 pub mod conversation;
 pub mod core_state;
 pub mod conversation_store;
+pub mod encoding;
 pub mod environment;
 pub mod ephemeral_commands;
 pub mod feature_flags;
@@ -36,6 +38,7 @@ pub mod project_state;
 pub mod protocol;
 pub mod protocol_dispatcher;
 pub mod reasoning_parser;
+pub mod redaction;
 pub mod semantic_patch;
 pub mod symbol_index;
 pub mod screenshot;
@@ -106,51 +109,34 @@ pub fn run() {
             crate::fs_watcher::restart_fs_watcher(&app.handle());
 
             // Background workspace indexing
-            let app_handle = app.handle().clone();
-            std::thread::spawn(move || {
-                let state = app_handle.state::<AppState>();
-                // Check if we have a workspace set
-                let workspace = state.workspace.lock().unwrap().workspace.clone();
-
-                if let Some(path) = workspace {
-                    let path_str = path.to_string_lossy().to_string();
-                    eprintln!(
-                        "[LanguageService] Triggering startup indexing for: {}",
-                        path_str
-                    );
-                    let service = state.language_service.clone();
-
-                    match service.index_directory(".") {
-                        Ok(stats) => {
-                            eprintln!(
-                                "[LanguageService] Startup indexing complete: {} files in {}ms",
-                                stats.files_indexed, stats.duration_ms
-                            );
-                        }
-                        Err(e) => {
-                            eprintln!("[LanguageService] Startup indexing failed: {}", e);
-                        }
-                    }
-                }
-            });
+            let workspace = app
+                .state::<AppState>()
+                .workspace
+                .lock()
+                .unwrap()
+                .workspace
+                .clone();
+            if let Some(path) = workspace {
+                eprintln!(
+                    "[LanguageService] Triggering startup indexing for: {}",
+                    path.display()
+                );
+                crate::commands::index::spawn_workspace_index(app.handle(), false);
+            }
 
             // Background IndexerManager initialization (non-blocking)
-            let app_handle_indexer = app.handle().clone();
-            std::thread::spawn(move || {
-                let state = app_handle_indexer.state::<AppState>();
-                let workspace = state.workspace.lock().unwrap().workspace.clone();
+            crate::commands::index::spawn_project_indexer(app.handle());
 
-                if let Some(path) = workspace {
-                    eprintln!("[Indexer] Starting background initialization for {:?}", path);
-                    match crate::indexer::IndexerManager::new(&path) {
-                        Ok(manager) => {
-                            eprintln!("[Indexer] Initialized with {} files", manager.file_count());
-                            *state.indexer_manager.lock().unwrap() = Some(manager);
-                        }
-                        Err(e) => {
-                            eprintln!("[Indexer] Failed to initialize: {}", e);
-                        }
-                    }
+            // Periodically drop expired idempotency keys
+            let app_handle_idempotency = app.handle().clone();
+            std::thread::spawn(move || loop {
+                std::thread::sleep(std::time::Duration::from_secs(10 * 60));
+                let purged = app_handle_idempotency
+                    .state::<AppState>()
+                    .idempotency_cache
+                    .purge_expired();
+                if purged > 0 {
+                    eprintln!("[Idempotency] Purged {} expired keys", purged);
                 }
             });
 
@@ -174,15 +160,26 @@ pub fn run() {
             // Files
             commands::files::open_workspace,
             commands::files::list_files,
+            commands::files::list_directory_depth,
+            commands::files::add_watch_path,
+            commands::files::remove_watch_path,
             commands::files::read_file_content,
             commands::files::write_file_content,
+            commands::files::get_file_encoding,
+            commands::files::get_file_outline,
+            commands::files::diff_files,
+            commands::index::index_status,
+            commands::index::reindex_workspace,
             commands::files::open_file_in_editor,
             // Project
             commands::project::read_binary_file,
             commands::project::get_recent_workspaces,
             commands::project::get_current_workspace,
+            commands::project::is_workspace_trusted,
+            commands::project::trust_workspace,
             commands::project::load_project_state,
             commands::project::save_project_state,
+            commands::project::set_autosave_enabled,
             commands::project::graceful_shutdown_with_state,
             commands::project::get_project_state_path,
             commands::project::get_user_id,
@@ -200,25 +197,42 @@ pub fn run() {
             // Settings
             commands::settings::get_global_settings,
             commands::settings::save_global_settings,
+            commands::settings::reload_api_config,
+            commands::settings::get_reasoning_models,
+            commands::settings::set_reasoning_models,
             commands::settings::test_ollama_connection,
             commands::settings::refresh_ollama_models,
             commands::settings::test_openai_compat_connection,
             commands::settings::refresh_openai_compat_models,
             // Chat
             commands::chat::send_message,
+            commands::chat::send_message_sync,
             commands::chat::list_models,
+            commands::chat::refresh_models,
             commands::chat::get_conversation,
+            commands::chat::recover_context,
+            commands::chat::get_token_usage,
             commands::chat::list_conversations,
             commands::chat::load_conversation,
             commands::chat::new_conversation,
+            commands::chat::fork_conversation,
+            commands::chat::regenerate_response,
+            commands::chat::edit_and_resend,
             commands::chat::delete_conversation,
+            commands::chat::get_tool_log,
+            commands::chat::get_agentic_status,
+            commands::chat::reset_agentic_loop,
+            commands::chat::list_commands,
             commands::chat::save_conversation,
+            commands::chat::export_conversation_markdown,
+            commands::chat::import_conversation,
             commands::chat::set_selected_model,
             commands::chat::get_selected_model,
             // Tools & Changes
             commands::tools::submit_command_result,
             commands::tools::approve_tool_decision,
             commands::tools::approve_single_command,
+            commands::tools::list_tools,
             // History
             commands::history::get_file_history,
             commands::history::revert_file_to_snapshot,
@@ -237,16 +251,23 @@ pub fn run() {
             // Cache
             commands::cache::warmup_cache,
             commands::cache::should_rewarm_cache,
+            commands::cache::warmup_status,
             // Local Context
             commands::local_context::list_local_conversations,
             commands::local_context::load_local_conversation,
             commands::local_context::search_local_moments,
             commands::local_context::get_file_context,
             commands::local_context::delete_local_conversation,
+            commands::local_context::rebuild_local_index,
             // State (Headless Core)
             commands::state::get_core_state,
             commands::state::get_feature_flags,
             commands::state::set_feature_flag,
+            commands::state::set_read_only_mode,
+            commands::state::get_read_only_mode,
+            commands::terminal::get_terminal_scrollback,
+            commands::state::get_idempotency_stats,
+            commands::state::clear_idempotency_cache,
             // Git commands
             git::git_status_summary,
             git::git_status_files,
@@ -258,6 +279,7 @@ pub fn run() {
             git::git_commit_preflight,
             git::git_push,
             git::git_diff,
+            git::get_uncommitted_context,
             git::git_generate_commit_message,
             git::git_generate_commit_message_ai,
             // Ephemeral
@@ -266,8 +288,10 @@ pub fn run() {
             ephemeral_commands::update_ephemeral_document,
             ephemeral_commands::close_ephemeral_document,
             ephemeral_commands::list_ephemeral_documents,
+            ephemeral_commands::recover_ephemeral_documents,
             ephemeral_commands::save_ephemeral_document,
             ephemeral_commands::save_ephemeral_document_to_workspace,
+            ephemeral_commands::save_ephemeral_to_workspace,
             // Protocol Dispatcher
             protocol_dispatcher::dispatch,
         ])
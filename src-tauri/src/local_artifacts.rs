@@ -1,7 +1,9 @@
+use regex::Regex;
 use serde::{Deserialize, Serialize};
 use std::fs;
 use std::path::{Path, PathBuf};
 
+use crate::conversation_store::Page;
 use crate::local_index::{ConversationIndex, LocalIndex, MomentIndex, CodeReferenceIndex};
 use crate::project_settings::get_zblade_dir;
 
@@ -129,6 +131,16 @@ impl ConversationArtifact {
     }
 }
 
+/// Counts reported by `LocalArtifactStore::rebuild_index`
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct IndexRebuildStats {
+    pub conversations: usize,
+    pub moments: usize,
+    pub references: usize,
+    /// Artifact files that could not be read or parsed
+    pub skipped: usize,
+}
+
 /// Local artifact store for a project
 pub struct LocalArtifactStore {
     project_path: PathBuf,
@@ -178,7 +190,79 @@ impl LocalArtifactStore {
         // Update SQLite index
         let index = LocalIndex::open(&self.project_path)
             .map_err(|e| format!("Failed to open index: {}", e))?;
+        self.index_artifact(&index, artifact, &path)?;
         
+        Ok(())
+    }
+    
+    /// Wipe the SQLite index and repopulate it from every conversation artifact on disk,
+    /// re-extracting code references from message content
+    pub fn rebuild_index(&self) -> Result<IndexRebuildStats, String> {
+        let index = LocalIndex::open(&self.project_path)
+            .map_err(|e| format!("Failed to open index: {}", e))?;
+        index.clear_conversation_data()
+            .map_err(|e| format!("Failed to clear index: {}", e))?;
+        
+        let mut stats = IndexRebuildStats::default();
+        let entries = match fs::read_dir(self.conversations_dir()) {
+            Ok(entries) => entries,
+            // No artifacts yet: the empty index is already correct
+            Err(_) => return Ok(stats),
+        };
+        
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.extension().and_then(|e| e.to_str()) != Some("json") {
+                continue;
+            }
+            
+            let artifact = fs::read_to_string(&path)
+                .map_err(|e| e.to_string())
+                .and_then(|content| {
+                    serde_json::from_str::<ConversationArtifact>(&content)
+                        .map_err(|e| e.to_string())
+                });
+            let mut artifact = match artifact {
+                Ok(artifact) => artifact,
+                Err(e) => {
+                    eprintln!("[LOCAL] Skipping unreadable artifact {}: {}", path.display(), e);
+                    stats.skipped += 1;
+                    continue;
+                }
+            };
+            
+            for msg in &mut artifact.messages {
+                for code_ref in extract_code_references(&msg.content) {
+                    let known = msg
+                        .code_references
+                        .iter()
+                        .any(|r| r.file == code_ref.file && r.lines == code_ref.lines);
+                    if !known {
+                        msg.code_references.push(code_ref);
+                    }
+                }
+            }
+            
+            let (moments, references) = self.index_artifact(&index, &artifact, &path)?;
+            stats.conversations += 1;
+            stats.moments += moments;
+            stats.references += references;
+        }
+        
+        eprintln!(
+            "[LOCAL] Rebuilt index: {} conversations, {} moments, {} references ({} skipped)",
+            stats.conversations, stats.moments, stats.references, stats.skipped
+        );
+        Ok(stats)
+    }
+    
+    /// Index a conversation artifact, returning the (moments, references) indexed
+    fn index_artifact(
+        &self,
+        index: &LocalIndex,
+        artifact: &ConversationArtifact,
+        path: &Path,
+    ) -> Result<(usize, usize), String> {
         let conv_index = ConversationIndex {
             id: artifact.conversation_id.clone(),
             project_id: artifact.project_id.clone(),
@@ -193,7 +277,10 @@ impl LocalArtifactStore {
         index.upsert_conversation(&conv_index)
             .map_err(|e| format!("Failed to update index: {}", e))?;
         
-        // Index code references from messages
+        // Index code references from messages, replacing those from earlier saves
+        index.delete_references_for_conversation(&artifact.conversation_id)
+            .map_err(|e| format!("Failed to update index: {}", e))?;
+        let mut references = 0;
         for msg in &artifact.messages {
             for code_ref in &msg.code_references {
                 let ref_index = CodeReferenceIndex {
@@ -206,11 +293,14 @@ impl LocalArtifactStore {
                     context: code_ref.context.clone(),
                     created_at: msg.timestamp.clone(),
                 };
-                let _ = index.insert_code_reference(&ref_index);
+                if index.insert_code_reference(&ref_index).is_ok() {
+                    references += 1;
+                }
             }
         }
         
         // Index moments
+        let mut moments = 0;
         for moment in &artifact.moments {
             let moment_index = MomentIndex {
                 id: moment.id.clone(),
@@ -223,10 +313,12 @@ impl LocalArtifactStore {
                 relevance_score: moment.relevance_score,
                 artifact_path: self.moment_path(&moment.id).to_string_lossy().to_string(),
             };
-            let _ = index.upsert_moment(&moment_index);
+            if index.upsert_moment(&moment_index).is_ok() {
+                moments += 1;
+            }
         }
         
-        Ok(())
+        Ok((moments, references))
     }
     
     /// Load a conversation artifact from disk
@@ -272,6 +364,24 @@ impl LocalArtifactStore {
             .map_err(|e| format!("Failed to list conversations: {}", e))
     }
     
+    /// List a page of conversations from the index, most recently updated first
+    pub fn list_conversations_page(
+        &self,
+        offset: usize,
+        limit: Option<usize>,
+    ) -> Result<Page<ConversationIndex>, String> {
+        let index = LocalIndex::open(&self.project_path)
+            .map_err(|e| format!("Failed to open index: {}", e))?;
+        
+        let limit = limit.map(|l| l.min(i64::MAX as usize) as i64).unwrap_or(-1);
+        let items = index.list_conversations_page(offset.min(i64::MAX as usize) as i64, limit)
+            .map_err(|e| format!("Failed to list conversations: {}", e))?;
+        let total = index.conversation_count()
+            .map_err(|e| format!("Failed to count conversations: {}", e))?;
+        
+        Ok(Page { items, total })
+    }
+    
     /// Search moments using full-text search
     pub fn search_moments(&self, query: &str, limit: i32) -> Result<Vec<MomentIndex>, String> {
         let index = LocalIndex::open(&self.project_path)
@@ -291,6 +401,108 @@ impl LocalArtifactStore {
     }
 }
 
+lazy_static::lazy_static! {
+    /// Inline `path:line` or `path:start-end` mentions, e.g. `src/foo.rs:42`
+    static ref INLINE_REF_RE: Regex =
+        Regex::new(r"(?:^|[\s`'\x22(\[])((?:[\w.-]+/)*[\w.-]+\.[A-Za-z][A-Za-z0-9]*):(\d+)(?:-(\d+))?")
+            .unwrap();
+    /// A file path with an extension, optionally followed by `:line` or `:start-end`
+    static ref FENCE_PATH_RE: Regex =
+        Regex::new(r"^((?:[\w.-]+/)*[\w.-]+\.[A-Za-z][A-Za-z0-9]*)(?::(\d+)(?:-(\d+))?)?$").unwrap();
+}
+
+/// Extract code references from message content: fenced code blocks that declare a filename
+/// (e.g. ```` ```rust src/main.rs ```` or ```` ```rust:src/main.rs:10-20 ````) and inline
+/// `path:line` mentions. Fenced blocks without a line range cover the block's own line count.
+pub fn extract_code_references(content: &str) -> Vec<CodeReference> {
+    let mut refs = Vec::new();
+
+    // (path, explicit range, lines seen) for the fenced block being read
+    let mut open_fence: Option<(Option<String>, Option<(i32, i32)>, i32)> = None;
+
+    for line in content.lines() {
+        let trimmed = line.trim_start();
+        if let Some(info) = trimmed.strip_prefix("```") {
+            match open_fence.take() {
+                Some((Some(file), range, count)) => {
+                    push_reference(&mut refs, &file, range.unwrap_or((1, count.max(1))), None);
+                }
+                Some((None, _, _)) => {}
+                None => {
+                    let (file, range) = parse_fence_info(info)
+                        .map(|(file, range)| (Some(file), range))
+                        .unwrap_or((None, None));
+                    open_fence = Some((file, range, 0));
+                }
+            }
+            continue;
+        }
+
+        if let Some((_, _, count)) = open_fence.as_mut() {
+            *count += 1;
+        }
+
+        for caps in INLINE_REF_RE.captures_iter(line) {
+            let start: i32 = match caps[2].parse() {
+                Ok(n) if n > 0 => n,
+                _ => continue,
+            };
+            let end = caps
+                .get(3)
+                .and_then(|m| m.as_str().parse().ok())
+                .filter(|&end| end >= start)
+                .unwrap_or(start);
+            let context: String = line.trim().chars().take(200).collect();
+            push_reference(&mut refs, &caps[1], (start, end), Some(context));
+        }
+    }
+
+    refs
+}
+
+fn push_reference(
+    refs: &mut Vec<CodeReference>,
+    file: &str,
+    lines: (i32, i32),
+    context: Option<String>,
+) {
+    if !refs.iter().any(|r| r.file == file && r.lines == lines) {
+        refs.push(CodeReference {
+            file: file.to_string(),
+            lines,
+            git_hash: None,
+            context,
+            diff: None,
+        });
+    }
+}
+
+/// Find a file path (and optional line range) in a code fence info string
+fn parse_fence_info(info: &str) -> Option<(String, Option<(i32, i32)>)> {
+    info.split(|c: char| c.is_whitespace() || c == ',')
+        .flat_map(|token| {
+            let token = token
+                .trim_start_matches("title=")
+                .trim_start_matches("file=")
+                .trim_matches(|c: char| c == '"' || c == '\'');
+            // `rust:src/main.rs` style: drop a leading language tag
+            let without_lang = token.split_once(':').map(|(_, rest)| rest);
+            std::iter::once(token).chain(without_lang)
+        })
+        .find_map(|token| {
+            let caps = FENCE_PATH_RE.captures(token)?;
+            let range = caps.get(2).and_then(|start| {
+                let start: i32 = start.as_str().parse().ok()?;
+                let end = caps
+                    .get(3)
+                    .and_then(|m| m.as_str().parse().ok())
+                    .unwrap_or(start);
+                Some((start, end))
+            });
+            Some((caps[1].to_string(), range))
+        })
+}
+
 /// Resolve a code reference to actual file content
 pub fn resolve_code_reference(project_path: &Path, code_ref: &CodeReference) -> Result<String, String> {
     let file_path = if Path::new(&code_ref.file).is_absolute() {
@@ -428,4 +640,68 @@ mod tests {
         let content = resolve_code_reference(project_path, &code_ref).unwrap();
         assert_eq!(content, "line 2\nline 3\nline 4");
     }
+
+    #[test]
+    fn test_extract_fenced_block_with_filename() {
+        let content = "Here is the fix:\n```rust src/main.rs\nfn main() {\n    run();\n}\n```\nDone.";
+        let refs = extract_code_references(content);
+        assert_eq!(refs.len(), 1);
+        assert_eq!(refs[0].file, "src/main.rs");
+        assert_eq!(refs[0].lines, (1, 3));
+
+        let refs = extract_code_references("```ts:src/app.ts:10-12\na\nb\nc\n```");
+        assert_eq!(refs.len(), 1);
+        assert_eq!(refs[0].file, "src/app.ts");
+        assert_eq!(refs[0].lines, (10, 12));
+
+        // A plain language tag is not a file reference
+        assert!(extract_code_references("```rust\nfn main() {}\n```").is_empty());
+    }
+
+    #[test]
+    fn test_extract_inline_path_line_references() {
+        let content = "The panic comes from `src/foo.rs:42`, see also lib/bar.ts:10-20 and src/foo.rs:42 again.\nVisit https://example.com:8080 for docs.";
+        let refs = extract_code_references(content);
+        assert_eq!(refs.len(), 2);
+        assert_eq!(refs[0].file, "src/foo.rs");
+        assert_eq!(refs[0].lines, (42, 42));
+        assert_eq!(refs[1].file, "lib/bar.ts");
+        assert_eq!(refs[1].lines, (10, 20));
+        assert!(refs[0].context.as_deref().unwrap().contains("panic"));
+    }
+
+    #[test]
+    fn test_rebuild_index_from_artifacts() {
+        let temp = tempdir().unwrap();
+        let project_path = temp.path();
+        crate::project_settings::init_zblade_dir(project_path).unwrap();
+        
+        let store = LocalArtifactStore::new(project_path);
+        let mut artifact = ConversationArtifact::new(
+            "conv_rebuild".to_string(),
+            "proj_test".to_string(),
+            "Rebuild".to_string(),
+        );
+        artifact.add_message(Message {
+            id: "msg_001".to_string(),
+            role: "assistant".to_string(),
+            content: "The bug is in src/auth.rs:12-18".to_string(),
+            timestamp: "2026-01-17T14:00:00Z".to_string(),
+            code_references: vec![],
+        });
+        store.save_conversation(&artifact).unwrap();
+        
+        // Simulate a stale index
+        LocalIndex::open(project_path).unwrap().clear_conversation_data().unwrap();
+        assert!(store.list_conversations().unwrap().is_empty());
+        
+        let stats = store.rebuild_index().unwrap();
+        assert_eq!(stats.conversations, 1);
+        assert_eq!(stats.references, 1);
+        assert_eq!(stats.skipped, 0);
+        
+        let refs = store.get_file_references("src/auth.rs").unwrap();
+        assert_eq!(refs.len(), 1);
+        assert_eq!((refs[0].start_line, refs[0].end_line), (12, 18));
+    }
 }
@@ -44,6 +44,15 @@ pub struct CodeReferenceIndex {
     pub created_at: String,
 }
 
+/// Embedding of a chunk of a workspace file, used for local semantic search
+#[derive(Debug, Clone)]
+pub struct ChunkEmbedding {
+    pub file_path: String,
+    pub start_line: i32,
+    pub end_line: i32,
+    pub embedding: Vec<f32>,
+}
+
 /// Local index manager for a project
 pub struct LocalIndex {
     conn: Connection,
@@ -131,6 +140,19 @@ impl LocalIndex {
                 first_referenced TEXT,
                 last_referenced TEXT
             );
+            
+            -- Code chunk embeddings for local semantic search
+            CREATE TABLE IF NOT EXISTS code_embeddings (
+                file_path TEXT NOT NULL,
+                start_line INTEGER NOT NULL,
+                end_line INTEGER NOT NULL,
+                model TEXT NOT NULL,
+                content_hash TEXT NOT NULL,
+                embedding BLOB NOT NULL,
+                PRIMARY KEY (file_path, start_line, end_line, model)
+            );
+            
+            CREATE INDEX IF NOT EXISTS idx_embedding_model ON code_embeddings(model);
         "#)?;
         
         // Create FTS5 virtual table for full-text search on moments
@@ -229,11 +251,17 @@ impl LocalIndex {
     
     /// List all conversations, ordered by updated_at descending
     pub fn list_conversations(&self) -> SqliteResult<Vec<ConversationIndex>> {
+        self.list_conversations_page(0, -1)
+    }
+    
+    /// List a page of conversations, ordered by updated_at descending.
+    /// A negative `limit` returns everything after `offset`.
+    pub fn list_conversations_page(&self, offset: i64, limit: i64) -> SqliteResult<Vec<ConversationIndex>> {
         let mut stmt = self.conn.prepare(
-            "SELECT id, project_id, title, created_at, updated_at, message_count, tags, artifact_path FROM conversations ORDER BY updated_at DESC"
+            "SELECT id, project_id, title, created_at, updated_at, message_count, tags, artifact_path FROM conversations ORDER BY updated_at DESC LIMIT ?1 OFFSET ?2"
         )?;
         
-        let rows = stmt.query_map([], |row| {
+        let rows = stmt.query_map(params![limit, offset], |row| {
             let tags_json: String = row.get(6)?;
             let tags: Vec<String> = serde_json::from_str(&tags_json).unwrap_or_default();
             
@@ -252,6 +280,28 @@ impl LocalIndex {
         rows.collect()
     }
     
+    /// Number of conversations in the index
+    pub fn conversation_count(&self) -> SqliteResult<usize> {
+        let count: i64 = self.conn.query_row("SELECT COUNT(*) FROM conversations", [], |row| row.get(0))?;
+        Ok(count as usize)
+    }
+    
+    /// Remove all conversations, moments and code references ahead of a rebuild.
+    /// Code embeddings are kept since they are derived from workspace files, not artifacts.
+    pub fn clear_conversation_data(&self) -> SqliteResult<()> {
+        self.conn.execute_batch(r#"
+            DELETE FROM code_references;
+            DELETE FROM moments;
+            DELETE FROM conversations;
+            DELETE FROM file_references;
+        "#)?;
+        
+        // Resync the FTS table in case it drifted from `moments`; it may not exist
+        let _ = self.conn.execute_batch("INSERT INTO moments_fts(moments_fts) VALUES('rebuild');");
+        
+        Ok(())
+    }
+    
     /// Delete a conversation and all related data
     pub fn delete_conversation(&self, id: &str) -> SqliteResult<()> {
         self.conn.execute("DELETE FROM conversations WHERE id = ?1", params![id])?;
@@ -389,6 +439,38 @@ impl LocalIndex {
         Ok(self.conn.last_insert_rowid())
     }
     
+    /// Delete all code references recorded for a conversation and recount the
+    /// files they pointed at, so re-indexing a conversation doesn't inflate
+    /// `file_references.reference_count`
+    pub fn delete_references_for_conversation(&self, conversation_id: &str) -> SqliteResult<()> {
+        let files: Vec<String> = {
+            let mut stmt = self.conn.prepare(
+                "SELECT DISTINCT file_path FROM code_references WHERE conversation_id = ?1",
+            )?;
+            let rows = stmt.query_map(params![conversation_id], |row| row.get(0))?;
+            rows.collect::<SqliteResult<_>>()?
+        };
+        self.conn.execute(
+            "DELETE FROM code_references WHERE conversation_id = ?1",
+            params![conversation_id],
+        )?;
+        for file_path in files {
+            self.conn.execute(
+                r#"
+                UPDATE file_references
+                SET reference_count = (SELECT COUNT(*) FROM code_references WHERE file_path = ?1)
+                WHERE file_path = ?1
+                "#,
+                params![file_path],
+            )?;
+            self.conn.execute(
+                "DELETE FROM file_references WHERE file_path = ?1 AND reference_count = 0",
+                params![file_path],
+            )?;
+        }
+        Ok(())
+    }
+    
     /// Get code references for a file
     pub fn get_references_for_file(&self, file_path: &str) -> SqliteResult<Vec<CodeReferenceIndex>> {
         let mut stmt = self.conn.prepare(
@@ -432,6 +514,93 @@ impl LocalIndex {
         
         rows.collect()
     }
+    
+    // =========================================================================
+    // Embedding Operations
+    // =========================================================================
+    
+    /// Content hash the file's embeddings were computed from, if it has any for `model`
+    pub fn embedded_file_hash(&self, file_path: &str, model: &str) -> SqliteResult<Option<String>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT content_hash FROM code_embeddings WHERE file_path = ?1 AND model = ?2 LIMIT 1"
+        )?;
+        
+        let mut rows = stmt.query(params![file_path, model])?;
+        match rows.next()? {
+            Some(row) => Ok(Some(row.get(0)?)),
+            None => Ok(None),
+        }
+    }
+    
+    /// Replace all embeddings of a file for `model` with a fresh set of chunks
+    pub fn replace_file_embeddings(
+        &self,
+        file_path: &str,
+        model: &str,
+        content_hash: &str,
+        chunks: &[ChunkEmbedding],
+    ) -> SqliteResult<()> {
+        let tx = self.conn.unchecked_transaction()?;
+        tx.execute(
+            "DELETE FROM code_embeddings WHERE file_path = ?1 AND model = ?2",
+            params![file_path, model],
+        )?;
+        
+        for chunk in chunks {
+            let blob: Vec<u8> = chunk.embedding.iter().flat_map(|v| v.to_le_bytes()).collect();
+            tx.execute(
+                r#"
+                INSERT OR REPLACE INTO code_embeddings (file_path, start_line, end_line, model, content_hash, embedding)
+                VALUES (?1, ?2, ?3, ?4, ?5, ?6)
+                "#,
+                params![file_path, chunk.start_line, chunk.end_line, model, content_hash, blob],
+            )?;
+        }
+        
+        tx.commit()
+    }
+    
+    /// Delete all embeddings of a file (e.g. after it was removed)
+    pub fn delete_file_embeddings(&self, file_path: &str) -> SqliteResult<()> {
+        self.conn.execute(
+            "DELETE FROM code_embeddings WHERE file_path = ?1",
+            params![file_path],
+        )?;
+        Ok(())
+    }
+    
+    /// Number of files with embeddings for `model`
+    pub fn embedded_file_count(&self, model: &str) -> SqliteResult<usize> {
+        self.conn.query_row(
+            "SELECT COUNT(DISTINCT file_path) FROM code_embeddings WHERE model = ?1",
+            params![model],
+            |row| row.get::<_, i64>(0).map(|n| n as usize),
+        )
+    }
+    
+    /// All chunk embeddings computed with `model`
+    pub fn list_embeddings(&self, model: &str) -> SqliteResult<Vec<ChunkEmbedding>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT file_path, start_line, end_line, embedding FROM code_embeddings WHERE model = ?1"
+        )?;
+        
+        let rows = stmt.query_map(params![model], |row| {
+            let blob: Vec<u8> = row.get(3)?;
+            let embedding = blob
+                .chunks_exact(4)
+                .map(|b| f32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+                .collect();
+            
+            Ok(ChunkEmbedding {
+                file_path: row.get(0)?,
+                start_line: row.get(1)?,
+                end_line: row.get(2)?,
+                embedding,
+            })
+        })?;
+        
+        rows.collect()
+    }
 }
 
 #[cfg(test)]
@@ -546,5 +715,64 @@ mod tests {
         // Get by conversation
         let refs = index.get_references_for_conversation("conv_123").unwrap();
         assert_eq!(refs.len(), 1);
+
+        // Re-indexing replaces the conversation's references instead of adding to the count
+        let reference_count = || -> i64 {
+            index
+                .conn
+                .query_row(
+                    "SELECT reference_count FROM file_references WHERE file_path = 'src/auth.ts'",
+                    [],
+                    |row| row.get(0),
+                )
+                .unwrap()
+        };
+        assert_eq!(reference_count(), 1);
+        index.delete_references_for_conversation("conv_123").unwrap();
+        index.insert_code_reference(&ref_).unwrap();
+        assert_eq!(reference_count(), 1);
+    }
+
+    #[test]
+    fn test_code_embeddings() {
+        let temp = tempdir().unwrap();
+        let project_path = temp.path();
+        crate::project_settings::init_zblade_dir(project_path).unwrap();
+        
+        let index = LocalIndex::open(project_path).unwrap();
+        
+        let chunks = vec![
+            ChunkEmbedding {
+                file_path: "src/auth.ts".to_string(),
+                start_line: 1,
+                end_line: 60,
+                embedding: vec![0.5, -1.25, 3.0],
+            },
+            ChunkEmbedding {
+                file_path: "src/auth.ts".to_string(),
+                start_line: 61,
+                end_line: 80,
+                embedding: vec![1.0, 0.0, 0.0],
+            },
+        ];
+        index.replace_file_embeddings("src/auth.ts", "nomic-embed-text", "abc", &chunks).unwrap();
+        
+        assert_eq!(
+            index.embedded_file_hash("src/auth.ts", "nomic-embed-text").unwrap().as_deref(),
+            Some("abc")
+        );
+        assert!(index.embedded_file_hash("src/auth.ts", "other-model").unwrap().is_none());
+        assert_eq!(index.embedded_file_count("nomic-embed-text").unwrap(), 1);
+        
+        let loaded = index.list_embeddings("nomic-embed-text").unwrap();
+        assert_eq!(loaded.len(), 2);
+        assert!(loaded.iter().any(|c| c.embedding == vec![0.5, -1.25, 3.0]));
+        
+        // Re-embedding replaces the old chunks
+        index.replace_file_embeddings("src/auth.ts", "nomic-embed-text", "def", &chunks[..1]).unwrap();
+        assert_eq!(index.list_embeddings("nomic-embed-text").unwrap().len(), 1);
+        
+        index.delete_file_embeddings("src/auth.ts").unwrap();
+        assert_eq!(index.embedded_file_count("nomic-embed-text").unwrap(), 0);
     }
 }
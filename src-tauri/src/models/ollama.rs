@@ -15,6 +15,11 @@ struct OllamaModelTag {
     name: String,
 }
 
+#[derive(Deserialize)]
+struct OllamaEmbeddingResponse {
+    embedding: Vec<f32>,
+}
+
 struct ModelCache {
     models: Vec<ModelInfo>,
     last_fetch: Instant,
@@ -58,6 +63,10 @@ async fn fetch_models_from_server(
             provider: Some("ollama".to_string()),
             reasoning_effort: None,
             api_id: None,
+            context_window: None,
+            supports_reasoning: None,
+            supports_tools: true,
+            max_output_tokens: None,
         })
         .collect();
 
@@ -154,6 +163,39 @@ pub async fn test_connection(ollama_url: &str) -> Result<(), String> {
     }
 }
 
+/// Embed each text with Ollama's `/api/embeddings` endpoint, returning vectors in input order
+pub async fn embed(ollama_url: &str, model: &str, texts: &[String]) -> Result<Vec<Vec<f32>>, String> {
+    let url = format!("{}/api/embeddings", ollama_url.trim_end_matches('/'));
+    let client = reqwest::Client::builder()
+        .timeout(Duration::from_secs(30))
+        .build()
+        .map_err(|e| e.to_string())?;
+
+    let mut embeddings = Vec::with_capacity(texts.len());
+    for text in texts {
+        let response = client
+            .post(&url)
+            .json(&serde_json::json!({ "model": model, "prompt": text }))
+            .send()
+            .await
+            .map_err(|e| format!("Failed to connect to Ollama: {}", e))?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            return Err(format!("Ollama embeddings returned {}: {}", status, body));
+        }
+
+        let parsed: OllamaEmbeddingResponse = response
+            .json()
+            .await
+            .map_err(|e| format!("Invalid Ollama embeddings response: {}", e))?;
+        embeddings.push(parsed.embedding);
+    }
+
+    Ok(embeddings)
+}
+
 pub fn clear_cache() {
     if let Ok(mut cache) = MODEL_CACHE.lock() {
         *cache = None;
@@ -67,6 +67,10 @@ async fn fetch_models_from_server(
                 provider: Some("openai-compat".to_string()),
                 reasoning_effort: None,
                 api_id: None,
+                context_window: None,
+                supports_reasoning: None,
+                supports_tools: true,
+                max_output_tokens: None,
             }
         })
         .collect();
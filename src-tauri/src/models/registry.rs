@@ -1,8 +1,9 @@
 use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
 use std::sync::{Arc, Mutex};
 use std::time::{Duration, Instant};
 
-const CACHE_TTL: Duration = Duration::from_secs(300); // 5 minutes
+const CACHE_TTL: Duration = Duration::from_secs(60);
 
 #[derive(Clone, Serialize, Deserialize, Debug)]
 pub struct ModelInfo {
@@ -15,6 +16,20 @@ pub struct ModelInfo {
     pub reasoning_effort: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub api_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub context_window: Option<u32>,
+    /// Explicit override for whether the model emits `<think>` reasoning tags
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub supports_reasoning: Option<bool>,
+    /// Whether the model accepts tool definitions
+    #[serde(default = "default_supports_tools")]
+    pub supports_tools: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_output_tokens: Option<u32>,
+}
+
+fn default_supports_tools() -> bool {
+    true
 }
 
 #[derive(Deserialize)]
@@ -43,6 +58,24 @@ struct BladeModel {
     supports_reasoning_effort: Option<bool>,
     #[serde(default)]
     prompt_template: Option<String>,
+    #[serde(default)]
+    supports_tools: Option<bool>,
+    #[serde(default)]
+    supports_reasoning: Option<bool>,
+    #[serde(default)]
+    max_output_tokens: Option<u32>,
+}
+
+/// Tool support as advertised in a model's `capabilities` list, if it says so
+/// either way. A list that just doesn't mention tools is no signal.
+fn tools_capability(capabilities: Option<&[String]>) -> Option<bool> {
+    capabilities?
+        .iter()
+        .find_map(|c| match c.to_lowercase().as_str() {
+            "tools" | "tool_use" | "tool_calling" | "function_calling" => Some(true),
+            "no_tools" | "no_tool_use" | "no_tool_calling" | "no_function_calling" => Some(false),
+            _ => None,
+        })
 }
 
 struct ModelCache {
@@ -51,10 +84,34 @@ struct ModelCache {
 }
 
 lazy_static::lazy_static! {
-    static ref MODEL_CACHE: Arc<Mutex<Option<ModelCache>>> = Arc::new(Mutex::new(None));
+    /// Keyed by Blade URL so switching servers never serves the other server's list
+    static ref MODEL_CACHE: Arc<Mutex<HashMap<String, ModelCache>>> =
+        Arc::new(Mutex::new(HashMap::new()));
+    /// Blade URLs with a background refresh in flight
+    static ref REFRESHING: Mutex<HashSet<String>> = Mutex::new(HashSet::new());
     static ref FETCH_LOCK: tokio::sync::Mutex<()> = tokio::sync::Mutex::new(());
 }
 
+/// Cached models for `blade_url` and whether they are still within the TTL
+fn cached_models(blade_url: &str) -> Option<(Vec<ModelInfo>, bool)> {
+    let cache = MODEL_CACHE.lock().ok()?;
+    let cached = cache.get(blade_url)?;
+    let fresh = cached.last_fetch.elapsed() < CACHE_TTL;
+    Some((cached.models.clone(), fresh))
+}
+
+fn store_models(blade_url: &str, models: Vec<ModelInfo>) {
+    if let Ok(mut cache) = MODEL_CACHE.lock() {
+        cache.insert(
+            blade_url.to_string(),
+            ModelCache {
+                models,
+                last_fetch: Instant::now(),
+            },
+        );
+    }
+}
+
 async fn fetch_models_from_server(
     blade_url: &str,
     api_key: &str,
@@ -97,6 +154,11 @@ async fn fetch_models_from_server(
                 (m.id.clone(), None)
             };
 
+            let supports_tools = m
+                .supports_tools
+                .or_else(|| tools_capability(m.capabilities.as_deref()))
+                .unwrap_or_else(default_supports_tools);
+
             ModelInfo {
                 id,
                 name: m.name,
@@ -104,6 +166,10 @@ async fn fetch_models_from_server(
                 provider: Some("zaguan".to_string()),
                 reasoning_effort: m.reasoning_effort,
                 api_id,
+                context_window: m.context_window,
+                supports_reasoning: m.supports_reasoning,
+                supports_tools,
+                max_output_tokens: m.max_output_tokens,
             }
         })
         .collect();
@@ -111,46 +177,71 @@ async fn fetch_models_from_server(
     Ok(models)
 }
 
+/// Models offered by the Blade server. Served from a per-URL cache; once the
+/// TTL has passed the stale list is returned while a background task refreshes it.
 pub async fn get_models(blade_url: &str, api_key: &str) -> Vec<ModelInfo> {
-    // 1. Fast path: Check cache first
-    if let Ok(cache) = MODEL_CACHE.lock() {
-        if let Some(ref cached) = *cache {
-            if cached.last_fetch.elapsed() < CACHE_TTL {
-                return cached.models.clone();
+    match cached_models(blade_url) {
+        Some((models, true)) => return models,
+        Some((models, false)) => {
+            spawn_background_refresh(blade_url, api_key);
+            return models;
+        }
+        None => {}
+    }
+
+    fetch_and_cache(blade_url, api_key, false).await
+}
+
+/// Refetch the model list now, ignoring the cache TTL
+pub async fn refresh_models(blade_url: &str, api_key: &str) -> Vec<ModelInfo> {
+    fetch_and_cache(blade_url, api_key, true).await
+}
+
+fn spawn_background_refresh(blade_url: &str, api_key: &str) {
+    match REFRESHING.lock() {
+        Ok(mut refreshing) => {
+            // Already being refreshed by another task
+            if !refreshing.insert(blade_url.to_string()) {
+                return;
             }
         }
+        Err(_) => return,
     }
 
-    // 2. Cache expired or missing - Acquire lock to coordinate fetching
+    let blade_url = blade_url.to_string();
+    let api_key = api_key.to_string();
+    tokio::spawn(async move {
+        fetch_and_cache(&blade_url, &api_key, true).await;
+        if let Ok(mut refreshing) = REFRESHING.lock() {
+            refreshing.remove(&blade_url);
+        }
+    });
+}
+
+async fn fetch_and_cache(blade_url: &str, api_key: &str, force: bool) -> Vec<ModelInfo> {
+    // 1. Acquire lock to coordinate fetching
     let _lock = FETCH_LOCK.lock().await;
 
-    // 3. Double-check cache after acquiring lock (in case another thread just finished fetching)
-    if let Ok(cache) = MODEL_CACHE.lock() {
-        if let Some(ref cached) = *cache {
-            if cached.last_fetch.elapsed() < CACHE_TTL {
-                return cached.models.clone();
-            }
+    // 2. Double-check cache after acquiring lock (in case another task just finished fetching)
+    if !force {
+        if let Some((models, true)) = cached_models(blade_url) {
+            return models;
         }
     }
 
-    // 4. Truly need to fetch
+    // 3. Truly need to fetch
     let mut retry_count = 0;
     let max_retries = 3;
 
     loop {
         match fetch_models_from_server(blade_url, api_key).await {
             Ok(models) => {
-                if let Ok(mut cache) = MODEL_CACHE.lock() {
-                    *cache = Some(ModelCache {
-                        models: models.clone(),
-                        last_fetch: Instant::now(),
-                    });
-                    eprintln!(
-                        "[MODEL REGISTRY] Successfully fetched {} models from {}",
-                        models.len(),
-                        blade_url
-                    );
-                }
+                store_models(blade_url, models.clone());
+                eprintln!(
+                    "[MODEL REGISTRY] Successfully fetched {} models from {}",
+                    models.len(),
+                    blade_url
+                );
                 return models;
             }
             Err(e) => {
@@ -173,14 +264,75 @@ pub async fn get_models(blade_url: &str, api_key: &str) -> Vec<ModelInfo> {
         }
     }
 
-    // 5. Fallback: If fetch failed but we have expired cache, use it anyway
-    if let Ok(cache) = MODEL_CACHE.lock() {
-        if let Some(ref cached) = *cache {
-            eprintln!("[MODEL REGISTRY] Using EXPIRED cache as fallback");
-            return cached.models.clone();
-        }
+    // 4. Fallback: If fetch failed but we have expired cache, use it anyway
+    if let Some((models, _)) = cached_models(blade_url) {
+        eprintln!("[MODEL REGISTRY] Using EXPIRED cache as fallback");
+        return models;
     }
 
-    // 6. Final fallback: empty list
+    // 5. Final fallback: empty list
     Vec::new()
 }
+
+/// Drop cached models so the next `get_models` refetches (e.g. after the Blade URL changed)
+pub fn clear_cache() {
+    if let Ok(mut cache) = MODEL_CACHE.lock() {
+        cache.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn model(id: &str) -> ModelInfo {
+        ModelInfo {
+            id: id.to_string(),
+            name: id.to_string(),
+            description: String::new(),
+            provider: None,
+            reasoning_effort: None,
+            api_id: None,
+            context_window: None,
+            supports_reasoning: None,
+            supports_tools: true,
+            max_output_tokens: None,
+        }
+    }
+
+    #[test]
+    fn test_cache_is_keyed_by_blade_url() {
+        store_models("http://blade-a.test", vec![model("a")]);
+
+        let (models, fresh) = cached_models("http://blade-a.test").unwrap();
+        assert!(fresh);
+        assert_eq!(models[0].id, "a");
+        assert!(cached_models("http://blade-b.test").is_none());
+    }
+
+    #[test]
+    fn test_model_metadata_defaults() {
+        // Older servers send none of the capability fields
+        let legacy: BladeModelsResponse = serde_json::from_str(
+            r#"{"models":[{"id":"m","name":"M","description":"d","capabilities":["chat"]}]}"#,
+        )
+        .unwrap();
+        let m = &legacy.models[0];
+        assert_eq!(m.supports_tools, None);
+        assert_eq!(tools_capability(m.capabilities.as_deref()), None);
+        assert_eq!(
+            tools_capability(Some(&["Function_Calling".to_string()])),
+            Some(true)
+        );
+        assert_eq!(
+            tools_capability(Some(&["chat".to_string(), "no_tools".to_string()])),
+            Some(false)
+        );
+        assert_eq!(tools_capability(Some(&[])), None);
+
+        let info: ModelInfo =
+            serde_json::from_str(r#"{"id":"m","name":"M","description":"d"}"#).unwrap();
+        assert!(info.supports_tools);
+        assert_eq!(info.max_output_tokens, None);
+    }
+}
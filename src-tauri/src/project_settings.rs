@@ -1,4 +1,5 @@
 use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet, HashSet};
 use std::fs;
 use std::path::{Path, PathBuf};
 
@@ -84,6 +85,12 @@ pub struct ContextSettings {
     pub max_tokens: u32,
     #[serde(default)]
     pub compression: CompressionSettings,
+    /// Tool results larger than this are truncated in the middle (local mode only)
+    #[serde(default = "default_max_tool_result_bytes")]
+    pub max_tool_result_bytes: usize,
+    /// Files larger than this are skipped by grep_search and codebase_search
+    #[serde(default = "default_max_grep_file_bytes")]
+    pub max_grep_file_bytes: u64,
 }
 
 impl Default for ContextSettings {
@@ -91,6 +98,8 @@ impl Default for ContextSettings {
         Self {
             max_tokens: 8000,
             compression: CompressionSettings::default(),
+            max_tool_result_bytes: default_max_tool_result_bytes(),
+            max_grep_file_bytes: default_max_grep_file_bytes(),
         }
     }
 }
@@ -112,8 +121,169 @@ impl Default for EditorSettings {
     }
 }
 
+/// Environment filtering for AI-run commands, so editor secrets don't leak into
+/// subprocess output that is fed back to the model.
+///
+/// A variable is removed when it matches a `deny` pattern and no `allow`
+/// pattern. Patterns are names with an optional leading or trailing `*`,
+/// matched case-insensitively. Defaults strip `ZAGUAN_API_KEY`, `BLADE_URL`
+/// and `*_KEY`/`*_TOKEN`/`*_SECRET`, while keeping PATH, HOME and locale vars.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CommandEnvSettings {
+    #[serde(default = "default_env_allow")]
+    pub allow: Vec<String>,
+    #[serde(default = "default_env_deny")]
+    pub deny: Vec<String>,
+}
+
+impl Default for CommandEnvSettings {
+    fn default() -> Self {
+        Self {
+            allow: default_env_allow(),
+            deny: default_env_deny(),
+        }
+    }
+}
+
+impl CommandEnvSettings {
+    pub fn is_denied(&self, name: &str) -> bool {
+        let matches = |pattern: &String| env_pattern_matches(pattern, name);
+        self.deny.iter().any(matches) && !self.allow.iter().any(matches)
+    }
+
+    /// Names of the current process's variables that commands must not inherit
+    pub fn denied_vars(&self) -> Vec<String> {
+        std::env::vars_os()
+            .filter_map(|(name, _)| name.into_string().ok())
+            .filter(|name| self.is_denied(name))
+            .collect()
+    }
+}
+
+fn env_pattern_matches(pattern: &str, name: &str) -> bool {
+    let pattern = pattern.to_ascii_uppercase();
+    let name = name.to_ascii_uppercase();
+    if let Some(suffix) = pattern.strip_prefix('*') {
+        name.ends_with(suffix)
+    } else if let Some(prefix) = pattern.strip_suffix('*') {
+        name.starts_with(prefix)
+    } else {
+        name == pattern
+    }
+}
+
+/// Masking of secrets (keys, tokens, private keys) in tool output before it
+/// is sent to the model. `extra_patterns` are regexes added to the built-in set.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RedactionSettings {
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+    #[serde(default)]
+    pub extra_patterns: Vec<String>,
+}
+
+impl Default for RedactionSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            extra_patterns: Vec::new(),
+        }
+    }
+}
+
+/// Tools that delete, move or execute, disabled together by `disable_destructive`
+pub const DESTRUCTIVE_TOOLS: &[&str] = &["delete_file", "move_file", "run_command"];
+
+/// Which tools the agent may call in this project.
+///
+/// An empty `allow` list permits every tool; `deny` and `disable_destructive`
+/// always win. Aliases (`rg`, `list_dir`, ...) are matched by their canonical name.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ToolSettings {
+    #[serde(default)]
+    pub allow: Vec<String>,
+    #[serde(default)]
+    pub deny: Vec<String>,
+    #[serde(default = "default_false")]
+    pub disable_destructive: bool,
+}
+
+impl ToolSettings {
+    pub fn is_enabled(&self, tool_name: &str) -> bool {
+        let name = crate::tools::canonical_tool_name(tool_name);
+        let listed = |list: &[String]| {
+            list.iter()
+                .any(|t| crate::tools::canonical_tool_name(t) == name)
+        };
+        if self.disable_destructive && DESTRUCTIVE_TOOLS.contains(&name) {
+            return false;
+        }
+        !listed(&self.deny) && (self.allow.is_empty() || listed(&self.allow))
+    }
+
+    /// Error result for a call to a tool this project has disabled
+    pub fn check(&self, tool_name: &str) -> Result<(), crate::tools::ToolResult> {
+        if self.is_enabled(tool_name) {
+            Ok(())
+        } else {
+            Err(crate::tools::ToolResult::err(format!(
+                "tool '{}' is disabled in this project",
+                tool_name
+            )))
+        }
+    }
+}
+
+/// Tools that report editor or workspace state, which legitimately repeat
+const LOOP_EXEMPT_TOOLS: &[&str] = &["get_editor_state", "get_workspace_structure"];
+
+/// Detection of the agent repeating a tool call with identical arguments.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LoopDetectionSettings {
+    /// Identical calls (same tool, same normalized arguments) allowed among recent
+    /// calls before another one is refused. Read and search tools get one extra.
+    #[serde(default = "default_loop_repeat_threshold")]
+    pub repeat_threshold: usize,
+    /// Tools never treated as looping, on top of the editor-state tools
+    #[serde(default)]
+    pub exempt_tools: Vec<String>,
+}
+
+impl Default for LoopDetectionSettings {
+    fn default() -> Self {
+        Self {
+            repeat_threshold: default_loop_repeat_threshold(),
+            exempt_tools: Vec::new(),
+        }
+    }
+}
+
+impl LoopDetectionSettings {
+    /// Identical calls to `tool_name` allowed before the next counts as a loop
+    /// (at least 1); `None` for exempt tools
+    pub fn repeat_limit(&self, tool_name: &str) -> Option<usize> {
+        let name = crate::tools::canonical_tool_name(tool_name);
+        let exempt = LOOP_EXEMPT_TOOLS.contains(&name)
+            || self
+                .exempt_tools
+                .iter()
+                .any(|t| crate::tools::canonical_tool_name(t) == name);
+        if exempt {
+            return None;
+        }
+        // Re-reading after an edit is common
+        // 0 would refuse even the first call
+        let threshold = self.repeat_threshold.max(1);
+        if matches!(name, "read_file" | "read_file_range" | "grep_search") {
+            Some(threshold + 1)
+        } else {
+            Some(threshold)
+        }
+    }
+}
+
 /// Per-project settings stored in .zblade/config/settings.json
-#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ProjectSettings {
     #[serde(default)]
     pub storage: StorageSettings,
@@ -127,6 +297,80 @@ pub struct ProjectSettings {
     /// Default: false (respect .gitignore for security)
     #[serde(default = "default_false")]
     pub allow_gitignored_files: bool,
+    /// Apply each batch of AI file changes all-or-nothing: changes run in order, and the
+    /// first failure reverts the ones already applied and skips the rest.
+    /// Default: false (best effort, each change stands on its own)
+    #[serde(default = "default_false")]
+    pub atomic_ai_changes: bool,
+    /// Seconds an AI-run terminal command may take before it is killed and
+    /// reported as timed out; 0 disables the limit. Interactive terminals are exempt.
+    /// Default: 120
+    #[serde(default = "default_command_timeout_secs")]
+    pub command_timeout_secs: u64,
+    /// Create a missing `cwd` inside the workspace for AI-run commands instead
+    /// of failing the command.
+    /// Default: false
+    #[serde(default = "default_false")]
+    pub auto_create_cwd: bool,
+    /// Environment variables withheld from AI-run commands
+    #[serde(default)]
+    pub command_env: CommandEnvSettings,
+    /// Secret redaction for tool output
+    #[serde(default)]
+    pub redaction: RedactionSettings,
+    /// Tools the agent may call
+    #[serde(default)]
+    pub tools: ToolSettings,
+    /// Tool-result round trips the agentic loop may take before it is stopped.
+    /// Default: 50
+    #[serde(default = "default_max_turns")]
+    pub max_turns: usize,
+    /// Tool calls the agent may make in response to one user message; further
+    /// calls are refused and the agent is told to answer. 0 disables the limit.
+    /// Default: 200
+    #[serde(default = "default_max_tool_calls_per_message")]
+    pub max_tool_calls_per_message: usize,
+    /// Repeated tool call detection
+    #[serde(default)]
+    pub loop_detection: LoopDetectionSettings,
+    /// Lines of AI-run command output sent to the model, keeping the end where
+    /// errors and summaries are; the terminal still shows everything. 0 sends it all.
+    /// Default: 400
+    #[serde(default = "default_command_output_tail_lines")]
+    pub command_output_tail_lines: usize,
+    /// Output lines kept per terminal for `get_terminal_scrollback`.
+    /// Default: 10000
+    #[serde(default = "default_terminal_scrollback_lines")]
+    pub terminal_scrollback_lines: usize,
+    /// Keep unsaved ephemeral documents (e.g. `@research` results) in
+    /// `.zblade/ephemeral/` so they can be recovered after a crash.
+    /// Default: false
+    #[serde(default = "default_false")]
+    pub persist_ephemeral_documents: bool,
+}
+
+impl Default for ProjectSettings {
+    fn default() -> Self {
+        Self {
+            storage: StorageSettings::default(),
+            context: ContextSettings::default(),
+            privacy: PrivacySettings::default(),
+            editor: EditorSettings::default(),
+            allow_gitignored_files: false,
+            atomic_ai_changes: false,
+            command_timeout_secs: default_command_timeout_secs(),
+            auto_create_cwd: false,
+            command_env: CommandEnvSettings::default(),
+            redaction: RedactionSettings::default(),
+            tools: ToolSettings::default(),
+            max_turns: DEFAULT_MAX_TURNS,
+            max_tool_calls_per_message: default_max_tool_calls_per_message(),
+            loop_detection: LoopDetectionSettings::default(),
+            command_output_tail_lines: default_command_output_tail_lines(),
+            terminal_scrollback_lines: default_terminal_scrollback_lines(),
+            persist_ephemeral_documents: false,
+        }
+    }
 }
 
 fn default_true() -> bool {
@@ -145,6 +389,83 @@ fn default_max_tokens() -> u32 {
     8000
 }
 
+fn default_max_tool_result_bytes() -> usize {
+    crate::tools::DEFAULT_MAX_TOOL_RESULT_BYTES
+}
+
+fn default_max_grep_file_bytes() -> u64 {
+    crate::tools::DEFAULT_MAX_GREP_FILE_BYTES
+}
+
+fn default_command_timeout_secs() -> u64 {
+    120
+}
+
+/// Agentic loop turn limit when no project overrides it
+pub const DEFAULT_MAX_TURNS: usize = 50;
+
+fn default_max_turns() -> usize {
+    DEFAULT_MAX_TURNS
+}
+
+fn default_max_tool_calls_per_message() -> usize {
+    200
+}
+
+fn default_loop_repeat_threshold() -> usize {
+    2
+}
+
+fn default_command_output_tail_lines() -> usize {
+    400
+}
+
+fn default_terminal_scrollback_lines() -> usize {
+    10_000
+}
+
+fn default_env_allow() -> Vec<String> {
+    [
+        "PATH", "HOME", "USER", "LOGNAME", "SHELL", "TMPDIR", "LANG", "LC_*",
+    ]
+    .iter()
+    .map(|s| s.to_string())
+    .collect()
+}
+
+fn default_env_deny() -> Vec<String> {
+    [
+        "ZAGUAN_API_KEY",
+        "BLADE_URL",
+        "*_KEY",
+        "*_TOKEN",
+        "*_SECRET",
+    ]
+    .iter()
+    .map(|s| s.to_string())
+    .collect()
+}
+
+/// Names of environment variables AI-run commands in this project must not inherit
+pub fn denied_command_env(project_path: Option<&Path>) -> Vec<String> {
+    project_path
+        .map(load_project_settings_or_default)
+        .unwrap_or_default()
+        .command_env
+        .denied_vars()
+}
+
+/// Redactor for tool output in this project, or `None` when redaction is disabled
+pub fn output_redactor(project_path: Option<&Path>) -> Option<crate::redaction::Redactor> {
+    let settings = project_path
+        .map(load_project_settings_or_default)
+        .unwrap_or_default()
+        .redaction;
+    settings
+        .enabled
+        .then(|| crate::redaction::Redactor::new(&settings.extra_patterns))
+}
+
 /// Get the .zblade directory path for a project
 pub fn get_zblade_dir(project_path: &Path) -> PathBuf {
     project_path.join(".zblade")
@@ -285,6 +606,85 @@ pub fn has_zblade_dir(project_path: &Path) -> bool {
     get_zblade_dir(project_path).exists()
 }
 
+/// Persisted "approve always" command roots, per project. Kept in the user's
+/// config directory rather than `.zblade/`, so a cloned repository can't
+/// pre-approve its own commands.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CommandAllowlist {
+    /// Canonical project path -> approved roots
+    #[serde(default)]
+    pub projects: BTreeMap<String, BTreeSet<String>>,
+}
+
+/// Get the user-level command allowlist file path
+pub fn get_command_allowlist_path() -> PathBuf {
+    crate::config::default_global_config_dir().join("command_allowlist.json")
+}
+
+/// Projects are keyed by canonical path so `./proj` and `/home/me/proj` agree
+fn allowlist_key(project_path: &Path) -> String {
+    fs::canonicalize(project_path)
+        .unwrap_or_else(|_| project_path.to_path_buf())
+        .to_string_lossy()
+        .to_string()
+}
+
+fn read_command_allowlist(path: &Path) -> CommandAllowlist {
+    let Ok(content) = fs::read_to_string(path) else {
+        return CommandAllowlist::default();
+    };
+    serde_json::from_str(&content).unwrap_or_else(|e| {
+        eprintln!(
+            "[SETTINGS] Failed to parse command allowlist {:?}: {}",
+            path, e
+        );
+        CommandAllowlist::default()
+    })
+}
+
+/// Load the allowlisted command roots for a project, returning an empty set if
+/// there are none or the file is missing or invalid
+pub fn load_command_allowlist(project_path: &Path) -> HashSet<String> {
+    load_command_allowlist_from(&get_command_allowlist_path(), project_path)
+}
+
+fn load_command_allowlist_from(path: &Path, project_path: &Path) -> HashSet<String> {
+    read_command_allowlist(path)
+        .projects
+        .remove(&allowlist_key(project_path))
+        .map(|roots| roots.into_iter().collect())
+        .unwrap_or_default()
+}
+
+/// Add command roots to a project's entry in the user's allowlist
+pub fn add_to_command_allowlist<I>(project_path: &Path, roots: I) -> Result<(), String>
+where
+    I: IntoIterator<Item = String>,
+{
+    add_to_command_allowlist_in(&get_command_allowlist_path(), project_path, roots)
+}
+
+fn add_to_command_allowlist_in<I>(path: &Path, project_path: &Path, roots: I) -> Result<(), String>
+where
+    I: IntoIterator<Item = String>,
+{
+    let mut allowlist = read_command_allowlist(path);
+    allowlist
+        .projects
+        .entry(allowlist_key(project_path))
+        .or_default()
+        .extend(roots);
+
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create config directory: {}", e))?;
+    }
+    let json = serde_json::to_string_pretty(&allowlist)
+        .map_err(|e| format!("Failed to serialize command allowlist: {}", e))?;
+    crate::utils::atomic_write(path, json)
+        .map_err(|e| format!("Failed to write command allowlist: {}", e))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -298,9 +698,12 @@ mod tests {
         assert!(settings.storage.cache.enabled);
         assert_eq!(settings.storage.cache.max_size_mb, 100);
         assert_eq!(settings.context.max_tokens, 8000);
+        assert_eq!(settings.context.max_tool_result_bytes, 32 * 1024);
+        assert_eq!(settings.context.max_grep_file_bytes, 5 * 1024 * 1024);
         assert!(settings.context.compression.enabled);
         assert_eq!(settings.context.compression.model, CompressionModel::Remote);
         assert!(!settings.privacy.telemetry);
+        assert_eq!(settings.command_timeout_secs, 120);
     }
 
     #[test]
@@ -311,6 +714,15 @@ mod tests {
 
         assert_eq!(restored.storage.mode, settings.storage.mode);
         assert_eq!(restored.context.max_tokens, settings.context.max_tokens);
+
+        // Settings written before the timeout existed keep the default limit
+        let legacy: ProjectSettings = serde_json::from_str("{}").unwrap();
+        assert_eq!(legacy.command_timeout_secs, 120);
+        assert!(legacy.redaction.enabled);
+        assert_eq!(legacy.max_turns, DEFAULT_MAX_TURNS);
+        assert_eq!(legacy.max_tool_calls_per_message, 200);
+        assert_eq!(legacy.command_output_tail_lines, 400);
+        assert_eq!(legacy.terminal_scrollback_lines, 10_000);
     }
 
     #[test]
@@ -348,4 +760,105 @@ mod tests {
         assert_eq!(loaded.storage.mode, StorageMode::Server);
         assert_eq!(loaded.context.max_tokens, 16000);
     }
+
+    #[test]
+    fn test_command_env_filter() {
+        let env = CommandEnvSettings::default();
+        for name in [
+            "ZAGUAN_API_KEY",
+            "BLADE_URL",
+            "OPENAI_API_KEY",
+            "github_token",
+            "AWS_SECRET",
+        ] {
+            assert!(env.is_denied(name), "{} should be stripped", name);
+        }
+        for name in ["PATH", "HOME", "LANG", "LC_ALL", "CARGO_HOME", "KEYBOARD"] {
+            assert!(!env.is_denied(name), "{} should be kept", name);
+        }
+
+        // Allow patterns win over deny patterns
+        let env = CommandEnvSettings {
+            allow: vec!["SSH_*".to_string()],
+            deny: vec!["*_KEY".to_string(), "NPM_*".to_string()],
+        };
+        assert!(!env.is_denied("SSH_KEY"));
+        assert!(env.is_denied("NPM_CONFIG"));
+    }
+
+    #[test]
+    fn test_tool_settings() {
+        let tools = ToolSettings::default();
+        assert!(tools.is_enabled("run_command"));
+        assert!(tools.check("delete_file").is_ok());
+
+        let tools = ToolSettings {
+            allow: Vec::new(),
+            deny: vec!["grep_search".to_string()],
+            disable_destructive: true,
+        };
+        for name in [
+            "delete_file",
+            "move_file",
+            "run_command",
+            "grep_search",
+            "rg",
+        ] {
+            assert!(!tools.is_enabled(name), "{} should be disabled", name);
+        }
+        assert!(tools.is_enabled("read_file"));
+        let err = tools.check("run_command").unwrap_err();
+        assert_eq!(
+            err.error.as_deref(),
+            Some("tool 'run_command' is disabled in this project")
+        );
+
+        // A non-empty allowlist makes the agent read-only
+        let tools = ToolSettings {
+            allow: vec!["read_file".to_string(), "list_directory".to_string()],
+            ..Default::default()
+        };
+        assert!(tools.is_enabled("list_dir"));
+        assert!(!tools.is_enabled("write_file"));
+    }
+
+    #[test]
+    fn test_loop_repeat_limit() {
+        let loops = LoopDetectionSettings {
+            repeat_threshold: 0,
+            exempt_tools: vec!["list_dir".to_string()],
+        };
+        assert_eq!(loops.repeat_limit("write_file"), Some(1));
+        assert_eq!(loops.repeat_limit("read_file"), Some(2));
+        assert_eq!(loops.repeat_limit("list_directory"), None);
+        assert_eq!(loops.repeat_limit("get_editor_state"), None);
+    }
+
+    #[test]
+    fn test_command_allowlist_roundtrip() {
+        let temp = tempdir().unwrap();
+        let file = temp.path().join("config").join("command_allowlist.json");
+        let project_path = temp.path().join("project");
+        let other_project = temp.path().join("other");
+        fs::create_dir(&project_path).unwrap();
+        fs::create_dir(&other_project).unwrap();
+
+        assert!(load_command_allowlist_from(&file, &project_path).is_empty());
+
+        add_to_command_allowlist_in(&file, &project_path, vec!["cargo".to_string()]).unwrap();
+        add_to_command_allowlist_in(
+            &file,
+            &project_path.join("..").join("project"),
+            vec!["npm".to_string(), "cargo".to_string()],
+        )
+        .unwrap();
+
+        let roots = load_command_allowlist_from(&file, &project_path);
+        assert_eq!(roots.len(), 2);
+        assert!(roots.contains("cargo"));
+        assert!(roots.contains("npm"));
+        assert!(load_command_allowlist_from(&file, &other_project).is_empty());
+        // Nothing is written into the project itself
+        assert!(!get_zblade_dir(&project_path).exists());
+    }
 }
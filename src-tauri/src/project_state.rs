@@ -4,6 +4,14 @@ use std::collections::hash_map::DefaultHasher;
 use std::fs;
 use std::hash::{Hash, Hasher};
 use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, Sender};
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::{AppHandle, Manager, Runtime};
+
+/// Quiet period after the last editor state change before it is autosaved
+const AUTOSAVE_DELAY: Duration = Duration::from_secs(2);
 
 /// Per-project state that persists across sessions
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
@@ -144,6 +152,16 @@ pub fn save_project_state(state: &ProjectState) -> Result<(), String> {
     Ok(())
 }
 
+/// Record the selected model in a project's saved state
+pub fn save_selected_model(project_path: &str, model_id: &str) -> Result<(), String> {
+    let mut state = load_project_state(project_path).unwrap_or(ProjectState {
+        project_path: project_path.to_string(),
+        ..Default::default()
+    });
+    state.selected_model_id = Some(model_id.to_string());
+    save_project_state(&state)
+}
+
 /// Delete project state from disk
 pub fn delete_project_state(project_path: &str) -> Result<(), String> {
     if let Some(state_path) = get_project_state_path(project_path) {
@@ -155,9 +173,192 @@ pub fn delete_project_state(project_path: &str) -> Result<(), String> {
     Ok(())
 }
 
+type SaveJob = Box<dyn FnOnce() + Send>;
+
+enum AutosaveCommand {
+    Schedule(SaveJob),
+    Cancel,
+    Flush(Sender<()>),
+}
+
+/// Debounced saving on one worker thread: each `schedule` supersedes the save
+/// still waiting and restarts the quiet period, so a burst of changes is
+/// written once
+pub struct Autosave {
+    enabled: AtomicBool,
+    commands: Mutex<Sender<AutosaveCommand>>,
+}
+
+impl Autosave {
+    pub fn new() -> Self {
+        Self::with_delay(AUTOSAVE_DELAY)
+    }
+
+    /// The worker exits once the `Autosave` is dropped
+    pub fn with_delay(delay: Duration) -> Self {
+        let (tx, rx) = mpsc::channel();
+        std::thread::spawn(move || run_autosave_worker(rx, delay));
+        Self {
+            enabled: AtomicBool::new(true),
+            commands: Mutex::new(tx),
+        }
+    }
+
+    pub fn set_enabled(&self, enabled: bool) {
+        self.enabled.store(enabled, Ordering::Relaxed);
+        if !enabled {
+            // Drop a save that is already waiting
+            self.send(AutosaveCommand::Cancel);
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.enabled.load(Ordering::Relaxed)
+    }
+
+    pub fn schedule(&self, save: impl FnOnce() + Send + 'static) {
+        if self.is_enabled() {
+            self.send(AutosaveCommand::Schedule(Box::new(save)));
+        }
+    }
+
+    /// Run the waiting save now, if any, and wait for it to finish
+    pub fn flush(&self) {
+        let (done_tx, done_rx) = mpsc::channel();
+        self.send(AutosaveCommand::Flush(done_tx));
+        let _ = done_rx.recv();
+    }
+
+    fn send(&self, command: AutosaveCommand) {
+        if self.commands.lock().unwrap().send(command).is_err() {
+            eprintln!("[ProjectState] Autosave worker is gone");
+        }
+    }
+}
+
+impl Default for Autosave {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn run_autosave_worker(commands: Receiver<AutosaveCommand>, delay: Duration) {
+    let mut pending: Option<SaveJob> = None;
+    loop {
+        let command = if pending.is_some() {
+            match commands.recv_timeout(delay) {
+                Ok(command) => command,
+                Err(RecvTimeoutError::Timeout) => {
+                    if let Some(save) = pending.take() {
+                        save();
+                    }
+                    continue;
+                }
+                Err(RecvTimeoutError::Disconnected) => return,
+            }
+        } else {
+            match commands.recv() {
+                Ok(command) => command,
+                Err(_) => return,
+            }
+        };
+        match command {
+            AutosaveCommand::Schedule(save) => pending = Some(save),
+            AutosaveCommand::Cancel => pending = None,
+            AutosaveCommand::Flush(done) => {
+                if let Some(save) = pending.take() {
+                    save();
+                }
+                let _ = done.send(());
+            }
+        }
+    }
+}
+
+/// Save the workspace's editor state (open files, active file, selected model)
+/// shortly, coalescing with other changes made in the meantime. The workspace and
+/// editor state are captured now, so a workspace switch before the save runs
+/// can't write one project's tabs into another's state.
+pub fn schedule_autosave<R: Runtime>(app_handle: &AppHandle<R>) {
+    let state = app_handle.state::<crate::app_state::AppState>();
+    let Some(root) = state.workspace.lock().unwrap().workspace.clone() else {
+        return;
+    };
+    let active_file = state.active_file.lock().unwrap().clone();
+    let open_files = state.open_files.lock().unwrap().clone();
+    let selected_model_id = state.selected_model_id.lock().unwrap().clone();
+
+    let save = move || {
+        let project_path = root.to_string_lossy().to_string();
+        let mut project_state = load_project_state(&project_path).unwrap_or(ProjectState {
+            project_path: project_path.clone(),
+            ..Default::default()
+        });
+        apply_editor_state(
+            &mut project_state,
+            active_file.as_deref(),
+            &open_files,
+            selected_model_id,
+        );
+        match save_project_state(&project_state) {
+            Ok(()) => eprintln!("[ProjectState] Autosaved {}", project_path),
+            Err(e) => eprintln!("[ProjectState] Autosave failed: {}", e),
+        }
+    };
+    state.project_state_autosave.schedule(save);
+}
+
+/// Update a saved state with the backend's view of the editor. Tabs still open
+/// keep their saved id, title and order; newly opened files are appended.
+fn apply_editor_state(
+    state: &mut ProjectState,
+    active_file: Option<&str>,
+    open_files: &[String],
+    selected_model_id: Option<String>,
+) {
+    let root = PathBuf::from(&state.project_path);
+    let relative = |path: &str| {
+        Path::new(path)
+            .strip_prefix(&root)
+            .map(|p| p.to_string_lossy().to_string())
+            .unwrap_or_else(|_| path.to_string())
+    };
+    let open: Vec<String> = open_files.iter().map(|p| relative(p)).collect();
+
+    state
+        .open_tabs
+        .retain(|tab| tab.path.as_ref().is_some_and(|p| open.contains(p)));
+    for path in open {
+        if state
+            .open_tabs
+            .iter()
+            .any(|t| t.path.as_ref() == Some(&path))
+        {
+            continue;
+        }
+        let title = Path::new(&path)
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_else(|| path.clone());
+        state.open_tabs.push(TabState {
+            id: format!("file-{}", path),
+            title,
+            tab_type: "file".to_string(),
+            path: Some(path),
+        });
+    }
+
+    state.active_file = active_file.map(relative);
+    if selected_model_id.is_some() {
+        state.selected_model_id = selected_model_id;
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::sync::atomic::AtomicU64;
+    use std::sync::Arc;
 
     #[test]
     fn test_project_state_filename() {
@@ -206,4 +407,73 @@ mod tests {
         assert_eq!(restored.open_tabs.len(), 1);
         assert_eq!(restored.selected_model_id, state.selected_model_id);
     }
+
+    #[test]
+    fn test_apply_editor_state() {
+        let mut state = ProjectState {
+            project_path: "/test/project".to_string(),
+            open_tabs: vec![
+                TabState {
+                    id: "tab-a".to_string(),
+                    title: "Alpha".to_string(),
+                    tab_type: "file".to_string(),
+                    path: Some("src/a.rs".to_string()),
+                },
+                TabState {
+                    id: "tab-b".to_string(),
+                    title: "b.rs".to_string(),
+                    tab_type: "file".to_string(),
+                    path: Some("src/b.rs".to_string()),
+                },
+            ],
+            selected_model_id: Some("saved-model".to_string()),
+            terminal_height: Some(300),
+            ..Default::default()
+        };
+
+        apply_editor_state(
+            &mut state,
+            Some("/test/project/src/c.rs"),
+            &["/test/project/src/a.rs".to_string(), "src/c.rs".to_string()],
+            None,
+        );
+
+        let tabs: Vec<(&str, &str)> = state
+            .open_tabs
+            .iter()
+            .map(|t| (t.id.as_str(), t.title.as_str()))
+            .collect();
+        assert_eq!(tabs, vec![("tab-a", "Alpha"), ("file-src/c.rs", "c.rs")]);
+        assert_eq!(state.active_file.as_deref(), Some("src/c.rs"));
+        assert_eq!(state.selected_model_id.as_deref(), Some("saved-model"));
+        assert_eq!(state.terminal_height, Some(300));
+    }
+
+    #[test]
+    fn test_autosave_coalesces_changes() {
+        // Long enough that only `flush` runs the saves
+        let autosave = Autosave::with_delay(Duration::from_secs(3600));
+        let saves = Arc::new(AtomicU64::new(0));
+        let schedule = |autosave: &Autosave| {
+            let saves = saves.clone();
+            autosave.schedule(move || {
+                saves.fetch_add(1, Ordering::SeqCst);
+            });
+        };
+
+        for _ in 0..5 {
+            schedule(&autosave);
+        }
+        autosave.flush();
+        assert_eq!(saves.load(Ordering::SeqCst), 1);
+        autosave.flush();
+        assert_eq!(saves.load(Ordering::SeqCst), 1);
+
+        // Disabling drops the waiting save and ignores new ones
+        schedule(&autosave);
+        autosave.set_enabled(false);
+        schedule(&autosave);
+        autosave.flush();
+        assert_eq!(saves.load(Ordering::SeqCst), 1);
+    }
 }
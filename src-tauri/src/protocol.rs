@@ -37,6 +37,12 @@ pub struct ChatMessage {
     pub content_before_tools: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub content_after_tools: Option<String>,
+    /// Timing and activity of a streamed assistant response
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub metrics: Option<MessageMetrics>,
+    /// `@file` references in a user message have been inlined into `content`
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub files_expanded: bool,
 }
 
 impl ChatMessage {
@@ -52,8 +58,55 @@ impl ChatMessage {
             progress: None,
             content_before_tools: None,
             content_after_tools: None,
+            metrics: None,
+            files_expanded: false,
         }
     }
+
+    /// Empty assistant message for a response that's about to stream, with its
+    /// metrics clock started
+    pub fn streaming_assistant() -> Self {
+        let mut msg = Self::new(ChatRole::Assistant, String::new());
+        msg.metrics = Some(MessageMetrics::started_now());
+        msg
+    }
+}
+
+/// Per-response metrics captured while streaming (timestamps are Unix milliseconds).
+/// `completed_at` is set when the response finishes, including any tool round trips.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct MessageMetrics {
+    pub started_at: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub completed_at: Option<u64>,
+    pub tool_call_count: usize,
+    pub chunk_count: usize,
+}
+
+impl MessageMetrics {
+    pub fn started_now() -> Self {
+        Self {
+            started_at: unix_millis(),
+            ..Default::default()
+        }
+    }
+
+    pub fn complete(&mut self) {
+        self.completed_at = Some(unix_millis());
+    }
+
+    /// How long the response took, once it has completed
+    pub fn duration_ms(&self) -> Option<u64> {
+        self.completed_at
+            .map(|done| done.saturating_sub(self.started_at))
+    }
+}
+
+fn unix_millis() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as u64
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -2,7 +2,7 @@ use crate::app_state::AppState;
 use crate::blade_protocol::{self, BladeError, BladeIntent, SystemEvent, Version};
 use crate::chat_orchestrator::handle_send_message;
 use crate::commands::{chat, files, tools};
-use tauri::{Emitter, State};
+use tauri::{Emitter, Manager, State};
 
 #[tauri::command]
 pub async fn dispatch(
@@ -334,6 +334,16 @@ pub async fn dispatch(
 
                 match std::fs::rename(&resolved_old, &resolved_new) {
                     Ok(_) => {
+                        // Keep open files and tabs pointing at the file's new location
+                        if state.rename_open_paths(&resolved_old, &resolved_new) {
+                            let _ = window.emit(
+                                crate::events::event_names::FILE_MOVED,
+                                crate::events::FileMovedPayload {
+                                    old_path: resolved_old.to_string_lossy().into_owned(),
+                                    new_path: resolved_new.to_string_lossy().into_owned(),
+                                },
+                            );
+                        }
                         let _ = window.emit(
                             "sys-event",
                             blade_protocol::BladeEvent::File(blade_protocol::FileEvent::Renamed {
@@ -357,19 +367,21 @@ pub async fn dispatch(
             
             match editor_intent {
                 blade_protocol::EditorIntent::OpenFile { path } => {
-                    if backend_authority {
-                        // Update backend state
-                        {
-                            let mut active = state.active_file.lock().unwrap();
-                            *active = Some(path.clone());
-                        }
-                        {
-                            let mut open = state.open_files.lock().unwrap();
-                            if !open.contains(&path) {
-                                open.push(path.clone());
-                            }
+                    // Always track the file for AI context, even under frontend authority
+                    {
+                        let mut active = state.active_file.lock().unwrap();
+                        *active = Some(path.clone());
+                    }
+                    {
+                        let mut open = state.open_files.lock().unwrap();
+                        if !open.contains(&path) {
+                            open.push(path.clone());
                         }
-                        
+                    }
+                    // Ask the UI to open the tab (same event AI edits use)
+                    let _ = window.emit("open-file", &path);
+
+                    if backend_authority {
                         // Emit FileOpened event
                         let _ = window.emit(
                             "blade-event",
@@ -446,7 +458,8 @@ pub async fn dispatch(
                     }
                     Ok(())
                 }
-                blade_protocol::EditorIntent::UpdateCursor { line, column } => {
+                blade_protocol::EditorIntent::UpdateCursor { line, column }
+                | blade_protocol::EditorIntent::SetCursor { line, column } => {
                     // Always update backend state for AI context (regardless of authority mode)
                     {
                         let mut cursor_line = state.cursor_line.lock().unwrap();
@@ -457,7 +470,11 @@ pub async fn dispatch(
                     // No event emission for cursor - too frequent, would cause noise
                     Ok(())
                 }
-                blade_protocol::EditorIntent::UpdateSelection { start, end } => {
+                blade_protocol::EditorIntent::UpdateSelection { start, end }
+                | blade_protocol::EditorIntent::SelectRange {
+                    start_line: start,
+                    end_line: end,
+                } => {
                     // Always update backend state for AI context
                     {
                         let mut sel_start = state.selection_start_line.lock().unwrap();
@@ -550,6 +567,7 @@ pub async fn dispatch(
                             let mut active_file = state.active_file.lock().unwrap();
                             *active_file = Some(p.clone());
                         }
+                        crate::project_state::schedule_autosave(window.app_handle());
                         
                         let _ = window.emit(
                             "blade-event",
@@ -623,6 +641,7 @@ pub async fn dispatch(
                             let mut active_file = state.active_file.lock().unwrap();
                             *active_file = tab_path;
                         }
+                        crate::project_state::schedule_autosave(window.app_handle());
                         
                         let _ = window.emit(
                             "blade-event",
@@ -810,12 +829,7 @@ pub async fn dispatch(
                 )
             }
             blade_protocol::TerminalIntent::Kill { id } => {
-                crate::terminal::kill_terminal(id, terminal_manager.clone()).map_err(|e| {
-                    blade_protocol::BladeError::Internal {
-                        trace_id: intent_id.to_string(),
-                        message: e,
-                    }
-                })
+                crate::terminal::kill_terminal(id, app_handle.clone(), terminal_manager.clone())
             }
         },
         BladeIntent::History(history_intent) => {
@@ -824,15 +838,19 @@ pub async fn dispatch(
                     println!("[History] ListConversations: project={}", project_id);
 
                     // Get config to create BladeClient
-                    let (blade_url, api_key) = {
+                    let (endpoints, api_key, timeout) = {
                         let config = state.config.lock().unwrap();
-                        (config.blade_url.clone(), config.api_key.clone())
+                        (config.normalized(), config.api_key.clone(), config.http_timeout())
                     };
+                    let endpoints = endpoints.map_err(invalid_blade_url)?;
 
-                    // Create HTTP client and BladeClient
-                    let http_client = reqwest::Client::new();
-                    let blade_client =
-                        crate::blade_client::BladeClient::new(blade_url, http_client, api_key);
+                    // Reuse the pooled HTTP client; bound the request so a hung server fails
+                    let blade_client = crate::blade_client::BladeClient::new(
+                        endpoints.http_base,
+                        state.blade_http.clone(),
+                        api_key,
+                    )
+                    .with_timeout(timeout);
 
                     // Call API
                     match blade_client.get_conversation_history(&project_id).await {
@@ -869,10 +887,7 @@ pub async fn dispatch(
                             Ok(())
                         }
                         Err(e) => {
-                            let error = blade_protocol::BladeError::Internal {
-                                trace_id: intent_id.to_string(),
-                                message: format!("{:?}", e),
-                            };
+                            let error = history_request_error(e, timeout, intent_id);
                             let _ = window.emit(
                                 "blade-event",
                                 blade_protocol::BladeEventEnvelope {
@@ -898,130 +913,84 @@ pub async fn dispatch(
                 blade_protocol::HistoryIntent::LoadConversation { session_id } => {
                     println!("[History] LoadConversation: session={}", session_id);
 
-                    // Get config to create BladeClient
-                    let (blade_url, api_key) = {
-                        let config = state.config.lock().unwrap();
-                        (config.blade_url.clone(), config.api_key.clone())
-                    };
-
-                    // Create HTTP client and BladeClient
-                    let http_client = reqwest::Client::new();
-                    let blade_client =
-                        crate::blade_client::BladeClient::new(blade_url, http_client, api_key);
-
-                    // Call API
-                    match blade_client.get_conversation(&session_id).await {
-                        Ok(response) => {
-                            // Parse response as FullConversation
-                            match serde_json::from_value::<blade_protocol::FullConversation>(
-                                response,
-                            ) {
-                                Ok(full_conversation) => {
-                                    // Verify message structure for debugging
-                                    eprintln!(
-                                        "[History] Loaded {} messages for session {}",
-                                        full_conversation.messages.len(),
-                                        full_conversation.session_id
-                                    );
+                    let full_conversation =
+                        fetch_full_conversation(&state, &session_id, intent_id).await?;
+                    apply_loaded_conversation(&state, &full_conversation);
 
-                                    // CRITICAL FIX: Update backend state to match the loaded conversation
-                                    // This ensures that subsequent "SendMessage" intents use the correct context and session ID
-                                    {
-                                        let mut conversation = state.conversation.lock().unwrap();
-
-                                        // Clear current conversation
-                                        conversation.clear();
-
-                                        // Update metadata
-                                        conversation.metadata.id = uuid::Uuid::new_v4().to_string(); // Temporary local ID
-                                        conversation.metadata.session_id =
-                                            Some(full_conversation.session_id.clone());
-                                        conversation.metadata.title =
-                                            full_conversation.title.clone();
-                                        // We don't have model_id in FullConversation, keep default or guess?
-                                        // Ideally we should get it. For now, keep existing or default.
-
-                                        // Convert messages
-                                        for msg in &full_conversation.messages {
-                                            let role = match msg.role.as_str() {
-                                                "user" => crate::protocol::ChatRole::User,
-                                                "assistant" => crate::protocol::ChatRole::Assistant,
-                                                "system" => crate::protocol::ChatRole::System,
-                                                "tool" => crate::protocol::ChatRole::Tool,
-                                                _ => crate::protocol::ChatRole::User,
-                                            };
-
-                                            let mut chat_msg = crate::protocol::ChatMessage::new(
-                                                role,
-                                                msg.content.clone(),
-                                            );
-
-                                            // Handle tool calls if present
-                                            if let Some(ref tc_val) = msg.tool_calls {
-                                                if let Ok(tool_calls) = serde_json::from_value::<
-                                                    Vec<crate::protocol::ToolCall>,
-                                                >(
-                                                    tc_val.clone()
-                                                ) {
-                                                    chat_msg.tool_calls = Some(tool_calls);
-                                                }
-                                            }
-
-                                            chat_msg.tool_call_id = msg.tool_call_id.clone();
-                                            // created_at is strictly for display in history, ChatMessage doesn't store it per message usually (or defaults to now)
-
-                                            conversation.push(chat_msg);
-                                        }
-
-                                        eprintln!("[History] Updated backend conversation state");
-                                    }
-
-                                    // Update ChatManager session_id
-                                    {
-                                        let mut mgr = state.chat_manager.lock().unwrap();
-                                        mgr.session_id = Some(full_conversation.session_id.clone());
-                                        eprintln!(
-                                            "[History] Updated ChatManager session_id to {}",
-                                            full_conversation.session_id
-                                        );
-                                    }
+                    let _ = window.emit(
+                        "blade-event",
+                        blade_protocol::BladeEventEnvelope {
+                            id: uuid::Uuid::new_v4(),
+                            timestamp: std::time::SystemTime::now()
+                                .duration_since(std::time::UNIX_EPOCH)
+                                .unwrap_or_default()
+                                .as_millis() as u64,
+                            causality_id: Some(intent_id.to_string()),
+                            event: blade_protocol::BladeEvent::History(
+                                blade_protocol::HistoryEvent::ConversationLoaded(full_conversation),
+                            ),
+                        },
+                    );
+                    Ok(())
+                }
+                blade_protocol::HistoryIntent::LoadConversationPage {
+                    session_id,
+                    before_message_id,
+                    limit,
+                } => {
+                    println!(
+                        "[History] LoadConversationPage: session={}, before={:?}, limit={}",
+                        session_id, before_message_id, limit
+                    );
 
-                                    let _ = window.emit(
-                                        "blade-event",
-                                        blade_protocol::BladeEventEnvelope {
-                                            id: uuid::Uuid::new_v4(),
-                                            timestamp: std::time::SystemTime::now()
-                                                .duration_since(std::time::UNIX_EPOCH)
-                                                .unwrap_or_default()
-                                                .as_millis()
-                                                as u64,
-                                            causality_id: Some(intent_id.to_string()),
-                                            event: blade_protocol::BladeEvent::History(
-                                                blade_protocol::HistoryEvent::ConversationLoaded(
-                                                    full_conversation,
-                                                ),
-                                            ),
-                                        },
-                                    );
-                                    Ok(())
-                                }
-                                Err(e) => {
-                                    eprintln!("[History] Failed to parse conversation data: {}", e);
-                                    Err(blade_protocol::BladeError::Internal {
-                                        trace_id: intent_id.to_string(),
-                                        message: format!(
-                                            "Failed to parse conversation data: {}",
-                                            e
-                                        ),
-                                    })
-                                }
+                    // Older pages come from the conversation fetched for the first page so
+                    // scrolling back doesn't refetch the whole thing each time
+                    let cached = if before_message_id.is_some() {
+                        state
+                            .history_page_cache
+                            .lock()
+                            .unwrap()
+                            .clone()
+                            .filter(|c| c.session_id == session_id)
+                    } else {
+                        None
+                    };
+                    let full_conversation = match cached {
+                        Some(conversation) => conversation,
+                        None => {
+                            let mut conversation =
+                                fetch_full_conversation(&state, &session_id, intent_id).await?;
+                            conversation.assign_message_ids();
+                            if before_message_id.is_none() {
+                                apply_loaded_conversation(&state, &conversation);
                             }
+                            *state.history_page_cache.lock().unwrap() = Some(conversation.clone());
+                            conversation
                         }
-                        Err(e) => Err(blade_protocol::BladeError::Internal {
-                            trace_id: intent_id.to_string(),
-                            message: format!("{:?}", e),
-                        }),
-                    }
+                    };
+
+                    let (messages, has_more) =
+                        full_conversation.page(before_message_id.as_deref(), limit);
+
+                    let _ = window.emit(
+                        "blade-event",
+                        blade_protocol::BladeEventEnvelope {
+                            id: uuid::Uuid::new_v4(),
+                            timestamp: std::time::SystemTime::now()
+                                .duration_since(std::time::UNIX_EPOCH)
+                                .unwrap_or_default()
+                                .as_millis() as u64,
+                            causality_id: Some(intent_id.to_string()),
+                            event: blade_protocol::BladeEvent::History(
+                                blade_protocol::HistoryEvent::ConversationPage {
+                                    session_id,
+                                    messages,
+                                    has_more,
+                                },
+                            ),
+                        },
+                    );
+                    Ok(())
                 }
             }
         }
@@ -1033,15 +1002,18 @@ pub async fn dispatch(
             match language_intent {
                 blade_protocol::LanguageIntent::ZlpMessage { data } => {
                     // 1. Get config
-                    let (blade_url, api_key) = {
+                    let (endpoints, api_key) = {
                         let config = state.config.lock().unwrap();
-                        (config.blade_url.clone(), config.api_key.clone())
+                        (config.normalized(), config.api_key.clone())
                     };
+                    let endpoints = endpoints.map_err(invalid_blade_url)?;
 
                     // 2. Create client
-                    let http_client = reqwest::Client::new();
-                    let blade_client =
-                        crate::blade_client::BladeClient::new(blade_url, http_client, api_key);
+                    let blade_client = crate::blade_client::BladeClient::new(
+                        endpoints.http_base,
+                        state.blade_http.clone(),
+                        api_key,
+                    );
 
                     // 3. Send request
                     let mut rx = blade_client.send_zlp_request(data).await.map_err(|e| {
@@ -1133,3 +1105,132 @@ pub async fn dispatch(
         }
     }
 }
+
+fn invalid_blade_url(message: String) -> BladeError {
+    BladeError::ValidationError {
+        field: "blade_url".to_string(),
+        message,
+    }
+}
+
+/// Map a failed history request to a protocol error, surfacing timeouts as `Timeout`
+fn history_request_error(
+    error: crate::blade_client::BladeRequestError,
+    timeout: std::time::Duration,
+    intent_id: uuid::Uuid,
+) -> BladeError {
+    eprintln!("[History] Request failed: {}", error);
+    match error {
+        crate::blade_client::BladeRequestError::Timeout => BladeError::Timeout {
+            timeout_ms: timeout.as_millis() as u64,
+        },
+        crate::blade_client::BladeRequestError::Failed(message) => BladeError::Internal {
+            trace_id: intent_id.to_string(),
+            message,
+        },
+    }
+}
+
+/// Fetch and parse a full server-side conversation for the History panel
+async fn fetch_full_conversation(
+    state: &AppState,
+    session_id: &str,
+    intent_id: uuid::Uuid,
+) -> Result<blade_protocol::FullConversation, BladeError> {
+    // Get config to create BladeClient
+    let (endpoints, api_key, timeout) = {
+        let config = state.config.lock().unwrap();
+        (config.normalized(), config.api_key.clone(), config.http_timeout())
+    };
+    let endpoints = endpoints.map_err(invalid_blade_url)?;
+
+    // Reuse the pooled HTTP client; bound the request so a hung server fails
+    let blade_client = crate::blade_client::BladeClient::new(
+        endpoints.http_base,
+        state.blade_http.clone(),
+        api_key,
+    )
+    .with_timeout(timeout);
+
+    let response = blade_client
+        .get_conversation(session_id)
+        .await
+        .map_err(|e| history_request_error(e, timeout, intent_id))?;
+
+    let full_conversation = serde_json::from_value::<blade_protocol::FullConversation>(response)
+        .map_err(|e| {
+            eprintln!("[History] Failed to parse conversation data: {}", e);
+            BladeError::Internal {
+                trace_id: intent_id.to_string(),
+                message: format!("Failed to parse conversation data: {}", e),
+            }
+        })?;
+
+    // Verify message structure for debugging
+    eprintln!(
+        "[History] Loaded {} messages for session {}",
+        full_conversation.messages.len(),
+        full_conversation.session_id
+    );
+    Ok(full_conversation)
+}
+
+/// Update backend state to match a loaded conversation so that subsequent
+/// "SendMessage" intents use the correct context and session ID
+fn apply_loaded_conversation(
+    state: &AppState,
+    full_conversation: &blade_protocol::FullConversation,
+) {
+    {
+        let mut conversation = state.conversation.lock().unwrap();
+
+        // Clear current conversation
+        conversation.clear();
+
+        // Update metadata
+        conversation.metadata.id = uuid::Uuid::new_v4().to_string(); // Temporary local ID
+        conversation.metadata.session_id = Some(full_conversation.session_id.clone());
+        conversation.metadata.title = full_conversation.title.clone();
+        // We don't have model_id in FullConversation, keep default or guess?
+        // Ideally we should get it. For now, keep existing or default.
+
+        // Convert messages
+        for msg in &full_conversation.messages {
+            let role = match msg.role.as_str() {
+                "user" => crate::protocol::ChatRole::User,
+                "assistant" => crate::protocol::ChatRole::Assistant,
+                "system" => crate::protocol::ChatRole::System,
+                "tool" => crate::protocol::ChatRole::Tool,
+                _ => crate::protocol::ChatRole::User,
+            };
+
+            let mut chat_msg = crate::protocol::ChatMessage::new(role, msg.content.clone());
+
+            // Handle tool calls if present
+            if let Some(ref tc_val) = msg.tool_calls {
+                if let Ok(tool_calls) =
+                    serde_json::from_value::<Vec<crate::protocol::ToolCall>>(tc_val.clone())
+                {
+                    chat_msg.tool_calls = Some(tool_calls);
+                }
+            }
+
+            chat_msg.tool_call_id = msg.tool_call_id.clone();
+            // created_at is strictly for display in history, ChatMessage doesn't store it per message usually (or defaults to now)
+
+            conversation.push(chat_msg);
+        }
+
+        eprintln!("[History] Updated backend conversation state");
+    }
+
+    // Update ChatManager session_id
+    {
+        let mut mgr = state.chat_manager.lock().unwrap();
+        mgr.session_id = Some(full_conversation.session_id.clone());
+        eprintln!(
+            "[History] Updated ChatManager session_id to {}",
+            full_conversation.session_id
+        );
+    }
+}
@@ -186,14 +186,11 @@ impl ReasoningParser {
     /// Process a text chunk, returning ordered segments of text/reasoning
     pub fn process_segments(&mut self, chunk: &str) -> Vec<ReasoningSegment> {
         let mut segments = Vec::new();
-        let mut remaining = chunk;
 
-        // If we have buffered content from a previous chunk, prepend it
-        if !self.tag_buffer.is_empty() {
-            let combined = format!("{}{}", self.tag_buffer, chunk);
-            self.tag_buffer.clear();
-            return self.process_segments(&combined);
-        }
+        // Stitch any partial tag left over from the previous chunk onto this one
+        let mut combined = std::mem::take(&mut self.tag_buffer);
+        combined.push_str(chunk);
+        let mut remaining = combined.as_str();
 
         loop {
             if remaining.is_empty() {
@@ -214,7 +211,7 @@ impl ReasoningParser {
 
                     // Skip past the opening tag
                     remaining = &remaining[idx + format.open_len()..];
-                } else if let Some(partial_idx) = self.find_partial_opening(remaining) {
+                } else if let Some(partial_idx) = self.partial_opening_start(remaining) {
                     let before = &remaining[..partial_idx];
                     if !before.is_empty() {
                         segments.push(ReasoningSegment::Text(before.to_string()));
@@ -285,34 +282,29 @@ impl ReasoningParser {
         best
     }
 
-    /// Check if the end of text contains a partial opening tag
-    fn find_partial_opening(&self, text: &str) -> Option<usize> {
-        // Check last N characters for partial matches
-        for format in &self.formats {
-            let tag = format.open_tag();
-            for i in 1..tag.len() {
-                let suffix = &tag[..i];
-                if text.ends_with(suffix) {
-                    return Some(text.len() - i);
-                }
-            }
-        }
-        None
+    /// Start index of a partial opening tag at the end of text, if any.
+    /// Prefers the longest partial match across all formats.
+    pub fn partial_opening_start(&self, text: &str) -> Option<usize> {
+        self.formats
+            .iter()
+            .filter_map(|format| partial_tag_len(text, format.open_tag()))
+            .max()
+            .map(|len| text.len() - len)
     }
 
     /// Check if the end of text contains a partial closing tag
     fn find_partial_closing(&self, text: &str, format: ReasoningFormat) -> Option<usize> {
-        let tag = format.close_tag();
-        for i in 1..tag.len() {
-            let suffix = &tag[..i];
-            if text.ends_with(suffix) {
-                return Some(text.len() - i);
-            }
-        }
-        None
+        partial_tag_len(text, format.close_tag()).map(|len| text.len() - len)
     }
 }
 
+/// Length of the longest proper prefix of `tag` that `text` ends with
+fn partial_tag_len(text: &str, tag: &str) -> Option<usize> {
+    (1..tag.len())
+        .rev()
+        .find(|&i| text.ends_with(&tag[..i]))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -391,4 +383,45 @@ mod tests {
         // Second call should return None
         assert_eq!(parser.interrupt_for_tool(), None);
     }
+
+    #[test]
+    fn test_tags_split_across_chunks() {
+        let mut parser = ReasoningParser::new();
+        let mut result = ParseResult::new();
+
+        for chunk in ["<thin", "k>reasoning</thi", "nk>text"] {
+            let r = parser.process(chunk);
+            result.text.push_str(&r.text);
+            result.reasoning.push_str(&r.reasoning);
+        }
+        for segment in parser.flush() {
+            match segment {
+                ReasoningSegment::Text(text) => result.text.push_str(&text),
+                ReasoningSegment::Reasoning(reasoning) => result.reasoning.push_str(&reasoning),
+            }
+        }
+
+        assert_eq!(result.reasoning, "reasoning");
+        assert_eq!(result.text, "text");
+    }
+
+    #[test]
+    fn test_thinking_tag_split_after_think_prefix() {
+        let mut parser = ReasoningParser::new();
+
+        let r1 = parser.process("Answer: <think");
+        assert_eq!(r1.text, "Answer: ");
+
+        let r2 = parser.process("ing>plan</thinking>done");
+        assert_eq!(r2.reasoning, "plan");
+        assert_eq!(r2.text, "done");
+    }
+
+    #[test]
+    fn test_partial_opening_start() {
+        let parser = ReasoningParser::new();
+        assert_eq!(parser.partial_opening_start("abc<thinki"), Some(3));
+        assert_eq!(parser.partial_opening_start("abc <"), Some(4));
+        assert_eq!(parser.partial_opening_start("a < b"), None);
+    }
 }
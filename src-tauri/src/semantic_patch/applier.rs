@@ -3,10 +3,10 @@
 //! Applies semantic patches to source files using AST-aware modification.
 //! Handles conflict detection and ensures valid state transitions.
 
-use super::diff::generate_diff;
+use super::diff::{generate_diff, DiffHunk};
 use super::patch::{InsertPosition, PatchOperation, PatchTarget, SemanticPatch};
 use crate::language_service::LanguageService;
-use crate::tree_sitter::Symbol;
+use crate::tree_sitter::{Language, Symbol, SymbolType, TreeSitterParser};
 use std::sync::Arc;
 
 /// Result of applying a patch
@@ -32,6 +32,7 @@ pub enum ApplyError {
     ServiceError(String),
     UnsupportedOperation(String),
     TargetNotFound(String),
+    AmbiguousTarget(String),
 }
 
 impl std::fmt::Display for ApplyError {
@@ -44,6 +45,7 @@ impl std::fmt::Display for ApplyError {
             ApplyError::ServiceError(s) => write!(f, "Service error: {}", s),
             ApplyError::UnsupportedOperation(o) => write!(f, "Unsupported operation: {}", o),
             ApplyError::TargetNotFound(t) => write!(f, "Target not found: {}", t),
+            ApplyError::AmbiguousTarget(t) => write!(f, "Ambiguous target: {}", t),
         }
     }
 }
@@ -67,6 +69,33 @@ impl PatchApplier {
 
     /// Apply a semantic patch to a file
     pub fn apply(&self, patch: &SemanticPatch) -> Result<ApplyResult, ApplyError> {
+        let (content, new_content) = self.compute(patch)?;
+
+        // Generate diff
+        let diff_hunks = generate_diff(&content, &new_content, 3);
+        let diff_str = diff_hunks
+            .iter()
+            .map(|h| h.to_string())
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        Ok(ApplyResult {
+            new_content,
+            diff: diff_str,
+            file_path: patch.file_path.clone(),
+            original_content: content,
+        })
+    }
+
+    /// Compute the change a patch would make and return it as diff hunks.
+    /// Nothing is written to disk.
+    pub fn preview(&self, patch: &SemanticPatch) -> Result<Vec<DiffHunk>, ApplyError> {
+        let (content, new_content) = self.compute(patch)?;
+        Ok(generate_diff(&content, &new_content, 3))
+    }
+
+    /// Resolve the patch target and return `(original, modified)` file content
+    fn compute(&self, patch: &SemanticPatch) -> Result<(String, String), ApplyError> {
         // Resolve full path for IO
         let full_path = self.language_service.resolve_path(&patch.file_path);
         let full_path_str = full_path.to_string_lossy();
@@ -114,20 +143,7 @@ impl PatchApplier {
             }
         };
 
-        // 4. Generate diff
-        let diff_hunks = generate_diff(&content, &new_content, 3);
-        let diff_str = diff_hunks
-            .iter()
-            .map(|h| h.to_string())
-            .collect::<Vec<_>>()
-            .join("\n");
-
-        Ok(ApplyResult {
-            new_content,
-            diff: diff_str,
-            file_path: patch.file_path.clone(),
-            original_content: content,
-        })
+        Ok((content, new_content))
     }
 
     /// Resolve target to byte range
@@ -142,24 +158,19 @@ impl PatchApplier {
             .map_err(|_| ApplyError::FileNotFound(full_path.to_string()))?;
 
         match target {
-            PatchTarget::Symbol {
-                name,
-                symbol_type: _,
-            } => {
+            PatchTarget::Symbol { name, symbol_type } => {
                 // Use semantic path (from patch) for symbol lookup
-                let symbols = self
-                    .language_service
-                    .get_file_symbols(semantic_path)
-                    .map_err(|e| ApplyError::ServiceError(e.to_string()))?;
-
-                let symbol = symbols
-                    .into_iter()
-                    .find(|s| s.name == *name)
-                    .ok_or_else(|| ApplyError::SymbolNotFound(name.clone()))?;
+                let symbol = self.find_unique_symbol(name, *symbol_type, semantic_path)?;
 
                 // Use full path for byte range calculation (reading file)
                 self.get_symbol_byte_range(&symbol, full_path)
             }
+            PatchTarget::SymbolBody { name, symbol_type } => {
+                let symbol = self.find_unique_symbol(name, *symbol_type, semantic_path)?;
+                let (start, end) = self.get_symbol_byte_range(&symbol, full_path)?;
+                self.get_body_byte_range(full_path, start, end)
+                    .ok_or_else(|| ApplyError::TargetNotFound(format!("body of {}", name)))
+            }
             PatchTarget::LineRange { start, end } => {
                 self.get_line_byte_range(full_path, *start, *end)
             }
@@ -174,6 +185,66 @@ impl PatchApplier {
         }
     }
 
+    /// Look up a symbol by name (and kind, if given), failing when more than one matches
+    fn find_unique_symbol(
+        &self,
+        name: &str,
+        symbol_type: Option<SymbolType>,
+        semantic_path: &str,
+    ) -> Result<Symbol, ApplyError> {
+        let mut matches: Vec<Symbol> = self
+            .language_service
+            .get_file_symbols(semantic_path)
+            .map_err(|e| ApplyError::ServiceError(e.to_string()))?
+            .into_iter()
+            .filter(|s| {
+                s.name == name && (symbol_type.is_none() || symbol_type == Some(s.symbol_type))
+            })
+            .collect();
+
+        match matches.len() {
+            0 => Err(ApplyError::SymbolNotFound(name.to_string())),
+            1 => Ok(matches.remove(0)),
+            n => {
+                let lines: Vec<String> = matches
+                    .iter()
+                    .map(|s| format!("{} at line {}", s.symbol_type, s.range.start.line + 1))
+                    .collect();
+                Err(ApplyError::AmbiguousTarget(format!(
+                    "{} matches {} symbols ({}); specify the symbol kind",
+                    name,
+                    n,
+                    lines.join(", ")
+                )))
+            }
+        }
+    }
+
+    /// Find the `body` node of the definition spanning `start..end`.
+    /// Arrow functions are indexed by their declarator, so look through its `value` too.
+    fn get_body_byte_range(
+        &self,
+        full_path: &str,
+        start: usize,
+        end: usize,
+    ) -> Option<(usize, usize)> {
+        let content = std::fs::read_to_string(full_path).ok()?;
+        let language = Language::from_path(full_path)?;
+        let mut parser = TreeSitterParser::new().ok()?;
+        let tree = parser.parse(&content, language).ok()?;
+
+        let mut node = tree.root_node().descendant_for_byte_range(start, end)?;
+        while node.start_byte() > start || node.end_byte() < end {
+            node = node.parent()?;
+        }
+
+        let body = node.child_by_field_name("body").or_else(|| {
+            node.child_by_field_name("value")
+                .and_then(|value| value.child_by_field_name("body"))
+        })?;
+        Some((body.start_byte(), body.end_byte()))
+    }
+
     /// Calculate byte range for a symbol
     fn get_symbol_byte_range(
         &self,
@@ -362,4 +433,65 @@ mod tests {
 
         // Implementation Fix needed in PatchApplier: normalize path for symbol lookup.
     }
+
+    #[test]
+    fn test_symbol_body_and_ambiguous_targets() {
+        let (applier, temp_dir) = create_test_env();
+        let file_path = temp_dir.path().join("body_test.rs");
+        fs::write(
+            &file_path,
+            "struct load {}\n\nfn load() -> u32 {\n    1\n}\n",
+        )
+        .unwrap();
+        let path = file_path.to_string_lossy().to_string();
+        applier.language_service.index_file(&path).unwrap();
+
+        let ambiguous = SemanticPatch {
+            target: PatchTarget::Symbol {
+                name: "load".to_string(),
+                symbol_type: None,
+            },
+            ..SemanticPatch::replace_symbol(&path, "load", None, "", "test")
+        };
+        assert!(matches!(
+            applier.apply(&ambiguous),
+            Err(ApplyError::AmbiguousTarget(_))
+        ));
+
+        let body = SemanticPatch {
+            target: PatchTarget::SymbolBody {
+                name: "load".to_string(),
+                symbol_type: Some(SymbolType::Function),
+            },
+            ..SemanticPatch::replace_symbol(&path, "load", None, "{\n    2\n}", "test")
+        };
+        let result = applier.apply(&body).unwrap();
+        assert_eq!(
+            result.new_content,
+            "struct load {}\n\nfn load() -> u32 {\n    2\n}\n"
+        );
+    }
+
+    #[test]
+    fn test_preview_does_not_write() {
+        let (applier, temp_dir) = create_test_env();
+        let file_path = temp_dir.path().join("preview_test.rs");
+        let original = "fn value() -> u32 {\n    1\n}\n";
+        fs::write(&file_path, original).unwrap();
+        let path = file_path.to_string_lossy().to_string();
+        applier.language_service.index_file(&path).unwrap();
+
+        let patch = SemanticPatch {
+            target: PatchTarget::SymbolBody {
+                name: "value".to_string(),
+                symbol_type: None,
+            },
+            ..SemanticPatch::replace_symbol(&path, "value", None, "{\n    2\n}", "test")
+        };
+        let hunks = applier.preview(&patch).unwrap();
+
+        assert_eq!(hunks.len(), 1);
+        assert!(hunks[0].to_string().contains("+    2"));
+        assert_eq!(fs::read_to_string(&file_path).unwrap(), original);
+    }
 }
@@ -90,6 +90,11 @@ pub enum PatchTarget {
         name: String,
         symbol_type: Option<SymbolType>,
     },
+    /// Target the body of a symbol (e.g. a function's block), leaving its signature intact
+    SymbolBody {
+        name: String,
+        symbol_type: Option<SymbolType>,
+    },
     /// Target a line range
     LineRange { start: u32, end: u32 },
     /// Target based on a text pattern (regex)
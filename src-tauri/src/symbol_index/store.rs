@@ -315,6 +315,20 @@ impl SymbolStore {
         Ok(count)
     }
 
+    /// Forget a file entirely: its symbols and its indexed hash
+    pub fn remove_file(&self, file_path: &str) -> Result<usize, SymbolStoreError> {
+        let conn = self.conn.lock().unwrap();
+        let count = conn.execute(
+            "DELETE FROM symbols WHERE file_path = ?1",
+            params![file_path],
+        )?;
+        conn.execute(
+            "DELETE FROM indexed_files WHERE file_path = ?1",
+            params![file_path],
+        )?;
+        Ok(count)
+    }
+
     /// Delete all symbols
     pub fn clear(&self) -> Result<(), SymbolStoreError> {
         let conn = self.conn.lock().unwrap();
@@ -1,7 +1,7 @@
-use crate::events::{event_names, TerminalCwdChangedPayload};
+use crate::events::{event_names, TerminalClosedPayload, TerminalCwdChangedPayload};
 use portable_pty::{CommandBuilder, NativePtySystem, PtySize, PtySystem};
 use std::{
-    collections::HashMap,
+    collections::{HashMap, VecDeque},
     io::{Read, Write},
     sync::{Arc, Mutex},
     thread,
@@ -23,16 +23,104 @@ pub struct PtyState {
 pub struct TerminalManager {
     // Map of Terminal ID -> PtyState
     pub ptys: Arc<Mutex<HashMap<String, PtyState>>>,
+    /// Recent output per terminal id, kept after the process exits until the
+    /// terminal is killed or its id is reused
+    pub scrollback: Arc<Mutex<HashMap<String, Scrollback>>>,
 }
 
 impl TerminalManager {
     pub fn new() -> Self {
         Self {
             ptys: Arc::new(Mutex::new(HashMap::new())),
+            scrollback: Arc::new(Mutex::new(HashMap::new())),
         }
     }
 }
 
+/// An unterminated line longer than this is stored as a line of its own
+const MAX_PARTIAL_LINE_BYTES: usize = 64 * 1024;
+
+/// Ring buffer of a terminal's most recent output lines, ANSI sequences
+/// included so the frontend can repaint from it
+pub struct Scrollback {
+    lines: VecDeque<String>,
+    partial: String,
+    max_lines: usize,
+}
+
+impl Scrollback {
+    pub fn new(max_lines: usize) -> Self {
+        Self {
+            lines: VecDeque::new(),
+            partial: String::new(),
+            max_lines,
+        }
+    }
+
+    pub fn push(&mut self, data: &str) {
+        self.partial.push_str(data);
+        while let Some(pos) = self.partial.find('\n') {
+            let line: String = self.partial.drain(..=pos).collect();
+            self.push_line(line);
+        }
+        if self.partial.len() > MAX_PARTIAL_LINE_BYTES {
+            let line = std::mem::take(&mut self.partial);
+            self.push_line(line);
+        }
+    }
+
+    fn push_line(&mut self, line: String) {
+        self.lines.push_back(line);
+        while self.lines.len() > self.max_lines {
+            self.lines.pop_front();
+        }
+    }
+
+    /// The last `max_lines` lines (all of them for `None`), followed by the
+    /// line still being written
+    pub fn tail(&self, max_lines: Option<usize>) -> String {
+        let skip = max_lines.map_or(0, |n| self.lines.len().saturating_sub(n));
+        let mut out: String = self.lines.iter().skip(skip).map(String::as_str).collect();
+        out.push_str(&self.partial);
+        out
+    }
+}
+
+/// Terminal the frontend runs AI-issued commands in (`BLADE_TERMINAL_ID` in
+/// src/constants/terminal.ts)
+const BLADE_TERMINAL_ID: &str = "blade";
+
+/// Editor environment variables the current project withholds from AI-run commands
+fn denied_command_env<R: Runtime>(app_handle: &tauri::AppHandle<R>) -> Vec<String> {
+    use tauri::Manager;
+    let workspace = app_handle
+        .try_state::<crate::app_state::AppState>()
+        .and_then(|state| state.workspace.lock().unwrap().workspace.clone());
+    crate::project_settings::denied_command_env(workspace.as_deref())
+}
+
+/// The user's shell settings from the global config
+fn user_shell<R: Runtime>(app_handle: &tauri::AppHandle<R>) -> crate::config::ShellSettings {
+    use tauri::Manager;
+    app_handle
+        .try_state::<crate::app_state::AppState>()
+        .map(|state| state.config.lock().unwrap().shell.clone())
+        .unwrap_or_default()
+}
+
+/// Terminals stay closed until the user trusts the open workspace
+fn ensure_workspace_trusted<R: Runtime>(app_handle: &tauri::AppHandle<R>) -> Result<(), String> {
+    use tauri::Manager;
+    let trusted = app_handle
+        .try_state::<crate::app_state::AppState>()
+        .is_none_or(|state| state.workspace.lock().unwrap().is_current_trusted());
+    if trusted {
+        Ok(())
+    } else {
+        Err(crate::workspace_manager::UNTRUSTED_WORKSPACE_ERROR.to_string())
+    }
+}
+
 // Commands to be exposed to Tauri
 
 // #[tauri::command]
@@ -43,6 +131,7 @@ pub fn create_terminal<R: Runtime>(
     app_handle: tauri::AppHandle<R>,
     state: tauri::State<'_, TerminalManager>,
 ) -> Result<(), String> {
+    ensure_workspace_trusted(&app_handle)?;
     let pty_system = NativePtySystem::default();
 
     // Configure the PTY
@@ -56,21 +145,28 @@ pub fn create_terminal<R: Runtime>(
         .map_err(|e| e.to_string())?;
 
     // Determine shell and command mode
-    let shell = std::env::var("SHELL").unwrap_or_else(|_| "bash".to_string());
-    let shell_name = std::path::Path::new(&shell)
-        .file_name()
-        .and_then(|name| name.to_str())
-        .unwrap_or("sh");
+    let shell_settings = user_shell(&app_handle);
+    let shell = shell_settings.program();
+    let shell_name = shell_settings.name();
 
     let (mut cmd, is_interactive) = if let Some(cmd_str) = command {
-        let mut builder = CommandBuilder::new(shell.clone());
-        builder.arg("-c");
-        builder.arg(cmd_str);
+        let mut builder = CommandBuilder::new(&shell);
+        builder.args(shell_settings.command_args(&cmd_str));
         (builder, false)
     } else {
-        (CommandBuilder::new(shell.clone()), true)
+        let mut builder = CommandBuilder::new(&shell);
+        builder.args(shell_settings.interactive_args());
+        (builder, true)
     };
 
+    // One-shot commands and the Blade terminal run AI-issued commands; the
+    // user's own interactive terminals keep the full environment
+    if !is_interactive || id == BLADE_TERMINAL_ID {
+        for name in denied_command_env(&app_handle) {
+            cmd.env_remove(name);
+        }
+    }
+
     // Set working directory if provided
     if let Some(path) = cwd {
         cmd.cwd(path);
@@ -151,10 +247,16 @@ pub fn create_terminal<R: Runtime>(
         },
     );
 
+    state.scrollback.lock().unwrap().insert(
+        id.clone(),
+        Scrollback::new(settings.terminal_scrollback_lines),
+    );
+
     // Spawn a thread to read output and emit to frontend
     let id_clone = id.clone();
     let app_handle_clone = app_handle.clone();
     let ptys_arc = state.ptys.clone();
+    let scrollback_arc = state.scrollback.clone();
 
     thread::spawn(move || {
         let mut buffer = [0u8; 4096];
@@ -166,17 +268,13 @@ pub fn create_terminal<R: Runtime>(
 
         let emit_output = |app: &tauri::AppHandle<R>, id: &str, data: String, seq: &Arc<Mutex<u64>>| {
             if data.is_empty() { return; }
-            let _seq = {
+            let seq = {
                 let mut seq_guard = seq.lock().unwrap();
                 let current = *seq_guard;
                 *seq_guard += 1;
                 current
             };
-            let payload = TerminalOutput {
-                id: id.to_string(),
-                data,
-            };
-            let _ = app.emit("terminal-output", payload);
+            emit_terminal_output(app, id, seq, data);
         };
 
         let process_chunk = |processable: &str,
@@ -193,6 +291,14 @@ pub fn create_terminal<R: Runtime>(
             // 1. Process STARTED sentinels first → sets active_cmd so output
             //    accumulation works for output in this same chunk.
             for call_id in &sentinel_result.started {
+                // The shell's echo of the command line carries the sentinel too
+                if active_cmd.as_deref() != Some(call_id.as_str()) {
+                    crate::commands::tools::spawn_command_timeout(
+                        app,
+                        id.to_string(),
+                        call_id.clone(),
+                    );
+                }
                 *active_cmd = Some(call_id.clone());
                 cmd_output_buffer.clear();
                 let _ = app.emit(
@@ -207,6 +313,9 @@ pub fn create_terminal<R: Runtime>(
             // 2. Emit cleaned output for terminal display AND accumulate for
             //    the active command (active_cmd is now set if start was in this chunk).
             if !sentinel_result.cleaned.is_empty() {
+                if let Some(scrollback) = scrollback_arc.lock().unwrap().get_mut(id) {
+                    scrollback.push(&sentinel_result.cleaned);
+                }
                 if active_cmd.is_some() {
                     cmd_output_buffer.push_str(&sentinel_result.cleaned);
                 }
@@ -226,6 +335,7 @@ pub fn create_terminal<R: Runtime>(
                         call_id: call_id.clone(),
                         exit_code: *exit_code,
                         output,
+                        cancelled: None,
                     },
                 );
             }
@@ -292,24 +402,38 @@ pub fn create_terminal<R: Runtime>(
             }
         }
 
-        // Emit exit event and cleanup PTY
-        let exit_code = {
+        // Emit exit event and cleanup PTY. A missing entry means kill_terminal
+        // already took the child, i.e. we killed it.
+        let (exit_code, killed) = {
             let mut ptys = ptys_arc.lock().unwrap();
             if let Some(mut pty) = ptys.remove(&id_clone) {
                 match pty.child.wait() {
-                    Ok(status) => status.exit_code() as i32,
-                    Err(_) => 1,
+                    Ok(status) => (status.exit_code() as i32, false),
+                    Err(_) => (1, false),
                 }
             } else {
-                0
+                (0, true)
             }
         };
 
+        // A command cut off with its terminal never prints its exit sentinel
+        if let Some(call_id) = active_cmd.take() {
+            emit_command_exited(
+                &app_handle_clone,
+                &id_clone,
+                &call_id,
+                if killed { 143 } else { exit_code }, // 143 = SIGTERM
+                cmd_output_buffer,
+                Some(killed),
+            );
+        }
+
         let _ = app_handle_clone.emit(
             "terminal-exit",
             TerminalExit {
                 id: id_clone,
                 exit_code,
+                cancelled: killed,
             },
         );
 
@@ -321,17 +445,145 @@ pub fn create_terminal<R: Runtime>(
     Ok(())
 }
 
-pub fn kill_terminal(
+/// Grace period between SIGTERM and the forced kill of a terminal's child process.
+#[cfg(unix)]
+const KILL_GRACE_PERIOD: std::time::Duration = std::time::Duration::from_secs(2);
+
+pub fn kill_terminal<R: Runtime>(
     id: String,
+    app_handle: tauri::AppHandle<R>,
     state: tauri::State<'_, TerminalManager>,
-) -> Result<(), String> {
-    let mut ptys = state.ptys.lock().unwrap();
-    if let Some(mut pty) = ptys.remove(&id) {
-        let _ = pty.child.kill();
-    }
+) -> Result<(), crate::blade_protocol::BladeError> {
+    // Remove the entry first so the reader thread doesn't race us for the child.
+    let pty = {
+        let mut ptys = state.ptys.lock().unwrap();
+        ptys.remove(&id)
+    };
+    let Some(pty) = pty else {
+        return Err(crate::blade_protocol::BladeError::ResourceNotFound { id });
+    };
+    state.scrollback.lock().unwrap().remove(&id);
+
+    // Terminate off the dispatcher thread: the grace period can take a while.
+    thread::spawn(move || {
+        let PtyState {
+            mut child, master, ..
+        } = pty;
+        terminate_child(&mut child);
+        // Dropping the master closes the PTY so the reader thread hits EOF.
+        drop(master);
+
+        let _ = app_handle.emit(
+            event_names::TERMINAL_CLOSED,
+            TerminalClosedPayload { id: id.clone() },
+        );
+    });
+
     Ok(())
 }
 
+/// Ask the child to exit with SIGTERM, then force-kill it once the grace period
+/// expires. On Windows `kill()` maps to `TerminateProcess` directly.
+fn terminate_child(child: &mut Box<dyn portable_pty::Child + Send + Sync>) {
+    #[cfg(unix)]
+    {
+        if let Some(pid) = child.process_id() {
+            let _ = std::process::Command::new("kill")
+                .arg("-TERM")
+                .arg(pid.to_string())
+                .status();
+
+            let deadline = std::time::Instant::now() + KILL_GRACE_PERIOD;
+            while std::time::Instant::now() < deadline {
+                if let Ok(Some(_)) = child.try_wait() {
+                    return;
+                }
+                thread::sleep(std::time::Duration::from_millis(100));
+            }
+        }
+    }
+
+    if let Err(e) = child.kill() {
+        eprintln!("[TERMINAL] Failed to kill child process: {}", e);
+    }
+    let _ = child.wait();
+}
+
+/// Kill the job running in the foreground of a terminal, leaving its shell
+/// alive. Returns false if the terminal doesn't exist.
+pub fn kill_foreground_job(manager: &TerminalManager, id: &str) -> bool {
+    #[cfg(unix)]
+    {
+        let pgid = {
+            let ptys = manager.ptys.lock().unwrap();
+            let Some(pty) = ptys.get(id) else {
+                return false;
+            };
+            // When the shell itself is in the foreground nothing is running
+            let shell_pid = pty.child.process_id();
+            pty.master
+                .process_group_leader()
+                .filter(|&pgid| pgid > 0 && Some(pgid as u32) != shell_pid)
+        };
+        let Some(pgid) = pgid else {
+            return true;
+        };
+
+        let signal_group = |signal: &str| {
+            std::process::Command::new("kill")
+                .args([signal, "--", &format!("-{}", pgid)])
+                .stderr(std::process::Stdio::null())
+                .status()
+                .is_ok_and(|status| status.success())
+        };
+        signal_group("-TERM");
+        let deadline = std::time::Instant::now() + KILL_GRACE_PERIOD;
+        while std::time::Instant::now() < deadline {
+            if !signal_group("-0") {
+                return true;
+            }
+            thread::sleep(std::time::Duration::from_millis(100));
+        }
+        signal_group("-KILL");
+        true
+    }
+
+    #[cfg(not(unix))]
+    {
+        // No process groups to signal: interrupt the job like Ctrl+C would
+        let mut ptys = manager.ptys.lock().unwrap();
+        match ptys.get_mut(id) {
+            Some(pty) => {
+                let _ = pty.writer.write_all(b"\x03");
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+/// Report a command as finished without its exit sentinel (e.g. after it was
+/// killed), so the frontend stops tracking it
+pub fn emit_command_exited<R: Runtime>(
+    app_handle: &tauri::AppHandle<R>,
+    terminal_id: &str,
+    call_id: &str,
+    exit_code: i32,
+    output: String,
+    cancelled: Option<bool>,
+) {
+    let _ = app_handle.emit(
+        "blade-cmd-exited",
+        BladeCmdExited {
+            terminal_id: terminal_id.to_string(),
+            call_id: call_id.to_string(),
+            exit_code,
+            output,
+            cancelled,
+        },
+    );
+}
+
 // #[tauri::command]
 pub fn write_to_terminal(
     id: String,
@@ -369,17 +621,36 @@ pub fn resize_terminal(
     Ok(())
 }
 
-// Event payload structs
-#[derive(Clone, serde::Serialize)]
-struct TerminalOutput {
-    id: String,
-    data: String,
+/// v1.1: Sequenced terminal output, which the terminal views reorder by `seq`
+fn emit_terminal_output<R: Runtime>(app: &tauri::AppHandle<R>, id: &str, seq: u64, data: String) {
+    let _ = app.emit(
+        "blade-event",
+        crate::blade_protocol::BladeEventEnvelope {
+            id: uuid::Uuid::new_v4(),
+            timestamp: std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_millis() as u64,
+            causality_id: None,
+            event: crate::blade_protocol::BladeEvent::Terminal(
+                crate::blade_protocol::TerminalEvent::Output {
+                    id: id.to_string(),
+                    seq,
+                    data,
+                },
+            ),
+        },
+    );
 }
 
+// Event payload structs
+
 #[derive(Clone, serde::Serialize)]
 struct TerminalExit {
     id: String,
     exit_code: i32,
+    /// The process was killed by us (stop/close) rather than exiting on its own
+    cancelled: bool,
 }
 
 #[derive(Clone, serde::Serialize)]
@@ -394,6 +665,9 @@ struct BladeCmdExited {
     call_id: String,
     exit_code: i32,
     output: String,
+    /// Unset when only the exit sentinel is known; see `submit_command_result`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    cancelled: Option<bool>,
 }
 
 // Sentinel markers used by the command execution system.
@@ -570,6 +844,7 @@ pub fn execute_command_in_terminal<R: Runtime>(
     app_handle: tauri::AppHandle<R>,
     state: tauri::State<'_, crate::AppState>,
 ) -> Result<(), String> {
+    ensure_workspace_trusted(&app_handle)?;
     let pty_system = NativePtySystem::default();
 
     let pair = pty_system
@@ -581,10 +856,13 @@ pub fn execute_command_in_terminal<R: Runtime>(
         })
         .map_err(|e| e.to_string())?;
 
-    // Execute the command directly (not a shell)
-    let mut cmd = CommandBuilder::new("sh");
-    cmd.arg("-c");
-    cmd.arg(&command);
+    // Run through the user's shell so PATH matches their terminals
+    let shell = user_shell(&app_handle);
+    let mut cmd = CommandBuilder::new(shell.program());
+    cmd.args(shell.command_args(&command));
+    for name in denied_command_env(&app_handle) {
+        cmd.env_remove(name);
+    }
 
     // Use provided cwd, or fall back to workspace path
     let working_dir = cwd.or_else(|| {
@@ -642,6 +920,7 @@ pub fn execute_command_in_terminal<R: Runtime>(
                 let exit_payload = TerminalExit {
                     id: id_clone.clone(),
                     exit_code: 130, // Standard SIGINT exit code
+                    cancelled: true,
                 };
                 let _ = app_handle.emit("terminal-exit", exit_payload);
 
@@ -657,19 +936,14 @@ pub fn execute_command_in_terminal<R: Runtime>(
                     accumulated_output.push_str(&output);
 
                     // v1.1: Increment sequence number
-                    let _seq = {
+                    let seq = {
                         let mut seq_guard = seq_counter.lock().unwrap();
                         let current = *seq_guard;
                         *seq_guard += 1;
                         current
                     };
 
-                    let payload = TerminalOutput {
-                        id: id_clone.clone(),
-                        data: output,
-                    };
-                    // Legacy format for compatibility
-                    let _ = app_handle.emit("terminal-output", payload);
+                    emit_terminal_output(&app_handle, &id_clone, seq, output);
                 }
                 Ok(_) => break,
                 Err(_) => break,
@@ -686,6 +960,7 @@ pub fn execute_command_in_terminal<R: Runtime>(
         let exit_payload = TerminalExit {
             id: id_clone.clone(),
             exit_code,
+            cancelled: false,
         };
         let _ = app_handle.emit("terminal-exit", exit_payload);
 
@@ -699,3 +974,18 @@ pub fn execute_command_in_terminal<R: Runtime>(
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_scrollback_keeps_last_lines() {
+        let mut scrollback = Scrollback::new(2);
+        scrollback.push("one\ntw");
+        scrollback.push("o\nthree\n$ ");
+        assert_eq!(scrollback.tail(None), "two\nthree\n$ ");
+        assert_eq!(scrollback.tail(Some(1)), "three\n$ ");
+        assert_eq!(scrollback.tail(Some(0)), "$ ");
+    }
+}
@@ -0,0 +1,363 @@
+//! Tool argument schemas
+//!
+//! Every tool zblade executes locally declares its fields once: the canonical
+//! name, the aliases models tend to use instead, the expected type and whether
+//! it is required. `validate_tool_args` checks a call against that schema
+//! before dispatch and rewrites aliased keys to their canonical name, so tool
+//! functions read a single key and malformed calls all fail the same way.
+
+use serde_json::Value;
+use std::collections::HashMap;
+
+use crate::tools::canonical_tool_name;
+
+const PATH_ALIASES: &[&str] = &["file_path", "filepath", "filename"];
+const DIR_ALIASES: &[&str] = &["dir", "directory"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArgType {
+    String,
+    /// Non-negative integer; numeric strings are converted
+    Integer,
+    /// "true"/"false" strings are converted
+    Boolean,
+    Array,
+    Object,
+}
+
+impl ArgType {
+    fn describe(self) -> &'static str {
+        match self {
+            ArgType::String => "a string",
+            ArgType::Integer => "a non-negative integer",
+            ArgType::Boolean => "a boolean",
+            ArgType::Array => "an array",
+            ArgType::Object => "an object",
+        }
+    }
+
+    fn matches(self, value: &Value) -> bool {
+        match self {
+            ArgType::String => value.is_string(),
+            ArgType::Integer => value.as_u64().is_some(),
+            ArgType::Boolean => value.is_boolean(),
+            ArgType::Array => value.is_array(),
+            ArgType::Object => value.is_object(),
+        }
+    }
+
+    /// Check `value`, converting string forms of integers and booleans in place
+    fn coerce(self, value: &mut Value) -> bool {
+        let converted = match (self, &*value) {
+            (ArgType::Integer, Value::String(s)) => s.trim().parse::<u64>().ok().map(Value::from),
+            (ArgType::Boolean, Value::String(s)) => s.trim().parse::<bool>().ok().map(Value::Bool),
+            _ => return self.matches(value),
+        };
+        match converted {
+            Some(v) => {
+                *value = v;
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct ArgSpec {
+    pub name: &'static str,
+    pub aliases: &'static [&'static str],
+    pub ty: ArgType,
+    pub required: bool,
+}
+
+impl ArgSpec {
+    fn aliases(self, aliases: &'static [&'static str]) -> Self {
+        Self { aliases, ..self }
+    }
+}
+
+fn required(name: &'static str, ty: ArgType) -> ArgSpec {
+    ArgSpec {
+        name,
+        aliases: &[],
+        ty,
+        required: true,
+    }
+}
+
+fn optional(name: &'static str, ty: ArgType) -> ArgSpec {
+    ArgSpec {
+        required: false,
+        ..required(name, ty)
+    }
+}
+
+/// Argument schema for a locally executed tool (aliases like `rg` resolve to
+/// their canonical tool). Tools without arguments or not run by zblade have none.
+pub fn tool_arg_specs(tool_name: &str) -> Option<Vec<ArgSpec>> {
+    use ArgType as T;
+
+    let path = || required("path", T::String).aliases(PATH_ALIASES);
+    let specs = match canonical_tool_name(tool_name) {
+        "read_file" => vec![path()],
+        "write_file" => vec![
+            path(),
+            required("content", T::String).aliases(&["contents", "text", "data"]),
+        ],
+        "edit_file" => vec![
+            path(),
+            required("old_content", T::String).aliases(&["old", "from"]),
+            required("new_content", T::String).aliases(&["new", "to"]),
+        ],
+        "edit_lines" => vec![
+            path(),
+            required("start_line", T::Integer),
+            required("end_line", T::Integer),
+            required("new_content", T::String).aliases(&["content", "new_text"]),
+        ],
+        "grep_search" => vec![
+            required("pattern", T::String).aliases(&["query", "regex"]),
+            optional("path", T::String).aliases(DIR_ALIASES),
+            optional("format", T::String),
+        ],
+        "codebase_search" => vec![
+            required("query", T::String),
+            optional("file_pattern", T::String),
+            optional("max_results", T::Integer),
+            optional("mode", T::String),
+        ],
+        "find_references" => vec![
+            required("name", T::String).aliases(&["symbol", "query"]),
+            optional("path", T::String).aliases(&["file_path", "file"]),
+            optional("line", T::Integer),
+            optional("max_results", T::Integer),
+        ],
+        "semantic_search" => vec![
+            required("query", T::String).aliases(&["q"]),
+            optional("max_results", T::Integer),
+        ],
+        "list_directory" | "get_workspace_structure" => vec![
+            optional("path", T::String).aliases(DIR_ALIASES),
+            optional("depth", T::Integer),
+            optional("limit", T::Integer),
+        ],
+        "read_file_range" => vec![
+            path(),
+            optional("start_line", T::Integer),
+            optional("end_line", T::Integer),
+            optional("context_lines", T::Integer),
+        ],
+        // Either `patches` or `old_text`/`new_text`; apply_edit checks which one is given
+        "apply_edit" => vec![
+            path(),
+            optional("patches", T::Array),
+            optional("old_text", T::String).aliases(&["old_content", "old", "from"]),
+            optional("new_text", T::String).aliases(&["new_content", "new", "to"]),
+        ],
+        "preview_patch" => vec![
+            path(),
+            required("old_text", T::String).aliases(&["old_content", "old", "from"]),
+            required("new_text", T::String).aliases(&["new_content", "new", "to"]),
+        ],
+        // The target may also be flattened into the top-level args
+        "semantic_edit" => vec![
+            path(),
+            required("operation", T::String),
+            required("code", T::String).aliases(&["content"]),
+            optional("target", T::Object),
+        ],
+        "find_files" => vec![
+            required("pattern", T::String),
+            optional("path", T::String),
+            optional("max_depth", T::Integer),
+        ],
+        "find_files_glob" => vec![
+            required("pattern", T::String).aliases(&["glob"]),
+            optional("path", T::String),
+            optional("case_sensitive", T::Boolean),
+        ],
+        "read_files_glob" => vec![
+            required("pattern", T::String).aliases(&["glob"]),
+            optional("path", T::String),
+            optional("case_sensitive", T::Boolean),
+            optional("max_files", T::Integer),
+            optional("max_bytes_per_file", T::Integer),
+        ],
+        "replace_in_files" => vec![
+            required("pattern", T::String).aliases(&["regex"]),
+            required("replacement", T::String).aliases(&["replace"]),
+            optional("file_glob", T::String).aliases(&["glob"]),
+            optional("path", T::String),
+            optional("dry_run", T::Boolean),
+        ],
+        "create_directory" | "get_file_info" => vec![required("path", T::String)],
+        "delete_file" => vec![
+            required("path", T::String),
+            optional("recursive", T::Boolean),
+        ],
+        "move_file" | "copy_file" => vec![
+            required("source", T::String),
+            required("destination", T::String),
+        ],
+        // Either `path` (with `against`) or `left_path`/`right_path`
+        "diff_files" => vec![
+            optional("path", T::String),
+            optional("left_path", T::String),
+            optional("right_path", T::String),
+            optional("against", T::String),
+        ],
+        "open_file" => vec![required("path", T::String), optional("line", T::Integer)],
+        "goto_line" => vec![required("line", T::Integer), optional("column", T::Integer)],
+        "replace_selection" | "insert_at_cursor" => vec![required("content", T::String)],
+        _ => return None,
+    };
+    Some(specs)
+}
+
+/// JSON schema of a tool's arguments, as described to the model
+pub fn args_json_schema(specs: &[ArgSpec]) -> Value {
+    let properties: serde_json::Map<String, Value> = specs
+        .iter()
+        .map(|spec| {
+            let ty = match spec.ty {
+                ArgType::String => "string",
+                ArgType::Integer => "integer",
+                ArgType::Boolean => "boolean",
+                ArgType::Array => "array",
+                ArgType::Object => "object",
+            };
+            (spec.name.to_string(), serde_json::json!({ "type": ty }))
+        })
+        .collect();
+    let required: Vec<&str> = specs
+        .iter()
+        .filter(|s| s.required)
+        .map(|s| s.name)
+        .collect();
+    serde_json::json!({
+        "type": "object",
+        "properties": properties,
+        "required": required,
+    })
+}
+
+/// Check `args` against the tool's schema, moving aliased keys to their
+/// canonical name and converting string-typed numbers and booleans. The error
+/// names every missing or wrong-typed field so the model can correct the call.
+pub fn validate_tool_args(
+    tool_name: &str,
+    args: &mut HashMap<String, Value>,
+) -> Result<(), String> {
+    let Some(specs) = tool_arg_specs(tool_name) else {
+        return Ok(());
+    };
+
+    let mut problems = Vec::new();
+    for spec in &specs {
+        let present = |key: &str| args.get(key).is_some_and(|v| !v.is_null());
+        let key = std::iter::once(spec.name)
+            .chain(spec.aliases.iter().copied())
+            .find(|key| present(key));
+        let Some(key) = key else {
+            if spec.required {
+                problems.push(format!("missing required field '{}'", spec.name));
+            }
+            continue;
+        };
+
+        if key != spec.name {
+            if let Some(value) = args.remove(key) {
+                args.insert(spec.name.to_string(), value);
+            }
+        }
+        let value = args.get_mut(spec.name).expect("present above");
+        if !spec.ty.coerce(value) {
+            problems.push(format!(
+                "field '{}' must be {}, got {}",
+                key,
+                spec.ty.describe(),
+                describe_value(value)
+            ));
+        }
+    }
+
+    if problems.is_empty() {
+        Ok(())
+    } else {
+        Err(format!(
+            "invalid arguments for {}: {}",
+            tool_name,
+            problems.join("; ")
+        ))
+    }
+}
+
+fn describe_value(value: &Value) -> String {
+    match value {
+        Value::Null => "null".to_string(),
+        Value::Bool(b) => b.to_string(),
+        Value::Number(n) => n.to_string(),
+        Value::String(_) => "a string".to_string(),
+        Value::Array(_) => "an array".to_string(),
+        Value::Object(_) => "an object".to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn args(value: Value) -> HashMap<String, Value> {
+        serde_json::from_value(value).unwrap()
+    }
+
+    #[test]
+    fn test_aliases_are_renamed_and_strings_converted() {
+        let mut call = args(json!({
+            "file_path": "src/lib.rs",
+            "start_line": "3",
+            "end_line": 5,
+            "content": "x"
+        }));
+        validate_tool_args("edit_lines", &mut call).unwrap();
+
+        assert_eq!(call["path"], json!("src/lib.rs"));
+        assert_eq!(call["new_content"], json!("x"));
+        assert_eq!(call["start_line"], json!(3));
+        assert!(!call.contains_key("file_path"));
+    }
+
+    #[test]
+    fn test_missing_and_wrong_typed_fields_are_reported() {
+        let mut call = args(json!({ "pattern": "foo", "path": null, "format": 1 }));
+        let err = validate_tool_args("rg", &mut call).unwrap_err();
+        assert_eq!(
+            err,
+            "invalid arguments for rg: field 'format' must be a string, got 1"
+        );
+
+        let mut call = args(json!({ "source": "a.txt", "destination": ["b.txt"] }));
+        let err = validate_tool_args("move_file", &mut call).unwrap_err();
+        assert_eq!(
+            err,
+            "invalid arguments for move_file: field 'destination' must be a string, got an array"
+        );
+
+        let mut call = args(json!({ "line": -2 }));
+        let err = validate_tool_args("goto_line", &mut call).unwrap_err();
+        assert!(err.contains("'line' must be a non-negative integer, got -2"));
+
+        let mut call = args(json!({}));
+        let err = validate_tool_args("write_file", &mut call).unwrap_err();
+        assert!(err.contains("missing required field 'path'; missing required field 'content'"));
+    }
+
+    #[test]
+    fn test_tools_without_schema_pass_through() {
+        let mut call = args(json!({ "anything": 1 }));
+        assert!(validate_tool_args("get_editor_state", &mut call).is_ok());
+        assert!(validate_tool_args("todo_write", &mut call).is_ok());
+    }
+}
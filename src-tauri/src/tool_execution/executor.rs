@@ -1,6 +1,6 @@
 use crate::tools::{self, ToolResult};
 use std::path::Path;
-use tauri::AppHandle;
+use tauri::{AppHandle, Manager};
 
 use tauri::Runtime;
 
@@ -15,6 +15,8 @@ pub struct ToolExecutionContext<R: Runtime> {
     pub selection_start_line: Option<usize>,
     pub selection_end_line: Option<usize>,
     pub app_handle: Option<AppHandle<R>>,
+    /// Conversation whose tool log records the calls; `None` skips the log
+    pub conversation_id: Option<String>,
 }
 
 impl<R: Runtime> ToolExecutionContext<R> {
@@ -39,12 +41,126 @@ impl<R: Runtime> ToolExecutionContext<R> {
             selection_start_line,
             selection_end_line,
             app_handle,
+            conversation_id: None,
         }
     }
+
+    pub fn with_conversation_id(mut self, conversation_id: Option<String>) -> Self {
+        self.conversation_id = conversation_id;
+        self
+    }
+}
+
+/// Tools that change files or run commands, refused in read-only mode
+const MUTATING_TOOLS: &[&str] = &[
+    "write_file",
+    "edit_file",
+    "edit_lines",
+    "apply_edit",
+    "semantic_edit",
+    "replace_in_files",
+    "regex_replace",
+    "delete_file",
+    "move_file",
+    "copy_file",
+    "create_directory",
+    "run_command",
+    "replace_selection",
+    "insert_at_cursor",
+];
+
+pub fn is_mutating_tool(tool_name: &str) -> bool {
+    MUTATING_TOOLS.contains(&tools::canonical_tool_name(tool_name))
+}
+
+/// Error result for a mutating tool while read-only mode is on, `None` otherwise
+pub fn read_only_refusal<R: Runtime>(
+    app_handle: Option<&AppHandle<R>>,
+    tool_name: &str,
+) -> Option<ToolResult> {
+    let read_only = app_handle
+        .and_then(|app| app.try_state::<crate::app_state::AppState>())
+        .is_some_and(|state| {
+            state
+                .read_only_mode
+                .load(std::sync::atomic::Ordering::SeqCst)
+        });
+    if !read_only || !is_mutating_tool(tool_name) {
+        return None;
+    }
+    Some(ToolResult::err(format!(
+        "Read-only mode is on: '{}' was not run. Do not modify files or run commands; \
+         describe the changes or commands you would make so the user can review them.",
+        tool_name
+    )))
 }
 
-/// Execute a tool with IDE context
+/// Execute a tool with IDE context, recording it in the context's
+/// conversation's tool log when an app handle is available
 pub fn execute_tool_with_context<R: Runtime>(
+    context: &ToolExecutionContext<R>,
+    call_id: &str,
+    tool_name: &str,
+    args: &str,
+) -> ToolResult {
+    let started = std::time::SystemTime::now();
+
+    let result = read_only_refusal(context.app_handle.as_ref(), tool_name)
+        .unwrap_or_else(|| run_tool(context, tool_name, args));
+
+    let state = context
+        .app_handle
+        .as_ref()
+        .and_then(|app| app.try_state::<crate::app_state::AppState>());
+    if let Some(state) = state {
+        record_tool_call(
+            &state,
+            context.conversation_id.as_deref(),
+            call_id,
+            tool_name,
+            args,
+            result.success,
+            started,
+        );
+    }
+
+    result
+}
+
+/// Record a call that started at `started` in the conversation's tool log.
+/// For calls run outside `execute_tool_with_context` (applied file changes,
+/// approved commands); `None` for the conversation skips the log.
+pub fn record_tool_call(
+    state: &crate::app_state::AppState,
+    conversation_id: Option<&str>,
+    call_id: &str,
+    tool_name: &str,
+    args: &str,
+    success: bool,
+    started: std::time::SystemTime,
+) {
+    let Some(conversation_id) = conversation_id else {
+        return;
+    };
+    let timestamp = started
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as u64;
+    let duration_ms = started.elapsed().unwrap_or_default().as_millis() as u64;
+    state.tool_log.record(
+        conversation_id,
+        crate::tool_execution::ToolInvocation::new(
+            call_id,
+            tool_name,
+            args,
+            success,
+            duration_ms,
+            timestamp,
+        ),
+    );
+}
+
+fn run_tool<R: Runtime>(
     context: &ToolExecutionContext<R>,
     tool_name: &str,
     args: &str,
@@ -82,3 +198,31 @@ pub fn execute_tool_with_context<R: Runtime>(
         context.app_handle.as_ref(),
     )
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mutating_tools() {
+        for name in [
+            "write_file",
+            "create_file",
+            "apply_patch",
+            "run_command",
+            "move_file",
+        ] {
+            assert!(is_mutating_tool(name), "{} mutates", name);
+        }
+        for name in [
+            "read_file",
+            "grep_search",
+            "rg",
+            "list_dir",
+            "get_editor_state",
+        ] {
+            assert!(!is_mutating_tool(name), "{} is read-only", name);
+        }
+        assert!(read_only_refusal::<tauri::Wry>(None, "write_file").is_none());
+    }
+}
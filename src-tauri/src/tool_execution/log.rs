@@ -0,0 +1,272 @@
+//! Tool invocation log
+//!
+//! Records every executed tool call per conversation so agent behaviour can
+//! be audited after the fact. Entries are capped per conversation (oldest
+//! dropped first) and appended to `<storage_dir>/<conversation_id>.jsonl`,
+//! one JSON object per line. The file is compacted once it holds twice the cap.
+
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::io::Write;
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+/// Default maximum number of entries kept per conversation
+pub const DEFAULT_MAX_ENTRIES: usize = 500;
+
+/// Arguments longer than this are truncated (file writes can be huge)
+const MAX_ARGUMENT_CHARS: usize = 1_000;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ToolInvocation {
+    pub call_id: String,
+    pub tool_name: String,
+    pub arguments: String,
+    pub success: bool,
+    pub duration_ms: u64,
+    /// Unix millis when the call started
+    pub timestamp: u64,
+}
+
+impl ToolInvocation {
+    pub fn new(
+        call_id: &str,
+        tool_name: &str,
+        arguments: &str,
+        success: bool,
+        duration_ms: u64,
+        timestamp: u64,
+    ) -> Self {
+        // The log sits on disk in plain text, so secrets in arguments are masked
+        let (arguments, _) = crate::redaction::redact_secrets(arguments);
+        let arguments = if arguments.chars().count() > MAX_ARGUMENT_CHARS {
+            arguments
+                .chars()
+                .take(MAX_ARGUMENT_CHARS)
+                .collect::<String>()
+                + "..."
+        } else {
+            arguments
+        };
+        Self {
+            call_id: call_id.to_string(),
+            tool_name: tool_name.to_string(),
+            arguments,
+            success,
+            duration_ms,
+            timestamp,
+        }
+    }
+}
+
+#[derive(Default)]
+struct ConversationLog {
+    entries: VecDeque<ToolInvocation>,
+    /// Lines in the on-disk file, including ones already trimmed from `entries`
+    lines_on_disk: usize,
+}
+
+pub struct ToolInvocationLog {
+    entries: Mutex<HashMap<String, ConversationLog>>,
+    max_entries: usize,
+    /// Directory for the on-disk copy; `None` keeps the log in memory only
+    storage_dir: Option<PathBuf>,
+}
+
+impl ToolInvocationLog {
+    pub fn new(max_entries: usize, storage_dir: Option<PathBuf>) -> Self {
+        if let Some(dir) = &storage_dir {
+            if let Err(e) = std::fs::create_dir_all(dir) {
+                eprintln!("[TOOL LOG] Failed to create {:?}: {}", dir, e);
+            }
+        }
+        Self {
+            entries: Mutex::new(HashMap::new()),
+            max_entries: max_entries.max(1),
+            storage_dir,
+        }
+    }
+
+    /// Append an entry, trimming the oldest ones past the cap
+    pub fn record(&self, conversation_id: &str, entry: ToolInvocation) {
+        let mut entries = self.entries.lock().unwrap();
+        let log = entries
+            .entry(conversation_id.to_string())
+            .or_insert_with(|| self.load(conversation_id));
+        self.append(conversation_id, &entry);
+        log.lines_on_disk += 1;
+        log.entries.push_back(entry);
+        while log.entries.len() > self.max_entries {
+            log.entries.pop_front();
+        }
+        if log.lines_on_disk > self.max_entries * 2 {
+            self.compact(conversation_id, log);
+        }
+    }
+
+    /// Entries for a conversation, oldest first
+    pub fn entries(&self, conversation_id: &str) -> Vec<ToolInvocation> {
+        let entries = self.entries.lock().unwrap();
+        match entries.get(conversation_id) {
+            Some(log) => log.entries.iter().cloned().collect(),
+            None => self.load(conversation_id).entries.into(),
+        }
+    }
+
+    /// Drop a conversation's log from memory and disk
+    pub fn remove(&self, conversation_id: &str) {
+        self.entries.lock().unwrap().remove(conversation_id);
+        if let Some(path) = self.log_path(conversation_id) {
+            let _ = std::fs::remove_file(path);
+        }
+    }
+
+    /// Conversation IDs are UUIDs; anything else is kept off disk so the ID
+    /// can't be used to reach outside the storage directory
+    fn log_path(&self, conversation_id: &str) -> Option<PathBuf> {
+        let valid = !conversation_id.is_empty()
+            && conversation_id
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+        if !valid {
+            return None;
+        }
+        self.storage_dir
+            .as_ref()
+            .map(|dir| dir.join(format!("{}.jsonl", conversation_id)))
+    }
+
+    fn load(&self, conversation_id: &str) -> ConversationLog {
+        let Some(path) = self.log_path(conversation_id) else {
+            return ConversationLog::default();
+        };
+        let Ok(content) = std::fs::read_to_string(&path) else {
+            return ConversationLog::default();
+        };
+        let mut log = ConversationLog::default();
+        for line in content.lines().filter(|l| !l.trim().is_empty()) {
+            log.lines_on_disk += 1;
+            match serde_json::from_str::<ToolInvocation>(line) {
+                Ok(entry) => log.entries.push_back(entry),
+                // A crash mid-append leaves at most one partial line
+                Err(e) => eprintln!("[TOOL LOG] Skipping unreadable entry in {:?}: {}", path, e),
+            }
+        }
+        while log.entries.len() > self.max_entries {
+            log.entries.pop_front();
+        }
+        log
+    }
+
+    fn append(&self, conversation_id: &str, entry: &ToolInvocation) {
+        let Some(path) = self.log_path(conversation_id) else {
+            return;
+        };
+        let result = serde_json::to_string(entry)
+            .map_err(|e| e.to_string())
+            .and_then(|line| {
+                std::fs::OpenOptions::new()
+                    .create(true)
+                    .append(true)
+                    .open(&path)
+                    .and_then(|mut file| writeln!(file, "{}", line))
+                    .map_err(|e| e.to_string())
+            });
+        if let Err(e) = result {
+            eprintln!("[TOOL LOG] Failed to append to {:?}: {}", path, e);
+        }
+    }
+
+    /// Rewrite the file with only the entries still kept in memory
+    fn compact(&self, conversation_id: &str, log: &mut ConversationLog) {
+        let Some(path) = self.log_path(conversation_id) else {
+            return;
+        };
+        let mut content = String::new();
+        for entry in &log.entries {
+            match serde_json::to_string(entry) {
+                Ok(line) => {
+                    content.push_str(&line);
+                    content.push('\n');
+                }
+                Err(e) => eprintln!("[TOOL LOG] Failed to serialize entry: {}", e),
+            }
+        }
+        match crate::utils::atomic_write(&path, &content) {
+            Ok(()) => log.lines_on_disk = log.entries.len(),
+            Err(e) => eprintln!("[TOOL LOG] Failed to compact {:?}: {}", path, e),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn entry(call_id: &str) -> ToolInvocation {
+        ToolInvocation::new(call_id, "read_file", "{}", true, 3, 0)
+    }
+
+    #[test]
+    fn test_record_trims_oldest() {
+        let log = ToolInvocationLog::new(2, None);
+        log.record("conv", entry("a"));
+        log.record("conv", entry("b"));
+        log.record("conv", entry("c"));
+        log.record("other", entry("d"));
+
+        let ids: Vec<_> = log.entries("conv").into_iter().map(|e| e.call_id).collect();
+        assert_eq!(ids, vec!["b", "c"]);
+        assert_eq!(log.entries("other").len(), 1);
+        assert!(log.entries("missing").is_empty());
+    }
+
+    #[test]
+    fn test_log_persists_per_conversation() {
+        let dir = TempDir::new().unwrap();
+        let log = ToolInvocationLog::new(10, Some(dir.path().to_path_buf()));
+        log.record("conv-1", entry("a"));
+        log.record("../escape", entry("b"));
+
+        let reloaded = ToolInvocationLog::new(10, Some(dir.path().to_path_buf()));
+        assert_eq!(reloaded.entries("conv-1"), vec![entry("a")]);
+        assert!(reloaded.entries("../escape").is_empty());
+
+        reloaded.remove("conv-1");
+        assert!(!dir.path().join("conv-1.jsonl").exists());
+    }
+
+    #[test]
+    fn test_log_file_is_appended_and_compacted() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("conv.jsonl");
+        let log = ToolInvocationLog::new(2, Some(dir.path().to_path_buf()));
+        for id in ["a", "b", "c", "d"] {
+            log.record("conv", entry(id));
+        }
+        assert_eq!(std::fs::read_to_string(&path).unwrap().lines().count(), 4);
+
+        // The fifth line passes twice the cap, leaving only the kept entries
+        log.record("conv", entry("e"));
+        assert_eq!(std::fs::read_to_string(&path).unwrap().lines().count(), 2);
+
+        let reloaded = ToolInvocationLog::new(2, Some(dir.path().to_path_buf()));
+        assert_eq!(reloaded.entries("conv"), vec![entry("d"), entry("e")]);
+    }
+
+    #[test]
+    fn test_secrets_in_arguments_are_redacted() {
+        let args = r#"{"command":"curl -H 'Authorization: Bearer abcdef123456' x"}"#;
+        let entry = ToolInvocation::new("a", "run_command", args, true, 0, 0);
+        assert!(!entry.arguments.contains("abcdef123456"));
+        assert!(entry.arguments.contains(crate::redaction::REDACTED));
+    }
+
+    #[test]
+    fn test_long_arguments_truncated() {
+        let args = "x".repeat(MAX_ARGUMENT_CHARS + 10);
+        let entry = ToolInvocation::new("a", "write_file", &args, true, 0, 0);
+        assert_eq!(entry.arguments.chars().count(), MAX_ARGUMENT_CHARS + 3);
+    }
+}
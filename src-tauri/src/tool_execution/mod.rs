@@ -1,3 +1,10 @@
+pub mod args;
 pub mod executor;
+pub mod log;
 
-pub use executor::{execute_tool_with_context, ToolExecutionContext};
+pub use args::{args_json_schema, tool_arg_specs, validate_tool_args, ArgSpec, ArgType};
+pub use executor::{
+    execute_tool_with_context, is_mutating_tool, read_only_refusal, record_tool_call,
+    ToolExecutionContext,
+};
+pub use log::{ToolInvocation, ToolInvocationLog};
@@ -3,11 +3,13 @@ use std::fs;
 use std::path::{Path, PathBuf};
 
 use regex::Regex;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use walkdir::WalkDir;
 
 use crate::gitignore_filter::GitignoreFilter;
+use crate::language_service::LanguageService;
 use crate::project_settings;
+use crate::tree_sitter::{Symbol, SymbolType};
 
 #[derive(Debug, Clone)]
 pub struct ToolResult {
@@ -15,13 +17,83 @@ pub struct ToolResult {
     pub content: String,
     pub error: Option<String>,
     pub skipped: bool,
+    /// Set for failures callers must handle differently from an ordinary tool error
+    pub error_kind: Option<ToolErrorKind>,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ToolErrorKind {
+    /// zcoderd sent a server-side tool (todo_write, attempt_completion, ...) here to
+    /// execute: a protocol desync that no retry by the model can fix
+    ServerSideToolMisrouted,
+}
+
+/// Canonical names of the tools `execute_tool_with_editor` dispatches
+/// (`run_command` is executed by the workflow, outside this table)
+pub const LOCAL_TOOLS: &[&str] = &[
+    "read_file",
+    "write_file",
+    "edit_file",
+    "edit_lines",
+    "grep_search",
+    "codebase_search",
+    "find_references",
+    "semantic_search",
+    "list_directory",
+    "get_editor_state",
+    "read_file_range",
+    "apply_edit",
+    "preview_patch",
+    "semantic_edit",
+    "get_workspace_structure",
+    "find_files",
+    "find_files_glob",
+    "read_files_glob",
+    "replace_in_files",
+    "create_directory",
+    "delete_file",
+    "move_file",
+    "copy_file",
+    "get_file_info",
+    "diff_files",
+    "open_file",
+    "goto_line",
+    "get_selection",
+    "replace_selection",
+    "insert_at_cursor",
+];
+
+/// Tools zcoderd handles itself; zblade should never be asked to run them
+pub const SERVER_SIDE_TOOLS: &[&str] = &[
+    "ask_followup_question",
+    "attempt_completion",
+    "new_task",
+    "generate_image",
+    "todo_write",
+];
+
 /// RFC: Large Tool Result Handling - Size limits
-const MAX_TOOL_RESULT_BYTES: usize = 50 * 1024; // 50KB
-const MAX_TOOL_RESULT_LINES: usize = 2000;
-const HEAD_LINES: usize = 100;
-const TAIL_LINES: usize = 50;
+/// Default threshold; projects override it via `context.max_tool_result_bytes`.
+pub const DEFAULT_MAX_TOOL_RESULT_BYTES: usize = 32 * 1024; // 32KB
+/// Bytes kept from each end of a truncated result (head and tail carry the most signal).
+const TRUNCATION_KEEP_BYTES: usize = 8 * 1024;
+
+/// Default size cap for files read by grep_search/codebase_search;
+/// projects override it via `context.max_grep_file_bytes`.
+pub const DEFAULT_MAX_GREP_FILE_BYTES: u64 = 5 * 1024 * 1024; // 5MB
+/// Leading bytes checked for NUL when deciding whether a file is binary.
+const BINARY_SNIFF_BYTES: usize = 8 * 1024;
+
+/// Default and maximum number of files `read_files_glob` returns contents for.
+const DEFAULT_GLOB_READ_FILES: usize = 20;
+const MAX_GLOB_READ_FILES: usize = 100;
+/// Default per-file size cap for `read_files_glob`; larger files are skipped.
+const DEFAULT_GLOB_READ_FILE_BYTES: u64 = 64 * 1024;
+/// Glob matches considered by `find_files_glob`.
+const MAX_GLOB_RESULTS: usize = 200;
+/// Files `replace_in_files` may scan; wider globs must be narrowed first.
+const MAX_REPLACE_FILES: usize = 2_000;
 
 impl ToolResult {
     pub fn ok(content: impl Into<String>) -> Self {
@@ -30,6 +102,7 @@ impl ToolResult {
             content: content.into(),
             error: None,
             skipped: false,
+            error_kind: None,
         }
     }
 
@@ -39,6 +112,7 @@ impl ToolResult {
             content: String::new(),
             error: Some(error.into()),
             skipped: false,
+            error_kind: None,
         }
     }
 
@@ -48,6 +122,19 @@ impl ToolResult {
             content: String::new(),
             error: Some(message.into()),
             skipped: true,
+            error_kind: None,
+        }
+    }
+
+    /// A server-side tool sent here to execute. The model is told not to call it again.
+    pub fn misrouted(tool_name: &str) -> Self {
+        Self {
+            error_kind: Some(ToolErrorKind::ServerSideToolMisrouted),
+            ..Self::err(format!(
+                "Tool '{}' is handled by the server and cannot run in the editor. \
+                 Do not call it again; continue without it.",
+                tool_name
+            ))
         }
     }
 
@@ -66,43 +153,51 @@ impl ToolResult {
 
     /// RFC: Large Tool Result Handling - Truncate content for local storage mode
     /// In local mode, zblade pre-truncates large results since zcoderd won't save to DB.
-    /// Returns truncated content with head/tail and guidance message.
-    pub fn to_tool_content_truncated(&self) -> String {
-        let content = self.to_tool_content();
-        truncate_large_content(&content)
+    /// Errors are never truncated: they are short and the model needs all of it.
+    pub fn to_tool_content_truncated(&self, max_bytes: usize) -> String {
+        if !self.success {
+            return self.to_tool_content();
+        }
+        truncate_large_content(&self.content, max_bytes)
+    }
+
+    /// Mask secrets in the content and error text; returns the number of redactions
+    pub fn redact_secrets(&mut self, redactor: &crate::redaction::Redactor) -> usize {
+        let (content, mut count) = redactor.redact(&self.content);
+        self.content = content;
+        if let Some(error) = &self.error {
+            let (error, n) = redactor.redact(error);
+            self.error = Some(error);
+            count += n;
+        }
+        count
     }
 }
 
-/// Truncate large content per RFC-LARGE-TOOL-RESULTS.md
-/// Shows first 100 lines + last 50 lines with truncation message
-pub fn truncate_large_content(content: &str) -> String {
+/// Truncate content larger than `max_bytes` in the middle, keeping the head and tail.
+/// Cut points are moved onto char boundaries so multibyte characters are never split.
+pub fn truncate_large_content(content: &str, max_bytes: usize) -> String {
     let bytes = content.len();
-    let lines: Vec<&str> = content.lines().collect();
-    let line_count = lines.len();
-
-    // Check if truncation is needed (either limit exceeded triggers truncation)
-    if bytes <= MAX_TOOL_RESULT_BYTES && line_count <= MAX_TOOL_RESULT_LINES {
+    if bytes <= max_bytes {
         return content.to_string();
     }
 
-    // Handle edge case: if content has fewer lines than HEAD + TAIL, just return as-is
-    // (this shouldn't happen if we exceeded limits, but be defensive)
-    if line_count <= HEAD_LINES + TAIL_LINES {
-        return content.to_string();
-    }
+    let keep = TRUNCATION_KEEP_BYTES.min(max_bytes / 2);
 
-    // Build truncated output with head + tail
-    let head: String = lines[..HEAD_LINES].join("\n");
-    let tail: String = lines[line_count - TAIL_LINES..].join("\n");
+    let mut head_end = keep;
+    while !content.is_char_boundary(head_end) {
+        head_end -= 1;
+    }
+    let mut tail_start = bytes - keep;
+    while !content.is_char_boundary(tail_start) {
+        tail_start += 1;
+    }
 
     format!(
-        "{}\n\n[TRUNCATED: {} bytes, {} lines - showing first {} and last {} lines]\nResult was too large. Use more specific tool parameters to get targeted results.\n\n{}",
-        head,
-        bytes,
-        line_count,
-        HEAD_LINES,
-        TAIL_LINES,
-        tail
+        "{}\n...[truncated {} bytes]...\n{}",
+        &content[..head_end],
+        tail_start - head_end,
+        &content[tail_start..]
     )
 }
 
@@ -139,6 +234,57 @@ fn create_gitignore_filter(workspace_root: &Path) -> Option<GitignoreFilter> {
     Some(filter)
 }
 
+/// Counts of files a search skipped without reading
+#[derive(Debug, Default, Serialize)]
+struct SearchSkipStats {
+    too_large: usize,
+    binary: usize,
+}
+
+impl SearchSkipStats {
+    /// Summary line like `(skipped 12 files: 3 too large, 9 binary)`, or None if nothing was skipped
+    fn summary(&self) -> Option<String> {
+        let total = self.too_large + self.binary;
+        if total == 0 {
+            return None;
+        }
+        Some(format!(
+            "(skipped {} file{}: {} too large, {} binary)",
+            total,
+            if total == 1 { "" } else { "s" },
+            self.too_large,
+            self.binary
+        ))
+    }
+}
+
+/// Read a file for text search, skipping files over `max_bytes` and files that look binary
+/// (a NUL byte in the first 8KB). Unreadable or non-UTF-8 files are skipped silently.
+fn read_searchable_file(path: &Path, max_bytes: u64, stats: &mut SearchSkipStats) -> Option<String> {
+    use std::io::Read;
+
+    let metadata = fs::metadata(path).ok()?;
+    if metadata.len() > max_bytes {
+        stats.too_large += 1;
+        return None;
+    }
+
+    let mut file = fs::File::open(path).ok()?;
+    let mut head = Vec::with_capacity(BINARY_SNIFF_BYTES);
+    (&mut file)
+        .take(BINARY_SNIFF_BYTES as u64)
+        .read_to_end(&mut head)
+        .ok()?;
+    if head.contains(&0) {
+        stats.binary += 1;
+        return None;
+    }
+
+    let mut bytes = head;
+    file.read_to_end(&mut bytes).ok()?;
+    String::from_utf8(bytes).ok()
+}
+
 // Editor state for IDE-specific tools
 pub struct EditorState {
     pub active_file: Option<String>,
@@ -150,6 +296,18 @@ pub struct EditorState {
     pub selection_end_line: Option<usize>,
 }
 
+/// Name a tool is dispatched under, resolving aliases like `rg` and `list_dir`
+pub fn canonical_tool_name(tool_name: &str) -> &str {
+    match tool_name {
+        "create_file" => "write_file",
+        "rg" => "grep_search",
+        "list_dir" => "list_directory",
+        "apply_patch" => "apply_edit",
+        "glob" => "find_files_glob",
+        other => other,
+    }
+}
+
 pub fn execute_tool(workspace_root: &Path, tool_name: &str, raw_args: &str) -> ToolResult {
     execute_tool_with_editor::<tauri::Wry>(workspace_root, tool_name, raw_args, None, None)
 }
@@ -159,8 +317,15 @@ pub fn execute_tool_with_editor<R: tauri::Runtime>(
     tool_name: &str,
     raw_args: &str,
     editor_state: Option<&EditorState>,
-    _app_handle: Option<&tauri::AppHandle<R>>,
+    app_handle: Option<&tauri::AppHandle<R>>,
 ) -> ToolResult {
+    if let Err(disabled) = project_settings::load_project_settings_or_default(workspace_root)
+        .tools
+        .check(tool_name)
+    {
+        return disabled;
+    }
+
     // Claude models sometimes prefix arguments with {} - strip it
     // But don't strip if the entire string is just "{}"
     let sanitized_args = if raw_args.starts_with("{}") && raw_args.len() > 2 {
@@ -174,7 +339,7 @@ pub fn execute_tool_with_editor<R: tauri::Runtime>(
         tool_name, raw_args, sanitized_args
     );
 
-    let args: HashMap<String, serde_json::Value> =
+    let mut args: HashMap<String, serde_json::Value> =
         match serde_json::from_str::<Args>(sanitized_args) {
             Ok(Args::Map(m)) => m,
             Ok(Args::Null) => HashMap::new(),
@@ -183,31 +348,64 @@ pub fn execute_tool_with_editor<R: tauri::Runtime>(
                 return ToolResult::err(format!("invalid tool args json: {e}"));
             }
         };
+    if let Err(e) = crate::tool_execution::validate_tool_args(tool_name, &mut args) {
+        eprintln!("[TOOL PARSE ERROR] {}", e);
+        return ToolResult::err(e);
+    }
 
     match tool_name {
         // Legacy tools (kept for compatibility)
         "read_file" => read_file(workspace_root, &args),
-        "write_file" | "create_file" => write_file(workspace_root, &args),
+        "write_file" | "create_file" => write_file(workspace_root, &args, app_handle),
         "edit_file" => edit_file(workspace_root, &args),
+        "edit_lines" => edit_lines(workspace_root, &args),
         "grep_search" | "rg" => grep_search(workspace_root, &args),
-        "codebase_search" => codebase_search(workspace_root, &args),
+        "codebase_search" => {
+            let language_service = language_service_from(app_handle);
+            codebase_search(workspace_root, &args, language_service.as_deref())
+        }
+        "find_references" => {
+            let language_service = language_service_from(app_handle);
+            find_references(workspace_root, &args, language_service.as_deref())
+        }
+        "semantic_search" => {
+            semantic_search(workspace_root, &args, embedding_config_from(app_handle))
+        }
         "list_directory" | "list_dir" => list_directory(workspace_root, &args),
 
         // Phase 1 IDE-specific tools
         "get_editor_state" => get_editor_state(editor_state),
         "read_file_range" => read_file_range(workspace_root, &args),
         "apply_edit" | "apply_patch" => apply_edit_tool(workspace_root, &args),
+        "preview_patch" => preview_patch(workspace_root, &args),
+        "semantic_edit" => {
+            use tauri::Manager;
+            match app_handle.and_then(|app| app.try_state::<crate::app_state::AppState>()) {
+                Some(state) => semantic_edit(
+                    workspace_root,
+                    &args,
+                    state.language_service.clone(),
+                    Some(state.history_service.as_ref()),
+                ),
+                None => ToolResult::err(
+                    "semantic_edit requires the language service, which is not available here",
+                ),
+            }
+        }
         "get_workspace_structure" => get_workspace_structure(workspace_root, &args),
 
 
         // New file system tools
         "find_files" => find_files(workspace_root, &args),
         "find_files_glob" | "glob" => find_files_glob(workspace_root, &args),
+        "read_files_glob" => read_files_glob(workspace_root, &args),
+        "replace_in_files" => replace_in_files_preview(workspace_root, &args),
         "create_directory" => create_directory(workspace_root, &args),
-        "delete_file" => delete_file(workspace_root, &args),
-        "move_file" => move_file(workspace_root, &args),
+        "delete_file" => delete_file(workspace_root, &args, app_handle),
+        "move_file" => move_file(workspace_root, &args, app_handle),
         "copy_file" => copy_file(workspace_root, &args),
         "get_file_info" => get_file_info(workspace_root, &args),
+        "diff_files" => diff_files(workspace_root, &args),
 
         // New editor interaction tools
         "open_file" => open_file(&args),
@@ -217,22 +415,100 @@ pub fn execute_tool_with_editor<R: tauri::Runtime>(
         "insert_at_cursor" => insert_at_cursor(&args),
 
         // Server-side tools (handled by zcoderd, not zblade)
-        "ask_followup_question" | "attempt_completion" | "new_task" | "generate_image" | "todo_write" => {
-            ToolResult::err(format!(
-                "Tool '{}' is a server-side tool that should be handled by zcoderd, not zblade. \
-                This error indicates a protocol issue - zblade should not receive execution requests for server-side tools.",
-                tool_name
-            ))
+        name if SERVER_SIDE_TOOLS.contains(&name) => {
+            report_misrouted_tool(app_handle, tool_name);
+            ToolResult::misrouted(tool_name)
         }
 
         _ => ToolResult::err(format!("unknown tool: {tool_name}")),
     }
 }
 
+/// Symbol-aware tools use the shared LanguageService when running inside the app
+fn language_service_from<R: tauri::Runtime>(
+    app_handle: Option<&tauri::AppHandle<R>>,
+) -> Option<std::sync::Arc<LanguageService>> {
+    use tauri::Manager;
+    let state = app_handle?.try_state::<crate::app_state::AppState>()?;
+    Some(state.language_service.clone())
+}
+
+/// Whether a path validation error means the path was outside the workspace sandbox
+pub(crate) fn is_outside_workspace_error(error: &str) -> bool {
+    error.contains("outside workspace")
+}
+
+/// Tell the frontend zcoderd sent a server-side tool here, so protocol desyncs
+/// show up as such instead of as an ordinary tool failure
+fn report_misrouted_tool<R: tauri::Runtime>(app_handle: Option<&tauri::AppHandle<R>>, tool: &str) {
+    use tauri::Emitter;
+    eprintln!(
+        "[PROTOCOL] Server-side tool '{}' was sent to zblade to execute",
+        tool
+    );
+    let Some(app) = app_handle else {
+        return;
+    };
+    let _ = app.emit(
+        "blade-event",
+        crate::blade_protocol::BladeEventEnvelope {
+            id: uuid::Uuid::new_v4(),
+            timestamp: std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_millis() as u64,
+            causality_id: None,
+            event: crate::blade_protocol::BladeEvent::System(
+                crate::blade_protocol::SystemEvent::ServerToolMisrouted {
+                    tool: tool.to_string(),
+                },
+            ),
+        },
+    );
+}
+
+/// Tell the UI a write-type tool was refused for targeting a path outside the workspace.
+/// The tool result keeps its error for the model; this only adds user-facing visibility.
+pub(crate) fn report_blocked_write<R: tauri::Runtime>(
+    app_handle: Option<&tauri::AppHandle<R>>,
+    tool: &str,
+    path: &str,
+) {
+    use tauri::{Emitter, Manager};
+    eprintln!("[SANDBOX] Blocked {} outside workspace: {}", tool, path);
+    let Some(app) = app_handle else {
+        return;
+    };
+
+    let total = app
+        .state::<crate::app_state::AppState>()
+        .blocked_writes
+        .fetch_add(1, std::sync::atomic::Ordering::Relaxed)
+        + 1;
+    let _ = app.emit(
+        "blade-event",
+        crate::blade_protocol::BladeEventEnvelope {
+            id: uuid::Uuid::new_v4(),
+            timestamp: std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_millis() as u64,
+            causality_id: None,
+            event: crate::blade_protocol::BladeEvent::Security(
+                crate::blade_protocol::SecurityEvent::BlockedWrite {
+                    path: path.to_string(),
+                    tool: tool.to_string(),
+                    total,
+                },
+            ),
+        },
+    );
+}
+
 /// Resolve a path (potentially relative) to an absolute path under the workspace.
 /// This handles edge cases like ".", "./src", "src/utils" by prepending workspace root.
 /// Does NOT require the path to exist (useful for write operations).
-fn resolve_path_in_workspace(workspace_root: &Path, path: &Path) -> Result<PathBuf, String> {
+pub(crate) fn resolve_path_in_workspace(workspace_root: &Path, path: &Path) -> Result<PathBuf, String> {
     let ws = fs::canonicalize(workspace_root).map_err(|e| format!("cannot canonicalize workspace: {}", e))?;
 
     // Handle relative paths by joining with workspace root
@@ -254,9 +530,45 @@ fn resolve_path_in_workspace(workspace_root: &Path, path: &Path) -> Result<PathB
         ));
     }
 
+    reject_escaping_symlinks(&ws, &normalized)?;
+
     Ok(normalized)
 }
 
+/// Reject a workspace path if any existing component below the workspace root is a
+/// symlink resolving outside the workspace (or one that can't be resolved, e.g. a loop).
+/// Symlinks that stay inside the workspace are allowed. Components that don't exist yet
+/// are skipped so new files can still be created.
+fn reject_escaping_symlinks(ws: &Path, normalized: &Path) -> Result<(), String> {
+    let Ok(relative) = normalized.strip_prefix(ws) else {
+        return Ok(());
+    };
+
+    let mut current = ws.to_path_buf();
+    for component in relative.components() {
+        current.push(component);
+        let Ok(meta) = fs::symlink_metadata(&current) else {
+            break;
+        };
+        if !meta.file_type().is_symlink() {
+            continue;
+        }
+
+        let target = fs::canonicalize(&current).map_err(|e| {
+            format!("cannot resolve symlink {}: {}", current.display(), e)
+        })?;
+        if !target.starts_with(ws) {
+            return Err(format!(
+                "path traverses symlink {} which resolves outside workspace ({})",
+                current.display(),
+                target.display()
+            ));
+        }
+    }
+
+    Ok(())
+}
+
 /// Normalize a path by resolving . and .. components without requiring the path to exist.
 /// This is similar to canonicalize but works for non-existent paths.
 fn normalize_path(path: &Path) -> PathBuf {
@@ -282,7 +594,10 @@ fn normalize_path(path: &Path) -> PathBuf {
 
 /// Validate and resolve a path under workspace. Requires the path to exist.
 /// Use resolve_path_in_workspace for paths that may not exist yet.
-fn validate_path_under_workspace(workspace_root: &Path, path: &Path) -> Result<PathBuf, String> {
+pub(crate) fn validate_path_under_workspace(
+    workspace_root: &Path,
+    path: &Path,
+) -> Result<PathBuf, String> {
     let ws = fs::canonicalize(workspace_root).map_err(|e| e.to_string())?;
 
     let candidate = if path.is_absolute() {
@@ -296,8 +611,8 @@ fn validate_path_under_workspace(workspace_root: &Path, path: &Path) -> Result<P
         return Err(format!("path does not exist: {}", candidate.display()));
     }
 
-    // For symlinks, we want to validate the link location, not the target
-    // This allows symlinks inside the workspace even if they point outside
+    // Validate the link location first, then make sure no symlink along the way
+    // leads out of the workspace (symlinks that stay inside are fine)
     let normalized = normalize_path(&candidate);
     
     // Validate the normalized path is under workspace
@@ -309,13 +624,15 @@ fn validate_path_under_workspace(workspace_root: &Path, path: &Path) -> Result<P
         ));
     }
 
+    reject_escaping_symlinks(&ws, &normalized)?;
+
     // Return the normalized path (not canonicalized) to preserve symlinks
     Ok(normalized)
 }
 
 fn read_file(workspace_root: &Path, args: &HashMap<String, serde_json::Value>) -> ToolResult {
-    let Some(path) = get_str_arg(args, &["path", "file_path", "filepath", "filename"]) else {
-        return ToolResult::err("missing required arg: path (or file_path)");
+    let Some(path) = get_str_arg(args, &["path"]) else {
+        return ToolResult::err("missing required arg: path");
     };
 
     let abs = match validate_path_under_workspace(workspace_root, Path::new(&path)) {
@@ -323,34 +640,51 @@ fn read_file(workspace_root: &Path, args: &HashMap<String, serde_json::Value>) -
         Err(e) => return ToolResult::err(e),
     };
 
-    match fs::read_to_string(&abs) {
-        Ok(s) => {
+    match crate::encoding::read_text(&abs) {
+        Ok((s, encoding)) => {
             let content = if s.is_empty() {
                 format!(
                     "=== File: {} (empty) ===\n// This file exists but contains no content.",
                     abs.to_string_lossy()
                 )
+            } else if encoding != crate::encoding::TextEncoding::Utf8 {
+                // Transcoded for reading; edits are written back in the same encoding
+                format!(
+                    "=== File: {} ({}) ===\n{}",
+                    abs.to_string_lossy(),
+                    encoding.label(),
+                    s
+                )
             } else {
                 format!("=== File: {} ===\n{}", abs.to_string_lossy(), s)
             };
             ToolResult::ok(content)
         }
-        Err(e) => ToolResult::err(e.to_string()),
+        Err(e) => ToolResult::err(e),
     }
 }
 
-fn write_file(workspace_root: &Path, args: &HashMap<String, serde_json::Value>) -> ToolResult {
-    let Some(path) = get_str_arg(args, &["path", "file_path", "filepath", "filename"]) else {
-        return ToolResult::err("missing required arg: path (or file_path)");
+fn write_file<R: tauri::Runtime>(
+    workspace_root: &Path,
+    args: &HashMap<String, serde_json::Value>,
+    app_handle: Option<&tauri::AppHandle<R>>,
+) -> ToolResult {
+    let Some(path) = get_str_arg(args, &["path"]) else {
+        return ToolResult::err("missing required arg: path");
     };
-    let Some(content) = get_str_arg(args, &["content", "contents", "text", "data"]) else {
-        return ToolResult::err("missing required arg: content (or contents/text)");
+    let Some(content) = get_str_arg(args, &["content"]) else {
+        return ToolResult::err("missing required arg: content");
     };
 
     // Use resolve_path_in_workspace which handles relative paths and doesn't require existence
     let abs = match resolve_path_in_workspace(workspace_root, Path::new(&path)) {
         Ok(p) => p,
-        Err(e) => return ToolResult::err(e),
+        Err(e) => {
+            if is_outside_workspace_error(&e) {
+                report_blocked_write(app_handle, "write_file", &path);
+            }
+            return ToolResult::err(e);
+        }
     };
 
     // Create parent directories if needed
@@ -360,21 +694,21 @@ fn write_file(workspace_root: &Path, args: &HashMap<String, serde_json::Value>)
         }
     }
 
-    match fs::write(&abs, content.as_bytes()) {
+    match crate::utils::atomic_write(&abs, content.as_bytes()) {
         Ok(()) => ToolResult::ok(format!("wrote {} bytes to {}", content.len(), abs.display())),
         Err(e) => ToolResult::err(format!("write failed: {}", e)),
     }
 }
 
 fn edit_file(workspace_root: &Path, args: &HashMap<String, serde_json::Value>) -> ToolResult {
-    let Some(path) = get_str_arg(args, &["path", "file_path", "filepath", "filename"]) else {
-        return ToolResult::err("missing required arg: path (or file_path)");
+    let Some(path) = get_str_arg(args, &["path"]) else {
+        return ToolResult::err("missing required arg: path");
     };
-    let Some(old_content) = get_str_arg(args, &["old_content", "old", "from"]) else {
-        return ToolResult::err("missing required arg: old_content (or old/from)");
+    let Some(old_content) = get_str_arg(args, &["old_content"]) else {
+        return ToolResult::err("missing required arg: old_content");
     };
-    let Some(new_content) = get_str_arg(args, &["new_content", "new", "to"]) else {
-        return ToolResult::err("missing required arg: new_content (or new/to)");
+    let Some(new_content) = get_str_arg(args, &["new_content"]) else {
+        return ToolResult::err("missing required arg: new_content");
     };
 
     let abs = match validate_path_under_workspace(workspace_root, Path::new(&path)) {
@@ -396,22 +730,66 @@ fn edit_file(workspace_root: &Path, args: &HashMap<String, serde_json::Value>) -
     out.push_str(&new_content);
     out.push_str(&content[pos + old_content.len()..]);
 
-    match fs::write(&abs, out.as_bytes()) {
+    match crate::utils::atomic_write(&abs, out.as_bytes()) {
         Ok(()) => ToolResult::ok("edit applied".to_string()),
         Err(e) => ToolResult::err(e.to_string()),
     }
 }
 
+/// `start_line`/`end_line` args as a 1-indexed inclusive range
+pub(crate) fn line_range_args(
+    args: &HashMap<String, serde_json::Value>,
+) -> Result<(usize, usize), String> {
+    let line = |key: &str| {
+        args.get(key)
+            .and_then(|v| v.as_u64())
+            .map(|n| n as usize)
+            .ok_or_else(|| format!("missing required arg: {}", key))
+    };
+    Ok((line("start_line")?, line("end_line")?))
+}
+
+fn edit_lines(workspace_root: &Path, args: &HashMap<String, serde_json::Value>) -> ToolResult {
+    let Some(path) = get_str_arg(args, &["path"]) else {
+        return ToolResult::err("missing required arg: path");
+    };
+    let Some(new_content) = get_str_arg(args, &["new_content"]) else {
+        return ToolResult::err("missing required arg: new_content");
+    };
+    let (start_line, end_line) = match line_range_args(args) {
+        Ok(range) => range,
+        Err(e) => return ToolResult::err(e),
+    };
+
+    let abs = match validate_path_under_workspace(workspace_root, Path::new(&path)) {
+        Ok(p) => p,
+        Err(e) => return ToolResult::err(e),
+    };
+    let content = match fs::read_to_string(&abs) {
+        Ok(s) => s,
+        Err(e) => return ToolResult::err(e.to_string()),
+    };
+
+    let out = match replace_line_range(&content, start_line, end_line, &new_content) {
+        Ok(out) => out,
+        Err(e) => return ToolResult::err(e),
+    };
+    match crate::utils::atomic_write(&abs, out.as_bytes()) {
+        Ok(()) => ToolResult::ok(format!(
+            "replaced lines {}-{} of {}",
+            start_line, end_line, path
+        )),
+        Err(e) => ToolResult::err(e.to_string()),
+    }
+}
+
 fn list_directory(workspace_root: &Path, args: &HashMap<String, serde_json::Value>) -> ToolResult {
     // Forward to get_workspace_structure with default depth 1 to provide a consistant tree view
     // which coding models (like Mercury/Qwen) prefer over flat lists.
     let mut new_args = args.clone();
 
     // Default to current dir if no path provided (handles empty args case)
-    if !new_args.contains_key("path")
-        && !new_args.contains_key("dir")
-        && !new_args.contains_key("directory")
-    {
+    if !new_args.contains_key("path") {
         new_args.insert(
             "path".to_string(),
             serde_json::Value::String(".".to_string()),
@@ -425,12 +803,12 @@ fn list_directory(workspace_root: &Path, args: &HashMap<String, serde_json::Valu
 }
 
 fn grep_search(workspace_root: &Path, args: &HashMap<String, serde_json::Value>) -> ToolResult {
-    let Some(pattern) = get_str_arg(args, &["pattern", "query", "regex"]) else {
+    let Some(pattern) = get_str_arg(args, &["pattern"]) else {
         return ToolResult::err(
             "grep_search requires a 'pattern' argument. Example: {\"pattern\": \"Priority\"}",
         );
     };
-    let path = get_str_arg(args, &["path", "dir", "directory"]).unwrap_or_else(|| ".".to_string());
+    let path = get_str_arg(args, &["path"]).unwrap_or_else(|| ".".to_string());
 
     let abs = match validate_path_under_workspace(workspace_root, Path::new(&path)) {
         Ok(p) => p,
@@ -442,10 +820,20 @@ fn grep_search(workspace_root: &Path, args: &HashMap<String, serde_json::Value>)
         Err(e) => return ToolResult::err(format!("invalid regex: {e}")),
     };
 
+    // Structured JSON is the default; "text" keeps the legacy path:line:content output
+    let text_format = get_str_arg(args, &["format"])
+        .map(|f| f.eq_ignore_ascii_case("text"))
+        .unwrap_or(false);
+    let max_file_bytes = project_settings::load_project_settings_or_default(workspace_root)
+        .context
+        .max_grep_file_bytes;
+
     // Load gitignore filter
     let gitignore_filter = create_gitignore_filter(workspace_root);
 
     let mut out = String::new();
+    let mut matches: Vec<GrepMatch> = Vec::new();
+    let mut skipped = SearchSkipStats::default();
     for entry in WalkDir::new(abs)
         .follow_links(false)
         .into_iter()
@@ -464,26 +852,97 @@ fn grep_search(workspace_root: &Path, args: &HashMap<String, serde_json::Value>)
             }
         }
 
-        let Ok(text) = fs::read_to_string(path) else {
+        let Some(text) = read_searchable_file(path, max_file_bytes, &mut skipped) else {
             continue;
         };
 
         for (idx, line) in text.lines().enumerate() {
-            if re.is_match(line) {
-                out.push_str(&format!(
-                    "{}:{}:{}\n",
-                    path.to_string_lossy(),
-                    idx + 1,
-                    line
-                ));
+            if text_format {
+                if re.is_match(line) {
+                    out.push_str(&format!(
+                        "{}:{}:{}\n",
+                        path.to_string_lossy(),
+                        idx + 1,
+                        line
+                    ));
+                }
+                continue;
+            }
+
+            for m in re.find_iter(line) {
+                matches.push(GrepMatch::new(path, idx + 1, line, m.start(), m.end()));
             }
         }
     }
 
-    ToolResult::ok(out)
+    if let Some(summary) = skipped.summary() {
+        eprintln!("[GREP] {}", summary);
+    }
+
+    if text_format {
+        if let Some(summary) = skipped.summary() {
+            out.push_str(&summary);
+            out.push('\n');
+        }
+        return ToolResult::ok(out);
+    }
+
+    // Skip counts go alongside the matches, like the summary line in the text format
+    let result = serde_json::json!({ "matches": matches, "skipped": skipped });
+    match serde_json::to_string(&result) {
+        Ok(json) => ToolResult::ok(json),
+        Err(e) => ToolResult::err(format!("Failed to serialize grep results: {}", e)),
+    }
+}
+
+/// A single grep hit with character-based columns for editor navigation.
+/// `line` is 1-indexed; `col_start`/`col_end` are 0-indexed character offsets (end exclusive).
+#[derive(Debug, Clone, Serialize)]
+struct GrepMatch {
+    path: String,
+    line: usize,
+    col_start: usize,
+    col_end: usize,
+    matched_text: String,
+    line_text: String,
+}
+
+impl GrepMatch {
+    fn new(path: &Path, line: usize, line_text: &str, byte_start: usize, byte_end: usize) -> Self {
+        // Regex offsets are bytes; count chars so multibyte text highlights the right glyphs
+        let col_start = line_text[..byte_start].chars().count();
+        let col_end = col_start + line_text[byte_start..byte_end].chars().count();
+        Self {
+            path: path.to_string_lossy().to_string(),
+            line,
+            col_start,
+            col_end,
+            matched_text: line_text[byte_start..byte_end].to_string(),
+            line_text: line_text.to_string(),
+        }
+    }
 }
 
-fn codebase_search(workspace_root: &Path, args: &HashMap<String, serde_json::Value>) -> ToolResult {
+/// Symbol kinds returned by `codebase_search` in `mode: "symbols"` (definitions only)
+const SYMBOL_SEARCH_TYPES: &[SymbolType] = &[
+    SymbolType::Function,
+    SymbolType::Method,
+    SymbolType::Class,
+    SymbolType::Struct,
+    SymbolType::Interface,
+    SymbolType::Type,
+    SymbolType::Enum,
+    SymbolType::Trait,
+    SymbolType::Module,
+    SymbolType::Namespace,
+    SymbolType::Constant,
+];
+
+fn codebase_search(
+    workspace_root: &Path,
+    args: &HashMap<String, serde_json::Value>,
+    language_service: Option<&LanguageService>,
+) -> ToolResult {
     let Some(query) = get_str_arg(args, &["query"]) else {
         return ToolResult::err(
             "codebase_search requires a 'query' argument. Example: {\"query\": \"struct User\"}",
@@ -496,6 +955,19 @@ fn codebase_search(workspace_root: &Path, args: &HashMap<String, serde_json::Val
         .and_then(|v| v.as_u64())
         .unwrap_or(50) as usize;
 
+    // Symbol mode goes through the index; a cold or unavailable index falls back to the regex scan
+    let symbols_mode = get_str_arg(args, &["mode"])
+        .map(|m| m.eq_ignore_ascii_case("symbols"))
+        .unwrap_or(false);
+    if symbols_mode {
+        match language_service.map(|ls| search_symbol_index(ls, &query, max_results)) {
+            Some(Ok(Some(output))) => return ToolResult::ok(output),
+            Some(Ok(None)) => eprintln!("[CODEBASE SEARCH] Symbol index is empty, falling back to regex scan"),
+            Some(Err(e)) => eprintln!("[CODEBASE SEARCH] Symbol search failed, falling back to regex scan: {}", e),
+            None => eprintln!("[CODEBASE SEARCH] Language service unavailable, falling back to regex scan"),
+        }
+    }
+
     let abs = match fs::canonicalize(workspace_root) {
         Ok(p) => p,
         Err(e) => return ToolResult::err(format!("cannot canonicalize workspace: {}", e)),
@@ -507,11 +979,16 @@ fn codebase_search(workspace_root: &Path, args: &HashMap<String, serde_json::Val
         Err(e) => return ToolResult::err(format!("invalid regex pattern: {}", e)),
     };
 
+    let max_file_bytes = project_settings::load_project_settings_or_default(workspace_root)
+        .context
+        .max_grep_file_bytes;
+
     // Load gitignore filter
     let gitignore_filter = create_gitignore_filter(workspace_root);
 
     let mut results = Vec::new();
     let mut count = 0;
+    let mut skipped = SearchSkipStats::default();
 
     for entry in WalkDir::new(&abs)
         .follow_links(false)
@@ -552,7 +1029,7 @@ fn codebase_search(workspace_root: &Path, args: &HashMap<String, serde_json::Val
             }
         }
 
-        let Ok(text) = fs::read_to_string(path) else {
+        let Some(text) = read_searchable_file(path, max_file_bytes, &mut skipped) else {
             continue;
         };
 
@@ -594,21 +1071,74 @@ fn codebase_search(workspace_root: &Path, args: &HashMap<String, serde_json::Val
         }
     }
 
+    let skipped_summary = skipped
+        .summary()
+        .map(|s| format!("\n{}", s))
+        .unwrap_or_default();
+
     if results.is_empty() {
-        return ToolResult::ok(format!("No matches found for query: '{}'", query));
+        return ToolResult::ok(format!(
+            "No matches found for query: '{}'{}",
+            query, skipped_summary
+        ));
     }
 
     let output = format!(
-        "Found {} matches for '{}' (showing up to {}):\n{}",
+        "Found {} matches for '{}' (showing up to {}):\n{}{}",
         count,
         query,
         max_results,
-        results.join("\n")
+        results.join("\n"),
+        skipped_summary
     );
 
     ToolResult::ok(output)
 }
 
+/// Query the symbol index for definitions matching `query`.
+/// Returns `Ok(None)` when the index has no symbols yet (cold), so callers can fall back.
+fn search_symbol_index(
+    language_service: &LanguageService,
+    query: &str,
+    max_results: usize,
+) -> Result<Option<String>, String> {
+    let stats = language_service.stats().map_err(|e| e.to_string())?;
+    if stats.symbols_extracted == 0 {
+        return Ok(None);
+    }
+
+    let results = language_service
+        .search_symbols_filtered(query, None, Some(SYMBOL_SEARCH_TYPES.to_vec()), max_results)
+        .map_err(|e| e.to_string())?;
+
+    if results.is_empty() {
+        return Ok(Some(format!("No symbols found for query: '{}'", query)));
+    }
+
+    let lines: Vec<String> = results
+        .iter()
+        .map(|r| {
+            let symbol = &r.symbol;
+            format!(
+                "{} {} — {}:{}-{}",
+                symbol.symbol_type,
+                symbol.name,
+                symbol.file_path,
+                symbol.range.start.line + 1,
+                symbol.range.end.line + 1
+            )
+        })
+        .collect();
+
+    Ok(Some(format!(
+        "Found {} symbols for '{}' (showing up to {}):\n{}",
+        results.len(),
+        query,
+        max_results,
+        lines.join("\n")
+    )))
+}
+
 // ===== Phase 1 IDE-Specific Tools =====
 
 fn get_editor_state(editor_state: Option<&EditorState>) -> ToolResult {
@@ -671,8 +1201,8 @@ fn get_editor_state(editor_state: Option<&EditorState>) -> ToolResult {
 }
 
 fn read_file_range(workspace_root: &Path, args: &HashMap<String, serde_json::Value>) -> ToolResult {
-    let Some(path) = get_str_arg(args, &["path", "file_path", "filepath", "filename"]) else {
-        return ToolResult::err("missing required arg: path (or file_path)");
+    let Some(path) = get_str_arg(args, &["path"]) else {
+        return ToolResult::err("missing required arg: path");
     };
 
     let abs = match validate_path_under_workspace(workspace_root, Path::new(&path)) {
@@ -680,9 +1210,9 @@ fn read_file_range(workspace_root: &Path, args: &HashMap<String, serde_json::Val
         Err(e) => return ToolResult::err(e),
     };
 
-    let content = match fs::read_to_string(&abs) {
-        Ok(s) => s,
-        Err(e) => return ToolResult::err(e.to_string()),
+    let content = match crate::encoding::read_text(&abs) {
+        Ok((s, _)) => s,
+        Err(e) => return ToolResult::err(e),
     };
 
     let lines: Vec<&str> = content.lines().collect();
@@ -724,19 +1254,158 @@ fn read_file_range(workspace_root: &Path, args: &HashMap<String, serde_json::Val
     ToolResult::ok(result)
 }
 
+/// Which matching strategy located `old_text` in the file
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PatchMatchStrategy {
+    Exact,
+    Fuzzy,
+    None,
+}
+
+impl PatchMatchStrategy {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PatchMatchStrategy::Exact => "exact",
+            PatchMatchStrategy::Fuzzy => "fuzzy",
+            PatchMatchStrategy::None => "none",
+        }
+    }
+}
+
+/// Outcome of matching a search/replace patch against file content
+#[derive(Debug, Clone)]
+pub struct PatchAttempt {
+    pub strategy: PatchMatchStrategy,
+    /// Number of candidate locations when more than one matched (0 when unique or not found)
+    pub ambiguous_count: usize,
+    pub result: Result<String, String>,
+}
+
 // Helper for applying patches with robust matching
 pub fn apply_patch_to_string(
     content: &str,
     old_text: &str,
     new_text: &str,
 ) -> Result<String, String> {
+    try_apply_patch(content, old_text, new_text).result
+}
+
+/// Dominant line ending of a file, so edits can be written back in the same style
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LineEnding {
+    Lf,
+    CrLf,
+}
+
+impl LineEnding {
+    pub fn detect(content: &str) -> Self {
+        let crlf = content.matches("\r\n").count();
+        let lf = content.matches('\n').count() - crlf;
+        if crlf > lf {
+            Self::CrLf
+        } else {
+            Self::Lf
+        }
+    }
+}
+
+/// Apply old/new text patches in order, keeping the content's dominant line ending.
+/// CRLF content is patched in LF form (model patches use LF) and converted back afterwards.
+pub fn apply_patches_preserving_eol(
+    content: &str,
+    patches: &[(&str, &str)],
+) -> Result<String, String> {
+    match LineEnding::detect(content) {
+        LineEnding::Lf => patches
+            .iter()
+            .try_fold(content.to_string(), |acc, (old, new)| {
+                apply_patch_to_string(&acc, old, new)
+            }),
+        LineEnding::CrLf => {
+            let mut out = content.replace("\r\n", "\n");
+            for (old, new) in patches {
+                out = apply_patch_to_string(
+                    &out,
+                    &old.replace("\r\n", "\n"),
+                    &new.replace("\r\n", "\n"),
+                )?;
+            }
+            Ok(out.replace('\n', "\r\n"))
+        }
+    }
+}
+
+/// Regex replacement with `$1`/`${name}` backreferences, limited to the first `count`
+/// matches (`None` replaces all). Returns the new content and the number of replacements;
+/// no match is an error so the model learns its pattern missed.
+pub fn regex_replace_content(
+    content: &str,
+    pattern: &str,
+    replacement: &str,
+    count: Option<usize>,
+) -> Result<(String, usize), String> {
+    let re = Regex::new(pattern).map_err(|e| format!("invalid regex: {e}"))?;
+    let found = re.find_iter(content).count();
+    if found == 0 {
+        return Err(format!(
+            "pattern '{}' matched nothing; read the file to check the exact text",
+            pattern
+        ));
+    }
+    let limit = count.unwrap_or(0);
+    let replaced = if limit == 0 { found } else { found.min(limit) };
+    Ok((re.replacen(content, limit, replacement).into_owned(), replaced))
+}
+
+/// Replace lines `start_line..=end_line` (1-indexed) with `new_content`, refusing ranges
+/// outside the file. The replaced block keeps the line ending of its last line, and an empty
+/// `new_content` deletes the lines.
+pub fn replace_line_range(
+    content: &str,
+    start_line: usize,
+    end_line: usize,
+    new_content: &str,
+) -> Result<String, String> {
+    let lines: Vec<&str> = content.split_inclusive('\n').collect();
+    if start_line == 0 || start_line > end_line || end_line > lines.len() {
+        return Err(format!(
+            "line range {}-{} is out of bounds (file has {} lines)",
+            start_line,
+            end_line,
+            lines.len()
+        ));
+    }
+
+    let mut out = String::with_capacity(content.len() + new_content.len());
+    for line in &lines[..start_line - 1] {
+        out.push_str(line);
+    }
+    out.push_str(new_content);
+    let last = lines[end_line - 1];
+    if !new_content.is_empty() && !new_content.ends_with('\n') && last.ends_with('\n') {
+        out.push_str(if last.ends_with("\r\n") { "\r\n" } else { "\n" });
+    }
+    for line in &lines[end_line..] {
+        out.push_str(line);
+    }
+    Ok(out)
+}
+
+/// Run the exact-then-fuzzy matching used by `apply_patch_to_string`,
+/// reporting which strategy matched alongside the resulting content.
+pub fn try_apply_patch(content: &str, old_text: &str, new_text: &str) -> PatchAttempt {
     // Strategy 1: Exact Match
     if let Some(pos) = content.find(old_text) {
         let mut out = String::with_capacity(content.len() - old_text.len() + new_text.len());
         out.push_str(&content[..pos]);
         out.push_str(new_text);
         out.push_str(&content[pos + old_text.len()..]);
-        return Ok(out);
+        let occurrences = content.matches(old_text).count();
+        return PatchAttempt {
+            strategy: PatchMatchStrategy::Exact,
+            ambiguous_count: if occurrences > 1 { occurrences } else { 0 },
+            result: Ok(out),
+        };
     }
 
     // Strategy 2: Line-by-Line Fuzzy Match (ignoring whitespace differences)
@@ -750,7 +1419,11 @@ pub fn apply_patch_to_string(
 
     // If old_text is empty or just whitespace, we can't fuzzy match safely
     if norm_old_lines.is_empty() || (norm_old_lines.len() == 1 && norm_old_lines[0].is_empty()) {
-        return Err("old_text not found (exact match failed, fuzzy match skipped for empty/whitespace input)".to_string());
+        return PatchAttempt {
+            strategy: PatchMatchStrategy::None,
+            ambiguous_count: 0,
+            result: Err("old_text not found (exact match failed, fuzzy match skipped for empty/whitespace input)".to_string()),
+        };
     }
 
     // Find all potential matches
@@ -837,35 +1510,832 @@ pub fn apply_patch_to_string(
             out.push('\n');
         }
 
-        Ok(out)
+        PatchAttempt {
+            strategy: PatchMatchStrategy::Fuzzy,
+            ambiguous_count: 0,
+            result: Ok(out),
+        }
     } else if matches.len() > 1 {
-        Err(format!(
-            "Ambiguous match: found {} occurrences of old_text (ignoring whitespace). Please provide more unique context.",
-            matches.len()
-        ))
+        PatchAttempt {
+            strategy: PatchMatchStrategy::None,
+            ambiguous_count: matches.len(),
+            result: Err(format!(
+                "Ambiguous match: found {} occurrences of old_text (ignoring whitespace). Please provide more unique context.",
+                matches.len()
+            )),
+        }
     } else {
-        Err(format!(
-            "old_text not found in file (searched {} chars). Exact match failed. Fuzzy match failed.",
-            old_text.len()
-        ))
+        PatchAttempt {
+            strategy: PatchMatchStrategy::None,
+            ambiguous_count: 0,
+            result: Err(format!(
+                "old_text not found in file (searched {} chars). Exact match failed. Fuzzy match failed.",
+                old_text.len()
+            )),
+        }
     }
 }
 
-/// Represents a single patch hunk for multi-patch operations
-#[derive(Debug, Clone)]
-#[allow(dead_code)]
-struct PatchHunk {
-    old_text: String,
-    new_text: String,
-    start_line: Option<usize>,
-    end_line: Option<usize>,
-}
+/// Default cap on reported usage sites for find_references
+const DEFAULT_REFERENCE_LIMIT: usize = 100;
 
-/// Result of applying multiple patches atomically
-#[derive(Debug)]
-#[allow(dead_code)]
-struct MultiPatchResult {
-    success: bool,
+/// List definitions and usage sites of a symbol across the workspace.
+/// Definitions come from the symbol index when it has them, otherwise from parsing;
+/// usages are identifier nodes found by tree-sitter, so comments and strings don't match.
+fn find_references(
+    workspace_root: &Path,
+    args: &HashMap<String, serde_json::Value>,
+    language_service: Option<&LanguageService>,
+) -> ToolResult {
+    let Some(name) = get_str_arg(args, &["name"]) else {
+        return ToolResult::err(
+            "find_references requires a 'name' argument. Example: {\"name\": \"UserService\"}",
+        );
+    };
+    let file_filter = match get_str_arg(args, &["path"]) {
+        Some(p) => match validate_path_under_workspace(workspace_root, Path::new(&p)) {
+            Ok(abs) => Some(abs),
+            Err(e) => return ToolResult::err(e),
+        },
+        None => None,
+    };
+    let line_filter = args
+        .get("line")
+        .and_then(|v| v.as_u64())
+        .map(|n| n as u32);
+    let max_results = args
+        .get("max_results")
+        .and_then(|v| v.as_u64())
+        .map(|n| n as usize)
+        .unwrap_or(DEFAULT_REFERENCE_LIMIT);
+
+    let abs_root = match fs::canonicalize(workspace_root) {
+        Ok(p) => p,
+        Err(e) => return ToolResult::err(format!("cannot canonicalize workspace: {}", e)),
+    };
+    let max_file_bytes = project_settings::load_project_settings_or_default(workspace_root)
+        .context
+        .max_grep_file_bytes;
+    let gitignore_filter = create_gitignore_filter(workspace_root);
+
+    let mut parser = match crate::tree_sitter::TreeSitterParser::new() {
+        Ok(p) => p,
+        Err(e) => return ToolResult::err(format!("Failed to initialize parser: {}", e)),
+    };
+
+    let mut parsed_definitions: Vec<Symbol> = Vec::new();
+    let mut references: Vec<String> = Vec::new();
+    let mut total_references = 0;
+    let mut skipped = SearchSkipStats::default();
+
+    for entry in WalkDir::new(&abs_root)
+        .follow_links(false)
+        .into_iter()
+        .filter_entry(|e| {
+            let dir_name = e.file_name().to_string_lossy();
+            !(e.file_type().is_dir() && DIRS_TO_ALWAYS_IGNORE.contains(&dir_name.as_ref()))
+        })
+        .filter_map(Result::ok)
+    {
+        if !entry.file_type().is_file() {
+            continue;
+        }
+
+        let path = entry.path();
+        if let Some(ref filter) = gitignore_filter {
+            if filter.should_ignore(path) {
+                continue;
+            }
+        }
+
+        let rel_path = path
+            .strip_prefix(&abs_root)
+            .unwrap_or(path)
+            .to_string_lossy()
+            .to_string();
+        let Some(language) = crate::tree_sitter::Language::from_path(&rel_path) else {
+            continue;
+        };
+
+        let Some(text) = read_searchable_file(path, max_file_bytes, &mut skipped) else {
+            continue;
+        };
+        // Cheap pre-check so we only parse files that mention the name
+        if !text.contains(name.as_str()) {
+            continue;
+        }
+
+        let Ok(tree) = parser.parse(&text, language) else {
+            continue;
+        };
+
+        parsed_definitions.extend(
+            crate::tree_sitter::extract_symbols(&tree, &text, language, &rel_path)
+                .into_iter()
+                .filter(|s| s.name == name),
+        );
+
+        let lines: Vec<&str> = text.lines().collect();
+        for pos in crate::tree_sitter::find_identifier_occurrences(&tree, &text, &name) {
+            total_references += 1;
+            if references.len() >= max_results {
+                continue;
+            }
+            let line_text = lines.get(pos.line as usize).copied().unwrap_or("");
+            let byte_col = (pos.character as usize).min(line_text.len());
+            let col = line_text
+                .get(..byte_col)
+                .map(|prefix| prefix.chars().count())
+                .unwrap_or(byte_col);
+            references.push(format!(
+                "{}:{}:{}: {}",
+                rel_path,
+                pos.line + 1,
+                col + 1,
+                line_text.trim()
+            ));
+        }
+    }
+
+    // Prefer indexed definitions; fall back to what we parsed during the scan
+    let indexed_definitions: Vec<Symbol> = language_service
+        .and_then(|ls| ls.search_symbols(&name, 50).ok())
+        .map(|results| {
+            results
+                .into_iter()
+                .map(|r| r.symbol)
+                .filter(|s| s.name == name)
+                .collect()
+        })
+        .unwrap_or_default();
+    let mut definitions = if indexed_definitions.is_empty() {
+        parsed_definitions
+    } else {
+        indexed_definitions
+    };
+
+    // A file/line hint narrows which definition the caller means
+    if let Some(ref file) = file_filter {
+        let rel_file = file
+            .strip_prefix(&abs_root)
+            .or_else(|_| file.strip_prefix(workspace_root))
+            .unwrap_or(file.as_path())
+            .to_string_lossy()
+            .to_string();
+        definitions.retain(|s| s.file_path == rel_file || Path::new(&s.file_path) == file.as_path());
+        if let Some(line) = line_filter {
+            let line0 = line.saturating_sub(1);
+            definitions.retain(|s| s.range.start.line <= line0 && line0 <= s.range.end.line);
+        }
+    }
+
+    let mut output = String::new();
+    if definitions.is_empty() {
+        output.push_str(&format!("No definitions found for '{}'\n", name));
+    } else {
+        output.push_str(&format!("Definitions of '{}':\n", name));
+        for def in &definitions {
+            output.push_str(&format!(
+                "  {} {} — {}:{}-{}\n",
+                def.symbol_type,
+                def.name,
+                def.file_path,
+                def.range.start.line + 1,
+                def.range.end.line + 1
+            ));
+        }
+    }
+
+    if total_references == 0 {
+        output.push_str(&format!("No references found for '{}'\n", name));
+    } else {
+        output.push_str(&format!(
+            "\nReferences to '{}' ({} found, showing up to {}):\n",
+            name, total_references, max_results
+        ));
+        for reference in &references {
+            output.push_str(reference);
+            output.push('\n');
+        }
+    }
+
+    if let Some(summary) = skipped.summary() {
+        output.push_str(&summary);
+        output.push('\n');
+    }
+
+    ToolResult::ok(output)
+}
+
+/// Lines per embedded chunk for local semantic search
+const EMBEDDING_CHUNK_LINES: usize = 60;
+const DEFAULT_SEMANTIC_RESULTS: usize = 10;
+
+/// Ollama settings used to compute local embeddings
+#[derive(Debug, Clone)]
+pub struct EmbeddingConfig {
+    pub ollama_url: String,
+    pub model: String,
+}
+
+impl EmbeddingConfig {
+    /// Embedding settings from the app config, or None when Ollama is disabled
+    pub fn from_api_config(config: &crate::config::ApiConfig) -> Option<Self> {
+        config.ollama_enabled.then(|| Self {
+            ollama_url: config.ollama_url.clone(),
+            model: config.embedding_model().to_string(),
+        })
+    }
+}
+
+fn embedding_config_from<R: tauri::Runtime>(
+    app_handle: Option<&tauri::AppHandle<R>>,
+) -> Option<EmbeddingConfig> {
+    use tauri::Manager;
+    let app = app_handle?;
+    let state = app.state::<crate::app_state::AppState>();
+    let config = state.config.lock().unwrap();
+    EmbeddingConfig::from_api_config(&config)
+}
+
+/// Split file content into chunks of `EMBEDDING_CHUNK_LINES` lines.
+/// Returns (start_line, end_line, text) with 1-based inclusive line numbers; blank chunks are dropped.
+fn chunk_for_embedding(content: &str) -> Vec<(usize, usize, String)> {
+    let lines: Vec<&str> = content.lines().collect();
+    lines
+        .chunks(EMBEDDING_CHUNK_LINES)
+        .enumerate()
+        .filter(|(_, chunk)| chunk.iter().any(|l| !l.trim().is_empty()))
+        .map(|(i, chunk)| {
+            let start = i * EMBEDDING_CHUNK_LINES + 1;
+            (start, start + chunk.len() - 1, chunk.join("\n"))
+        })
+        .collect()
+}
+
+fn cosine_similarity(a: &[f32], b: &[f32]) -> f32 {
+    if a.len() != b.len() || a.is_empty() {
+        return 0.0;
+    }
+    let dot: f32 = a.iter().zip(b).map(|(x, y)| x * y).sum();
+    let norm_a = a.iter().map(|x| x * x).sum::<f32>().sqrt();
+    let norm_b = b.iter().map(|x| x * x).sum::<f32>().sqrt();
+    if norm_a == 0.0 || norm_b == 0.0 {
+        0.0
+    } else {
+        dot / (norm_a * norm_b)
+    }
+}
+
+/// Stored with each embedding, so it must stay the same across builds and toolchains
+fn content_hash(content: &str) -> String {
+    use sha2::{Digest, Sha256};
+
+    format!("{:x}", Sha256::digest(content.as_bytes()))
+}
+
+/// Run Ollama embedding requests from synchronous tool code. Tools already execute on the
+/// async runtime, so the requests are driven on a dedicated thread.
+fn embed_blocking(config: &EmbeddingConfig, texts: Vec<String>) -> Result<Vec<Vec<f32>>, String> {
+    let url = config.ollama_url.clone();
+    let model = config.model.clone();
+    std::thread::spawn(move || {
+        tauri::async_runtime::block_on(crate::models::ollama::embed(&url, &model, &texts))
+    })
+    .join()
+    .map_err(|_| "embedding thread panicked".to_string())?
+}
+
+/// Embed a file's chunks unless its stored embeddings already match the content.
+/// Returns true when new embeddings were written.
+fn embed_file_if_changed(
+    index: &crate::local_index::LocalIndex,
+    config: &EmbeddingConfig,
+    rel_path: &str,
+    content: &str,
+) -> Result<bool, String> {
+    let hash = content_hash(content);
+    let stored = index
+        .embedded_file_hash(rel_path, &config.model)
+        .map_err(|e| e.to_string())?;
+    if stored.as_deref() == Some(hash.as_str()) {
+        return Ok(false);
+    }
+
+    let chunks = chunk_for_embedding(content);
+    let vectors = embed_blocking(config, chunks.iter().map(|c| c.2.clone()).collect())?;
+    let embedded: Vec<crate::local_index::ChunkEmbedding> = chunks
+        .iter()
+        .zip(vectors)
+        .map(|((start, end, _), embedding)| crate::local_index::ChunkEmbedding {
+            file_path: rel_path.to_string(),
+            start_line: *start as i32,
+            end_line: *end as i32,
+            embedding,
+        })
+        .collect();
+
+    index
+        .replace_file_embeddings(rel_path, &config.model, &hash, &embedded)
+        .map_err(|e| e.to_string())?;
+    Ok(true)
+}
+
+/// Canonical workspace root that embeddings are stored relative to, so the
+/// watcher, editor saves and searches all key files the same way
+fn embedding_root(workspace_root: &Path) -> PathBuf {
+    fs::canonicalize(workspace_root).unwrap_or_else(|_| workspace_root.to_path_buf())
+}
+
+/// `path` relative to the canonical `root`, resolving symlinks and `..` in it
+/// (through its parent when the file itself is gone)
+fn embedding_rel_path(root: &Path, path: &Path) -> Option<String> {
+    if let Ok(rel) = path.strip_prefix(root) {
+        return Some(rel.to_string_lossy().to_string());
+    }
+    let canonical = fs::canonicalize(path).ok().or_else(|| {
+        let parent = fs::canonicalize(path.parent()?).ok()?;
+        Some(parent.join(path.file_name()?))
+    })?;
+    canonical
+        .strip_prefix(root)
+        .ok()
+        .map(|rel| rel.to_string_lossy().to_string())
+}
+
+/// Open the workspace's index only if it already has embeddings for `model`:
+/// indexing stays opt-in until the first `semantic_search`
+fn open_embedded_index(
+    root: &Path,
+    model: &str,
+) -> Result<Option<crate::local_index::LocalIndex>, String> {
+    // Don't create an index just because files changed
+    let db_path = project_settings::get_zblade_dir(root)
+        .join("index")
+        .join("conversations.db");
+    if !db_path.exists() {
+        return Ok(None);
+    }
+    let index = crate::local_index::LocalIndex::open(root).map_err(|e| e.to_string())?;
+    let embedded = index
+        .embedded_file_count(model)
+        .map_err(|e| e.to_string())?;
+    Ok((embedded > 0).then_some(index))
+}
+
+/// Embed every new or changed code file in the workspace.
+/// Returns the number of files embedded.
+fn sync_workspace_embeddings(root: &Path, config: &EmbeddingConfig) -> Result<usize, String> {
+    let index = crate::local_index::LocalIndex::open(root)
+        .map_err(|e| format!("Failed to open local index: {}", e))?;
+    let max_file_bytes = project_settings::load_project_settings_or_default(root)
+        .context
+        .max_grep_file_bytes;
+    let gitignore_filter = create_gitignore_filter(root);
+    let mut skipped = SearchSkipStats::default();
+    let mut embedded_files = 0;
+
+    for entry in WalkDir::new(root)
+        .follow_links(false)
+        .into_iter()
+        .filter_entry(|e| {
+            let dir_name = e.file_name().to_string_lossy();
+            !(e.file_type().is_dir() && DIRS_TO_ALWAYS_IGNORE.contains(&dir_name.as_ref()))
+        })
+        .filter_map(Result::ok)
+    {
+        let path = entry.path().to_path_buf();
+        if !entry.file_type().is_file() || !crate::indexer::types::is_code_file(&path) {
+            continue;
+        }
+        if let Some(ref filter) = gitignore_filter {
+            if filter.should_ignore(&path) {
+                continue;
+            }
+        }
+
+        let rel_path = path
+            .strip_prefix(root)
+            .unwrap_or(&path)
+            .to_string_lossy()
+            .to_string();
+        let Some(content) = read_searchable_file(&path, max_file_bytes, &mut skipped) else {
+            continue;
+        };
+        if embed_file_if_changed(&index, config, &rel_path, &content)
+            .map_err(|e| format!("Failed to embed {}: {}", rel_path, e))?
+        {
+            embedded_files += 1;
+        }
+    }
+    if let Some(summary) = skipped.summary() {
+        eprintln!("[EMBEDDINGS] {}", summary);
+    }
+    Ok(embedded_files)
+}
+
+/// Embed changed files that still exist. Returns the number of files embedded.
+fn embed_changed_files(
+    root: &Path,
+    config: &EmbeddingConfig,
+    changes: &[crate::fs_watcher::FileChange],
+) -> Result<usize, String> {
+    let index = crate::local_index::LocalIndex::open(root).map_err(|e| e.to_string())?;
+    let max_file_bytes = project_settings::load_project_settings_or_default(root)
+        .context
+        .max_grep_file_bytes;
+    let mut skipped = SearchSkipStats::default();
+    let mut refreshed = 0;
+
+    for change in changes {
+        let path = PathBuf::from(&change.path);
+        if !path.is_file() || !crate::indexer::types::is_code_file(&path) {
+            continue;
+        }
+        let Some(rel_path) = embedding_rel_path(root, &path) else {
+            continue;
+        };
+        let Some(content) = read_searchable_file(&path, max_file_bytes, &mut skipped) else {
+            continue;
+        };
+        if embed_file_if_changed(&index, config, &rel_path, &content)? {
+            refreshed += 1;
+        }
+    }
+    Ok(refreshed)
+}
+
+enum EmbeddingJob {
+    SyncWorkspace,
+    Refresh(Vec<crate::fs_watcher::FileChange>),
+}
+
+/// Computes embeddings on one background thread, so neither `semantic_search`
+/// nor the fs watcher waits on Ollama. Jobs run in the order they were queued.
+struct EmbeddingWorker {
+    jobs: std::sync::Mutex<std::sync::mpsc::Sender<(PathBuf, EmbeddingConfig, EmbeddingJob)>>,
+    /// Set while a workspace sync is queued or running, so repeated searches
+    /// don't pile up walks of the whole workspace
+    syncing: std::sync::Arc<std::sync::atomic::AtomicBool>,
+}
+
+lazy_static::lazy_static! {
+    static ref EMBEDDING_WORKER: EmbeddingWorker = EmbeddingWorker::start();
+}
+
+impl EmbeddingWorker {
+    fn start() -> Self {
+        use std::sync::atomic::Ordering;
+
+        let (tx, rx) = std::sync::mpsc::channel::<(PathBuf, EmbeddingConfig, EmbeddingJob)>();
+        let syncing = std::sync::Arc::new(std::sync::atomic::AtomicBool::new(false));
+        let worker_syncing = syncing.clone();
+        std::thread::spawn(move || {
+            for (root, config, job) in rx {
+                match job {
+                    EmbeddingJob::SyncWorkspace => {
+                        match sync_workspace_embeddings(&root, &config) {
+                            Ok(0) => {}
+                            Ok(n) => eprintln!("[EMBEDDINGS] Embedded {} new or changed files", n),
+                            Err(e) => eprintln!("[EMBEDDINGS] Workspace sync failed: {}", e),
+                        }
+                        worker_syncing.store(false, Ordering::SeqCst);
+                    }
+                    EmbeddingJob::Refresh(changes) => {
+                        match embed_changed_files(&root, &config, &changes) {
+                            Ok(0) => {}
+                            Ok(n) => eprintln!("[EMBEDDINGS] Re-embedded {} changed files", n),
+                            Err(e) => eprintln!("[EMBEDDINGS] Failed to refresh embeddings: {}", e),
+                        }
+                    }
+                }
+            }
+        });
+        Self {
+            jobs: std::sync::Mutex::new(tx),
+            syncing,
+        }
+    }
+
+    fn send(&self, root: PathBuf, config: EmbeddingConfig, job: EmbeddingJob) {
+        if self.jobs.lock().unwrap().send((root, config, job)).is_err() {
+            eprintln!("[EMBEDDINGS] Embedding worker is gone");
+        }
+    }
+}
+
+/// Queue embedding of every new or changed file in the workspace, unless a sync
+/// is already pending
+fn queue_workspace_embedding(root: &Path, config: &EmbeddingConfig) {
+    use std::sync::atomic::Ordering;
+
+    if !EMBEDDING_WORKER.syncing.swap(true, Ordering::SeqCst) {
+        let job = EmbeddingJob::SyncWorkspace;
+        EMBEDDING_WORKER.send(root.to_path_buf(), config.clone(), job);
+    }
+}
+
+/// Keep embeddings in sync with files changed on disk (fs watcher events and
+/// editor saves). The changed files' embeddings are dropped right away, so a
+/// search never matches content that is gone, and re-embedded in the background.
+/// Only workspaces that already have embeddings are touched, so indexing stays
+/// opt-in until the first `semantic_search`.
+pub fn refresh_file_embeddings(
+    workspace_root: &Path,
+    config: &EmbeddingConfig,
+    changes: Vec<crate::fs_watcher::FileChange>,
+) -> Result<(), String> {
+    let root = embedding_root(workspace_root);
+    let Some(index) = open_embedded_index(&root, &config.model)? else {
+        return Ok(());
+    };
+    for change in &changes {
+        if let Some(rel_path) = embedding_rel_path(&root, Path::new(&change.path)) {
+            index
+                .delete_file_embeddings(&rel_path)
+                .map_err(|e| e.to_string())?;
+        }
+    }
+    EMBEDDING_WORKER.send(root, config.clone(), EmbeddingJob::Refresh(changes));
+    Ok(())
+}
+
+/// Rank workspace code chunks by cosine similarity to the query using local Ollama
+/// embeddings. Each search queues a background sync that embeds new or changed
+/// files; the search itself uses the embeddings stored so far.
+fn semantic_search(
+    workspace_root: &Path,
+    args: &HashMap<String, serde_json::Value>,
+    config: Option<EmbeddingConfig>,
+) -> ToolResult {
+    let Some(query) = get_str_arg(args, &["query"]) else {
+        return ToolResult::err(
+            "semantic_search requires a 'query' argument. Example: {\"query\": \"where are auth tokens refreshed\"}",
+        );
+    };
+    let Some(config) = config else {
+        return ToolResult::err(
+            "semantic_search requires Ollama to be enabled in Settings > Local AI",
+        );
+    };
+    let max_results = args
+        .get("max_results")
+        .and_then(|v| v.as_u64())
+        .map(|n| n as usize)
+        .unwrap_or(DEFAULT_SEMANTIC_RESULTS);
+
+    let abs_root = embedding_root(workspace_root);
+    let index = match crate::local_index::LocalIndex::open(&abs_root) {
+        Ok(index) => index,
+        Err(e) => return ToolResult::err(format!("Failed to open local index: {}", e)),
+    };
+    queue_workspace_embedding(&abs_root, &config);
+    match index.embedded_file_count(&config.model) {
+        Ok(0) => {
+            return ToolResult::ok(
+                "The semantic index for this workspace is being built in the background. \
+                 Use grep_search or codebase_search for now and try semantic_search again later.",
+            )
+        }
+        Ok(_) => {}
+        Err(e) => return ToolResult::err(format!("Failed to read embeddings: {}", e)),
+    }
+
+    let query_embedding = match embed_blocking(&config, vec![query.clone()]) {
+        Ok(mut vectors) if !vectors.is_empty() => vectors.remove(0),
+        Ok(_) => return ToolResult::err("Ollama returned no embedding for the query"),
+        Err(e) => return ToolResult::err(format!("Failed to embed query: {}", e)),
+    };
+    let chunks = match index.list_embeddings(&config.model) {
+        Ok(chunks) => chunks,
+        Err(e) => return ToolResult::err(format!("Failed to read embeddings: {}", e)),
+    };
+
+    let mut scored: Vec<(f32, crate::local_index::ChunkEmbedding)> = chunks
+        .into_iter()
+        .map(|c| (cosine_similarity(&query_embedding, &c.embedding), c))
+        .collect();
+    scored.sort_by(|a, b| b.0.partial_cmp(&a.0).unwrap_or(std::cmp::Ordering::Equal));
+    scored.truncate(max_results);
+
+    if scored.is_empty() {
+        return ToolResult::ok(format!("No embedded code found for '{}'", query));
+    }
+
+    let mut output = format!("Semantic matches for '{}':\n", query);
+    for (score, chunk) in &scored {
+        let first_line = fs::read_to_string(abs_root.join(&chunk.file_path))
+            .ok()
+            .and_then(|text| {
+                text.lines()
+                    .skip(chunk.start_line.saturating_sub(1) as usize)
+                    .take((chunk.end_line - chunk.start_line + 1).max(0) as usize)
+                    .map(str::trim)
+                    .find(|l| !l.is_empty())
+                    .map(str::to_string)
+            })
+            .unwrap_or_default();
+        output.push_str(&format!(
+            "{}:{}-{} (score {:.3}): {}\n",
+            chunk.file_path, chunk.start_line, chunk.end_line, score, first_line
+        ));
+    }
+
+    ToolResult::ok(output)
+}
+
+/// Build the `SemanticPatch` described by `semantic_edit` arguments.
+/// Operations: `replace_body`, `insert_before`, `insert_after`.
+pub fn build_semantic_patch(
+    args: &HashMap<String, serde_json::Value>,
+    file_path: &str,
+) -> Result<crate::semantic_patch::SemanticPatch, String> {
+    use crate::semantic_patch::{InsertPosition, PatchOperation, PatchTarget, SemanticPatch};
+
+    let operation_name = get_str_arg(args, &["operation"]).ok_or_else(|| {
+        "missing required arg: operation (replace_body, insert_before or insert_after)".to_string()
+    })?;
+    let code =
+        get_str_arg(args, &["code"]).ok_or_else(|| "missing required arg: code".to_string())?;
+
+    // The target may be nested ({ target: { symbol_kind, symbol_name } }) or flattened
+    let target_args: HashMap<String, serde_json::Value> = args
+        .get("target")
+        .and_then(|v| v.as_object())
+        .map(|o| o.clone().into_iter().collect())
+        .unwrap_or_else(|| args.clone());
+    let symbol_name = get_str_arg(&target_args, &["symbol_name", "name"])
+        .ok_or_else(|| "missing required arg: target.symbol_name".to_string())?;
+    let symbol_type = get_str_arg(&target_args, &["symbol_kind", "kind"])
+        .map(|kind| kind.parse::<SymbolType>())
+        .transpose()?;
+
+    let (operation, target) = match operation_name.as_str() {
+        "replace_body" => (
+            PatchOperation::Replace,
+            PatchTarget::SymbolBody {
+                name: symbol_name.clone(),
+                symbol_type,
+            },
+        ),
+        "insert_before" | "insert_after" => (
+            PatchOperation::Insert {
+                position: if operation_name == "insert_before" {
+                    InsertPosition::Before
+                } else {
+                    InsertPosition::After
+                },
+            },
+            PatchTarget::Symbol {
+                name: symbol_name.clone(),
+                symbol_type,
+            },
+        ),
+        other => {
+            return Err(format!(
+                "unknown operation '{}': expected replace_body, insert_before or insert_after",
+                other
+            ))
+        }
+    };
+
+    Ok(SemanticPatch {
+        id: uuid::Uuid::new_v4().to_string(),
+        description: format!("semantic_edit {} {}", operation_name, symbol_name),
+        file_path: file_path.to_string(),
+        operation,
+        target,
+        content: Some(code),
+        confidence: 1.0,
+    })
+}
+
+/// Apply an AST-aware edit to a named symbol via `PatchApplier`.
+/// Inside the app, `semantic_edit` calls go through the change pipeline in `ai_workflow`;
+/// this direct path serves callers without one.
+fn semantic_edit(
+    workspace_root: &Path,
+    args: &HashMap<String, serde_json::Value>,
+    language_service: std::sync::Arc<LanguageService>,
+    history: Option<&crate::history::HistoryService>,
+) -> ToolResult {
+    let Some(path) = get_str_arg(args, &["path"]) else {
+        return ToolResult::err("missing required arg: path");
+    };
+
+    let abs = match validate_path_under_workspace(workspace_root, Path::new(&path)) {
+        Ok(p) => p,
+        Err(e) => return ToolResult::err(e),
+    };
+    let abs_str = abs.to_string_lossy().to_string();
+
+    let patch = match build_semantic_patch(args, &abs_str) {
+        Ok(p) => p,
+        Err(e) => return ToolResult::err(e),
+    };
+
+    // Re-index so symbol ranges reflect what's on disk right now
+    if let Err(e) = language_service.index_file(&abs_str) {
+        return ToolResult::err(format!("Failed to parse {}: {}", path, e));
+    }
+
+    let applier = crate::semantic_patch::PatchApplier::new(language_service.clone());
+    let result = match applier.apply(&patch) {
+        Ok(r) => r,
+        Err(e) => return ToolResult::err(e.to_string()),
+    };
+
+    if let Some(history) = history {
+        if let Err(e) = history.create_snapshot(&abs, None) {
+            eprintln!("[HISTORY] Failed to create snapshot for {}: {}", path, e);
+        }
+    }
+
+    if let Err(e) = crate::utils::atomic_write(&abs, result.new_content.as_bytes()) {
+        return ToolResult::err(format!("Failed to write file: {}", e));
+    }
+    // Keep symbols current for the next semantic edit of this file
+    if let Err(e) = language_service.index_file(&abs_str) {
+        eprintln!("[SEMANTIC EDIT] Failed to re-index {}: {}", path, e);
+    }
+    ToolResult::ok(format!("Applied semantic edit to {}\n{}", path, result.diff))
+}
+
+/// Number of unchanged lines shown around each hunk in patch previews
+const PREVIEW_CONTEXT_LINES: usize = 3;
+
+/// Dry-run a search/replace patch: report how it would match and the resulting diff,
+/// without writing anything to disk.
+fn preview_patch(workspace_root: &Path, args: &HashMap<String, serde_json::Value>) -> ToolResult {
+    let Some(path) = get_str_arg(args, &["path"]) else {
+        return ToolResult::err("missing required arg: path");
+    };
+    let Some(old_text) = get_str_arg(args, &["old_text"]) else {
+        return ToolResult::err("missing required arg: old_text");
+    };
+    let Some(new_text) = get_str_arg(args, &["new_text"]) else {
+        return ToolResult::err("missing required arg: new_text");
+    };
+
+    let abs = match validate_path_under_workspace(workspace_root, Path::new(&path)) {
+        Ok(p) => p,
+        Err(e) => return ToolResult::err(e),
+    };
+
+    let content = match fs::read_to_string(&abs) {
+        Ok(s) => s,
+        Err(e) => return ToolResult::err(e.to_string()),
+    };
+
+    let attempt = try_apply_patch(&content, &old_text, &new_text);
+    let (matched, preview_unified_diff, error) = match &attempt.result {
+        Ok(new_content) => (true, format_unified_diff(&path, &content, new_content), None),
+        Err(e) => (false, String::new(), Some(e.clone())),
+    };
+
+    let result = serde_json::json!({
+        "matched": matched,
+        "strategy": attempt.strategy.as_str(),
+        "ambiguous_count": attempt.ambiguous_count,
+        "preview_unified_diff": preview_unified_diff,
+        "error": error,
+    });
+
+    match serde_json::to_string_pretty(&result) {
+        Ok(json) => ToolResult::ok(json),
+        Err(e) => ToolResult::err(format!("Failed to serialize preview: {}", e)),
+    }
+}
+
+fn format_unified_diff(path: &str, old_content: &str, new_content: &str) -> String {
+    let hunks =
+        crate::semantic_patch::generate_diff(old_content, new_content, PREVIEW_CONTEXT_LINES);
+    if hunks.is_empty() {
+        return String::new();
+    }
+
+    let mut out = format!("--- a/{}\n+++ b/{}\n", path, path);
+    for hunk in &hunks {
+        out.push_str(&hunk.to_string());
+    }
+    out
+}
+
+/// Represents a single patch hunk for multi-patch operations
+#[derive(Debug, Clone)]
+#[allow(dead_code)]
+struct PatchHunk {
+    old_text: String,
+    new_text: String,
+    start_line: Option<usize>,
+    end_line: Option<usize>,
+}
+
+/// Result of applying multiple patches atomically
+#[derive(Debug)]
+#[allow(dead_code)]
+struct MultiPatchResult {
+    success: bool,
     applied_count: usize,
     total_count: usize,
     error: Option<String>,
@@ -960,8 +2430,8 @@ fn apply_multi_patch_to_string(content: &str, patches: &[PatchHunk]) -> Result<S
 }
 
 fn apply_edit_tool(workspace_root: &Path, args: &HashMap<String, serde_json::Value>) -> ToolResult {
-    let Some(path) = get_str_arg(args, &["path", "file_path", "filepath", "filename"]) else {
-        return ToolResult::err("missing required arg: path (or file_path)");
+    let Some(path) = get_str_arg(args, &["path"]) else {
+        return ToolResult::err("missing required arg: path");
     };
 
     let abs = match validate_path_under_workspace(workspace_root, Path::new(&path)) {
@@ -1016,7 +2486,7 @@ fn apply_edit_tool(workspace_root: &Path, args: &HashMap<String, serde_json::Val
 
             // Apply multi-patch atomically
             match apply_multi_patch_to_string(&content, &patches) {
-                Ok(new_content) => match fs::write(&abs, new_content.as_bytes()) {
+                Ok(new_content) => match crate::utils::atomic_write(&abs, new_content.as_bytes()) {
                     Ok(()) => {
                         let count = patches.len();
                         ToolResult::ok(format!(
@@ -1035,17 +2505,15 @@ fn apply_edit_tool(workspace_root: &Path, args: &HashMap<String, serde_json::Val
         }
     } else {
         // Legacy single-patch format
-        let Some(old_text) = get_str_arg(args, &["old_text", "old_content", "old", "from"]) else {
-            return ToolResult::err(
-                "missing required arg: old_text (or old_content/old/from) or patches array",
-            );
+        let Some(old_text) = get_str_arg(args, &["old_text"]) else {
+            return ToolResult::err("missing required arg: old_text or patches array");
         };
-        let Some(new_text) = get_str_arg(args, &["new_text", "new_content", "new", "to"]) else {
-            return ToolResult::err("missing required arg: new_text (or new_content/new/to)");
+        let Some(new_text) = get_str_arg(args, &["new_text"]) else {
+            return ToolResult::err("missing required arg: new_text");
         };
 
         match apply_patch_to_string(&content, &old_text, &new_text) {
-            Ok(new_content) => match fs::write(&abs, new_content.as_bytes()) {
+            Ok(new_content) => match crate::utils::atomic_write(&abs, new_content.as_bytes()) {
                 Ok(()) => ToolResult::ok(format!("Applied edit to {}", path)),
                 Err(e) => ToolResult::err(e.to_string()),
             },
@@ -1110,7 +2578,7 @@ fn get_workspace_structure(
     workspace_root: &Path,
     args: &HashMap<String, serde_json::Value>,
 ) -> ToolResult {
-    let path = get_str_arg(args, &["path", "dir", "directory"]).unwrap_or_else(|| ".".to_string());
+    let path = get_str_arg(args, &["path"]).unwrap_or_else(|| ".".to_string());
     let depth = args.get("depth").and_then(|v| v.as_u64()).unwrap_or(DEFAULT_LIST_DEPTH as u64) as usize;
     let limit = args.get("limit").and_then(|v| v.as_u64()).unwrap_or(DEFAULT_LIST_LIMIT as u64) as usize;
     let limit = limit.min(MAX_LIST_LIMIT); // Cap at maximum
@@ -1262,9 +2730,26 @@ fn find_files(workspace_root: &Path, args: &HashMap<String, serde_json::Value>)
     ToolResult::ok(results.join("\n"))
 }
 
-fn find_files_glob(workspace_root: &Path, args: &HashMap<String, serde_json::Value>) -> ToolResult {
-    let Some(pattern) = get_str_arg(args, &["pattern", "glob"]) else {
-        return ToolResult::err("missing required arg: pattern");
+/// Files matching the `pattern` (and optional base `path`/`case_sensitive`) args, up to `limit`.
+/// The flag is true when matching stopped at the limit.
+fn glob_workspace_files(
+    workspace_root: &Path,
+    args: &HashMap<String, serde_json::Value>,
+    limit: usize,
+) -> Result<(Vec<PathBuf>, bool), String> {
+    glob_workspace_files_filtered(workspace_root, args, limit, None)
+}
+
+/// `glob_workspace_files` leaving out files `gitignore` ignores, so they don't count
+/// towards `limit`
+fn glob_workspace_files_filtered(
+    workspace_root: &Path,
+    args: &HashMap<String, serde_json::Value>,
+    limit: usize,
+    gitignore: Option<&GitignoreFilter>,
+) -> Result<(Vec<PathBuf>, bool), String> {
+    let Some(pattern) = get_str_arg(args, &["pattern"]) else {
+        return Err("missing required arg: pattern".to_string());
     };
 
     // Optional base path within workspace
@@ -1292,50 +2777,270 @@ fn find_files_glob(workspace_root: &Path, args: &HashMap<String, serde_json::Val
         .unwrap_or(false);
 
     let mut matches = Vec::new();
-    let mut count = 0;
-    const MAX_RESULTS: usize = 200;
 
-    let options = glob::MatchOptions {
-        case_sensitive: case_sensitive,
-        require_literal_separator: false,
-        require_literal_leading_dot: false,
-    };
+    let options = glob::MatchOptions {
+        case_sensitive: case_sensitive,
+        require_literal_separator: false,
+        require_literal_leading_dot: false,
+    };
+
+    let paths = glob::glob_with(&pattern_str, options)
+        .map_err(|e| format!("Invalid glob pattern: {}", e))?;
+    for entry in paths {
+        match entry {
+            Ok(path) => {
+                let ignored = gitignore.is_some_and(|filter| filter.should_ignore(&path));
+                if path.is_file() && !ignored {
+                    matches.push(path);
+                }
+            }
+            Err(e) => eprintln!("Glob error: {:?}", e),
+        }
+        if matches.len() >= limit {
+            return Ok((matches, true));
+        }
+    }
+
+    Ok((matches, false))
+}
+
+fn find_files_glob(workspace_root: &Path, args: &HashMap<String, serde_json::Value>) -> ToolResult {
+    let (matches, truncated) = match glob_workspace_files(workspace_root, args, MAX_GLOB_RESULTS) {
+        Ok(found) => found,
+        Err(e) => return ToolResult::err(e),
+    };
+
+    if matches.is_empty() {
+        return ToolResult::ok("No matching files found.");
+    }
+
+    let mut output = matches
+        .iter()
+        .map(|path| {
+            path.strip_prefix(workspace_root)
+                .unwrap_or(path)
+                .to_string_lossy()
+                .to_string()
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+    if truncated {
+        output.push_str(&format!(
+            "\n... (truncated after {} results)",
+            MAX_GLOB_RESULTS
+        ));
+    }
+
+    ToolResult::ok(output)
+}
+
+/// Read every file matching a glob in one call, with `read_file`-style headers.
+/// Honors the gitignore filter and workspace boundary, skips binary and oversized files,
+/// and reports files left out because of the limits.
+fn read_files_glob(workspace_root: &Path, args: &HashMap<String, serde_json::Value>) -> ToolResult {
+    let max_files = args
+        .get("max_files")
+        .and_then(|v| v.as_u64())
+        .map(|n| n as usize)
+        .unwrap_or(DEFAULT_GLOB_READ_FILES)
+        .clamp(1, MAX_GLOB_READ_FILES);
+    let max_bytes = args
+        .get("max_bytes_per_file")
+        .and_then(|v| v.as_u64())
+        .unwrap_or(DEFAULT_GLOB_READ_FILE_BYTES);
+
+    let gitignore_filter = create_gitignore_filter(workspace_root);
+    let (matches, truncated) = match glob_workspace_files_filtered(
+        workspace_root,
+        args,
+        MAX_GLOB_RESULTS,
+        gitignore_filter.as_ref(),
+    ) {
+        Ok(found) => found,
+        Err(e) => return ToolResult::err(e),
+    };
+
+    let mut sections = Vec::new();
+    let mut skipped = SearchSkipStats::default();
+    let mut over_limit = 0;
+
+    for path in matches {
+        // Glob patterns can climb out of the workspace (`../*`) or through symlinks
+        let Ok(abs) = validate_path_under_workspace(workspace_root, &path) else {
+            continue;
+        };
+        if sections.len() >= max_files {
+            over_limit += 1;
+            continue;
+        }
+        if let Some(content) = read_searchable_file(&abs, max_bytes, &mut skipped) {
+            sections.push(if content.is_empty() {
+                format!(
+                    "=== File: {} (empty) ===\n// This file exists but contains no content.",
+                    abs.to_string_lossy()
+                )
+            } else {
+                format!("=== File: {} ===\n{}", abs.to_string_lossy(), content)
+            });
+        }
+    }
+
+    let mut notes = Vec::new();
+    if over_limit > 0 {
+        notes.push(format!(
+            "({} more file{} elided: max_files is {})",
+            over_limit,
+            if over_limit == 1 { "" } else { "s" },
+            max_files
+        ));
+    }
+    if let Some(summary) = skipped.summary() {
+        notes.push(summary);
+    }
+    if truncated {
+        notes.push(format!("(glob stopped after {} matches)", MAX_GLOB_RESULTS));
+    }
+
+    if sections.is_empty() {
+        notes.insert(0, "No readable files matched.".to_string());
+        return ToolResult::ok(notes.join("\n"));
+    }
+
+    let mut output = sections.join("\n\n");
+    for note in notes {
+        output.push_str("\n\n");
+        output.push_str(&note);
+    }
+    ToolResult::ok(output)
+}
+
+/// One file's share of a `replace_in_files` call
+#[derive(Debug, Clone, PartialEq)]
+pub struct FileReplacement {
+    /// Workspace-relative path
+    pub path: String,
+    pub old_content: String,
+    pub new_content: String,
+    pub matches: usize,
+}
+
+/// Compute the edits a `replace_in_files` call would make without writing anything.
+/// `replacement` may use `$1`/`${name}` capture references. Honors the gitignore filter and
+/// workspace boundary, and skips binary and oversized files like `grep_search`.
+pub fn plan_replace_in_files(
+    workspace_root: &Path,
+    args: &HashMap<String, serde_json::Value>,
+) -> Result<Vec<FileReplacement>, String> {
+    let pattern = get_str_arg(args, &["pattern", "regex"])
+        .ok_or_else(|| "missing required arg: pattern".to_string())?;
+    let replacement = get_str_arg(args, &["replacement", "replace"])
+        .ok_or_else(|| "missing required arg: replacement".to_string())?;
+    let re = Regex::new(&pattern).map_err(|e| format!("invalid regex: {e}"))?;
+
+    let mut glob_args = HashMap::new();
+    let file_glob = get_str_arg(args, &["file_glob", "glob"]).unwrap_or_else(|| "**/*".into());
+    glob_args.insert("pattern".to_string(), serde_json::Value::String(file_glob));
+    if let Some(path) = args.get("path") {
+        glob_args.insert("path".to_string(), path.clone());
+    }
+    let (files, truncated) = glob_workspace_files(workspace_root, &glob_args, MAX_REPLACE_FILES)?;
+    if truncated {
+        return Err(format!(
+            "file_glob matches more than {} files; narrow it before replacing",
+            MAX_REPLACE_FILES
+        ));
+    }
+
+    let root = fs::canonicalize(workspace_root).unwrap_or_else(|_| workspace_root.to_path_buf());
+    let max_file_bytes = project_settings::load_project_settings_or_default(workspace_root)
+        .context
+        .max_grep_file_bytes;
+    let gitignore_filter = create_gitignore_filter(workspace_root);
+    let mut skipped = SearchSkipStats::default();
+    let mut replacements = Vec::new();
 
-    match glob::glob_with(&pattern_str, options) {
-        Ok(paths) => {
-            for entry in paths {
-                match entry {
-                    Ok(path) => {
-                        if path.is_file() {
-                            let rel = path
-                                .strip_prefix(workspace_root)
-                                .unwrap_or(&path)
-                                .to_string_lossy()
-                                .to_string();
-                            matches.push(rel);
-                            count += 1;
-                        }
-                    }
-                    Err(e) => eprintln!("Glob error: {:?}", e),
-                }
-                if count >= MAX_RESULTS {
-                    break;
-                }
+    for path in files {
+        let Ok(abs) = validate_path_under_workspace(workspace_root, &path) else {
+            continue;
+        };
+        if let Some(ref filter) = gitignore_filter {
+            if filter.should_ignore(&abs) {
+                continue;
             }
         }
-        Err(e) => return ToolResult::err(format!("Invalid glob pattern: {}", e)),
+        let Some(old_content) = read_searchable_file(&abs, max_file_bytes, &mut skipped) else {
+            continue;
+        };
+        let matches = re.find_iter(&old_content).count();
+        if matches == 0 {
+            continue;
+        }
+        let new_content = re
+            .replace_all(&old_content, replacement.as_str())
+            .into_owned();
+        if new_content == old_content {
+            continue;
+        }
+        replacements.push(FileReplacement {
+            path: abs
+                .strip_prefix(&root)
+                .unwrap_or(&abs)
+                .to_string_lossy()
+                .to_string(),
+            old_content,
+            new_content,
+            matches,
+        });
     }
 
-    if matches.is_empty() {
-        return ToolResult::ok("No matching files found.");
+    if let Some(summary) = skipped.summary() {
+        eprintln!("[REPLACE] {}", summary);
     }
+    Ok(replacements)
+}
 
-    let mut output = matches.join("\n");
-    if count >= MAX_RESULTS {
-        output.push_str(&format!("\n... (truncated after {} results)", MAX_RESULTS));
+/// `replace_in_files` as a tool call: only dry runs execute here. Real replacements are
+/// applied by the AI workflow so each file goes through the change pipeline.
+fn replace_in_files_preview(
+    workspace_root: &Path,
+    args: &HashMap<String, serde_json::Value>,
+) -> ToolResult {
+    let dry_run = args
+        .get("dry_run")
+        .and_then(|v| v.as_bool())
+        .unwrap_or(false);
+    if !dry_run {
+        return ToolResult::err(
+            "replace_in_files can only preview here (dry_run: true); \
+             replacements are applied through the editor's change workflow",
+        );
     }
 
-    ToolResult::ok(output)
+    let replacements = match plan_replace_in_files(workspace_root, args) {
+        Ok(r) => r,
+        Err(e) => return ToolResult::err(e),
+    };
+    ToolResult::ok(summarize_replacements(&replacements, true))
+}
+
+/// "N matches in M files" followed by one line per file
+pub fn summarize_replacements(replacements: &[FileReplacement], dry_run: bool) -> String {
+    if replacements.is_empty() {
+        return "No matches found.".to_string();
+    }
+    let total: usize = replacements.iter().map(|r| r.matches).sum();
+    let mut out = format!(
+        "{} {} match{} in {} file{}:",
+        if dry_run { "Would replace" } else { "Replaced" },
+        total,
+        if total == 1 { "" } else { "es" },
+        replacements.len(),
+        if replacements.len() == 1 { "" } else { "s" }
+    );
+    for r in replacements {
+        out.push_str(&format!("\n  {} ({})", r.path, r.matches));
+    }
+    out
 }
 
 fn create_directory(
@@ -1353,12 +3058,24 @@ fn create_directory(
     }
 }
 
-fn delete_file(workspace_root: &Path, args: &HashMap<String, serde_json::Value>) -> ToolResult {
+fn delete_file<R: tauri::Runtime>(
+    workspace_root: &Path,
+    args: &HashMap<String, serde_json::Value>,
+    app_handle: Option<&tauri::AppHandle<R>>,
+) -> ToolResult {
     let Some(path_str) = get_str_arg(args, &["path"]) else {
         return ToolResult::err("missing required arg: path");
     };
 
-    let path = workspace_root.join(path_str);
+    let path = match resolve_path_in_workspace(workspace_root, Path::new(&path_str)) {
+        Ok(p) => p,
+        Err(e) => {
+            if is_outside_workspace_error(&e) {
+                report_blocked_write(app_handle, "delete_file", &path_str);
+            }
+            return ToolResult::err(e);
+        }
+    };
     let recursive = args
         .get("recursive")
         .and_then(|v| v.as_bool())
@@ -1380,7 +3097,11 @@ fn delete_file(workspace_root: &Path, args: &HashMap<String, serde_json::Value>)
     }
 }
 
-fn move_file(workspace_root: &Path, args: &HashMap<String, serde_json::Value>) -> ToolResult {
+fn move_file<R: tauri::Runtime>(
+    workspace_root: &Path,
+    args: &HashMap<String, serde_json::Value>,
+    app_handle: Option<&tauri::AppHandle<R>>,
+) -> ToolResult {
     let Some(source_str) = get_str_arg(args, &["source"]) else {
         return ToolResult::err("missing required arg: source");
     };
@@ -1388,11 +3109,38 @@ fn move_file(workspace_root: &Path, args: &HashMap<String, serde_json::Value>) -
         return ToolResult::err("missing required arg: destination");
     };
 
-    let source = workspace_root.join(source_str);
-    let dest = workspace_root.join(dest_str);
-
-    match fs::rename(&source, &dest) {
-        Ok(_) => ToolResult::ok(format!("Moved {} to {}", source.display(), dest.display())),
+    let mut resolved = Vec::with_capacity(2);
+    for path_str in [&source_str, &dest_str] {
+        match resolve_path_in_workspace(workspace_root, Path::new(path_str)) {
+            Ok(p) => resolved.push(p),
+            Err(e) => {
+                if is_outside_workspace_error(&e) {
+                    report_blocked_write(app_handle, "move_file", path_str);
+                }
+                return ToolResult::err(e);
+            }
+        }
+    }
+    let (source, dest) = (&resolved[0], &resolved[1]);
+
+    match fs::rename(source, dest) {
+        Ok(_) => {
+            if let Some(app) = app_handle {
+                use tauri::{Emitter, Manager};
+                if let Some(state) = app.try_state::<crate::app_state::AppState>() {
+                    if state.rename_open_paths(source, dest) {
+                        let _ = app.emit(
+                            crate::events::event_names::FILE_MOVED,
+                            crate::events::FileMovedPayload {
+                                old_path: source.to_string_lossy().into_owned(),
+                                new_path: dest.to_string_lossy().into_owned(),
+                            },
+                        );
+                    }
+                }
+            }
+            ToolResult::ok(format!("Moved {} to {}", source.display(), dest.display()))
+        }
         Err(e) => ToolResult::err(format!("Failed to move file: {}", e)),
     }
 }
@@ -1448,24 +3196,336 @@ fn get_file_info(workspace_root: &Path, args: &HashMap<String, serde_json::Value
         return ToolResult::err("missing required arg: path");
     };
 
-    let path = workspace_root.join(path_str);
-    match fs::metadata(&path) {
-        Ok(metadata) => {
-            let info = serde_json::json!({
-                "path": path.display().to_string(),
-                "size": metadata.len(),
-                "is_directory": metadata.is_dir(),
-                "is_file": metadata.is_file(),
-                "modified": metadata.modified()
-                    .ok()
-                    .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
-                    .map(|d| d.as_secs()),
-                "readonly": metadata.permissions().readonly(),
-            });
-            ToolResult::ok(serde_json::to_string_pretty(&info).unwrap_or_default())
+    let path = match validate_path_under_workspace(workspace_root, Path::new(&path_str)) {
+        Ok(p) => p,
+        Err(e) => return ToolResult::err(e),
+    };
+    let metadata = match fs::metadata(&path) {
+        Ok(metadata) => metadata,
+        Err(e) => return ToolResult::err(format!("Failed to get file info: {}", e)),
+    };
+
+    let (is_binary, line_count) = if metadata.is_file() {
+        match scan_file_lines(&path) {
+            Ok(scan) => scan,
+            Err(e) => return ToolResult::err(format!("Failed to read file: {}", e)),
+        }
+    } else {
+        (false, None)
+    };
+    let language = metadata
+        .is_file()
+        .then(|| crate::tree_sitter::Language::from_path(&path_str))
+        .flatten()
+        .map(|l| l.display_name());
+    // Without git, the gitignore rules still tell us whether the file is ignored
+    let git_status = file_git_status(workspace_root, &path).or_else(|| {
+        GitignoreFilter::new(workspace_root)
+            .should_ignore(&path)
+            .then_some("ignored")
+    });
+
+    let info = serde_json::json!({
+        "path": path.display().to_string(),
+        "size_bytes": metadata.len(),
+        "line_count": line_count,
+        "modified_time": metadata.modified()
+            .ok()
+            .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
+            .map(|d| d.as_secs()),
+        "is_binary": is_binary,
+        "language": language,
+        "git_status": git_status,
+        "is_directory": metadata.is_dir(),
+        "readonly": metadata.permissions().readonly(),
+    });
+    ToolResult::ok(serde_json::to_string_pretty(&info).unwrap_or_default())
+}
+
+/// Whether a file looks binary (NUL in the first 8KB) and, if not, its line count.
+/// Streams the file so large files aren't loaded into memory.
+fn scan_file_lines(path: &Path) -> std::io::Result<(bool, Option<usize>)> {
+    use std::io::Read;
+
+    let mut file = fs::File::open(path)?;
+    let mut buf = vec![0u8; BINARY_SNIFF_BYTES];
+    let mut lines = 0;
+    let mut last = None;
+    let mut first_chunk = true;
+    loop {
+        let n = file.read(&mut buf)?;
+        if n == 0 {
+            break;
+        }
+        let chunk = &buf[..n];
+        if first_chunk && chunk.contains(&0) {
+            return Ok((true, None));
+        }
+        first_chunk = false;
+        lines += chunk.iter().filter(|&&b| b == b'\n').count();
+        last = chunk.last().copied();
+    }
+    // A final line without a trailing newline still counts
+    if last.is_some_and(|b| b != b'\n') {
+        lines += 1;
+    }
+    Ok((false, Some(lines)))
+}
+
+/// Git state of one file: clean, modified, untracked, staged or ignored.
+/// A directory gets the strongest state of everything under it.
+/// None when git is unavailable or the workspace isn't a repository.
+fn file_git_status(workspace_root: &Path, path: &Path) -> Option<&'static str> {
+    let git = |args: &[&str]| {
+        std::process::Command::new("git")
+            .arg("-C")
+            .arg(workspace_root)
+            .args(args)
+            .arg(path)
+            .output()
+            .ok()
+    };
+
+    if !path.is_dir() {
+        let output = git(&["status", "--porcelain=v1", "--ignored", "--"])?;
+        if !output.status.success() {
+            return None;
+        }
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        return Some(classify_git_status(&stdout));
+    }
+
+    // Ignored files inside a directory don't make the directory itself ignored
+    let output = git(&["status", "--porcelain=v1", "--"])?;
+    if !output.status.success() {
+        return None;
+    }
+    let state = classify_dir_git_status(&String::from_utf8_lossy(&output.stdout));
+    if state == "clean" && git(&["check-ignore", "-q", "--"]).is_some_and(|o| o.status.success()) {
+        return Some("ignored");
+    }
+    Some(state)
+}
+
+/// Combine the `git status --porcelain=v1` lines of everything under a directory:
+/// modified beats staged, which beats untracked
+fn classify_dir_git_status(porcelain: &str) -> &'static str {
+    let rank = |state: &str| match state {
+        "modified" => 3,
+        "staged" => 2,
+        "untracked" => 1,
+        _ => 0,
+    };
+    porcelain
+        .lines()
+        .map(classify_git_status)
+        .filter(|state| *state != "ignored")
+        .max_by_key(|state| rank(state))
+        .unwrap_or("clean")
+}
+
+/// Map a file's `git status --porcelain=v1` line to a single state.
+/// Unstaged changes win over staged ones since they're what's on disk.
+pub(crate) fn classify_git_status(porcelain: &str) -> &'static str {
+    let Some(line) = porcelain.lines().next() else {
+        return "clean";
+    };
+    let mut codes = line.chars();
+    let index = codes.next().unwrap_or(' ');
+    let worktree = codes.next().unwrap_or(' ');
+    match (index, worktree) {
+        ('!', '!') => "ignored",
+        ('?', '?') => "untracked",
+        (_, ' ') => "staged",
+        _ => "modified",
+    }
+}
+
+/// Line-count product above which the quadratic LCS diff hands over to diffy
+const MAX_LCS_DIFF_CELLS: usize = 4_000_000;
+
+/// What `diff_files` compares.
+pub(crate) enum DiffTarget<'a> {
+    /// Two files in the workspace
+    Files { left: &'a str, right: &'a str },
+    /// A file's working copy against its content at git HEAD
+    Head { path: &'a str },
+    /// A renamed file's working copy against its old path's content at git HEAD
+    Renamed { from: &'a str, path: &'a str },
+}
+
+fn diff_files(workspace_root: &Path, args: &HashMap<String, serde_json::Value>) -> ToolResult {
+    let path = get_str_arg(args, &["path"]);
+    let left = get_str_arg(args, &["left_path"]);
+    let right = get_str_arg(args, &["right_path"]);
+
+    let target = match get_str_arg(args, &["against"]) {
+        Some(against) if !against.eq_ignore_ascii_case("head") => {
+            return ToolResult::err(format!(
+                "unsupported 'against' value '{}' (only \"head\" is supported)",
+                against
+            ));
+        }
+        Some(_) => match &path {
+            Some(path) => DiffTarget::Head { path },
+            None => return ToolResult::err("missing required arg: path"),
+        },
+        None => match (&left, &right) {
+            (Some(left), Some(right)) => DiffTarget::Files { left, right },
+            _ => return ToolResult::err(
+                "missing required args: left_path and right_path (or path with against: \"head\")",
+            ),
+        },
+    };
+
+    match diff_in_workspace(workspace_root, target) {
+        Ok(diff) if diff.is_empty() => ToolResult::ok("No differences."),
+        Ok(diff) => ToolResult::ok(diff),
+        Err(e) => ToolResult::err(e),
+    }
+}
+
+/// Unified diff for a `DiffTarget`, empty when both sides match.
+/// Paths must stay inside the workspace; HEAD content is read with `git show`.
+pub(crate) fn diff_in_workspace(
+    workspace_root: &Path,
+    target: DiffTarget,
+) -> Result<String, String> {
+    let ws = fs::canonicalize(workspace_root).map_err(|e| e.to_string())?;
+    let label = |path: &Path| workspace_label(&ws, path);
+
+    match target {
+        DiffTarget::Files { left, right } => {
+            let left = validate_path_under_workspace(workspace_root, Path::new(left))?;
+            let right = validate_path_under_workspace(workspace_root, Path::new(right))?;
+            let old = read_diff_side(&left)?;
+            let new = read_diff_side(&right)?;
+            Ok(unified_diff(
+                &format!("a/{}", label(&left)),
+                &format!("b/{}", label(&right)),
+                &old,
+                &new,
+            ))
+        }
+        DiffTarget::Head { path } => head_diff(workspace_root, &ws, path, path),
+        DiffTarget::Renamed { from, path } => head_diff(workspace_root, &ws, from, path),
+    }
+}
+
+/// Diff of `path`'s working copy against `from`'s content at HEAD (the same file
+/// unless it was renamed)
+fn head_diff(workspace_root: &Path, ws: &Path, from: &str, path: &str) -> Result<String, String> {
+    let label = |path: &Path| workspace_label(ws, path);
+
+    // A file deleted from the working tree still has HEAD content to compare
+    let old_abs = resolve_path_in_workspace(workspace_root, Path::new(from))?;
+    let abs = resolve_path_in_workspace(workspace_root, Path::new(path))?;
+    let head = read_head_content(ws, &old_abs)?;
+    let working = if abs.exists() {
+        Some(read_diff_side(&abs)?)
+    } else {
+        None
+    };
+    if head.is_none() && working.is_none() {
+        return Err(format!("path does not exist: {}", abs.display()));
+    }
+
+    let old_label = head.as_ref().map_or("/dev/null".to_string(), |_| {
+        format!("a/{}", label(&old_abs))
+    });
+    let new_label = working
+        .as_ref()
+        .map_or("/dev/null".to_string(), |_| format!("b/{}", label(&abs)));
+    Ok(unified_diff(
+        &old_label,
+        &new_label,
+        head.as_deref().unwrap_or(""),
+        working.as_deref().unwrap_or(""),
+    ))
+}
+
+fn workspace_label(ws: &Path, path: &Path) -> String {
+    path.strip_prefix(ws)
+        .unwrap_or(path)
+        .to_string_lossy()
+        .replace('\\', "/")
+}
+
+fn read_diff_side(path: &Path) -> Result<String, String> {
+    if path.is_dir() {
+        return Err(format!("cannot diff a directory: {}", path.display()));
+    }
+    if let Ok((true, _)) = scan_file_lines(path) {
+        return Err(format!("cannot diff binary file: {}", path.display()));
+    }
+    crate::encoding::read_text(path)
+        .map(|(content, _)| content)
+        .map_err(|e| format!("Failed to read {}: {}", path.display(), e))
+}
+
+/// Content of a file at git HEAD; None when the file isn't tracked there (e.g. newly added).
+fn read_head_content(workspace_root: &Path, path: &Path) -> Result<Option<String>, String> {
+    let dir = path.parent().unwrap_or(workspace_root);
+    // The parent may be gone along with a deleted file; `git -C` needs an existing directory
+    let dir = dir
+        .ancestors()
+        .find(|d| d.is_dir())
+        .unwrap_or(workspace_root);
+    let relative = path.strip_prefix(dir).unwrap_or(path);
+    let spec = format!("HEAD:./{}", relative.to_string_lossy().replace('\\', "/"));
+
+    let output = std::process::Command::new("git")
+        .arg("-C")
+        .arg(dir)
+        .args(["show", &spec])
+        .output()
+        .map_err(|e| format!("failed to run git: {}", e))?;
+    if output.status.success() {
+        if output.stdout.contains(&0) {
+            return Err(format!("cannot diff binary file: {}", path.display()));
         }
-        Err(e) => ToolResult::err(format!("Failed to get file info: {}", e)),
+        return crate::encoding::decode(&output.stdout)
+            .map(|(content, _)| Some(content))
+            .map_err(|e| format!("Failed to decode HEAD content: {}", e));
+    }
+
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    if stderr.contains("exists on disk, but not in") || stderr.contains("does not exist in") {
+        return Ok(None);
+    }
+    Err(format!("git show failed: {}", stderr.trim()))
+}
+
+/// Unified diff of two texts, or an empty string when they match.
+/// Like `format_unified_diff`, but with explicit labels and a fallback for large files.
+pub(crate) fn unified_diff(old_label: &str, new_label: &str, old: &str, new: &str) -> String {
+    if old == new {
+        return String::new();
+    }
+
+    let cells = old.lines().count().saturating_mul(new.lines().count());
+    let hunks = if cells <= MAX_LCS_DIFF_CELLS {
+        crate::semantic_patch::generate_diff(old, new, PREVIEW_CONTEXT_LINES)
+            .iter()
+            .map(|hunk| hunk.to_string())
+            .collect::<String>()
+    } else {
+        // diffy's Myers diff stays linear in memory; drop its own file header
+        diffy::create_patch(old, new)
+            .to_string()
+            .lines()
+            .skip(2)
+            .map(|line| format!("{}\n", line))
+            .collect()
+    };
+    if hunks.is_empty() {
+        // Only line endings or a trailing newline differ
+        return format!(
+            "--- {}\n+++ {}\n(files differ only in line endings or trailing newline)\n",
+            old_label, new_label
+        );
     }
+    format!("--- {}\n+++ {}\n{}", old_label, new_label, hunks)
 }
 
 fn open_file(args: &HashMap<String, serde_json::Value>) -> ToolResult {
@@ -1551,3 +3611,630 @@ fn insert_at_cursor(args: &HashMap<String, serde_json::Value>) -> ToolResult {
 
     ToolResult::ok(serde_json::to_string(&result).unwrap_or_default())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_truncate_large_content_keeps_head_and_tail() {
+        let content = format!("{}{}{}", "a".repeat(10_000), "b".repeat(30_000), "c".repeat(10_000));
+        let truncated = truncate_large_content(&content, DEFAULT_MAX_TOOL_RESULT_BYTES);
+
+        assert!(truncated.starts_with(&"a".repeat(TRUNCATION_KEEP_BYTES)));
+        assert!(truncated.ends_with(&"c".repeat(TRUNCATION_KEEP_BYTES)));
+        let omitted = content.len() - 2 * TRUNCATION_KEEP_BYTES;
+        assert!(truncated.contains(&format!("\n...[truncated {} bytes]...\n", omitted)));
+    }
+
+    #[test]
+    fn test_truncate_large_content_is_utf8_safe() {
+        // 3-byte chars never line up with the 8KB cut points
+        let content = "€".repeat(20_000);
+        let truncated = truncate_large_content(&content, 1024);
+        assert!(truncated.contains("...[truncated"));
+        assert!(truncated.starts_with('€'));
+        assert!(truncated.ends_with('€'));
+    }
+
+    #[test]
+    fn test_errors_are_never_truncated() {
+        let result = ToolResult::err("x".repeat(100_000));
+        assert_eq!(result.to_tool_content_truncated(1024).len(), "tool_error: ".len() + 100_000);
+    }
+
+    #[test]
+    fn test_try_apply_patch_reports_strategy() {
+        let content = "fn main() {\n    let x = 1;\n}\n";
+
+        let exact = try_apply_patch(content, "let x = 1;", "let x = 2;");
+        assert_eq!(exact.strategy, PatchMatchStrategy::Exact);
+        assert_eq!(exact.ambiguous_count, 0);
+        assert_eq!(exact.result.unwrap(), "fn main() {\n    let x = 2;\n}\n");
+
+        let fuzzy = try_apply_patch(content, "let x = 1;\n  }", "let x = 3;\n}");
+        assert_eq!(fuzzy.strategy, PatchMatchStrategy::Fuzzy);
+        assert!(fuzzy.result.is_ok());
+
+        let missing = try_apply_patch(content, "let y = 1;", "let y = 2;");
+        assert_eq!(missing.strategy, PatchMatchStrategy::None);
+        assert!(missing.result.is_err());
+    }
+
+    #[test]
+    fn test_try_apply_patch_counts_ambiguous_fuzzy_matches() {
+        let content = "  a();\n  b();\n  a();\n";
+        let attempt = try_apply_patch(content, "\ta();", "c();");
+        assert_eq!(attempt.strategy, PatchMatchStrategy::None);
+        assert_eq!(attempt.ambiguous_count, 2);
+    }
+
+    #[test]
+    fn test_preview_patch_does_not_write() {
+        let temp = tempfile::tempdir().unwrap();
+        let root = temp.path();
+        fs::write(root.join("lib.rs"), "fn a() {}\n").unwrap();
+
+        let mut args = HashMap::new();
+        args.insert("path".to_string(), serde_json::json!("lib.rs"));
+        args.insert("old_text".to_string(), serde_json::json!("fn a() {}"));
+        args.insert("new_text".to_string(), serde_json::json!("fn b() {}"));
+
+        let result = preview_patch(root, &args);
+        assert!(result.success);
+        let json: serde_json::Value = serde_json::from_str(&result.content).unwrap();
+        assert_eq!(json["matched"], true);
+        assert_eq!(json["strategy"], "exact");
+        let diff = json["preview_unified_diff"].as_str().unwrap();
+        assert!(diff.contains("-fn a() {}"));
+        assert!(diff.contains("+fn b() {}"));
+
+        assert_eq!(fs::read_to_string(root.join("lib.rs")).unwrap(), "fn a() {}\n");
+    }
+
+    #[test]
+    fn test_grep_match_columns_are_char_based() {
+        let line = "let héllo = \"wörld\";";
+        let re = Regex::new("wörld").unwrap();
+        let m = re.find(line).unwrap();
+
+        let hit = GrepMatch::new(Path::new("a.rs"), 3, line, m.start(), m.end());
+        assert_eq!(hit.line, 3);
+        assert_eq!(hit.col_start, 13);
+        assert_eq!(hit.col_end, 18);
+        assert_eq!(hit.matched_text, "wörld");
+        let chars: String = line.chars().skip(hit.col_start).take(hit.col_end - hit.col_start).collect();
+        assert_eq!(chars, "wörld");
+    }
+
+    #[test]
+    fn test_grep_search_formats() {
+        let temp = tempfile::tempdir().unwrap();
+        let root = temp.path();
+        fs::write(root.join("a.txt"), "foo bar foo\n").unwrap();
+
+        let mut args = HashMap::new();
+        args.insert("pattern".to_string(), serde_json::json!("foo"));
+
+        let structured = grep_search(root, &args);
+        let result: serde_json::Value = serde_json::from_str(&structured.content).unwrap();
+        let hits = &result["matches"];
+        assert_eq!(hits.as_array().unwrap().len(), 2);
+        assert_eq!(hits[1]["col_start"], 8);
+        assert_eq!(hits[1]["col_end"], 11);
+        assert_eq!(result["skipped"]["binary"], 0);
+
+        fs::write(root.join("blob.bin"), [b'f', b'o', b'o', 0x00]).unwrap();
+        let with_binary = grep_search(root, &args);
+        let result: serde_json::Value = serde_json::from_str(&with_binary.content).unwrap();
+        assert_eq!(result["skipped"]["binary"], 1);
+        assert_eq!(result["skipped"]["too_large"], 0);
+
+        args.insert("format".to_string(), serde_json::json!("text"));
+        let text = grep_search(root, &args);
+        assert!(text.content.ends_with(":1:foo bar foo\n"));
+    }
+
+    #[test]
+    fn test_read_searchable_file_skips_large_and_binary() {
+        let temp = tempfile::tempdir().unwrap();
+        let root = temp.path();
+        fs::write(root.join("text.txt"), "hello\n").unwrap();
+        fs::write(root.join("blob.bin"), [0x89, b'P', b'N', b'G', 0x00, 0x01]).unwrap();
+        fs::write(root.join("big.log"), "x".repeat(64)).unwrap();
+
+        let mut stats = SearchSkipStats::default();
+        assert_eq!(
+            read_searchable_file(&root.join("text.txt"), 32, &mut stats).as_deref(),
+            Some("hello\n")
+        );
+        assert!(read_searchable_file(&root.join("blob.bin"), 32, &mut stats).is_none());
+        assert!(read_searchable_file(&root.join("big.log"), 32, &mut stats).is_none());
+
+        assert_eq!(stats.binary, 1);
+        assert_eq!(stats.too_large, 1);
+        assert_eq!(
+            stats.summary().as_deref(),
+            Some("(skipped 2 files: 1 too large, 1 binary)")
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_read_file_refuses_symlink_escaping_workspace() {
+        let temp = tempfile::tempdir().unwrap();
+        let root = temp.path();
+        fs::create_dir(root.join("src")).unwrap();
+        fs::write(root.join("src/lib.rs"), "fn main() {}\n").unwrap();
+        std::os::unix::fs::symlink("/etc", root.join("etc_link")).unwrap();
+        std::os::unix::fs::symlink(root.join("src"), root.join("src_link")).unwrap();
+        std::os::unix::fs::symlink("loop_b", root.join("loop_a")).unwrap();
+        std::os::unix::fs::symlink("loop_a", root.join("loop_b")).unwrap();
+
+        let escaped = execute_tool(root, "read_file", r#"{"path": "etc_link/passwd"}"#);
+        assert!(!escaped.success);
+        let error = escaped.error.unwrap();
+        assert!(error.contains("resolves outside workspace"), "{}", error);
+
+        let inside = execute_tool(root, "read_file", r#"{"path": "src_link/lib.rs"}"#);
+        assert!(inside.success, "{:?}", inside.error);
+
+        assert!(resolve_path_in_workspace(root, Path::new("etc_link/new.conf")).is_err());
+        assert!(resolve_path_in_workspace(root, Path::new("loop_a/file")).is_err());
+    }
+
+    #[test]
+    fn test_delete_and_move_refuse_paths_outside_workspace() {
+        let outer = tempfile::tempdir().unwrap();
+        let root = outer.path().join("ws");
+        fs::create_dir(&root).unwrap();
+        fs::write(outer.path().join("secret.txt"), "keep").unwrap();
+        fs::write(root.join("a.txt"), "a").unwrap();
+
+        let deleted = execute_tool(&root, "delete_file", r#"{"path": "../secret.txt"}"#);
+        assert!(!deleted.success);
+        assert!(is_outside_workspace_error(&deleted.error.unwrap()));
+        assert!(outer.path().join("secret.txt").exists());
+
+        let moved = execute_tool(
+            &root,
+            "move_file",
+            r#"{"source": "a.txt", "destination": "../a.txt"}"#,
+        );
+        assert!(!moved.success);
+        assert!(root.join("a.txt").exists());
+        assert!(!outer.path().join("a.txt").exists());
+    }
+
+    #[test]
+    fn test_write_file_replaces_atomically_and_keeps_mode() {
+        let temp = tempfile::tempdir().unwrap();
+        let root = temp.path();
+        let script = root.join("run.sh");
+        fs::write(&script, "echo old\n").unwrap();
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            fs::set_permissions(&script, fs::Permissions::from_mode(0o755)).unwrap();
+        }
+
+        let result = execute_tool(
+            root,
+            "write_file",
+            r#"{"path": "run.sh", "content": "echo new\n"}"#,
+        );
+        assert!(result.success, "{:?}", result.error);
+        assert_eq!(fs::read_to_string(&script).unwrap(), "echo new\n");
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = fs::metadata(&script).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o755);
+        }
+
+        let leftovers: Vec<_> = fs::read_dir(root)
+            .unwrap()
+            .filter_map(|e| e.ok())
+            .filter(|e| e.file_name().to_string_lossy().contains(".tmp-"))
+            .collect();
+        assert!(leftovers.is_empty());
+    }
+
+    #[test]
+    fn test_patches_keep_crlf_line_endings() {
+        let crlf = "fn a() {\r\n    1\r\n}\r\n\r\nfn b() {\r\n    2\r\n}\r\n";
+        assert_eq!(LineEnding::detect(crlf), LineEnding::CrLf);
+
+        let out = apply_patches_preserving_eol(
+            crlf,
+            &[
+                ("    1\n}", "    10\n}"),
+                ("fn b() {\n    2", "fn b() {\n    // two\n    2"),
+            ],
+        )
+        .unwrap();
+        assert_eq!(
+            out,
+            "fn a() {\r\n    10\r\n}\r\n\r\nfn b() {\r\n    // two\r\n    2\r\n}\r\n"
+        );
+        assert!(!out.replace("\r\n", "").contains('\n'));
+
+        let lf = "a\nb\n";
+        assert_eq!(LineEnding::detect(lf), LineEnding::Lf);
+        assert_eq!(
+            apply_patches_preserving_eol(lf, &[("b", "c")]).unwrap(),
+            "a\nc\n"
+        );
+    }
+
+    #[test]
+    fn test_read_files_glob_applies_limits() {
+        let temp = tempfile::tempdir().unwrap();
+        let root = temp.path();
+        fs::create_dir(root.join("src")).unwrap();
+        fs::write(root.join("src/a.rs"), "fn a() {}\n").unwrap();
+        fs::write(root.join("src/b.rs"), "fn b() {}\n").unwrap();
+        fs::write(root.join("src/c.rs"), "fn c() {}\n").unwrap();
+        fs::write(root.join("src/big.rs"), "x".repeat(256)).unwrap();
+        fs::write(root.join("src/blob.rs"), [0x00, 0x01, 0x02]).unwrap();
+
+        let result = execute_tool(
+            root,
+            "read_files_glob",
+            r#"{"pattern": "src/*.rs", "max_files": 2, "max_bytes_per_file": 64}"#,
+        );
+        assert!(result.success, "{:?}", result.error);
+        assert_eq!(result.content.matches("=== File: ").count(), 2);
+        assert!(result.content.contains("(1 more file elided: max_files is 2)"), "{}", result.content);
+
+        let all = execute_tool(
+            root,
+            "read_files_glob",
+            r#"{"pattern": "src/*.rs", "max_bytes_per_file": 64}"#,
+        );
+        assert_eq!(all.content.matches("=== File: ").count(), 3);
+        let skipped = "(skipped 2 files: 1 too large, 1 binary)";
+        assert!(all.content.contains(skipped), "{}", all.content);
+    }
+
+    #[test]
+    fn test_glob_limit_counts_only_files_not_ignored() {
+        let temp = tempfile::tempdir().unwrap();
+        let root = fs::canonicalize(temp.path()).unwrap();
+        fs::write(root.join(".gitignore"), "gen/\n").unwrap();
+        fs::create_dir(root.join("gen")).unwrap();
+        fs::create_dir(root.join("src")).unwrap();
+        fs::write(root.join("gen/a.rs"), "").unwrap();
+        fs::write(root.join("gen/b.rs"), "").unwrap();
+        fs::write(root.join("src/a.rs"), "").unwrap();
+
+        let mut args = HashMap::new();
+        args.insert("pattern".to_string(), serde_json::json!("**/*.rs"));
+        let filter = GitignoreFilter::new(&root);
+
+        let (files, truncated) =
+            glob_workspace_files_filtered(&root, &args, 2, Some(&filter)).unwrap();
+        assert_eq!(files, vec![root.join("src/a.rs")]);
+        assert!(!truncated);
+
+        let (files, truncated) = glob_workspace_files(&root, &args, 2).unwrap();
+        assert_eq!(files.len(), 2);
+        assert!(truncated);
+    }
+
+    #[test]
+    fn test_replace_in_files_plans_without_writing() {
+        let temp = tempfile::tempdir().unwrap();
+        let root = temp.path();
+        fs::create_dir(root.join("src")).unwrap();
+        fs::write(root.join("src/a.rs"), "fn old_name() {}\nold_name();\n").unwrap();
+        fs::write(root.join("src/b.rs"), "fn other() {}\n").unwrap();
+        fs::write(root.join("notes.md"), "old_name\n").unwrap();
+
+        let args: HashMap<String, serde_json::Value> = serde_json::from_str(
+            r#"{"pattern": "old_(\\w+)", "replacement": "new_$1", "file_glob": "src/**/*.rs"}"#,
+        )
+        .unwrap();
+        let plan = plan_replace_in_files(root, &args).unwrap();
+        assert_eq!(plan.len(), 1);
+        assert_eq!(plan[0].path, Path::new("src").join("a.rs").to_str().unwrap());
+        assert_eq!(plan[0].matches, 2);
+        assert_eq!(plan[0].new_content, "fn new_name() {}\nnew_name();\n");
+
+        let preview = execute_tool(
+            root,
+            "replace_in_files",
+            r#"{"pattern": "old_name", "replacement": "x", "dry_run": true}"#,
+        );
+        assert!(preview.success, "{:?}", preview.error);
+        assert!(preview.content.starts_with("Would replace 3 matches in 2 files:"));
+        let unpreviewed = r#"{"pattern": "old_name", "replacement": "x"}"#;
+        assert!(!execute_tool(root, "replace_in_files", unpreviewed).success);
+        assert_eq!(fs::read_to_string(root.join("notes.md")).unwrap(), "old_name\n");
+    }
+
+    #[test]
+    fn test_regex_replace_content() {
+        let content = "let a = foo(1);\nlet b = foo(2);\n";
+        let (out, n) =
+            regex_replace_content(content, r"foo\((\d+)\)", "bar($1, None)", None).unwrap();
+        assert_eq!(out, "let a = bar(1, None);\nlet b = bar(2, None);\n");
+        assert_eq!(n, 2);
+
+        let (out, n) =
+            regex_replace_content(content, r"let (?P<v>\w)", "const ${v}", Some(1)).unwrap();
+        assert_eq!(out, "const a = foo(1);\nlet b = foo(2);\n");
+        assert_eq!(n, 1);
+
+        let err = regex_replace_content(content, "baz", "qux", None).unwrap_err();
+        assert!(err.contains("matched nothing"), "{}", err);
+        let err = regex_replace_content(content, "(", "", None).unwrap_err();
+        assert!(err.starts_with("invalid regex"), "{}", err);
+    }
+
+    #[test]
+    fn test_replace_line_range() {
+        let content = "one\ntwo\nthree\nfour\n";
+        assert_eq!(
+            replace_line_range(content, 2, 3, "TWO").unwrap(),
+            "one\nTWO\nfour\n"
+        );
+        assert_eq!(
+            replace_line_range(content, 4, 4, "").unwrap(),
+            "one\ntwo\nthree\n"
+        );
+        assert_eq!(
+            replace_line_range("a\r\nb\r\n", 1, 1, "A").unwrap(),
+            "A\r\nb\r\n"
+        );
+
+        assert!(replace_line_range(content, 0, 1, "x").is_err());
+        assert!(replace_line_range(content, 3, 2, "x").is_err());
+        assert!(replace_line_range(content, 4, 5, "x").is_err());
+    }
+
+    #[test]
+    fn test_get_file_info_reports_lines_language_and_binary() {
+        let temp = tempfile::tempdir().unwrap();
+        let root = temp.path();
+        fs::write(root.join("lib.rs"), "fn a() {}\nfn b() {}").unwrap();
+        fs::write(root.join("logo.png"), [0x89, b'P', b'N', b'G', 0x00, 0x01]).unwrap();
+
+        let result = execute_tool(root, "get_file_info", r#"{"path": "lib.rs"}"#);
+        assert!(result.success, "{:?}", result.error);
+        let info: serde_json::Value = serde_json::from_str(&result.content).unwrap();
+        assert_eq!(info["line_count"], 2);
+        assert_eq!(info["language"], "Rust");
+        assert_eq!(info["is_binary"], false);
+        assert_eq!(info["size_bytes"], 19);
+
+        let result = execute_tool(root, "get_file_info", r#"{"path": "logo.png"}"#);
+        let info: serde_json::Value = serde_json::from_str(&result.content).unwrap();
+        assert_eq!(info["is_binary"], true);
+        assert!(info["line_count"].is_null());
+        assert!(info["language"].is_null());
+    }
+
+    #[test]
+    fn test_diff_files_between_workspace_files() {
+        let temp = tempfile::tempdir().unwrap();
+        let root = &temp.path().join("ws");
+        fs::create_dir(root).unwrap();
+        fs::write(temp.path().join("outside.txt"), "secret\n").unwrap();
+        fs::write(root.join("old.txt"), "one\ntwo\nthree\n").unwrap();
+        fs::write(root.join("new.txt"), "one\n2\nthree\n").unwrap();
+
+        let result = execute_tool(
+            root,
+            "diff_files",
+            r#"{"left_path": "old.txt", "right_path": "new.txt"}"#,
+        );
+        assert!(result.success, "{:?}", result.error);
+        assert_eq!(
+            result.content,
+            "--- a/old.txt\n+++ b/new.txt\n@@ -1,3 +1,3 @@\n one\n-two\n+2\n three\n"
+        );
+
+        let result = execute_tool(
+            root,
+            "diff_files",
+            r#"{"left_path": "old.txt", "right_path": "old.txt"}"#,
+        );
+        assert_eq!(result.content, "No differences.");
+
+        let result = execute_tool(
+            root,
+            "diff_files",
+            r#"{"left_path": "old.txt", "right_path": "../outside.txt"}"#,
+        );
+        assert!(!result.success);
+        let result = execute_tool(
+            root,
+            "diff_files",
+            r#"{"path": "old.txt", "against": "main"}"#,
+        );
+        assert!(!result.success);
+    }
+
+    #[test]
+    fn test_diff_files_against_head() {
+        let temp = tempfile::tempdir().unwrap();
+        let root = temp.path();
+        let git = |args: &[&str]| {
+            let status = std::process::Command::new("git")
+                .arg("-C")
+                .arg(root)
+                .args(["-c", "user.name=test", "-c", "user.email=test@example.com"])
+                .args(args)
+                .status()
+                .unwrap();
+            assert!(status.success(), "git {:?} failed", args);
+        };
+        git(&["init", "-q"]);
+        fs::write(root.join("lib.rs"), "one\ntwo\n").unwrap();
+        git(&["add", "lib.rs"]);
+        git(&["commit", "-q", "-m", "init"]);
+        fs::write(root.join("lib.rs"), "one\n2\n").unwrap();
+
+        let result = execute_tool(
+            root,
+            "diff_files",
+            r#"{"path": "lib.rs", "against": "HEAD"}"#,
+        );
+        assert!(result.success, "{:?}", result.error);
+        assert_eq!(
+            result.content,
+            "--- a/lib.rs\n+++ b/lib.rs\n@@ -1,2 +1,2 @@\n one\n-two\n+2\n"
+        );
+
+        fs::write(root.join("lib.rs"), "one\ntwo\n").unwrap();
+        let result = execute_tool(
+            root,
+            "diff_files",
+            r#"{"path": "lib.rs", "against": "head"}"#,
+        );
+        assert_eq!(result.content, "No differences.");
+    }
+
+    #[test]
+    fn test_local_tools_are_dispatched() {
+        let temp = tempfile::tempdir().unwrap();
+        for tool in LOCAL_TOOLS {
+            let result = execute_tool(temp.path(), tool, r#"{"path": "missing.txt"}"#);
+            let error = result.error.unwrap_or_default();
+            assert!(!error.contains("unknown tool"), "{tool} is not dispatched");
+        }
+    }
+
+    #[test]
+    fn test_classify_git_status() {
+        assert_eq!(classify_git_status(""), "clean");
+        assert_eq!(classify_git_status(" M src/lib.rs\n"), "modified");
+        assert_eq!(classify_git_status("MM src/lib.rs\n"), "modified");
+        assert_eq!(classify_git_status("A  src/new.rs\n"), "staged");
+        assert_eq!(classify_git_status("?? notes.txt\n"), "untracked");
+        assert_eq!(classify_git_status("!! target/\n"), "ignored");
+    }
+
+    #[test]
+    fn test_classify_dir_git_status() {
+        assert_eq!(classify_dir_git_status(""), "clean");
+        assert_eq!(
+            classify_dir_git_status("?? src/new.rs\nA  src/lib.rs\n"),
+            "staged"
+        );
+        assert_eq!(
+            classify_dir_git_status("?? src/new.rs\nA  src/lib.rs\n M src/main.rs\n"),
+            "modified"
+        );
+        assert_eq!(
+            classify_dir_git_status("?? src/a.rs\n?? src/b.rs\n"),
+            "untracked"
+        );
+    }
+
+    #[test]
+    fn test_symbol_search_falls_back_when_index_is_cold() {
+        let temp = tempfile::tempdir().unwrap();
+        let store = std::sync::Arc::new(
+            crate::symbol_index::SymbolStore::new(&temp.path().join("symbols.db")).unwrap(),
+        );
+        let service = LanguageService::new(temp.path().to_path_buf(), store).unwrap();
+
+        assert_eq!(search_symbol_index(&service, "authenticate", 10).unwrap(), None);
+
+        fs::write(
+            temp.path().join("auth.ts"),
+            "function authenticate(token: string): boolean {\n    return token.length > 0;\n}\n",
+        )
+        .unwrap();
+        service.index_file("auth.ts").unwrap();
+
+        let output = search_symbol_index(&service, "authenticate", 10)
+            .unwrap()
+            .unwrap();
+        assert!(output.contains("function authenticate"));
+        assert!(output.contains("auth.ts:1-3"));
+    }
+
+    #[test]
+    fn test_chunk_for_embedding_line_ranges() {
+        let content = (1..=130).map(|i| format!("line {}", i)).collect::<Vec<_>>().join("\n");
+        let chunks = chunk_for_embedding(&content);
+        assert_eq!(chunks.len(), 3);
+        assert_eq!((chunks[0].0, chunks[0].1), (1, 60));
+        assert_eq!((chunks[2].0, chunks[2].1), (121, 130));
+        assert!(chunks[2].2.starts_with("line 121"));
+
+        // Whitespace-only chunks are not worth embedding
+        assert!(chunk_for_embedding("\n   \n\n").is_empty());
+    }
+
+    #[test]
+    fn test_cosine_similarity() {
+        assert!((cosine_similarity(&[1.0, 0.0], &[2.0, 0.0]) - 1.0).abs() < 1e-6);
+        assert!(cosine_similarity(&[1.0, 0.0], &[0.0, 3.0]).abs() < 1e-6);
+        assert_eq!(cosine_similarity(&[1.0, 0.0], &[1.0]), 0.0);
+        assert_eq!(cosine_similarity(&[0.0, 0.0], &[1.0, 1.0]), 0.0);
+    }
+
+    #[test]
+    fn test_find_references_ignores_comments_and_strings() {
+        let temp = tempfile::tempdir().unwrap();
+        let root = temp.path();
+        fs::create_dir_all(root.join("src")).unwrap();
+        fs::write(
+            root.join("src").join("lib.rs"),
+            "pub fn load() {}\n\n// load is documented here\nfn run() {\n    let _ = \"load\";\n    load();\n}\n",
+        )
+        .unwrap();
+
+        let mut args = HashMap::new();
+        args.insert("name".to_string(), serde_json::json!("load"));
+
+        let result = find_references(root, &args, None);
+        assert!(result.success);
+        assert!(result.content.contains("function load"));
+        assert!(result.content.contains("(2 found"));
+        assert!(result.content.contains("src/lib.rs:6:5: load();"));
+        assert!(!result.content.contains("documented"));
+    }
+
+    #[test]
+    fn test_semantic_edit_replaces_function_body() {
+        let temp = tempfile::tempdir().unwrap();
+        let root = temp.path();
+        fs::write(root.join("lib.rs"), "fn answer() -> u32 {\n    41\n}\n").unwrap();
+
+        let store = std::sync::Arc::new(
+            crate::symbol_index::SymbolStore::new(&root.join("symbols.db")).unwrap(),
+        );
+        let service =
+            std::sync::Arc::new(LanguageService::new(root.to_path_buf(), store).unwrap());
+
+        let mut args = HashMap::new();
+        args.insert("path".to_string(), serde_json::json!("lib.rs"));
+        args.insert(
+            "target".to_string(),
+            serde_json::json!({ "symbol_kind": "function", "symbol_name": "answer" }),
+        );
+        args.insert("operation".to_string(), serde_json::json!("replace_body"));
+        args.insert(
+            "code".to_string(),
+            serde_json::json!("{\n    let x = 42;\n    x\n}"),
+        );
+
+        let result = semantic_edit(root, &args, service.clone(), None);
+        assert!(result.success, "{:?}", result.error);
+        assert_eq!(
+            fs::read_to_string(root.join("lib.rs")).unwrap(),
+            "fn answer() -> u32 {\n    let x = 42;\n    x\n}\n"
+        );
+
+        // The index is refreshed, so the next edit sees the new range
+        let key = fs::canonicalize(root.join("lib.rs")).unwrap();
+        let symbols = service.get_file_symbols(&key.to_string_lossy()).unwrap();
+        let answer = symbols.iter().find(|s| s.name == "answer").unwrap();
+        assert_eq!(answer.range.end.line, 3);
+    }
+}
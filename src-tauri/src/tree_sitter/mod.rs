@@ -15,4 +15,7 @@ mod symbol;
 
 pub use parser::{Language, TreeSitterParser};
 pub use query::QueryManager;
-pub use symbol::{extract_symbols, Position, Range, Symbol, SymbolExtractor, SymbolType};
+pub use symbol::{
+    build_outline, extract_symbols, find_identifier_occurrences, OutlineNode, Position, Range,
+    Symbol, SymbolExtractor, SymbolType,
+};
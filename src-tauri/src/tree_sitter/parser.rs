@@ -33,6 +33,61 @@ impl Language {
         }
     }
 
+    /// Detect language from the path, falling back to the shebang or a
+    /// content hint on the first line for extensionless/unknown files
+    pub fn detect(path: &str, first_line: Option<&str>) -> Option<Self> {
+        if let Some(language) = Self::from_path(path) {
+            return Some(language);
+        }
+        let line = first_line?.trim();
+        if let Some(shebang) = line.strip_prefix("#!") {
+            return Self::from_shebang(shebang);
+        }
+
+        if line.starts_with("#![") || line.starts_with("//!") {
+            Some(Language::Rust)
+        } else if line.starts_with("# -*- coding") || line.starts_with("# vim: set ft=python") {
+            Some(Language::Python)
+        } else if line == "'use strict';" || line == "\"use strict\";" {
+            Some(Language::JavaScript)
+        } else if line.starts_with("// @ts-") {
+            Some(Language::TypeScript)
+        } else {
+            None
+        }
+    }
+
+    /// Map a shebang interpreter (`/usr/bin/env python3`, `/usr/bin/node`, ...)
+    fn from_shebang(shebang: &str) -> Option<Self> {
+        let mut parts = shebang.split_whitespace();
+        let mut interpreter = parts.next()?.rsplit('/').next()?;
+        if interpreter == "env" {
+            // Skip env flags such as `-S`
+            interpreter = parts.find(|p| !p.starts_with('-'))?;
+        }
+
+        match interpreter {
+            i if i.starts_with("python") => Some(Language::Python),
+            "node" | "nodejs" => Some(Language::JavaScript),
+            "ts-node" | "tsx" | "deno" | "bun" => Some(Language::TypeScript),
+            "run-cargo-script" | "rust-script" => Some(Language::Rust),
+            _ => None,
+        }
+    }
+
+    /// Parse an explicit language override (`"python"`, `"tsx"`, ...)
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.trim().to_lowercase().as_str() {
+            "typescript" | "ts" => Some(Language::TypeScript),
+            "tsx" => Some(Language::Tsx),
+            "javascript" | "js" => Some(Language::JavaScript),
+            "jsx" => Some(Language::Jsx),
+            "python" | "py" => Some(Language::Python),
+            "rust" | "rs" => Some(Language::Rust),
+            _ => None,
+        }
+    }
+
     /// Get display name for the language
     pub fn display_name(&self) -> &'static str {
         match self {
@@ -190,6 +245,35 @@ mod tests {
         assert_eq!(Language::from_path("data.json"), None);
     }
 
+    #[test]
+    fn test_language_detect_fallbacks() {
+        let shebang = Some("#!/usr/bin/env python3");
+        assert_eq!(
+            Language::detect("bin/manage", shebang),
+            Some(Language::Python)
+        );
+        assert_eq!(
+            Language::detect("bin/serve", Some("#!/usr/bin/node")),
+            Some(Language::JavaScript)
+        );
+        assert_eq!(
+            Language::detect("run", Some("#!/usr/bin/env -S deno run")),
+            Some(Language::TypeScript)
+        );
+        assert_eq!(
+            Language::detect("lib", Some("//! Crate docs")),
+            Some(Language::Rust)
+        );
+        // The extension wins over the first line
+        assert_eq!(Language::detect("main.rs", shebang), Some(Language::Rust));
+        assert_eq!(Language::detect("bin/run", Some("#!/bin/sh")), None);
+        assert_eq!(Language::detect("README", None), None);
+
+        assert_eq!(Language::from_name("Python"), Some(Language::Python));
+        assert_eq!(Language::from_name("tsx"), Some(Language::Tsx));
+        assert_eq!(Language::from_name("cobol"), None);
+    }
+
     #[test]
     fn test_parse_typescript() {
         let mut parser = TreeSitterParser::new().unwrap();
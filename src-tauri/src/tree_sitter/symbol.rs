@@ -554,6 +554,67 @@ pub fn extract_symbols(
     extractor.extract(tree, source, language)
 }
 
+/// A symbol and the symbols nested inside it, for outline and breadcrumb views
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OutlineNode {
+    pub name: String,
+    pub symbol_type: SymbolType,
+    pub range: Range,
+    pub children: Vec<OutlineNode>,
+}
+
+/// Nest extracted symbols under their parents, each level in source order
+pub fn build_outline(symbols: &[Symbol]) -> Vec<OutlineNode> {
+    fn children_of(symbols: &[Symbol], parent_id: Option<&str>) -> Vec<OutlineNode> {
+        let mut nodes: Vec<OutlineNode> = symbols
+            .iter()
+            .filter(|s| s.parent_id.as_deref() == parent_id)
+            .map(|s| OutlineNode {
+                name: s.name.clone(),
+                symbol_type: s.symbol_type,
+                range: s.range,
+                children: children_of(symbols, Some(&s.id)),
+            })
+            .collect();
+        nodes.sort_by_key(|n| (n.range.start.line, n.range.start.character));
+        nodes
+    }
+    children_of(symbols, None)
+}
+
+/// Find every identifier node whose text equals `name`.
+///
+/// Only identifier-like nodes are considered (`identifier`, `type_identifier`,
+/// `property_identifier`, ...), so mentions inside comments and string literals
+/// are not reported. Positions are 0-indexed with byte-based columns, as reported
+/// by tree-sitter.
+pub fn find_identifier_occurrences(tree: &Tree, source: &str, name: &str) -> Vec<Position> {
+    let mut occurrences = Vec::new();
+    let mut cursor = tree.walk();
+
+    loop {
+        let node = cursor.node();
+        if node.kind().ends_with("identifier")
+            && node.utf8_text(source.as_bytes()).ok() == Some(name)
+        {
+            let start = node.start_position();
+            occurrences.push(Position::new(start.row as u32, start.column as u32));
+        }
+
+        if cursor.goto_first_child() {
+            continue;
+        }
+        loop {
+            if cursor.goto_next_sibling() {
+                break;
+            }
+            if !cursor.goto_parent() {
+                return occurrences;
+            }
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -596,6 +657,32 @@ class UserService {
             .any(|s| s.name == "getUser" && s.symbol_type == SymbolType::Method));
     }
 
+    #[test]
+    fn test_build_outline_nests_methods() {
+        let mut parser = TreeSitterParser::new().unwrap();
+        let code = r#"
+class UserService {
+    getUser(id: string) {}
+    deleteUser(id: string) {}
+}
+
+function helper() {}
+"#;
+        let tree = parser.parse(code, Language::TypeScript).unwrap();
+        let symbols = extract_symbols(&tree, code, Language::TypeScript, "service.ts");
+        let outline = build_outline(&symbols);
+
+        assert_eq!(outline.len(), 2);
+        assert_eq!(outline[0].name, "UserService");
+        assert_eq!(outline[1].name, "helper");
+        let methods: Vec<&str> = outline[0]
+            .children
+            .iter()
+            .map(|c| c.name.as_str())
+            .collect();
+        assert_eq!(methods, vec!["getUser", "deleteUser"]);
+    }
+
     #[test]
     fn test_extract_rust_function() {
         let mut parser = TreeSitterParser::new().unwrap();
@@ -633,4 +720,17 @@ class Calculator:
             .iter()
             .any(|s| s.name == "subtract" && s.symbol_type == SymbolType::Method));
     }
+
+    #[test]
+    fn test_find_identifier_occurrences_skips_comments_and_strings() {
+        let mut parser = TreeSitterParser::new().unwrap();
+        let code = "fn load() {}\n// load is called below\nfn main() {\n    let s = \"load\";\n    load();\n}\n";
+        let tree = parser.parse(code, Language::Rust).unwrap();
+
+        let occurrences = find_identifier_occurrences(&tree, code, "load");
+        assert_eq!(
+            occurrences,
+            vec![Position::new(0, 3), Position::new(4, 4)]
+        );
+    }
 }
@@ -1,6 +1,15 @@
 use serde::{Deserialize, Serialize};
 use std::sync::Mutex;
-use std::time::Instant;
+use std::time::{Duration, Instant};
+
+/// Delays before each retry of a warmup that failed with a retryable error
+const RETRY_DELAYS: [Duration; 3] = [
+    Duration::from_secs(1),
+    Duration::from_secs(2),
+    Duration::from_secs(4),
+];
+/// After a failed warmup, rewarm once this much time has passed (instead of 5 minutes)
+const FAILURE_REWARM_DELAY: Duration = Duration::from_secs(30);
 
 /// Warmup trigger types per Blade Protocol v2.1
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -37,6 +46,24 @@ pub struct WarmupResponse {
     pub message: Option<String>,
 }
 
+/// Outcome of the most recent warmup, for the `warmup_status` command
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct WarmupStatus {
+    /// RFC 3339 time of the last successful warmup
+    pub last_success_at: Option<String>,
+    /// Error from the last warmup, cleared on success
+    pub last_error: Option<String>,
+    /// Requests made by the last warmup, including retries
+    pub attempts: usize,
+}
+
+/// A single warmup attempt's failure
+enum AttemptError {
+    /// Connection problems, timeouts and 5xx/429 responses
+    Retryable(String),
+    Fatal(String),
+}
+
 /// Warmup client for proactive cache warming
 pub struct WarmupClient {
     base_url: String,
@@ -44,6 +71,8 @@ pub struct WarmupClient {
     user_id: String,
     http_client: reqwest::Client,
     last_warmup: Mutex<Option<Instant>>,
+    last_failure: Mutex<Option<Instant>>,
+    status: Mutex<WarmupStatus>,
 }
 
 impl WarmupClient {
@@ -61,6 +90,8 @@ impl WarmupClient {
             user_id,
             http_client,
             last_warmup: Mutex::new(None),
+            last_failure: Mutex::new(None),
+            status: Mutex::new(WarmupStatus::default()),
         }
     }
 
@@ -87,45 +118,107 @@ impl WarmupClient {
             session_id, model, request.trigger
         );
 
+        let mut attempts = 0;
+        let result = loop {
+            attempts += 1;
+            match self.send_warmup(&url, &request).await {
+                Ok(data) => break Ok(data),
+                Err(AttemptError::Retryable(e)) if attempts <= RETRY_DELAYS.len() => {
+                    let delay = RETRY_DELAYS[attempts - 1];
+                    eprintln!(
+                        "[WARMUP] Attempt {} failed, retrying in {:?}: {}",
+                        attempts, delay, e
+                    );
+                    tokio::time::sleep(delay).await;
+                }
+                Err(AttemptError::Retryable(e)) | Err(AttemptError::Fatal(e)) => break Err(e),
+            }
+        };
+
+        let mut status = self.status.lock().unwrap();
+        status.attempts = attempts;
+        match &result {
+            Ok(data) => {
+                eprintln!(
+                    "[WARMUP] Response: type={}, provider={}, artifacts={}, ready={}, duration={}ms",
+                    data.response_type,
+                    data.provider,
+                    data.artifacts_loaded,
+                    data.cache_ready,
+                    data.duration_ms
+                );
+
+                // Track last warmup time
+                *self.last_warmup.lock().unwrap() = Some(Instant::now());
+                *self.last_failure.lock().unwrap() = None;
+                status.last_success_at = Some(chrono::Utc::now().to_rfc3339());
+                status.last_error = None;
+            }
+            Err(e) => {
+                eprintln!("[WARMUP] Failed after {} attempt(s): {}", attempts, e);
+                *self.last_failure.lock().unwrap() = Some(Instant::now());
+                status.last_error = Some(e.clone());
+            }
+        }
+
+        result
+    }
+
+    /// Make one warmup request, classifying failures as retryable or not
+    async fn send_warmup(
+        &self,
+        url: &str,
+        request: &WarmupRequest,
+    ) -> Result<WarmupResponse, AttemptError> {
         let response = self
             .http_client
-            .post(&url)
+            .post(url)
             .header("Content-Type", "application/json")
             .header("Authorization", format!("Bearer {}", self.api_key))
-            .json(&request)
+            .json(request)
             .send()
             .await
-            .map_err(|e| format!("Warmup request failed: {}", e))?;
+            .map_err(|e| {
+                let message = format!("Warmup request failed: {}", e);
+                if e.is_connect() || e.is_timeout() || e.is_request() {
+                    AttemptError::Retryable(message)
+                } else {
+                    AttemptError::Fatal(message)
+                }
+            })?;
 
         if !response.status().is_success() {
             let status = response.status();
             let text = response.text().await.unwrap_or_default();
-            return Err(format!("Warmup error {}: {}", status, text));
+            let message = format!("Warmup error {}: {}", status, text);
+            return Err(
+                if status.is_server_error() || status == reqwest::StatusCode::TOO_MANY_REQUESTS {
+                    AttemptError::Retryable(message)
+                } else {
+                    AttemptError::Fatal(message)
+                },
+            );
         }
 
-        let data: WarmupResponse = response
+        response
             .json()
             .await
-            .map_err(|e| format!("Failed to parse warmup response: {}", e))?;
-
-        eprintln!(
-            "[WARMUP] Response: type={}, provider={}, artifacts={}, ready={}, duration={}ms",
-            data.response_type,
-            data.provider,
-            data.artifacts_loaded,
-            data.cache_ready,
-            data.duration_ms
-        );
-
-        // Track last warmup time
-        *self.last_warmup.lock().unwrap() = Some(Instant::now());
+            .map_err(|e| AttemptError::Fatal(format!("Failed to parse warmup response: {}", e)))
+    }
 
-        Ok(data)
+    /// Outcome of the most recent warmup
+    pub fn status(&self) -> WarmupStatus {
+        self.status.lock().unwrap().clone()
     }
 
     /// Check if we should rewarm based on inactivity
-    /// Returns true if more than 5 minutes have passed since last warmup
+    /// Returns true if more than 5 minutes have passed since last warmup,
+    /// or 30 seconds since a failed one
     pub fn should_rewarm(&self) -> bool {
+        if let Some(failed) = *self.last_failure.lock().unwrap() {
+            return failed.elapsed() > FAILURE_REWARM_DELAY;
+        }
+
         let last = self.last_warmup.lock().unwrap();
         match *last {
             Some(instant) => instant.elapsed().as_secs() > 300, // 5 minutes
@@ -145,3 +238,22 @@ pub fn provider_supports_cache(provider: &str) -> bool {
     matches!(provider.to_lowercase().as_str(), "anthropic" | "openai")
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_should_rewarm_sooner_after_failure() {
+        let client = WarmupClient::new(String::new(), String::new(), String::new());
+        assert!(client.should_rewarm());
+
+        *client.last_warmup.lock().unwrap() = Some(Instant::now());
+        assert!(!client.should_rewarm());
+
+        // A fresh failure waits briefly, an older one triggers a rewarm
+        *client.last_failure.lock().unwrap() = Some(Instant::now());
+        assert!(!client.should_rewarm());
+        *client.last_failure.lock().unwrap() = Instant::now().checked_sub(Duration::from_secs(31));
+        assert!(client.should_rewarm());
+    }
+}
@@ -1,11 +1,31 @@
 use serde::{Deserialize, Serialize};
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
-#[derive(Serialize, Deserialize, Clone)]
+/// Error returned for command execution in a workspace the user hasn't trusted
+pub const UNTRUSTED_WORKSPACE_ERROR: &str =
+    "workspace not trusted: commands and the terminal are disabled until the user trusts this workspace";
+
+#[derive(Serialize, Deserialize, Clone, Default)]
 pub struct WorkspaceState {
     pub last_workspace: Option<String>,
     pub recent_workspaces: Vec<String>,
+    /// Workspaces the user allowed to run commands. `None` in state files
+    /// written before workspace trust existed.
+    #[serde(default)]
+    pub trusted_workspaces: Option<Vec<String>>,
+}
+
+impl WorkspaceState {
+    /// Trusted workspace list; workspaces opened before trust existed count as trusted
+    fn trusted(&self) -> Vec<String> {
+        self.trusted_workspaces.clone().unwrap_or_else(|| {
+            self.recent_workspaces
+                .iter()
+                .map(|p| trust_key(Path::new(p)))
+                .collect()
+        })
+    }
 }
 
 pub struct WorkspaceManager {
@@ -94,27 +114,8 @@ impl WorkspaceManager {
         if let Some(workspace) = &self.workspace {
             let workspace_str = workspace.to_string_lossy().to_string();
 
-            // Load existing state or create new
-            let mut state = if self.state_path.exists() {
-                if let Ok(content) = fs::read_to_string(&self.state_path) {
-                    serde_json::from_str::<WorkspaceState>(&content).unwrap_or_else(|_| {
-                        WorkspaceState {
-                            last_workspace: None,
-                            recent_workspaces: Vec::new(),
-                        }
-                    })
-                } else {
-                    WorkspaceState {
-                        last_workspace: None,
-                        recent_workspaces: Vec::new(),
-                    }
-                }
-            } else {
-                WorkspaceState {
-                    last_workspace: None,
-                    recent_workspaces: Vec::new(),
-                }
-            };
+            let mut state = self.load_state();
+            state.trusted_workspaces = Some(state.trusted());
 
             // Update state
             state.last_workspace = Some(workspace_str.clone());
@@ -124,25 +125,115 @@ impl WorkspaceManager {
             state.recent_workspaces.insert(0, workspace_str);
             state.recent_workspaces.truncate(10);
 
-            // Save state
-            if let Some(parent) = self.state_path.parent() {
-                let _ = fs::create_dir_all(parent);
-            }
+            let _ = self.write_state(&state);
+        }
+    }
 
-            if let Ok(json) = serde_json::to_string_pretty(&state) {
-                let _ = fs::write(&self.state_path, json);
-            }
+    fn load_state(&self) -> WorkspaceState {
+        fs::read_to_string(&self.state_path)
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default()
+    }
+
+    fn write_state(&self, state: &WorkspaceState) -> Result<(), String> {
+        if let Some(parent) = self.state_path.parent() {
+            fs::create_dir_all(parent).map_err(|e| e.to_string())?;
         }
+        let json = serde_json::to_string_pretty(state).map_err(|e| e.to_string())?;
+        fs::write(&self.state_path, json).map_err(|e| e.to_string())
     }
 
     pub fn get_recent_workspaces(&self) -> Vec<String> {
-        if self.state_path.exists() {
-            if let Ok(content) = fs::read_to_string(&self.state_path) {
-                if let Ok(state) = serde_json::from_str::<WorkspaceState>(&content) {
-                    return state.recent_workspaces;
-                }
-            }
+        self.load_state().recent_workspaces
+    }
+
+    /// Whether the user has trusted `path` to run commands
+    pub fn is_trusted(&self, path: &Path) -> bool {
+        let key = trust_key(path);
+        self.load_state().trusted().contains(&key)
+    }
+
+    /// Whether the open workspace may run commands; with no workspace open
+    /// there is nothing to protect
+    pub fn is_current_trusted(&self) -> bool {
+        match self.workspace.as_deref() {
+            Some(path) => self.is_trusted(path),
+            None => true,
+        }
+    }
+
+    /// Remember `path` as trusted
+    pub fn trust(&self, path: &Path) -> Result<(), String> {
+        let key = trust_key(path);
+        let mut state = self.load_state();
+        let mut trusted = state.trusted();
+        if !trusted.contains(&key) {
+            trusted.push(key);
+        }
+        state.trusted_workspaces = Some(trusted);
+        self.write_state(&state)
+    }
+}
+
+/// Trust is keyed by canonical path so `./proj` and `/home/me/proj` agree
+fn trust_key(path: &Path) -> String {
+    fs::canonicalize(path)
+        .unwrap_or_else(|_| path.to_path_buf())
+        .to_string_lossy()
+        .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn manager_in(dir: &Path) -> WorkspaceManager {
+        WorkspaceManager {
+            workspace: None,
+            state_path: dir.join("workspace_state.json"),
         }
-        Vec::new()
+    }
+
+    #[test]
+    fn test_workspace_trust() {
+        let dir = tempfile::tempdir().unwrap();
+        let project = dir.path().join("project");
+        fs::create_dir(&project).unwrap();
+        let mut manager = manager_in(dir.path());
+
+        assert!(manager.is_current_trusted());
+        manager.set_workspace(project.clone());
+        assert!(!manager.is_trusted(&project));
+        assert!(!manager.is_current_trusted());
+
+        manager.trust(&project).unwrap();
+        assert!(manager.is_current_trusted());
+        // Reopening keeps the trust
+        let mut reopened = manager_in(dir.path());
+        reopened.set_workspace(project.clone());
+        assert!(reopened.is_current_trusted());
+    }
+
+    #[test]
+    fn test_workspaces_opened_before_trust_stay_trusted() {
+        let dir = tempfile::tempdir().unwrap();
+        let old = dir.path().join("old");
+        let new = dir.path().join("new");
+        fs::create_dir(&old).unwrap();
+        fs::create_dir(&new).unwrap();
+        // Recent paths were stored as opened, not canonicalized
+        let recent = old.join("..").join("old");
+        let legacy = serde_json::json!({
+            "last_workspace": recent,
+            "recent_workspaces": [recent],
+        });
+        fs::write(dir.path().join("workspace_state.json"), legacy.to_string()).unwrap();
+
+        let mut manager = manager_in(dir.path());
+        assert!(manager.is_trusted(&old));
+        manager.set_workspace(new.clone());
+        assert!(!manager.is_trusted(&new));
+        assert!(manager.is_trusted(&old));
     }
 }
@@ -17,7 +17,8 @@ pub enum ConnectionState {
 
 /// Manages a persistent WebSocket connection to zcoderd
 pub struct WsConnectionManager {
-    blade_url: RwLock<String>,
+    /// `ws(s)://` base from `ApiConfig::normalized`
+    ws_base: RwLock<String>,
     api_key: RwLock<String>,
     client: Mutex<Option<BladeWsClient>>,
     state: RwLock<ConnectionState>,
@@ -27,9 +28,9 @@ pub struct WsConnectionManager {
 
 impl WsConnectionManager {
     /// Create a new connection manager
-    pub fn new(blade_url: String, api_key: String) -> Self {
+    pub fn new(ws_base: String, api_key: String) -> Self {
         Self {
-            blade_url: RwLock::new(blade_url),
+            ws_base: RwLock::new(ws_base),
             api_key: RwLock::new(api_key),
             client: Mutex::new(None),
             state: RwLock::new(ConnectionState::Disconnected),
@@ -39,10 +40,10 @@ impl WsConnectionManager {
     }
 
     /// Update credentials (e.g., when user changes API key in settings)
-    pub async fn update_credentials(&self, blade_url: String, api_key: String) {
+    pub async fn update_credentials(&self, ws_base: String, api_key: String) {
         {
-            let mut url = self.blade_url.write().await;
-            *url = blade_url;
+            let mut url = self.ws_base.write().await;
+            *url = ws_base;
         }
         {
             let mut key = self.api_key.write().await;
@@ -87,12 +88,12 @@ impl WsConnectionManager {
             *state = ConnectionState::Connecting;
         }
 
-        let blade_url = self.blade_url.read().await.clone();
+        let ws_base = self.ws_base.read().await.clone();
         let api_key = self.api_key.read().await.clone();
 
-        eprintln!("[WS MANAGER] Connecting to {}", blade_url);
+        eprintln!("[WS MANAGER] Connecting to {}", ws_base);
 
-        let client = BladeWsClient::new(blade_url, api_key);
+        let client = BladeWsClient::new(ws_base, api_key);
         
         match client.connect().await {
             Ok(event_rx) => {
@@ -12,6 +12,7 @@ import { MarkdownRenderer } from './MarkdownRenderer';
 const REVERTIBLE_TOOLS = new Set([
     'apply_patch',
     'edit_file',
+    'edit_lines',
     'write_file',
     'create_file',
     'delete_file',
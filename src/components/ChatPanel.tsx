@@ -28,6 +28,7 @@ interface ChatPanelProps {
     messages: ChatMessageType[];
     loading: boolean;
     error: string | null;
+    commandHint?: string | null;
     sendMessage: (text: string, attachments?: ImageAttachment[]) => void;
     stopGeneration: () => void;
     models: ModelInfo[];
@@ -50,6 +51,7 @@ const ChatPanelComponent: React.FC<ChatPanelProps> = ({
     messages,
     loading,
     error,
+    commandHint,
     sendMessage,
     stopGeneration,
     models,
@@ -69,7 +71,7 @@ const ChatPanelComponent: React.FC<ChatPanelProps> = ({
 }) => {
     const { t } = useTranslation();
     useCommandExecution();
-    const { loadConversation } = useHistory();
+    const { loadConversation, loadOlderMessages, hasOlderMessages } = useHistory();
     const messagesEndRef = useRef<HTMLDivElement>(null);
     const isUserAtBottomRef = useRef(true);
     const prevMessageCountRef = useRef(0);
@@ -152,6 +154,31 @@ const ChatPanelComponent: React.FC<ChatPanelProps> = ({
     // Track visible range for virtualization
     const [visibleRange, setVisibleRange] = useState({ start: 0, end: 50 });
     const scrollContainerRef = useRef<HTMLDivElement>(null);
+
+    // Lazily prepend older history pages when the user scrolls to the top
+    const loadingOlderRef = useRef(false);
+    const loadOlderRef = useRef<() => void>(() => {});
+    loadOlderRef.current = () => {
+        if (!hasOlderMessages || loadingOlderRef.current) return;
+        loadingOlderRef.current = true;
+        const container = scrollContainerRef.current;
+        const prevScrollHeight = container?.scrollHeight ?? 0;
+        loadOlderMessages()
+            .then(older => {
+                if (older.length === 0) return;
+                onLoadConversation([...older, ...messages]);
+                // Keep the viewport anchored on the message the user was looking at
+                requestAnimationFrame(() => {
+                    if (container) {
+                        container.scrollTop += container.scrollHeight - prevScrollHeight;
+                    }
+                });
+            })
+            .catch(e => console.error('Failed to load older messages:', e))
+            .finally(() => {
+                loadingOlderRef.current = false;
+            });
+    };
     
     // Scroll handler - memoized to prevent recreation on every render
     const handleScroll = useCallback((e: React.UIEvent<HTMLDivElement>) => {
@@ -159,6 +186,10 @@ const ChatPanelComponent: React.FC<ChatPanelProps> = ({
         // Use a larger threshold (100px) to be more resilient to large appends (e.g. code blocks)
         const isBottom = Math.abs(target.scrollHeight - target.scrollTop - target.clientHeight) < 100;
         isUserAtBottomRef.current = isBottom;
+
+        if (target.scrollTop < 50) {
+            loadOlderRef.current();
+        }
         
         // Update visible range for virtualization (throttled)
         // Estimate ~150px per message on average
@@ -342,6 +373,9 @@ const ChatPanelComponent: React.FC<ChatPanelProps> = ({
                                 'list_dir': 'Listing Directory',
                                 'list_directory': 'Listing Directory',
                                 'codebase_search': 'Searching Codebase',
+                                'find_references': 'Finding References',
+                                'semantic_search': 'Semantic Search',
+                                'semantic_edit': 'Editing Symbol',
                                 'get_workspace_structure': 'Analyzing Workspace',
                                 'view_file': 'Viewing File',
                                 'replace_file_content': 'Replacing Content',
@@ -371,6 +405,12 @@ const ChatPanelComponent: React.FC<ChatPanelProps> = ({
                             );
                         })()}
 
+                        {commandHint && (
+                            <div className="p-3 mx-4 mb-4 bg-amber-500/5 border border-amber-500/20 text-amber-400 rounded-sm text-xs font-mono">
+                                HINT: {commandHint}
+                            </div>
+                        )}
+
                         {error && (
                             <div className="p-3 mx-4 mb-4 bg-red-500/5 border border-red-500/20 text-red-400 rounded-sm text-xs font-mono">
                                 ERR: {error}
@@ -5,6 +5,8 @@ import '@xterm/xterm/css/xterm.css';
 import { invoke } from '@tauri-apps/api/core';
 import { listen } from '@tauri-apps/api/event';
 import { BladeDispatcher } from '../services/blade';
+import { TerminalBuffer } from '../utils/eventBuffer';
+import type { BladeEventEnvelope } from '../types/blade';
 import { Terminal } from 'lucide-react';
 
 interface ChatTerminalProps {
@@ -79,13 +81,22 @@ export const ChatTerminal: React.FC<ChatTerminalProps> = ({
                     }).catch(err => console.error('Failed to send input:', err));
                 });
 
-                // Listen for output
-                unlistenOutput = await listen<{ id: string; data: string }>(
-                    'terminal-output',
+                // Listen for output, applied in sequence order
+                const outputBuffer = new TerminalBuffer((id, data) => {
+                    if (id === terminalId) {
+                        term.write(data);
+                        outputBufferRef.current += data;
+                    }
+                });
+                unlistenOutput = await listen<BladeEventEnvelope>(
+                    'blade-event',
                     (event) => {
-                        if (event.payload.id === terminalId) {
-                            term.write(event.payload.data);
-                            outputBufferRef.current += event.payload.data;
+                        const envelope = event.payload;
+                        if (envelope.event.type !== 'Terminal') return;
+                        const terminalEvent = envelope.event.payload;
+                        if (terminalEvent.type === 'Output' && terminalEvent.payload.id === terminalId) {
+                            const { seq, data } = terminalEvent.payload;
+                            outputBuffer.addOutput(terminalId, seq, data);
                         }
                     }
                 );
@@ -13,6 +13,7 @@ import { EditorProvider, useEditor } from '../contexts/EditorContext';
 import { useUncommittedChanges } from '../hooks/useUncommittedChanges';
 import { useChat } from '../hooks/useChat';
 import { StorageSetupModal } from './StorageSetupModal';
+import { WorkspaceTrustModal } from './WorkspaceTrustModal';
 import { useProjectState, type ProjectState } from '../hooks/useProjectState';
 import { useWarmup } from '../hooks/useWarmup';
 import { useGitStatus } from '../hooks/useGitStatus';
@@ -24,6 +25,7 @@ const FileHistoryPanel = React.lazy(() => import('./FileHistoryPanel').then(modu
 const SettingsModal = React.lazy(() => import('./SettingsModal').then(module => ({ default: module.SettingsModal })));
 const ProtocolExplorer = React.lazy(() => import('./dev/ProtocolExplorer').then(module => ({ default: module.ProtocolExplorer })));
 import type { BackendSettings } from '../types/settings';
+import type { FileMovedPayload, WorkspaceChangedPayload } from '../types/events';
 
 // Helper to convert backend TabInfo to frontend Tab
 function tabInfoToTab(info: TabInfo): Tab {
@@ -282,6 +284,24 @@ const AppLayoutInner: React.FC = () => {
         checkZbladeDir();
     }, [workspacePath, hasCheckedZblade]);
 
+    // Workspace trust: commands and the terminal stay disabled until the user trusts the folder
+    const [untrustedWorkspace, setUntrustedWorkspace] = useState<string | null>(null);
+    useEffect(() => {
+        if (!workspacePath) return;
+        invoke<boolean>('is_workspace_trusted', { path: workspacePath })
+            .then(trusted => setUntrustedWorkspace(trusted ? null : workspacePath))
+            .catch(e => console.error('[Layout] Failed to check workspace trust:', e));
+    }, [workspacePath]);
+
+    useEffect(() => {
+        const unlistenPromise = listen<WorkspaceChangedPayload>('workspace-trust-required', (event) => {
+            setUntrustedWorkspace(event.payload.workspace_path);
+        });
+        return () => {
+            unlistenPromise.then(unlisten => unlisten());
+        };
+    }, []);
+
     // Handle project state restoration
     const handleStateLoaded = useCallback((state: ProjectState) => {
         console.log('[Layout] Restoring project state:', state);
@@ -598,6 +618,7 @@ const AppLayoutInner: React.FC = () => {
         let unlistenEphemeral: (() => void) | undefined;
         let unlistenResearchProgress: (() => void) | undefined;
         let unlistenChangeApplied: (() => void) | undefined;
+        let unlistenFileMoved: (() => void) | undefined;
         let unlistenChatError: (() => void) | undefined;
 
         const setupListeners = async () => {
@@ -757,6 +778,23 @@ const AppLayoutInner: React.FC = () => {
 
             // NOTE: context-length-exceeded is now handled in useChat.ts where it belongs
 
+            // Follow renames and moves in the open tabs instead of reopening them
+            unlistenFileMoved = await listen<FileMovedPayload>('file-moved', (event) => {
+                const { old_path, new_path } = event.payload;
+                const rebase = (path: string) => {
+                    if (path === old_path) return new_path;
+                    if (path.startsWith(`${old_path}/`)) return new_path + path.slice(old_path.length);
+                    return null;
+                };
+                setTabs(prev => prev.map(tab => {
+                    const moved = tab.type === 'file' && tab.path ? rebase(tab.path) : null;
+                    if (!moved) return tab;
+                    const oldName = tab.path!.split('/').pop();
+                    const title = tab.title === oldName ? (moved.split('/').pop() || moved) : tab.title;
+                    return { ...tab, path: moved, title };
+                }));
+            });
+
             // Listen for change-applied events to convert ephemeral tabs to file tabs
             unlistenChangeApplied = await listen<{ change_id: string; file_path: string }>('change-applied', (event) => {
                 console.log('[LAYOUT] Change applied:', event.payload);
@@ -820,6 +858,7 @@ const AppLayoutInner: React.FC = () => {
             if (unlistenEphemeral) unlistenEphemeral();
             if (unlistenResearchProgress) unlistenResearchProgress();
             if (unlistenChangeApplied) unlistenChangeApplied();
+            if (unlistenFileMoved) unlistenFileMoved();
             if (unlistenChatError) unlistenChatError();
         };
     }, [tabs]);
@@ -1076,6 +1115,7 @@ const AppLayoutInner: React.FC = () => {
                                 messages={chat.messages}
                                 loading={chat.loading}
                                 error={chat.error}
+                                commandHint={chat.commandHint}
                                 sendMessage={chat.sendMessage}
                                 stopGeneration={chat.stopGeneration}
                                 models={chat.models}
@@ -1159,6 +1199,16 @@ const AppLayoutInner: React.FC = () => {
                     onComplete={() => setShowStorageSetup(false)}
                 />
             )}
+
+            {/* Workspace trust prompt, after first-time setup */}
+            {untrustedWorkspace && !showStorageSetup && (
+                <WorkspaceTrustModal
+                    isOpen={true}
+                    workspacePath={untrustedWorkspace}
+                    onTrusted={() => setUntrustedWorkspace(null)}
+                    onDismiss={() => setUntrustedWorkspace(null)}
+                />
+            )}
         </div>
     );
 };
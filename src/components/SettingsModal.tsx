@@ -37,10 +37,23 @@ interface SettingsState {
     localAi: {
         ollamaEnabled: boolean;
         ollamaUrl: string;
+        ollamaEmbeddingModel: string;
         openaiCompatEnabled: boolean;
         openaiCompatUrl: string;
     };
     allowGitIgnoredFiles?: boolean;  // Per-project setting
+    atomicAiChanges?: boolean;  // Per-project setting (settings.json only)
+    commandTimeoutSecs?: number;  // Per-project setting (settings.json only)
+    autoCreateCwd?: boolean;  // Per-project setting (settings.json only)
+    commandEnv?: BackendSettings['command_env'];  // Per-project setting (settings.json only)
+    redaction?: BackendSettings['redaction'];  // Per-project setting (settings.json only)
+    tools?: BackendSettings['tools'];  // Per-project setting (settings.json only)
+    maxTurns?: number;  // Per-project setting (settings.json only)
+    maxToolCallsPerMessage?: number;  // Per-project setting (settings.json only)
+    loopDetection?: BackendSettings['loop_detection'];  // Per-project setting (settings.json only)
+    commandOutputTailLines?: number;  // Per-project setting (settings.json only)
+    terminalScrollbackLines?: number;  // Per-project setting (settings.json only)
+    persistEphemeralDocuments?: boolean;  // Per-project setting (settings.json only)
 }
 
 const defaultSettings: SettingsState = {
@@ -73,6 +86,7 @@ const defaultSettings: SettingsState = {
     localAi: {
         ollamaEnabled: false,
         ollamaUrl: 'http://localhost:11434',
+        ollamaEmbeddingModel: 'nomic-embed-text',
         openaiCompatEnabled: false,
         openaiCompatUrl: 'http://localhost:8080/v1',
     },
@@ -97,6 +111,7 @@ function backendGlobalToFrontend(backend: ApiConfig): Pick<SettingsState, 'accou
         localAi: {
             ollamaEnabled: backend.ollama_enabled,
             ollamaUrl: backend.ollama_url,
+            ollamaEmbeddingModel: backend.ollama_embedding_model,
             openaiCompatEnabled: backend.openai_compat_enabled,
             openaiCompatUrl: backend.openai_compat_url,
         },
@@ -110,6 +125,7 @@ function frontendGlobalToBackend(frontend: SettingsState): ApiConfig {
         user_id: frontend.account.userId,
         ollama_enabled: frontend.localAi.ollamaEnabled,
         ollama_url: frontend.localAi.ollamaUrl,
+        ollama_embedding_model: frontend.localAi.ollamaEmbeddingModel,
         openai_compat_enabled: frontend.localAi.openaiCompatEnabled,
         openai_compat_url: frontend.localAi.openaiCompatUrl,
         theme: frontend.account.theme,
@@ -139,6 +155,18 @@ function backendToFrontend(backend: BackendSettings): Omit<SettingsState, 'accou
         },
         editor: {},
         allowGitIgnoredFiles: backend.allow_gitignored_files,
+        atomicAiChanges: backend.atomic_ai_changes,
+        commandTimeoutSecs: backend.command_timeout_secs,
+        autoCreateCwd: backend.auto_create_cwd,
+        commandEnv: backend.command_env,
+        redaction: backend.redaction,
+        tools: backend.tools,
+        maxTurns: backend.max_turns,
+        maxToolCallsPerMessage: backend.max_tool_calls_per_message,
+        loopDetection: backend.loop_detection,
+        commandOutputTailLines: backend.command_output_tail_lines,
+        terminalScrollbackLines: backend.terminal_scrollback_lines,
+        persistEphemeralDocuments: backend.persist_ephemeral_documents,
     };
 }
 
@@ -164,6 +192,18 @@ function frontendToBackend(frontend: SettingsState): BackendSettings {
         },
         editor: {},
         allow_gitignored_files: frontend.allowGitIgnoredFiles || false,
+        atomic_ai_changes: frontend.atomicAiChanges || false,
+        command_timeout_secs: frontend.commandTimeoutSecs ?? 120,
+        auto_create_cwd: frontend.autoCreateCwd || false,
+        command_env: frontend.commandEnv,
+        redaction: frontend.redaction,
+        tools: frontend.tools,
+        max_turns: frontend.maxTurns ?? 50,
+        max_tool_calls_per_message: frontend.maxToolCallsPerMessage ?? 200,
+        loop_detection: frontend.loopDetection,
+        command_output_tail_lines: frontend.commandOutputTailLines ?? 400,
+        terminal_scrollback_lines: frontend.terminalScrollbackLines ?? 10000,
+        persist_ephemeral_documents: frontend.persistEphemeralDocuments || false,
     };
 }
 
@@ -536,6 +576,21 @@ const LocalAiSettings: React.FC<LocalAiSettingsProps> = ({ settings, onChange, o
                     />
                 </div>
 
+                <div className="space-y-2">
+                    <label className="text-xs text-[var(--fg-secondary)] block">Embedding Model</label>
+                    <input
+                        type="text"
+                        value={settings.ollamaEmbeddingModel}
+                        onChange={(e) => onChange({ ollamaEmbeddingModel: e.target.value })}
+                        placeholder="nomic-embed-text"
+                        disabled={!settings.ollamaEnabled}
+                        className="w-full bg-[var(--bg-app)] border border-[var(--border-subtle)] rounded-lg py-2 px-3 text-sm text-[var(--fg-primary)] focus:outline-none focus:border-[var(--accent-primary)] placeholder-[var(--fg-tertiary)] disabled:opacity-60"
+                    />
+                    <div className="text-xs text-[var(--fg-tertiary)]">
+                        Used by semantic search to embed workspace code locally.
+                    </div>
+                </div>
+
                 <div className="flex items-center gap-3">
                     <button
                         type="button"
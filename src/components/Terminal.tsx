@@ -241,18 +241,7 @@ export default function Terminal({ id = "main-terminal", cwd }: TerminalProps) {
             );
         }
 
-        // 3. Listen for output from backend (legacy)
-        const unlistenLegacy = listen<{ id: string; data: string }>(
-            "terminal-output",
-            (event) => {
-                if (event.payload.id === id) {
-                    const cleaned = sanitizeTerminalOutput(event.payload.data);
-                    if (cleaned) term.write(cleaned);
-                }
-            }
-        );
-
-        // v1.1: Listen for blade-event with sequence numbers
+        // 3. v1.1: Listen for blade-event with sequence numbers
         const unlistenV11 = listen<BladeEventEnvelope>(
             "blade-event",
             (event) => {
@@ -328,7 +317,6 @@ export default function Terminal({ id = "main-terminal", cwd }: TerminalProps) {
             }
 
             resizeObserver.disconnect();
-            unlistenLegacy.then((unlisten) => unlisten());
             unlistenV11.then((unlisten) => unlisten());
 
             // Dispose logic
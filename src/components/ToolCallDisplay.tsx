@@ -87,6 +87,7 @@ export const ToolCallDisplay: React.FC<ToolCallDisplayProps> = ({
         const nameMap: Record<string, string> = {
             'apply_patch': 'Applying Code Changes',
             'edit_file': 'Editing File',
+            'edit_lines': 'Editing Lines',
             'read_file': 'Reading File',
             'write_file': 'Writing File',
             'list_files': 'Listing Files',
@@ -97,10 +98,14 @@ export const ToolCallDisplay: React.FC<ToolCallDisplayProps> = ({
             'list_directory': 'Listing Directory',
             'get_workspace_structure': 'Analyzing Workspace',
             'codebase_search': 'Searching Codebase',
+            'find_references': 'Finding References',
+            'semantic_search': 'Semantic Search',
+            'semantic_edit': 'Editing Symbol',
             'get_editor_state': 'Getting Editor State',
             'read_file_range': 'Reading File Range',
             'find_files': 'Finding Files',
             'find_files_glob': 'Finding Files (Glob)',
+            'read_files_glob': 'Reading Files (Glob)',
             'glob': 'Glob Search',
             'find_by_name': 'Find Files by Name',
             'view_file_outline': 'Viewing File Outline',
@@ -144,6 +149,7 @@ export const ToolCallDisplay: React.FC<ToolCallDisplayProps> = ({
         'write_file',
         'apply_patch',
         'edit_file',
+        'edit_lines',
         'create_file',
         'delete_file',
         'list_files',
@@ -0,0 +1,110 @@
+'use client';
+import React, { useState } from 'react';
+import { invoke } from '@tauri-apps/api/core';
+import { ShieldAlert, ShieldCheck, Loader2 } from 'lucide-react';
+
+interface WorkspaceTrustModalProps {
+    isOpen: boolean;
+    workspacePath: string;
+    onTrusted: () => void;
+    onDismiss: () => void;
+}
+
+export const WorkspaceTrustModal: React.FC<WorkspaceTrustModalProps> = ({
+    isOpen,
+    workspacePath,
+    onTrusted,
+    onDismiss,
+}) => {
+    const [isTrusting, setIsTrusting] = useState(false);
+    const [error, setError] = useState<string | null>(null);
+
+    const handleTrust = async () => {
+        setIsTrusting(true);
+        setError(null);
+
+        try {
+            await invoke('trust_workspace', { path: workspacePath });
+            console.log('[WorkspaceTrust] Trusted:', workspacePath);
+            onTrusted();
+        } catch (e) {
+            console.error('[WorkspaceTrust] Failed:', e);
+            setError(String(e));
+        } finally {
+            setIsTrusting(false);
+        }
+    };
+
+    if (!isOpen) return null;
+
+    return (
+        <div className="fixed inset-0 z-[9999] flex items-center justify-center">
+            {/* Backdrop */}
+            <div className="absolute inset-0 bg-black/70 backdrop-blur-sm" />
+
+            {/* Modal */}
+            <div className="relative bg-[var(--bg-surface)] border border-[var(--border-focus)] rounded-xl shadow-2xl w-full max-w-lg mx-4 animate-in fade-in zoom-in-95 duration-200">
+                {/* Header */}
+                <div className="px-8 pt-8 pb-4 flex items-start gap-4">
+                    <div className="p-3 rounded-lg bg-amber-500/20">
+                        <ShieldAlert className="w-6 h-6 text-amber-400" />
+                    </div>
+                    <div className="flex-1 min-w-0">
+                        <h2 className="text-xl font-bold text-[var(--fg-primary)]">
+                            Do you trust this workspace?
+                        </h2>
+                        <p className="mt-1 text-xs font-mono text-[var(--fg-tertiary)] break-all">
+                            {workspacePath}
+                        </p>
+                    </div>
+                </div>
+
+                <div className="px-8 py-2 text-sm text-[var(--fg-secondary)] space-y-2">
+                    <p>
+                        Commands, the terminal and the project's <code>.zblade/.env</code> are disabled
+                        until you trust this folder.
+                    </p>
+                    <p>
+                        Only trust folders whose contents you know, since the agent can then run
+                        commands in them.
+                    </p>
+                </div>
+
+                {/* Error */}
+                {error && (
+                    <div className="mx-8 mt-2 p-3 bg-red-500/10 border border-red-500/30 rounded-lg text-sm text-red-400">
+                        {error}
+                    </div>
+                )}
+
+                {/* Footer */}
+                <div className="px-8 py-6 flex items-center justify-end gap-3">
+                    <button
+                        onClick={onDismiss}
+                        disabled={isTrusting}
+                        className="px-4 py-2.5 text-[var(--fg-secondary)] hover:text-[var(--fg-primary)] hover:bg-[var(--bg-surface-hover)] rounded-lg transition-colors disabled:opacity-50"
+                    >
+                        Stay in restricted mode
+                    </button>
+                    <button
+                        onClick={handleTrust}
+                        disabled={isTrusting}
+                        className="flex items-center gap-2 px-6 py-2.5 bg-emerald-600 hover:bg-emerald-500 text-white font-medium rounded-lg transition-colors disabled:opacity-50 disabled:cursor-not-allowed"
+                    >
+                        {isTrusting ? (
+                            <>
+                                <Loader2 className="w-4 h-4 animate-spin" />
+                                Trusting...
+                            </>
+                        ) : (
+                            <>
+                                <ShieldCheck className="w-4 h-4" />
+                                Trust workspace
+                            </>
+                        )}
+                    </button>
+                </div>
+            </div>
+        </div>
+    );
+};
@@ -4,7 +4,7 @@ import { invoke } from '@tauri-apps/api/core';
 import { BladeDispatcher } from '../services/blade';
 import type { ChatMessage, ImageAttachment, ModelInfo, ToolCall } from '../types/chat';
 import type { Change } from '../types/change';
-import { EventNames, type RequestConfirmationPayload, type StructuredAction, type ChangeAppliedPayload, type AllEditsAppliedPayload, type ToolExecutionCompletedPayload } from '../types/events';
+import { EventNames, type CommandHintPayload, type RequestConfirmationPayload, type StructuredAction, type ChangeAppliedPayload, type AllEditsAppliedPayload, type ToolExecutionCompletedPayload } from '../types/events';
 import { useEditor } from '../contexts/EditorContext';
 import { MessageBuffer } from '../utils/eventBuffer';
 import type { BladeEventEnvelope } from '../types/blade';
@@ -18,6 +18,8 @@ export function useChat() {
     const blocksRef = useRef<Map<string, import('../types/chat').MessageBlock[]>>(new Map());
     const [loading, setLoading] = useState(false);
     const [error, setError] = useState<string | null>(null);
+    // Shown when a message looks like a mistyped @command (the message is still sent)
+    const [commandHint, setCommandHint] = useState<string | null>(null);
     
     // Tool activity state for streaming progress display
     const [toolActivity, setToolActivity] = useState<{ toolName: string; filePath: string; action: string } | null>(null);
@@ -31,6 +33,8 @@ export function useChat() {
     // This prevents re-rendering on every single streaming chunk
     const pendingUpdatesRef = useRef<Map<string, { content: string; reasoning: string; blocks: import('../types/chat').MessageBlock[] }>>(new Map());
     const flushScheduledRef = useRef<number | null>(null);
+    // Tool call id -> history group of the batch it was applied in (from BatchCompleted)
+    const changeBatchRef = useRef<Map<string, string>>(new Map());
     const FLUSH_INTERVAL_MS = 50; // 20fps - smooth enough for human perception
 
     // Flush pending updates to state
@@ -140,6 +144,16 @@ export function useChat() {
         }
     }, []);
 
+    // The Blade URL or API key may have changed; fetch the model list again
+    useEffect(() => {
+        if (typeof window === 'undefined' || !('__TAURI_INTERNALS__' in window)) return;
+        let unlisten: (() => void) | undefined;
+        listen(EventNames.CONFIG_RELOADED, () => {
+            refreshModels().catch(() => {});
+        }).then(u => { unlisten = u; });
+        return () => { unlisten?.(); };
+    }, [refreshModels]);
+
     // Wrapper that syncs with backend when model changes
     const setSelectedModelId = useCallback(async (modelId: string) => {
         hasExplicitModelRef.current = true;
@@ -217,6 +231,7 @@ export function useChat() {
         let unlistenUpdate: (() => void) | undefined;
         let unlistenDone: (() => void) | undefined;
         let unlistenError: (() => void) | undefined;
+        let unlistenCommandHint: (() => void) | undefined;
         let unlistenContextLength: (() => void) | undefined;
         let unlistenPerm: (() => void) | undefined;
         let unlistenChanges: (() => void) | undefined;
@@ -345,6 +360,10 @@ export function useChat() {
             });
             unlistenError = u3;
 
+            unlistenCommandHint = await listen<CommandHintPayload>(EventNames.COMMAND_HINT, (event) => {
+                setCommandHint(event.payload.message);
+            });
+
             // RFC: Context Length Recovery - listen for context limit exceeded events
             const uContextLength = await listen<{
                 message: string;
@@ -677,6 +696,16 @@ export function useChat() {
                             }, 2000);
                         }
                     }
+                } else if (envelope.event.type === 'Workflow') {
+                    const workflowEvent = envelope.event.payload;
+                    if (workflowEvent.type === 'BatchCompleted') {
+                        const { batch_id, change_ids } = workflowEvent.payload;
+                        for (const changeId of change_ids) {
+                            changeBatchRef.current.set(changeId, batch_id);
+                        }
+                    } else if (workflowEvent.type === 'AgentStopped') {
+                        setError(workflowEvent.payload.reason);
+                    }
                 }
             });
             unlistenV11 = u11;
@@ -685,6 +714,7 @@ export function useChat() {
                 if (unlistenUpdate) unlistenUpdate();
                 if (unlistenDone) unlistenDone();
                 if (unlistenError) unlistenError();
+                if (unlistenCommandHint) unlistenCommandHint();
                 if (unlistenContextLength) unlistenContextLength();
                 if (unlistenMessageTooLarge) unlistenMessageTooLarge();
                 if (unlistenPerm) unlistenPerm();
@@ -712,6 +742,7 @@ export function useChat() {
         try {
             setLoading(true);
             setError(null);
+            setCommandHint(null);
 
             // Get editor state from context
             const activeFile = editorState.activeFile;
@@ -834,8 +865,10 @@ export function useChat() {
 
     const undoTool = useCallback(async (toolCallId: string) => {
         try {
-            console.log('[useChat] Undoing tool batch:', toolCallId);
-            const revertedFiles = await invoke<string[]>('undo_batch', { groupId: toolCallId });
+            // Undo the whole change set the tool call was applied in
+            const groupId = changeBatchRef.current.get(toolCallId) ?? toolCallId;
+            console.log('[useChat] Undoing tool batch:', groupId);
+            const revertedFiles = await invoke<string[]>('undo_batch', { groupId });
             console.log('[useChat] Reverted files:', revertedFiles);
             // We might want to show a toast or notification here
         } catch (e) {
@@ -848,6 +881,7 @@ export function useChat() {
         messages,
         loading,
         error,
+        commandHint,
         sendMessage,
         stopGeneration,
         models,
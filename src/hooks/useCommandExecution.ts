@@ -160,13 +160,13 @@ export function useCommandExecution() {
                 // No-op: output accumulation is already active
             });
 
-            unlistenCmdExitDetected = await listen<{ terminal_id: string; call_id: string; exit_code: number; output: string }>('blade-cmd-exited', (event) => {
+            unlistenCmdExitDetected = await listen<{ terminal_id: string; call_id: string; exit_code: number; output: string; cancelled?: boolean }>('blade-cmd-exited', (event) => {
                 if (event.payload.terminal_id !== BLADE_TERMINAL_ID) return;
-                const { call_id: callId, exit_code: exitCode, output: cmdOutput } = event.payload;
+                const { call_id: callId, exit_code: exitCode, output: cmdOutput, cancelled } = event.payload;
                 const pending = pendingCommandsRef.current.get(callId);
                 if (pending) {
                     // Use output accumulated in Rust (reliable, no race condition)
-                    handleCommandComplete(callId, cmdOutput, exitCode);
+                    handleCommandComplete(callId, cmdOutput, exitCode, cancelled);
                     pendingCommandsRef.current.delete(callId);
                     if (activeCallIdRef.current === callId) {
                         activeCallIdRef.current = null;
@@ -174,7 +174,7 @@ export function useCommandExecution() {
                 }
             });
 
-            // terminal-output events are used by Terminal.tsx for display.
+            // Terminal Output blade-events are used by Terminal.tsx for display.
             // Output accumulation for command results is handled in Rust.
             unlistenOutput = undefined;
 
@@ -197,7 +197,9 @@ export function useCommandExecution() {
         };
     }, [flushPendingInputs, sendCommandToBlade]);
 
-    const handleCommandComplete = async (callId: string, output: string, exitCode: number) => {
+    // `cancelled` is only known when the backend killed the command itself;
+    // left undefined, the backend falls back to treating exit code 130 as a cancel
+    const handleCommandComplete = async (callId: string, output: string, exitCode: number, cancelled?: boolean) => {
         console.log('[CMD EXEC] Complete:', { callId, exitCode, outputLength: output.length });
 
         // Update local state - Remove execution to unmount terminal
@@ -213,6 +215,7 @@ export function useCommandExecution() {
                 callId,
                 output,
                 exitCode,
+                cancelled,
             });
             console.log('[CMD EXEC] Result submitted to backend');
         } catch (err) {
@@ -2,14 +2,35 @@ import { useState, useCallback, useEffect } from 'react';
 import { listen } from '@tauri-apps/api/event';
 import { invoke } from '@tauri-apps/api/core';
 import { BladeDispatcher } from '../services/blade';
-import type { ConversationSummary, BladeEventEnvelope } from '../types/blade';
+import type { ConversationSummary, BladeEventEnvelope, HistoryMessage } from '../types/blade';
 import type { ChatMessage } from '../types/chat';
 import { ensureMessagesHaveBlocks } from '../utils/messageBlocks';
 
+// Messages fetched per page when opening a server-side conversation
+const HISTORY_PAGE_SIZE = 50;
+
+// Convert history messages to ChatMessage format
+function toChatMessages(history: HistoryMessage[]): ChatMessage[] {
+    return history.map(msg => ({
+        id: msg.id ?? crypto.randomUUID(),
+        role: msg.role === 'user' ? 'User' :
+            msg.role === 'assistant' ? 'Assistant' :
+                msg.role === 'tool' ? 'Tool' : 'System',
+        content: msg.content,
+        // Mark all tool calls as complete since they're historical
+        tool_calls: msg.tool_calls?.map(tc => ({
+            ...tc,
+            status: 'complete' as const
+        })),
+        tool_call_id: msg.tool_call_id
+    }));
+}
+
 export function useHistory() {
     const [conversations, setConversations] = useState<ConversationSummary[]>([]);
     const [loading, setLoading] = useState(false);
     const [error, setError] = useState<string | null>(null);
+    const [olderCursor, setOlderCursor] = useState<{ sessionId: string; beforeId: string } | null>(null);
 
     // Listen for History Events from backend
     useEffect(() => {
@@ -78,10 +99,10 @@ export function useHistory() {
 
             if (useLocal) {
                 console.log('[useHistory] Loading conversations from LOCAL storage');
-                const localConversations = await invoke<any[]>('list_conversations');
+                const localConversations = await invoke<{ items: any[]; total: number }>('list_conversations');
 
                 // Map Rust metadata to UI Summary format
-                const conversations: ConversationSummary[] = localConversations.map(c => ({
+                const conversations: ConversationSummary[] = localConversations.items.map(c => ({
                     id: c.id,
                     project_id: projectId,
                     title: c.title,
@@ -112,6 +133,44 @@ export function useHistory() {
         }
     }, []);
 
+    // Request one page of a server-side conversation and wait for its ConversationPage event
+    const requestPage = useCallback(async (sessionId: string, beforeMessageId?: string) => {
+        const intentId = crypto.randomUUID();
+        return new Promise<{ messages: HistoryMessage[]; hasMore: boolean }>((resolve, reject) => {
+            const setup = async () => {
+                const unlisten = await listen<BladeEventEnvelope>('blade-event', (event) => {
+                    const envelope = event.payload;
+                    if (envelope.causality_id !== intentId || envelope.event.type !== 'History') return;
+
+                    const historyEvent = envelope.event.payload;
+                    if (historyEvent.type === 'ConversationPage') {
+                        unlisten();
+                        resolve({
+                            messages: historyEvent.payload.messages,
+                            hasMore: historyEvent.payload.has_more
+                        });
+                    }
+                });
+
+                try {
+                    await BladeDispatcher.history({
+                        type: 'LoadConversationPage',
+                        payload: {
+                            session_id: sessionId,
+                            before_message_id: beforeMessageId,
+                            limit: HISTORY_PAGE_SIZE
+                        }
+                    }, intentId);
+                } catch (e) {
+                    unlisten();
+                    reject(e);
+                }
+            };
+
+            setup();
+        });
+    }, []);
+
     const loadConversation = useCallback(async (sessionId: string): Promise<ChatMessage[]> => {
         // We need projectId to check settings, but loadConversation doesn't take it as arg.
         // However, we can assume if we are loading a conversation, we might need to check Global or try local first.
@@ -154,6 +213,7 @@ export function useHistory() {
                     tool_calls: msg.tool_calls // Ensure this field exists or is mapped
                 }));
 
+                setOlderCursor(null);
                 setLoading(false);
                 return ensureMessagesHaveBlocks(chatMessages);
             } catch (e) {
@@ -163,69 +223,43 @@ export function useHistory() {
                 throw e;
             }
         } else {
-            return new Promise((resolve, reject) => {
-                setLoading(true);
-                setError(null);
-
-                // Set up one-time listener for the ConversationLoaded event
-                const setupOneTimeListener = async () => {
-                    const unlisten = await listen<BladeEventEnvelope>('blade-event', (event) => {
-                        const envelope = event.payload;
-
-                        if (envelope.event.type === 'History') {
-                            const historyEvent = envelope.event.payload;
-
-                            if (historyEvent.type === 'ConversationLoaded' &&
-                                historyEvent.payload.session_id === sessionId) {
-
-                                // Convert history messages to ChatMessage format
-                                const messages: ChatMessage[] = historyEvent.payload.messages.map(msg => ({
-                                    id: crypto.randomUUID(),
-                                    role: msg.role === 'user' ? 'User' :
-                                        msg.role === 'assistant' ? 'Assistant' :
-                                            msg.role === 'tool' ? 'Tool' : 'System',
-                                    content: msg.content,
-                                    // Mark all tool calls as complete since they're historical
-                                    tool_calls: msg.tool_calls?.map(tc => ({
-                                        ...tc,
-                                        status: 'complete' as const
-                                    })),
-                                    tool_call_id: msg.tool_call_id
-                                }));
-
-                                unlisten();
-                                setLoading(false);
-                                // Reconstruct blocks for proper conversation flow ordering
-                                resolve(ensureMessagesHaveBlocks(messages));
-                            }
-                        }
-                    });
-
-                    // Dispatch LoadConversation Intent via BCP
-                    try {
-                        await BladeDispatcher.history({
-                            type: 'LoadConversation',
-                            payload: { session_id: sessionId }
-                        });
-                    } catch (e) {
-                        console.error('Failed to load conversation:', e);
-                        setError(e instanceof Error ? e.message : String(e));
-                        setLoading(false);
-                        unlisten();
-                        reject(e);
-                    }
-                };
-
-                setupOneTimeListener();
-            });
+            setLoading(true);
+            setError(null);
+            try {
+                // Only the newest page is rendered up front; older ones load on scroll
+                const page = await requestPage(sessionId);
+                setOlderCursor(page.hasMore && page.messages[0]?.id
+                    ? { sessionId, beforeId: page.messages[0].id }
+                    : null);
+                setLoading(false);
+                // Reconstruct blocks for proper conversation flow ordering
+                return ensureMessagesHaveBlocks(toChatMessages(page.messages));
+            } catch (e) {
+                console.error('Failed to load conversation:', e);
+                setError(e instanceof Error ? e.message : String(e));
+                setLoading(false);
+                throw e;
+            }
         }
-    }, []);
+    }, [requestPage]);
+
+    // Load the page of messages preceding the oldest one loaded so far
+    const loadOlderMessages = useCallback(async (): Promise<ChatMessage[]> => {
+        if (!olderCursor) return [];
+        const page = await requestPage(olderCursor.sessionId, olderCursor.beforeId);
+        setOlderCursor(page.hasMore && page.messages[0]?.id
+            ? { sessionId: olderCursor.sessionId, beforeId: page.messages[0].id }
+            : null);
+        return ensureMessagesHaveBlocks(toChatMessages(page.messages));
+    }, [olderCursor, requestPage]);
 
     return {
         conversations,
         loading,
         error,
         fetchConversations,
-        loadConversation
+        loadConversation,
+        loadOlderMessages,
+        hasOlderMessages: olderCursor !== null
     };
 }
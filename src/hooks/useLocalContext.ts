@@ -11,6 +11,11 @@ export interface ConversationIndex {
     artifact_path: string;
 }
 
+export interface Page<T> {
+    items: T[];
+    total: number;
+}
+
 export interface MomentIndex {
     id: string;
     conversation_id: string;
@@ -82,8 +87,8 @@ export interface ConversationArtifact {
     };
 }
 
-export async function listLocalConversations(projectPath: string): Promise<ConversationIndex[]> {
-    return invoke<ConversationIndex[]>('list_local_conversations', { projectPath });
+export async function listLocalConversations(projectPath: string, offset?: number, limit?: number): Promise<Page<ConversationIndex>> {
+    return invoke<Page<ConversationIndex>>('list_local_conversations', { projectPath, offset, limit });
 }
 
 export async function loadLocalConversation(projectPath: string, conversationId: string): Promise<ConversationArtifact> {
@@ -103,13 +108,13 @@ export async function deleteLocalConversation(projectPath: string, conversationI
 }
 
 export function useLocalContext(workspacePath: string | null) {
-    const listConversations = async (): Promise<ConversationIndex[]> => {
-        if (!workspacePath) return [];
+    const listConversations = async (offset?: number, limit?: number): Promise<Page<ConversationIndex>> => {
+        if (!workspacePath) return { items: [], total: 0 };
         try {
-            return await listLocalConversations(workspacePath);
+            return await listLocalConversations(workspacePath, offset, limit);
         } catch (e) {
             console.error('[LocalContext] Failed to list conversations:', e);
-            return [];
+            return { items: [], total: 0 };
         }
     };
 
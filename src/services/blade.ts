@@ -79,8 +79,8 @@ export class BladeDispatcher {
         return this.dispatch("Terminal", { type: "Terminal", payload: intent });
     }
 
-    static async history(intent: HistoryIntent) {
-        return this.dispatch("History", { type: "History", payload: intent });
+    static async history(intent: HistoryIntent, id?: string) {
+        return this.dispatch("History", { type: "History", payload: intent }, undefined, id);
     }
 
     static async language(intent: LanguageIntent, id?: string) {
@@ -67,6 +67,9 @@ export type EditorIntent =
     | { type: "SetActiveFile"; payload: { path: string | null } }
     | { type: "UpdateCursor"; payload: { line: number; column: number } }
     | { type: "UpdateSelection"; payload: { start: number; end: number } }
+    // Scripting: drive the editor to steer AI context
+    | { type: "SetCursor"; payload: { line: number; column: number } }
+    | { type: "SelectRange"; payload: { start_line: number; end_line: number } }
     | { type: "GetState"; payload: Record<string, never> }
     // Tab management (headless)
     | { type: "OpenTab"; payload: { id: string; title: string; path?: string; tab_type?: string; content?: string; suggested_name?: string } }
@@ -118,7 +121,8 @@ export type TerminalIntent =
 
 export type HistoryIntent =
     | { type: "ListConversations"; payload: { project_id: string } }
-    | { type: "LoadConversation"; payload: { session_id: string } };
+    | { type: "LoadConversation"; payload: { session_id: string } }
+    | { type: "LoadConversationPage"; payload: { session_id: string; before_message_id?: string; limit: number } };
 
 export type SystemIntent =
     | { type: "SetLogLevel"; payload: { level: string } };
@@ -135,7 +139,11 @@ export type BladeEvent =
     | { type: "Terminal"; payload: TerminalEvent }
     | { type: "History"; payload: HistoryEvent }
     | { type: "System"; payload: SystemEvent }
-    | { type: "Language"; payload: LanguageEvent };
+    | { type: "Language"; payload: LanguageEvent }
+    | { type: "Security"; payload: SecurityEvent };
+
+export type SecurityEvent =
+    | { type: "BlockedWrite"; payload: { path: string; tool: string; total: number } };
 
 export type ChatEvent =
     | { type: "ChatState"; payload: { messages: ChatMessage[] } }
@@ -187,7 +195,8 @@ export type WorkflowEvent =
     | { type: "ApprovalRequested"; payload: { batch_id: string; items: string[] } }
     // v1.1 variants
     | { type: "ActionCompleted"; payload: { action_id: string; success: boolean } }
-    | { type: "BatchCompleted"; payload: { batch_id: string; succeeded: number; failed: number } }
+    | { type: "BatchCompleted"; payload: { batch_id: string; succeeded: number; failed: number; change_ids: string[]; failed_ids: string[]; rolled_back: boolean } }
+    | { type: "AgentStopped"; payload: { reason: string } }
     // Legacy v1.0 variant
     | { type: "TaskCompleted"; payload: { task_id: string; success: boolean } };
 
@@ -207,6 +216,7 @@ export type ConversationSummary = {
 };
 
 export type HistoryMessage = {
+    id?: string;
     role: "user" | "assistant" | "tool" | "system";
     content: string;
     tool_calls?: Array<{
@@ -223,14 +233,18 @@ export type HistoryMessage = {
 
 export type HistoryEvent =
     | { type: "ConversationList"; payload: { conversations: ConversationSummary[] } }
-    | { type: "ConversationLoaded"; payload: { session_id: string; project_id: string; title: string; created_at: string; last_active_at: string; message_count: number; messages: HistoryMessage[] } };
+    | { type: "ConversationLoaded"; payload: { session_id: string; project_id: string; title: string; created_at: string; last_active_at: string; message_count: number; messages: HistoryMessage[] } }
+    | { type: "ConversationPage"; payload: { session_id: string; messages: HistoryMessage[]; has_more: boolean } };
 
 export type SystemEvent =
     | { type: "IntentFailed"; payload: { intent_id: string; error: BladeError } }
     | { type: "ProcessStarted"; payload: { intent_id: string } }
     | { type: "ProcessCompleted"; payload: { intent_id: string } }
     | { type: "ProtocolVersion"; payload: { supported: Version[]; current: Version } } // v1.1: version negotiation
-    | { type: "ProcessProgress"; payload: { intent_id: string; progress: number; message: string } }; // v1.1: progress updates
+    | { type: "ProcessProgress"; payload: { intent_id: string; progress: number; message: string } } // v1.1: progress updates
+    | { type: "PersistenceFailed"; payload: { scope: "conversation" | "artifact"; message: string } }
+    | { type: "IndexProgress"; payload: { done: number; total: number } }
+    | { type: "ServerToolMisrouted"; payload: { tool: string } };
 
 // ===================================
 // Models
@@ -260,7 +274,7 @@ export interface ChatMessage {
 // ===================================
 
 export type LanguageIntent =
-    | { type: "IndexFile"; payload: { file_path: string } }
+    | { type: "IndexFile"; payload: { file_path: string; language?: string } }
     | { type: "IndexWorkspace"; payload: Record<string, never> }
     | { type: "SearchSymbols"; payload: { query: string; file_path?: string | null; symbol_types?: string[] | null } }
     | { type: "GetSymbolAt"; payload: { file_path: string; line: number; character: number } }
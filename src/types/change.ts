@@ -41,4 +41,13 @@ export type Change =
     path: string;
     applied?: boolean;
     error?: string;
+  }
+  // AST-aware symbol edit; diff is a unified-diff preview
+  | {
+    change_type: "semantic";
+    id: string;
+    path: string;
+    diff: string;
+    applied?: boolean;
+    error?: string;
   };
@@ -26,12 +26,21 @@ export interface ChatMessage {
     progress?: ProgressInfo;
     content_before_tools?: string;
     content_after_tools?: string;
+    metrics?: MessageMetrics;
     commandExecutions?: CommandExecution[];
     todos?: TodoItem[];
     blocks?: MessageBlock[];
     researchActivities?: ResearchActivity[];
 }
 
+/** Timing and activity of a streamed assistant response (Unix ms timestamps) */
+export interface MessageMetrics {
+    started_at: number;
+    completed_at?: number;
+    tool_call_count: number;
+    chunk_count: number;
+}
+
 export interface ResearchActivity {
     id: string;
     message: string;
@@ -78,6 +87,10 @@ export interface ModelInfo {
     provider?: string;
     reasoning_effort?: string;
     api_id?: string;
+    context_window?: number;
+    supports_reasoning?: boolean;
+    supports_tools: boolean;
+    max_output_tokens?: number;
 }
 
 export interface EditProposal {
@@ -97,3 +110,13 @@ export interface CommandExecution {
     duration?: number;
     timestamp: number;
 }
+
+/** Entry returned by the `get_tool_log` command */
+export interface ToolInvocation {
+    call_id: string;
+    tool_name: string;
+    arguments: string;
+    success: boolean;
+    duration_ms: number;
+    timestamp: number;
+}
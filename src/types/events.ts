@@ -8,6 +8,8 @@
  * These types must match the Rust definitions in src-tauri/src/events.rs
  */
 
+import type { ApiConfig } from './settings';
+
 /**
  * Event names as constants to prevent typos
  */
@@ -32,11 +34,17 @@ export const EventNames = {
   /** Tool execution completed successfully */
   TOOL_EXECUTION_COMPLETED: 'tool-execution-completed',
   
+  /** Secrets were masked in tool output before it was sent to the model */
+  SECRETS_REDACTED: 'secrets-redacted',
+  
   /** AI model changed */
   MODEL_CHANGED: 'model-changed',
   
   /** Command execution completed */
   COMMAND_EXECUTED: 'command-executed',
+
+  /** A message looked like a misused @command and wasn't sent */
+  COMMAND_HINT: 'command-hint',
   
   // === File Edit Workflow ===
   
@@ -71,11 +79,17 @@ export const EventNames = {
   
   /** Active file/tab changed */
   ACTIVE_FILE_CHANGED: 'active-file-changed',
+
+  /** A file or directory with open tabs was renamed or moved */
+  FILE_MOVED: 'file-moved',
   
   // === Workspace ===
   
   /** Workspace folder changed */
   WORKSPACE_CHANGED: 'workspace-changed',
+
+  /** Opened workspace isn't trusted yet; commands and the terminal stay disabled */
+  WORKSPACE_TRUST_REQUIRED: 'workspace-trust-required',
   
   /** Project files changed (added/deleted) */
   PROJECT_FILES_CHANGED: 'project-files-changed',
@@ -95,6 +109,9 @@ export const EventNames = {
   
   /** General backend error */
   BACKEND_ERROR: 'backend-error',
+
+  /** API config was re-read from disk */
+  CONFIG_RELOADED: 'config-reloaded',
   
   // === Documents ===
   
@@ -123,6 +140,17 @@ export interface CommandExecutedPayload {
   duration?: number;
 }
 
+/**
+ * Payload for command-hint event
+ */
+export interface CommandHintPayload {
+  /** The `@word` as typed */
+  input: string;
+  /** Usage of the command the user most likely meant, e.g. `@research <query>` */
+  suggestion: string;
+  message: string;
+}
+
 /**
  * Payload for terminal-cwd-changed event
  */
@@ -208,6 +236,13 @@ export interface ToolExecutionCompletedPayload {
   skipped?: boolean;
 }
 
+/**
+ * Payload for secrets-redacted event
+ */
+export interface SecretsRedactedPayload {
+  count: number;
+}
+
 /**
  * Payload for model-changed event
  */
@@ -286,6 +321,14 @@ export interface ActiveFileChangedPayload {
   previous_file_path: string | null;
 }
 
+/**
+ * Payload for file-moved event
+ */
+export interface FileMovedPayload {
+  old_path: string;
+  new_path: string;
+}
+
 /**
  * Payload for workspace-changed event
  */
@@ -334,7 +377,9 @@ export interface EventMap {
   [EventNames.REQUEST_CONFIRMATION]: RequestConfirmationPayload;
   [EventNames.TOOL_EXECUTION_STARTED]: ToolExecutionStartedPayload;
   [EventNames.TOOL_EXECUTION_COMPLETED]: ToolExecutionCompletedPayload;
+  [EventNames.SECRETS_REDACTED]: SecretsRedactedPayload;
   [EventNames.MODEL_CHANGED]: ModelChangedPayload;
+  [EventNames.COMMAND_HINT]: CommandHintPayload;
   
   // File Edit Workflow
   [EventNames.PROPOSE_EDIT]: ProposeEditPayload;
@@ -349,14 +394,17 @@ export interface EventMap {
   [EventNames.FILE_SAVED]: FileSavedPayload;
   [EventNames.FILE_MODIFIED]: FileModifiedPayload;
   [EventNames.ACTIVE_FILE_CHANGED]: ActiveFileChangedPayload;
+  [EventNames.FILE_MOVED]: FileMovedPayload;
   
   // Workspace
   [EventNames.WORKSPACE_CHANGED]: WorkspaceChangedPayload;
+  [EventNames.WORKSPACE_TRUST_REQUIRED]: WorkspaceChangedPayload;
   [EventNames.PROJECT_FILES_CHANGED]: ProjectFilesChangedPayload;
   
   // Connection & Status
   [EventNames.CONNECTION_STATUS]: ConnectionStatusPayload;
   [EventNames.BACKEND_ERROR]: BackendErrorPayload;
+  [EventNames.CONFIG_RELOADED]: ApiConfig;
   
   // Documents
   [EventNames.OPEN_EPHEMERAL_DOCUMENT]: OpenEphemeralDocumentPayload;
@@ -3,4 +3,9 @@ export interface FileEntry {
     path: string;
     is_dir: boolean;
     children?: FileEntry[];
+    /** Directories in a depth-limited tree: whether there is anything to expand */
+    has_children?: boolean;
+    git_status?: 'modified' | 'untracked' | 'staged' | 'ignored' | 'clean';
+    /** File size in bytes (files only) */
+    size?: number;
 }
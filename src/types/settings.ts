@@ -4,8 +4,18 @@ export interface ApiConfig {
     user_id: string;
     ollama_enabled: boolean;
     ollama_url: string;
+    ollama_embedding_model: string;
+    reasoning_models?: string[];
     openai_compat_enabled: boolean;
     openai_compat_url: string;
+    ws_ping_interval_secs?: number;
+    http_timeout_secs?: number;
+    default_model?: string | null;
+    shell?: {
+        shell_path: string | null;
+        login_shell: boolean;
+        extra_args: string[];
+    };
     theme: string;
     markdown_view: string;
 }
@@ -31,4 +41,29 @@ export interface BackendSettings {
     };
     editor: {};
     allow_gitignored_files: boolean;
+    atomic_ai_changes?: boolean;
+    command_timeout_secs?: number;
+    auto_create_cwd?: boolean;
+    command_env?: {
+        allow: string[];
+        deny: string[];
+    };
+    redaction?: {
+        enabled: boolean;
+        extra_patterns: string[];
+    };
+    tools?: {
+        allow: string[];
+        deny: string[];
+        disable_destructive: boolean;
+    };
+    max_turns?: number;
+    max_tool_calls_per_message?: number;
+    loop_detection?: {
+        repeat_threshold: number;
+        exempt_tools: string[];
+    };
+    command_output_tail_lines?: number;
+    terminal_scrollback_lines?: number;
+    persist_ephemeral_documents?: boolean;
 }